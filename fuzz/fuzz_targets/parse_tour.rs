@@ -0,0 +1,22 @@
+//! Arbitrary bytes read as a tour must give a valid permutation or an error,
+//! and a parsed tour must survive a write and re-parse unchanged.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use std::sync::LazyLock;
+use tsp_solver::{EdgeWeightType, TspInstance, parse_tour_reader, tsplib_tour};
+
+static INSTANCE: LazyLock<TspInstance> = LazyLock::new(|| {
+    let coords: Vec<(f64, f64)> = (0..8).map(|i| (i as f64, (i * i % 5) as f64)).collect();
+    TspInstance::from_coordinates("fuzz8", &coords, EdgeWeightType::Euc2D, None).unwrap()
+});
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(tour) = parse_tour_reader(data, &INSTANCE) else {
+        return;
+    };
+    let node_ids: Vec<usize> = tour.iter().map(|&idx| idx + 1).collect();
+    let written = tsplib_tour(&INSTANCE.name, &node_ids);
+    assert_eq!(parse_tour_reader(written.as_bytes(), &INSTANCE), Ok(tour));
+});
@@ -0,0 +1,29 @@
+//! Arbitrary bytes must parse or fail cleanly, and whatever parses must
+//! survive a write and re-parse unchanged.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use tsp_solver::{ParseOptions, parse_tsp_reader, tsplib_instance};
+
+fuzz_target!(|data: &[u8]| {
+    // Distances are quadratic in the dimension; keep each input quick.
+    let options = ParseOptions {
+        max_dimension: 2000,
+        ..ParseOptions::default()
+    };
+    let Ok(instance) = parse_tsp_reader(data, &options) else {
+        return;
+    };
+    let written = tsplib_instance(&instance);
+    let reparsed = parse_tsp_reader(written.as_bytes(), &options)
+        .unwrap_or_else(|e| panic!("written instance does not parse: {}\n{}", e, written));
+    assert_eq!(reparsed.dimension, instance.dimension);
+    for i in 0..instance.dimension {
+        for j in 0..instance.dimension {
+            let (a, b) = (instance.dist_matrix.get(i, j), reparsed.dist_matrix.get(i, j));
+            assert!(a == b || (a.is_nan() && b.is_nan()), "distance ({}, {})", i, j);
+        }
+    }
+    assert_eq!(tsplib_instance(&reparsed), written);
+});
@@ -0,0 +1,688 @@
+//! Benchmark mode: solves every instance of a directory over several seeds
+//! and compares the results with the best-known lengths, reported the way
+//! ACO papers do: best and mean gap per instance, plus the mean gap and the
+//! number of instances solved to optimality over the whole set.
+
+use crate::config::Config;
+use crate::edge_frequency::EdgeFrequencies;
+use crate::parser::{TspInstance, parse_tsp_file_with_options};
+use crate::prepared::PreparedInstance;
+use crate::results::{IterationRecord, RunRecord, history_channel, iteration_history};
+use crate::solution::LengthPrecision;
+use crate::solver::{Ablation, AcoSolver, solve_random};
+use crate::utils::embedded_optimum;
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// Results of all runs on one instance. Gaps are in percent and only known
+/// when the instance has a best-known length.
+#[derive(Debug, Clone)]
+pub struct BenchRow {
+    pub instance: String,
+    pub dimension: usize,
+    /// [`TspInstance::content_hash_hex`], to trace the row to its input.
+    pub instance_hash: String,
+    pub best_known: Option<f64>,
+    pub runs: usize,
+    pub best: f64,
+    pub mean: f64,
+    pub best_gap: Option<f64>,
+    pub mean_gap: Option<f64>,
+    /// Mean wall-clock time of a single (single-threaded) run.
+    pub mean_seconds: f64,
+    /// One entry per `Config::target_gaps`, empty without a best-known length.
+    pub time_to_target: Vec<TimeToTarget>,
+    /// How often each edge appears in the runs' best tours.
+    pub edge_frequencies: EdgeFrequencies,
+    /// The individual runs, in seed order.
+    pub records: Vec<RunRecord>,
+}
+
+/// How many runs came within `gap` percent of the best-known length, and
+/// how long they took to get there on average.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeToTarget {
+    pub gap: f64,
+    pub reached: usize,
+    pub mean_seconds: Option<f64>,
+    pub mean_iterations: Option<f64>,
+}
+
+/// Outcome of one seeded run.
+struct Run {
+    seed: u64,
+    tour: Vec<usize>,
+    length: f64,
+    seconds: f64,
+    /// Per target gap: iterations run and seconds when it was first reached.
+    reached: Vec<Option<(usize, f64)>>,
+    history: Vec<IterationRecord>,
+}
+
+/// Aggregate statistics over the instances with a best-known length.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchSummary {
+    pub instances: usize,
+    pub with_best_known: usize,
+    pub mean_best_gap: Option<f64>,
+    pub mean_gap: Option<f64>,
+    /// Instances where the best run matched the best-known length.
+    pub optimal_found: usize,
+}
+
+/// The `.tsp` and `.npy` files in `path`, sorted, or `path` itself if it is
+/// a file.
+pub fn bench_instances(path: &str) -> Result<Vec<PathBuf>, String> {
+    let path = Path::new(path);
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let entries =
+        fs::read_dir(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let mut instances: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| {
+                    ext.eq_ignore_ascii_case("tsp") || ext.eq_ignore_ascii_case("npy")
+                })
+        })
+        .collect();
+    instances.sort();
+    Ok(instances)
+}
+
+/// Best-known length of `name`: the solutions file takes precedence over
+/// the embedded TSPLIB optima.
+pub fn best_known_length(name: &str, solutions: &HashMap<String, f64>) -> Option<f64> {
+    let key = name.split('.').next().unwrap_or(name).to_lowercase();
+    solutions
+        .get(&key)
+        .copied()
+        .or_else(|| embedded_optimum(&key))
+}
+
+/// Runs `config.num_seeds` seeded runs (starting at `config.seed`, or 1) on
+/// the instance at `path`. Runs are spread over threads, one run per thread.
+pub fn bench_instance(
+    path: &Path,
+    config: &Config,
+    solutions: &HashMap<String, f64>,
+) -> Result<BenchRow, String> {
+    let instance = parse_tsp_file_with_options(&path.to_string_lossy(), &config.into())?;
+    Ok(bench_row(&instance, config, solutions))
+}
+
+/// Runs the configured colony and then each [`Ablation`] of it on the
+/// instance at `path`, over the same seeds. The full colony comes first,
+/// followed by the ablations in the order of [`Ablation::ALL`].
+pub fn ablation_study(
+    path: &Path,
+    config: &Config,
+    solutions: &HashMap<String, f64>,
+) -> Result<Vec<BenchRow>, String> {
+    let instance = parse_tsp_file_with_options(&path.to_string_lossy(), &config.into())?;
+    let mut rows = vec![bench_row(&instance, config, solutions)];
+    for ablation in Ablation::ALL {
+        let mut ablated = config.clone();
+        ablation.apply(&mut ablated);
+        rows.push(bench_row(&instance, &ablated, solutions));
+    }
+    Ok(rows)
+}
+
+fn bench_row(
+    instance: &TspInstance,
+    config: &Config,
+    solutions: &HashMap<String, f64>,
+) -> BenchRow {
+    let best_known = best_known_length(&instance.name, solutions);
+    let results = run_seeds(instance, config, best_known);
+    let runs = results.len();
+    let best = results.iter().map(|r| r.length).fold(f64::MAX, f64::min);
+    let mean = results.iter().map(|r| r.length).sum::<f64>() / runs as f64;
+    let gap = |length: f64| best_known.map(|known| gap_percent(length, known));
+    let time_to_target = match best_known {
+        Some(_) => config
+            .target_gaps
+            .iter()
+            .enumerate()
+            .map(|(k, &gap)| {
+                let hits: Vec<(usize, f64)> =
+                    results.iter().filter_map(|run| run.reached[k]).collect();
+                let mean = |values: Vec<f64>| {
+                    (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+                };
+                TimeToTarget {
+                    gap,
+                    reached: hits.len(),
+                    mean_seconds: mean(hits.iter().map(|h| h.1).collect()),
+                    mean_iterations: mean(hits.iter().map(|h| h.0 as f64).collect()),
+                }
+            })
+            .collect(),
+        None => Vec::new(),
+    };
+    let instance_hash = instance.content_hash_hex();
+    let records = results
+        .iter()
+        .map(|run| RunRecord {
+            instance: instance.name.clone(),
+            instance_hash: instance_hash.clone(),
+            seed: run.seed,
+            length: run.length,
+            gap: gap(run.length),
+            seconds: run.seconds,
+            tour: run.tour.clone(),
+            history: run.history.clone(),
+        })
+        .collect();
+    BenchRow {
+        instance: instance.name.clone(),
+        dimension: instance.dimension,
+        instance_hash,
+        best_known,
+        runs,
+        best,
+        mean,
+        best_gap: gap(best),
+        mean_gap: gap(mean),
+        mean_seconds: results.iter().map(|r| r.seconds).sum::<f64>() / runs as f64,
+        time_to_target,
+        edge_frequencies: EdgeFrequencies::new(instance, results.iter().map(|r| &r.tour[..])),
+        records,
+    }
+}
+
+/// All seeded runs on `instance`, in seed order. With a `reference` length
+/// every run records when it first came within each of `config.target_gaps`
+/// percent of it.
+fn run_seeds(instance: &TspInstance, config: &Config, reference: Option<f64>) -> Vec<Run> {
+    let base_seed = config.seed.unwrap_or(1);
+    let prepared = PreparedInstance::new(instance);
+    (0..config.num_seeds.max(1) as u64)
+        .into_par_iter()
+        .map(|s| {
+            let (event_sender, events) = history_channel(config);
+            let run_config = Config {
+                seed: Some(base_seed + s),
+                event_sender,
+                ..config.clone()
+            };
+            let start_time = Instant::now();
+            if config.ablation == Some(Ablation::RandomTours) {
+                let (tour, length) = solve_random(instance, &run_config);
+                let length = match config.gap_precision {
+                    LengthPrecision::Exact if !tour.is_empty() => instance.tour_length(&tour),
+                    _ => length,
+                };
+                return Run {
+                    seed: base_seed + s,
+                    tour,
+                    length,
+                    seconds: start_time.elapsed().as_secs_f64(),
+                    reached: vec![None; config.target_gaps.len()],
+                    history: Vec::new(),
+                };
+            }
+            let mut solver = AcoSolver::quiet(&prepared, &run_config);
+            let mut reached = vec![None; config.target_gaps.len()];
+            while let Some(reference) = reference
+                && !solver.is_finished()
+                && reached.iter().any(Option::is_none)
+            {
+                solver.step();
+                if solver.best_tour_length() == f64::MAX {
+                    continue;
+                }
+                let gap = gap_percent(
+                    at_precision(solver.best_tour_length(), config.gap_precision),
+                    reference,
+                );
+                for (target, hit) in config.target_gaps.iter().zip(&mut reached) {
+                    if hit.is_none() && gap <= target + 1e-9 {
+                        *hit = Some((solver.iteration(), start_time.elapsed().as_secs_f64()));
+                    }
+                }
+            }
+            let solution = solver.solve();
+            Run {
+                seed: base_seed + s,
+                length: solution.length_with(config.gap_precision),
+                tour: solution.tour,
+                seconds: start_time.elapsed().as_secs_f64(),
+                reached,
+                history: iteration_history(&events),
+            }
+        })
+        .collect()
+}
+
+/// Relative excess of `length` over `reference`, in percent.
+/// `length` as compared with references at `precision`.
+fn at_precision(length: f64, precision: LengthPrecision) -> f64 {
+    match precision {
+        LengthPrecision::Rounded => length.round(),
+        LengthPrecision::Exact => length,
+    }
+}
+
+pub fn gap_percent(length: f64, reference: f64) -> f64 {
+    if reference == 0.0 {
+        if length == 0.0 { 0.0 } else { f64::INFINITY }
+    } else {
+        (length - reference) / reference * 100.0
+    }
+}
+
+/// Aggregates `rows`; instances without a best-known length only count
+/// towards `instances`.
+pub fn summarize(rows: &[BenchRow]) -> BenchSummary {
+    let known: Vec<&BenchRow> = rows.iter().filter(|r| r.best_known.is_some()).collect();
+    let mean =
+        |gaps: Vec<f64>| (!gaps.is_empty()).then(|| gaps.iter().sum::<f64>() / gaps.len() as f64);
+    BenchSummary {
+        instances: rows.len(),
+        with_best_known: known.len(),
+        mean_best_gap: mean(known.iter().filter_map(|r| r.best_gap).collect()),
+        mean_gap: mean(known.iter().filter_map(|r| r.mean_gap).collect()),
+        optimal_found: known
+            .iter()
+            .filter(|r| r.best_gap.is_some_and(|gap| gap <= 1e-9))
+            .count(),
+    }
+}
+
+/// Target gaps reported for `rows`, empty if no instance has a best-known
+/// length.
+fn target_gaps(rows: &[BenchRow]) -> Vec<f64> {
+    rows.iter()
+        .find(|row| !row.time_to_target.is_empty())
+        .map_or(Vec::new(), |row| {
+            row.time_to_target.iter().map(|t| t.gap).collect()
+        })
+}
+
+/// Plain text table of the results, one line per instance, followed by the
+/// time-to-target table and the aggregate statistics.
+pub fn format_table(rows: &[BenchRow]) -> String {
+    let optional = |value: Option<f64>, precision: usize| {
+        value.map_or("-".to_string(), |v| format!("{:.*}", precision, v))
+    };
+    let percent = |gap: Option<f64>| gap.map_or("-".to_string(), |g| format!("{:.2}%", g));
+    let mut table = format!(
+        "  {:<14} {:>6} {:>11} {:>11} {:>11} {:>9} {:>9} {:>9}\n",
+        "Instance", "Nodes", "Best known", "Best", "Mean", "Best gap", "Mean gap", "Time (s)"
+    );
+    for row in rows {
+        let _ = writeln!(
+            table,
+            "  {:<14} {:>6} {:>11} {:>11.0} {:>11.1} {:>9} {:>9} {:>9.2}",
+            row.instance,
+            row.dimension,
+            optional(row.best_known, 0),
+            row.best,
+            row.mean,
+            percent(row.best_gap),
+            percent(row.mean_gap),
+            row.mean_seconds
+        );
+    }
+    let targets = target_gaps(rows);
+    if !targets.is_empty() {
+        let _ = write!(
+            table,
+            "\n  Time to target (runs reached, mean seconds, mean iterations)\n  {:<14}",
+            "Instance"
+        );
+        for gap in &targets {
+            let _ = write!(table, " {:>18}", format!("{}%", gap));
+        }
+        table.push('\n');
+        for row in rows.iter().filter(|row| !row.time_to_target.is_empty()) {
+            let _ = write!(table, "  {:<14}", row.instance);
+            for target in &row.time_to_target {
+                let cell = match (target.mean_seconds, target.mean_iterations) {
+                    (Some(seconds), Some(iterations)) => format!(
+                        "{}/{} {:.2}s {:.0}it",
+                        target.reached, row.runs, seconds, iterations
+                    ),
+                    _ => format!("0/{}", row.runs),
+                };
+                let _ = write!(table, " {:>18}", cell);
+            }
+            table.push('\n');
+        }
+    }
+    let summary = summarize(rows);
+    let _ = writeln!(
+        table,
+        "\n  {} instances, {} with a best-known length",
+        summary.instances, summary.with_best_known
+    );
+    if summary.with_best_known > 0 {
+        let _ = writeln!(
+            table,
+            "  Mean best gap: {}, mean gap: {}, optimal found: {}/{}",
+            percent(summary.mean_best_gap),
+            percent(summary.mean_gap),
+            summary.optimal_found,
+            summary.with_best_known
+        );
+    }
+    table
+}
+
+/// Plain text table of [`ablation_study`] results: the mean gap of the full
+/// colony and of every ablation per instance (mean lengths for instances
+/// without a best-known length), and the mean gaps over all instances.
+pub fn format_ablations(studies: &[Vec<BenchRow>]) -> String {
+    let mut table = format!("  {:<14} {:>14}", "Instance", "Full");
+    for ablation in Ablation::ALL {
+        let _ = write!(table, " {:>14}", ablation.name());
+    }
+    table.push('\n');
+    for rows in studies {
+        let _ = write!(table, "  {:<14}", rows[0].instance);
+        for row in rows {
+            let cell = match row.mean_gap {
+                Some(gap) => format!("{:.2}%", gap),
+                None => format!("{:.1}", row.mean),
+            };
+            let _ = write!(table, " {:>14}", cell);
+        }
+        table.push('\n');
+    }
+    let known: Vec<&Vec<BenchRow>> = studies
+        .iter()
+        .filter(|rows| rows[0].best_known.is_some())
+        .collect();
+    if !known.is_empty() {
+        let _ = write!(table, "\n  {:<14}", "Mean gap");
+        for k in 0..=Ablation::ALL.len() {
+            let mean = known
+                .iter()
+                .filter_map(|rows| rows[k].mean_gap)
+                .sum::<f64>()
+                / known.len() as f64;
+            let _ = write!(table, " {:>14}", format!("{:.2}%", mean));
+        }
+        table.push('\n');
+    }
+    table
+}
+
+/// Writes one CSV line per instance; unknown values are left empty.
+pub fn write_csv(rows: &[BenchRow], path: &str) -> Result<(), String> {
+    let optional = |value: Option<f64>| value.map_or(String::new(), |v| v.to_string());
+    let targets = target_gaps(rows);
+    let mut csv =
+        "instance,dimension,instance_hash,best_known,runs,best,mean,best_gap,mean_gap,mean_seconds"
+            .to_string();
+    for gap in &targets {
+        let _ = write!(csv, ",ttt{0}_reached,ttt{0}_seconds,ttt{0}_iterations", gap);
+    }
+    csv.push('\n');
+    for row in rows {
+        let _ = write!(
+            csv,
+            "{},{},{},{},{},{},{},{},{},{}",
+            row.instance,
+            row.dimension,
+            row.instance_hash,
+            optional(row.best_known),
+            row.runs,
+            row.best,
+            row.mean,
+            optional(row.best_gap),
+            optional(row.mean_gap),
+            row.mean_seconds
+        );
+        for k in 0..targets.len() {
+            match row.time_to_target.get(k) {
+                Some(target) => {
+                    let _ = write!(
+                        csv,
+                        ",{},{},{}",
+                        target.reached,
+                        optional(target.mean_seconds),
+                        optional(target.mean_iterations)
+                    );
+                }
+                None => csv.push_str(",,,"),
+            }
+        }
+        csv.push('\n');
+    }
+    fs::write(path, csv).map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+/// Mean gaps of two configurations on one instance, run over the same seeds.
+#[derive(Debug, Clone)]
+pub struct ComparisonRow {
+    pub instance: String,
+    /// Best-known length, or the best length either configuration found.
+    pub reference: f64,
+    pub best_known: bool,
+    pub mean_gap_a: f64,
+    pub mean_gap_b: f64,
+    /// Seeds on which one configuration found the strictly shorter tour.
+    pub wins_a: usize,
+    pub wins_b: usize,
+}
+
+/// Result of a two-sided Wilcoxon signed-rank test.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignedRankTest {
+    /// Pairs with a non-zero difference; ties are dropped.
+    pub pairs: usize,
+    /// Rank sum of the positive differences.
+    pub statistic: f64,
+    pub p_value: f64,
+}
+
+// Up to this many pairs the p-value comes from the exact null distribution,
+// beyond it from the normal approximation.
+const EXACT_SIGNED_RANK_PAIRS: usize = 50;
+
+/// Wilcoxon signed-rank test of whether `differences` are centered on zero.
+/// Tied absolute differences share their average rank.
+pub fn wilcoxon_signed_rank(differences: &[f64]) -> SignedRankTest {
+    let mut nonzero: Vec<f64> = differences
+        .iter()
+        .copied()
+        .filter(|d| d.abs() > 1e-12)
+        .collect();
+    nonzero.sort_by(|a, b| a.abs().total_cmp(&b.abs()));
+    let n = nonzero.len();
+    if n == 0 {
+        return SignedRankTest {
+            pairs: 0,
+            statistic: 0.0,
+            p_value: 1.0,
+        };
+    }
+
+    let mut ranks = vec![0.0; n];
+    let mut tie_correction = 0.0;
+    let mut start = 0;
+    while start < n {
+        let mut end = start + 1;
+        while end < n && (nonzero[end].abs() - nonzero[start].abs()).abs() <= 1e-12 {
+            end += 1;
+        }
+        let rank = (start + end + 1) as f64 / 2.0;
+        ranks[start..end].fill(rank);
+        let t = (end - start) as f64;
+        tie_correction += t * t * t - t;
+        start = end;
+    }
+    let statistic: f64 = nonzero
+        .iter()
+        .zip(&ranks)
+        .filter(|&(d, _)| *d > 0.0)
+        .map(|(_, r)| r)
+        .sum();
+
+    let nf = n as f64;
+    let mean = nf * (nf + 1.0) / 4.0;
+    let p_value = if n <= EXACT_SIGNED_RANK_PAIRS {
+        // Ranks are multiples of 1/2, so count sign assignments over doubled
+        // ranks. counts[s]: assignments whose doubled positive sum is s.
+        let doubled: Vec<usize> = ranks.iter().map(|r| (r * 2.0).round() as usize).collect();
+        let total: usize = doubled.iter().sum();
+        let mut counts = vec![0.0f64; total + 1];
+        counts[0] = 1.0;
+        for &r in &doubled {
+            for s in (r..=total).rev() {
+                counts[s] += counts[s - r];
+            }
+        }
+        let observed = (statistic * 2.0).round() as usize;
+        let deviation = observed.abs_diff(total / 2) as f64;
+        let extreme: f64 = counts
+            .iter()
+            .enumerate()
+            .filter(|&(s, _)| (s as f64 - total as f64 / 2.0).abs() >= deviation - 1e-9)
+            .map(|(_, c)| c)
+            .sum();
+        (extreme / 2f64.powi(n as i32)).min(1.0)
+    } else {
+        let variance = nf * (nf + 1.0) * (2.0 * nf + 1.0) / 24.0 - tie_correction / 48.0;
+        let z = ((statistic - mean).abs() - 0.5).max(0.0) / variance.sqrt();
+        (2.0 * (1.0 - standard_normal_cdf(z))).min(1.0)
+    };
+    SignedRankTest {
+        pairs: n,
+        statistic,
+        p_value,
+    }
+}
+
+fn standard_normal_cdf(z: f64) -> f64 {
+    // Abramowitz and Stegun 7.1.26, absolute error below 1.5e-7.
+    let x = z.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.3275911 * x);
+    let poly = t
+        * (0.254829592
+            + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let erf = 1.0 - poly * (-x * x).exp();
+    if z >= 0.0 {
+        0.5 * (1.0 + erf)
+    } else {
+        0.5 * (1.0 - erf)
+    }
+}
+
+/// Runs `config_a` and `config_b` on the instance at `path` over the same
+/// seeds. Returns the row and the per-seed gap differences (B minus A).
+pub fn compare_instance(
+    path: &Path,
+    config_a: &Config,
+    config_b: &Config,
+    solutions: &HashMap<String, f64>,
+) -> Result<(ComparisonRow, Vec<f64>), String> {
+    let instance = parse_tsp_file_with_options(&path.to_string_lossy(), &config_a.into())?;
+    let lengths = |config| -> Vec<f64> {
+        run_seeds(&instance, config, None)
+            .iter()
+            .map(|run| run.length)
+            .collect()
+    };
+    let (lengths_a, lengths_b) = (lengths(config_a), lengths(config_b));
+    let best_known = best_known_length(&instance.name, solutions);
+    let reference = best_known.unwrap_or_else(|| {
+        lengths_a
+            .iter()
+            .chain(&lengths_b)
+            .copied()
+            .fold(f64::MAX, f64::min)
+    });
+    let gaps_a: Vec<f64> = lengths_a
+        .iter()
+        .map(|&l| gap_percent(l, reference))
+        .collect();
+    let gaps_b: Vec<f64> = lengths_b
+        .iter()
+        .map(|&l| gap_percent(l, reference))
+        .collect();
+    let mean = |gaps: &[f64]| gaps.iter().sum::<f64>() / gaps.len() as f64;
+    let row = ComparisonRow {
+        instance: instance.name.clone(),
+        reference,
+        best_known: best_known.is_some(),
+        mean_gap_a: mean(&gaps_a),
+        mean_gap_b: mean(&gaps_b),
+        wins_a: lengths_a
+            .iter()
+            .zip(&lengths_b)
+            .filter(|(a, b)| a < b)
+            .count(),
+        wins_b: lengths_a
+            .iter()
+            .zip(&lengths_b)
+            .filter(|(a, b)| b < a)
+            .count(),
+    };
+    let differences = gaps_a.iter().zip(&gaps_b).map(|(a, b)| b - a).collect();
+    Ok((row, differences))
+}
+
+/// Plain text table of a comparison with the signed-rank test over all
+/// paired runs and its verdict at the 5% level.
+pub fn format_comparison(
+    rows: &[ComparisonRow],
+    test: &SignedRankTest,
+    names: (&str, &str),
+) -> String {
+    let mut table = format!(
+        "  {:<14} {:>11} {:>10} {:>10} {:>7} {:>7}\n",
+        "Instance", "Reference", "Gap A", "Gap B", "A wins", "B wins"
+    );
+    for row in rows {
+        let marker = if row.best_known { "" } else { "*" };
+        let _ = writeln!(
+            table,
+            "  {:<14} {:>10.0}{:1} {:>9.2}% {:>9.2}% {:>7} {:>7}",
+            row.instance,
+            row.reference,
+            marker,
+            row.mean_gap_a,
+            row.mean_gap_b,
+            row.wins_a,
+            row.wins_b
+        );
+    }
+    if rows.iter().any(|row| !row.best_known) {
+        table.push_str("  * no best-known length, gaps relative to the best run\n");
+    }
+    let mean = |gap: fn(&ComparisonRow) -> f64| {
+        rows.iter().map(gap).sum::<f64>() / rows.len().max(1) as f64
+    };
+    let (mean_a, mean_b) = (mean(|r| r.mean_gap_a), mean(|r| r.mean_gap_b));
+    let _ = writeln!(
+        table,
+        "\n  A = {}, B = {}\n  Mean gap: A {:.2}%, B {:.2}%",
+        names.0, names.1, mean_a, mean_b
+    );
+    let _ = writeln!(
+        table,
+        "  Wilcoxon signed-rank test: W+ = {:.1} over {} non-tied pairs, p = {:.4}",
+        test.statistic, test.pairs, test.p_value
+    );
+    let verdict = if test.p_value >= 0.05 {
+        "No significant difference at the 5% level".to_string()
+    } else if mean_b < mean_a {
+        format!("{} is significantly better at the 5% level", names.1)
+    } else {
+        format!("{} is significantly better at the 5% level", names.0)
+    };
+    let _ = writeln!(table, "  {}", verdict);
+    table
+}
@@ -0,0 +1,127 @@
+//! Directory of finished solutions keyed by instance, settings and seed, so
+//! scripts re-running an identical seeded solve get the stored result back
+//! instead of solving again.
+//!
+//! Each entry is a solution JSON file named after a hash of the instance's
+//! content hash, the crate version, [`Config::settings`], the tour
+//! constraints and the seed. Only runs that are reproducible are cached:
+//! unseeded, time-budgeted, resumed and distributed runs always solve.
+
+use crate::config::Config;
+use crate::parser::TspInstance;
+use crate::provenance::{Fnv, Provenance, VERSION};
+use crate::solution::Solution;
+use std::path::{Path, PathBuf};
+
+pub struct ResultCache {
+    dir: PathBuf,
+}
+
+impl ResultCache {
+    /// A cache in `dir`, created on the first [`ResultCache::put`].
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        ResultCache { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Cache key of solving `instance` with `config` as 16 hex digits,
+    /// `None` if the run is not reproducible.
+    pub fn key(instance: &TspInstance, config: &Config) -> Option<String> {
+        let seed = config.seed?;
+        if config.time_budget.is_some() || config.resume.is_some() || config.distributed.is_some() {
+            return None;
+        }
+        let mut hash = Fnv::default();
+        for part in [
+            instance.content_hash_hex(),
+            VERSION.to_string(),
+            config.settings(),
+            // Shape the tour without being search settings.
+            format!("warm_start={:?}", config.warm_start),
+            format!("locked={:?}", config.locked_nodes),
+            format!("pinned={:?}", config.pinned_positions),
+            format!("subsequences={:?}", config.subsequences),
+            format!("pairs={:?}", config.pickup_delivery),
+            format!("turn_penalty={:?}", config.turn_penalty),
+            seed.to_string(),
+        ] {
+            hash.write(part.as_bytes());
+            hash.write(&[0]);
+        }
+        Some(format!("{:016x}", hash.0))
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+
+    /// The stored solution of solving `instance` with `config`, if any.
+    /// Unreadable entries and entries whose provenance does not match (a
+    /// hash collision) count as misses.
+    pub fn get(&self, instance: &TspInstance, config: &Config) -> Option<Solution> {
+        let key = Self::key(instance, config)?;
+        let text = std::fs::read_to_string(self.path(&key)).ok()?;
+        let solution = Solution::from_json(&text).ok()?;
+        let expected = Provenance::new(instance, config);
+        let matches = solution.provenance.as_ref().is_some_and(|provenance| {
+            provenance.instance_hash == expected.instance_hash
+                && provenance.version == expected.version
+                && provenance.config == expected.config
+                && provenance.seed == expected.seed
+        });
+        (matches && solution.is_complete(instance.dimension)).then_some(solution)
+    }
+
+    /// Stores `solution` as the result of solving `instance` with `config`.
+    /// Returns whether it was stored; non-reproducible runs and incomplete
+    /// tours are not. The file is written under a temporary name and renamed,
+    /// so concurrent readers never see a partial entry.
+    pub fn put(
+        &self,
+        instance: &TspInstance,
+        config: &Config,
+        solution: &Solution,
+    ) -> Result<bool, String> {
+        let Some(key) = Self::key(instance, config) else {
+            return Ok(false);
+        };
+        if !solution.is_complete(instance.dimension) {
+            return Ok(false);
+        }
+        std::fs::create_dir_all(&self.dir).map_err(|e| {
+            format!(
+                "Failed to create cache directory {}: {}",
+                self.dir.display(),
+                e
+            )
+        })?;
+        let path = self.path(&key);
+        let partial = self.dir.join(format!("{}.{}.tmp", key, std::process::id()));
+        let mut stored = solution.clone();
+        // The gap depends on the optima file of the caller, not the run.
+        stored.gap = None;
+        std::fs::write(&partial, stored.to_json() + "\n")
+            .and_then(|()| std::fs::rename(&partial, &path))
+            .map_err(|e| format!("Failed to write cache entry {}: {}", path.display(), e))?;
+        Ok(true)
+    }
+
+    /// [`crate::solve_instance`] through the cache: a stored solution is
+    /// returned as is, otherwise the instance is solved and the result
+    /// stored. The flag tells whether the solution came from the cache.
+    pub fn solve(
+        &self,
+        instance: &TspInstance,
+        config: &Config,
+    ) -> Result<(Solution, bool), String> {
+        if let Some(solution) = self.get(instance, config) {
+            return Ok((solution, true));
+        }
+        let solution = crate::solve_instance(instance, config)?;
+        self.put(instance, config, &solution)?;
+        Ok((solution, false))
+    }
+}
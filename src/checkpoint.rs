@@ -0,0 +1,206 @@
+//! Checkpoints of an ACO run (pheromone trails, best tour and the
+//! iteration counters) written when the process is asked to stop, so that a
+//! preempted run can be continued with `--resume`.
+//!
+//! File format:
+//!
+//! ```text
+//! CHECKPOINT 1
+//! DIMENSION <n>
+//! ITERATION <iterations run>
+//! LAST_IMPROVEMENT <iteration>
+//! SEED <seed or none>
+//! PROVENANCE <instance hash> <version> <settings>  (optional)
+//! BEST <length> <tour...>
+//! PHEROMONE                                        (followed by n rows of n levels)
+//! ```
+//!
+//! Ant random streams are derived from the seed and the iteration number,
+//! so the seed and `ITERATION` fully describe the random state of a seeded
+//! run.
+
+use crate::provenance::Provenance;
+use std::fmt::Write as _;
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static SHUTDOWN_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checkpoint {
+    pub iteration: usize,
+    pub last_improvement: usize,
+    pub seed: Option<u64>,
+    /// Empty if no complete tour had been found yet.
+    pub best_tour: Vec<usize>,
+    pub best_length: f64,
+    pub pheromone: Vec<Vec<f64>>,
+    /// Instance and settings the run was started with, if recorded.
+    pub provenance: Option<Provenance>,
+}
+
+impl Checkpoint {
+    pub fn dimension(&self) -> usize {
+        self.pheromone.len()
+    }
+
+    pub fn write(&self, path: &str) -> Result<(), String> {
+        let n = self.dimension();
+        let mut text = format!(
+            "CHECKPOINT 1\nDIMENSION {}\nITERATION {}\nLAST_IMPROVEMENT {}\n",
+            n, self.iteration, self.last_improvement
+        );
+        match self.seed {
+            Some(seed) => {
+                let _ = writeln!(text, "SEED {}", seed);
+            }
+            None => text.push_str("SEED none\n"),
+        }
+        if let Some(provenance) = &self.provenance {
+            let _ = writeln!(
+                text,
+                "PROVENANCE {} {} {}",
+                provenance.instance_hash, provenance.version, provenance.config
+            );
+        }
+        let _ = write!(text, "BEST {}", self.best_length);
+        for node in &self.best_tour {
+            let _ = write!(text, " {}", node);
+        }
+        text.push_str("\nPHEROMONE\n");
+        for row in &self.pheromone {
+            let levels: Vec<String> = row.iter().map(|level| level.to_string()).collect();
+            text.push_str(&levels.join(" "));
+            text.push('\n');
+        }
+        // Write to a temporary file first so an interrupted write never
+        // replaces a good checkpoint with a truncated one.
+        let tmp_path = format!("{}.tmp", path);
+        fs::write(&tmp_path, text)
+            .and_then(|()| fs::rename(&tmp_path, path))
+            .map_err(|e| format!("Failed to write checkpoint {}: {}", path, e))
+    }
+
+    pub fn read(path: &str) -> Result<Checkpoint, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to open checkpoint {}: {}", path, e))?;
+        let mut lines = text.lines().filter(|line| !line.starts_with("PROVENANCE "));
+        if lines.next().map(str::trim) != Some("CHECKPOINT 1") {
+            return Err(format!("{} is not a checkpoint file", path));
+        }
+        let mut field = |key: &str| -> Result<Vec<&str>, String> {
+            let line = lines
+                .next()
+                .ok_or_else(|| format!("Checkpoint is missing {}", key))?;
+            let mut parts = line.split_whitespace();
+            if parts.next() != Some(key) {
+                return Err(format!("Expected {} in checkpoint, got '{}'", key, line));
+            }
+            Ok(parts.collect())
+        };
+        fn number<T: std::str::FromStr>(value: Option<&&str>, key: &str) -> Result<T, String> {
+            value
+                .and_then(|v| v.parse().ok())
+                .ok_or_else(|| format!("Invalid {} in checkpoint", key))
+        }
+
+        let n: usize = number(field("DIMENSION")?.first(), "DIMENSION")?;
+        let iteration = number(field("ITERATION")?.first(), "ITERATION")?;
+        let last_improvement = number(field("LAST_IMPROVEMENT")?.first(), "LAST_IMPROVEMENT")?;
+        let seed = match field("SEED")?.first() {
+            Some(&"none") => None,
+            value => Some(number(value, "SEED")?),
+        };
+        let provenance = text
+            .lines()
+            .find_map(|line| line.strip_prefix("PROVENANCE "))
+            .map(|recorded| {
+                let mut parts = recorded.splitn(3, ' ');
+                let mut part = || parts.next().unwrap_or_default().to_string();
+                Provenance {
+                    instance_hash: part(),
+                    version: part(),
+                    config: part(),
+                    seed,
+                }
+            });
+        let best = field("BEST")?;
+        let best_length = number(best.first(), "BEST")?;
+        let best_tour = best[1..]
+            .iter()
+            .map(|node| number(Some(node), "BEST tour"))
+            .collect::<Result<Vec<usize>, _>>()?;
+        if !best_tour.is_empty() && best_tour.len() != n {
+            return Err(format!(
+                "Checkpoint tour has {} nodes, expected {}",
+                best_tour.len(),
+                n
+            ));
+        }
+        field("PHEROMONE")?;
+        let pheromone = lines
+            .by_ref()
+            .take(n)
+            .map(|line| {
+                let row = line
+                    .split_whitespace()
+                    .map(|level| number(Some(&level), "PHEROMONE level"))
+                    .collect::<Result<Vec<f64>, _>>()?;
+                if row.len() == n {
+                    Ok(row)
+                } else {
+                    Err(format!(
+                        "Pheromone row has {} levels, expected {}",
+                        row.len(),
+                        n
+                    ))
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if pheromone.len() != n {
+            return Err(format!(
+                "Checkpoint has {} pheromone rows, expected {}",
+                pheromone.len(),
+                n
+            ));
+        }
+        Ok(Checkpoint {
+            iteration,
+            last_improvement,
+            seed,
+            best_tour,
+            best_length,
+            pheromone,
+            provenance,
+        })
+    }
+}
+
+/// Makes SIGTERM and SIGINT request a graceful stop instead of killing the
+/// process: running solvers finish their iteration, write a checkpoint and
+/// return. A second signal terminates immediately. No-op off Unix.
+pub fn install_shutdown_handler() {
+    #[cfg(unix)]
+    {
+        const SIGINT: i32 = 2;
+        const SIGTERM: i32 = 15;
+        const SIG_DFL: usize = 0;
+        unsafe extern "C" {
+            fn signal(signum: i32, handler: usize) -> usize;
+        }
+        extern "C" fn handle(signum: i32) {
+            SHUTDOWN_REQUESTED.store(true, Ordering::SeqCst);
+            // Restore the default action for the next signal.
+            unsafe { signal(signum, SIG_DFL) };
+        }
+        unsafe {
+            signal(SIGINT, handle as extern "C" fn(i32) as usize);
+            signal(SIGTERM, handle as extern "C" fn(i32) as usize);
+        }
+    }
+}
+
+/// Whether a stop was requested by a signal since the handler was installed.
+pub fn shutdown_requested() -> bool {
+    SHUTDOWN_REQUESTED.load(Ordering::SeqCst)
+}
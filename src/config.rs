@@ -1,15 +1,157 @@
+use crate::checkpoint::Checkpoint;
+use crate::distributed::{ColonyRanges, DistributedRole};
+use crate::events::SolverEvent;
+use crate::local_search::{self, LocalSearch, LocalSearchTarget};
+use crate::matrix_import::RoadMetric;
+use crate::memory;
+use crate::parser::{DistancePolicy, ParseOptions};
+use crate::portfolio::PORTFOLIO_MEMORY_BUDGET;
+use crate::report::ReportFormat;
+use crate::routing::{Depot, RouteLimits};
+use crate::solution::LengthPrecision;
+use crate::solver::{
+    Ablation, DepositSchedule, EvaporationModel, HeuristicStrategy, InitStrategy, Perturbation,
+    ReheatSchedule, ReheatTrigger, StartPolicy, TurnPenalty,
+};
+use crate::stopping::{NoImprovementFor, StoppingCriterion, TargetLength, TimeLimit};
+use std::sync::Arc;
+use std::sync::mpsc::Sender;
+use std::time::Duration;
+
+/// What the binary does with the parsed configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Solve `file_path` (the default).
+    Solve,
+    /// `watch <dir>`: solve instance files dropped into a directory.
+    Watch(String),
+    /// `replay <file> <svg>`: render a recorded run.
+    Replay { replay: String, svg: String },
+    /// `sensitivity <file>`: vary one parameter at a time around the config.
+    Sensitivity,
+    /// `tune <file>`: race random configurations against the current one.
+    Tune,
+    /// `robustness <file>`: re-solve under distance noise and compare with
+    /// the tour found on the original data.
+    Robustness,
+    /// `bench <dir|file>`: seeded runs on every instance, compared with the
+    /// best-known lengths (or two configurations with each other).
+    Bench,
+    /// `experiment <name> <dir|file>`: seeded runs on every instance, kept
+    /// in a timestamped session directory, see [`crate::experiment`].
+    Experiment(String),
+    /// `diff-snapshots <a> <b>`: first iteration where two recorded runs
+    /// differ.
+    DiffSnapshots { left: String, right: String },
+}
+
+/// Which algorithm solves the instance.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Backend {
+    /// ACO on the whole instance.
+    Aco,
+    /// Cluster-first, route-second with the given number of clusters.
+    Decomposed(usize),
+    /// Parallel partition-and-merge with the given tiles per side.
+    Partitioned(usize),
+    /// Multilevel coarsening down to the given number of nodes.
+    Multilevel(usize),
+    /// Picked from the instance's features, see [`crate::portfolio`].
+    Portfolio,
+}
+
 #[derive(Debug, Clone)]
 pub struct Config {
     pub file_path: Option<String>,
     pub num_iters: usize,
+    pub tour_budget: Option<usize>, // Stop after this many ant tours instead of num_iters iterations
     pub num_ants: usize,
+    pub start_policy: StartPolicy,
     pub alpha: f64,     // Pheromone influence
     pub beta: f64,      // Heuristic influence
     pub evap_rate: f64, // Rho
-    pub q_val: f64,     // Pheromone deposit amount scaling factor
-    pub init_pheromone: f64,
+    pub evaporation: EvaporationModel,
+    pub q_val: f64,              // Pheromone deposit amount scaling factor
+    pub normalize_deposit: bool, // Deposit q_val * L_nn / L, independent of the distance scale
+    pub init_pheromone: InitStrategy,
     pub elitist_weight: f64, // Weight for the elitist ant's pheromone deposit
     pub min_pheromone_val: f64, // Minimum pheromone value
+    pub max_dimension: usize,
+    pub max_memory: Option<usize>, // Bytes; selects dense, triangular or lazy storage
+    pub candidate_list_size: usize, // Nearest neighbours ants choose from (0 = all nodes)
+    pub spatial_pruning: bool,     // Find candidates in nearby grid cells only (planar coordinates)
+    pub distance_policy: Option<DistancePolicy>, // None: the metric's default // Larger instances are rejected instead of allocated
+    pub backend: Backend,
+    pub time_budget: Option<Duration>, // Sizes ACO runs to end near it; shared by the portfolio's stages (None there: 10 s)
+    pub online_interval: usize,        // Iterations between incorporating streamed cities
+    pub length_check_interval: usize, // Iterations between exact recomputations of the best length (0 = never)
+    pub verify_lengths: bool, // Panic if an improved best length differs from its exact recomputation
+    pub integer_costs: bool,  // Accumulate tour lengths as TSPLIB-rounded i64
+    pub stagnation_limit: usize, // Iterations without improvement before a Stagnation event
+    pub ablation: Option<Ablation>, // Set with Ablation::apply, which also zeroes the exponents
+    pub ablations: bool,      // Bench mode: also run every ablation
+    pub reheat: Option<ReheatSchedule>, // Perturb the pheromone trails on a schedule
+    pub deposit_schedule: Option<DepositSchedule>, // None: every ant deposits, plus the elitist global best
+    pub stopping: Vec<Arc<dyn StoppingCriterion>>, // Stop early when any is met (besides num_iters)
+    pub event_sender: Option<Sender<SolverEvent>>,
+    pub heuristic: HeuristicStrategy,
+    pub warm_start: Option<Vec<usize>>, // Initial best tour (0-based indices)
+    pub initial_tour: Option<String>,   // TSPLIB .tour file loaded as the warm start
+    pub resume_path: Option<String>,    // Checkpoint to continue from
+    pub resume: Option<Checkpoint>,     // Loaded checkpoint, restored by the solver
+    pub checkpoint_path: Option<String>, // Written on SIGTERM/SIGINT
+    pub ant_chunk: usize,               // Ants constructed per rayon task
+    pub evaporation_chunk: Option<usize>, // Pheromone rows per rayon task; None sizes tasks by the dimension
+    pub pin_threads: bool,                // Pin rayon workers to CPUs (Linux)
+    pub locked_nodes: Vec<usize>, // Keep their warm start positions, only the rest is reoptimized
+    pub pinned_positions: Vec<(usize, usize)>, // (node, position) pairs, both 0-based
+    pub subsequences: Vec<Vec<usize>>, // Chains visited contiguously and in order (0-based)
+    pub pickup_delivery: Vec<(usize, usize)>, // (pickup, delivery) pairs, pickup first (0-based)
+    pub print_legs: bool,         // Print the per-leg breakdown of the best tour
+    pub legs_path: Option<String>, // CSV export of the per-leg breakdown
+    pub solution_path: Option<String>, // JSON export of the Solution
+    pub cache_dir: Option<String>, // Reuse solutions of identical seeded runs stored here
+    pub visits_path: Option<String>, // CSV or GeoJSON export of the stops in visit order
+    pub node_data_path: Option<String>, // CSV of node metadata joined on node ids
+    pub report_format: ReportFormat, // How the binary presents the run
+    pub gap_precision: LengthPrecision, // Tour length compared with known optima
+    pub tour_path: Option<String>, // Full tour as node ids (TSPLIB format for .tour paths)
+    pub depots: Vec<Depot>,       // Non-empty switches to multi-depot mTSP
+    pub route_limits: RouteLimits, // Capacity and route length limits in mTSP mode
+    pub demands: Vec<f64>,        // Per-node demand for the capacity limit (empty = 1 per city)
+    pub turn_penalty: Option<TurnPenalty>, // Cost of (prev, current, next) transitions
+    pub turn_weight: Option<f64>, // CLI: turning angle penalty per radian, becomes turn_penalty
+    pub turn_in_heuristic: bool,  // CLI: sets TurnPenalty::in_heuristic
+    pub matrix_metric: RoadMetric, // Weight read from routing API .json matrices
+    pub local_search: Vec<Arc<dyn LocalSearch>>, // Applied in order
+    pub local_search_target: LocalSearchTarget,
+    pub pheromone_export: Option<String>, // CSV or .npy path for the final pheromone matrix
+    pub pheromone_export_interval: usize, // Also export every k iterations (0 = final only)
+    pub distributed: Option<DistributedRole>,
+    pub exchange_interval: usize, // Iterations between tour exchanges with the coordinator
+    pub colony_ranges: Option<ColonyRanges>, // Workers draw alpha/beta/rho from these and pass on successful ones
+    pub dashboard_addr: Option<String>,      // Serve a live web dashboard while solving
+    pub replay_path: Option<String>,         // Record new best tours for later playback
+    pub replay_pheromone_interval: usize,    // Pheromone snapshot every k iterations (0 = none)
+    pub replay_ants_interval: usize,         // Sampled ant tours every k iterations (0 = none)
+    pub replay_ants: usize,                  // Ants sampled per recorded iteration
+    pub snapshot_path: Option<String>,       // Pheromone and best tour hashes after every iteration
+    pub command: Command,
+    pub seed: Option<u64>,   // Makes runs reproducible; None draws from the OS
+    pub num_seeds: usize, // Seeded runs per setting in sensitivity analysis (samples per noise level in robustness mode)
+    pub seed_list: Vec<u64>, // Explicit seeds of experiment mode (empty = num_seeds from seed)
+    pub experiment_config: Option<String>, // Config file applied and archived in experiment mode
+    pub output_dir: String, // Where experiment mode creates its session directories
+    pub noise_levels: Vec<f64>, // Relative distance noise levels of robustness mode
+    pub plot_path: Option<String>, // SVG plot of sensitivity results
+    pub csv_path: Option<String>, // CSV of per-instance benchmark results
+    pub edge_frequency_path: Option<String>, // CSV or SVG of edges in the runs' best tours
+    pub parquet_path: Option<String>, // Parquet file of bench/experiment runs (feature `parquet`)
+    pub sqlite_path: Option<String>, // SQLite log of bench/experiment runs (feature `sqlite`)
+    pub compare_configs: Option<(String, String)>, // Config files compared in bench mode
+    pub target_gaps: Vec<f64>, // Gaps (percent) whose time to target bench mode reports
+    pub tune_candidates: usize, // Configurations entering the tuning race
+    pub tune_budget: usize, // Solver runs the tuning race may use
 }
 
 impl Default for Config {
@@ -17,14 +159,94 @@ impl Default for Config {
         Config {
             file_path: None,
             num_iters: 1000,
+            tour_budget: None,
             num_ants: 50,
+            start_policy: StartPolicy::Random,
             alpha: 1.0,
             beta: 3.0,
             evap_rate: 0.1,
+            evaporation: EvaporationModel::Uniform,
             q_val: 100.0,
-            init_pheromone: 0.1,
+            normalize_deposit: false,
+            init_pheromone: InitStrategy::Constant(0.1),
             elitist_weight: 1.0, // e.g. 1 means global best adds pheromone like one ant
             min_pheromone_val: 1e-5,
+            max_dimension: 20_000,
+            max_memory: None,
+            candidate_list_size: 0,
+            spatial_pruning: false,
+            distance_policy: None,
+            backend: Backend::Aco,
+            time_budget: None,
+            online_interval: 10,
+            length_check_interval: 100,
+            verify_lengths: false,
+            integer_costs: false,
+            stagnation_limit: 100,
+            ablation: None,
+            ablations: false,
+            reheat: None,
+            deposit_schedule: None,
+            stopping: Vec::new(),
+            event_sender: None,
+            heuristic: HeuristicStrategy::InverseDistance,
+            warm_start: None,
+            initial_tour: None,
+            resume_path: None,
+            resume: None,
+            checkpoint_path: None,
+            ant_chunk: 1,
+            evaporation_chunk: None,
+            pin_threads: false,
+            locked_nodes: Vec::new(),
+            pinned_positions: Vec::new(),
+            subsequences: Vec::new(),
+            pickup_delivery: Vec::new(),
+            print_legs: false,
+            legs_path: None,
+            solution_path: None,
+            cache_dir: None,
+            visits_path: None,
+            node_data_path: None,
+            report_format: ReportFormat::Console,
+            gap_precision: LengthPrecision::Rounded,
+            tour_path: None,
+            depots: Vec::new(),
+            route_limits: RouteLimits::default(),
+            demands: Vec::new(),
+            turn_penalty: None,
+            turn_weight: None,
+            turn_in_heuristic: false,
+            matrix_metric: RoadMetric::Distance,
+            local_search: Vec::new(),
+            local_search_target: LocalSearchTarget::GlobalBest,
+            pheromone_export: None,
+            pheromone_export_interval: 0,
+            distributed: None,
+            exchange_interval: 50,
+            colony_ranges: None,
+            dashboard_addr: None,
+            replay_path: None,
+            replay_pheromone_interval: 0,
+            replay_ants_interval: 0,
+            replay_ants: 5,
+            snapshot_path: None,
+            command: Command::Solve,
+            seed: None,
+            num_seeds: 5,
+            seed_list: Vec::new(),
+            experiment_config: None,
+            output_dir: "experiments".to_string(),
+            noise_levels: vec![0.01, 0.05, 0.1, 0.2],
+            plot_path: None,
+            csv_path: None,
+            edge_frequency_path: None,
+            parquet_path: None,
+            sqlite_path: None,
+            compare_configs: None,
+            target_gaps: vec![5.0, 2.0, 1.0, 0.0],
+            tune_candidates: 16,
+            tune_budget: 200,
         }
     }
 }
@@ -33,7 +255,26 @@ impl Config {
     pub fn build(mut args: impl Iterator<Item = String>) -> Result<Config, &'static str> {
         args.next();
 
-        let mut config = Config::default();
+        let config = Config::default().with_args(args)?;
+        let needs_file = matches!(
+            config.command,
+            Command::Solve
+                | Command::Sensitivity
+                | Command::Tune
+                | Command::Robustness
+                | Command::Bench
+                | Command::Experiment(_)
+        );
+        if needs_file && config.file_path.is_none() {
+            return Err("TSPLIB file path not provided");
+        }
+
+        Ok(config)
+    }
+
+    /// `self` with the command line options in `args` applied on top.
+    pub fn with_args(self, mut args: impl Iterator<Item = String>) -> Result<Config, &'static str> {
+        let mut config = self;
 
         while let Some(arg) = args.next() {
             match arg.as_str() {
@@ -51,6 +292,14 @@ impl Config {
                         .parse()
                         .map_err(|_| "Invalid number for --iters")?
                 }
+                "--tour-budget" => {
+                    config.tour_budget = Some(
+                        args.next()
+                            .ok_or("Missing value for --tour-budget")?
+                            .parse()
+                            .map_err(|_| "Invalid number for --tour-budget")?,
+                    )
+                }
                 "-a" | "--alpha" => {
                     config.alpha = args
                         .next()
@@ -79,12 +328,21 @@ impl Config {
                         .parse()
                         .map_err(|_| "Invalid number for --q-val")?
                 }
+                "--normalize-deposit" => config.normalize_deposit = true,
                 "-p" | "--init-pheromone" => {
-                    config.init_pheromone = args
+                    config.init_pheromone = match args
                         .next()
                         .ok_or("Missing value for --init-pheromone")?
-                        .parse()
-                        .map_err(|_| "Invalid number for --init-pheromone")?
+                        .as_str()
+                    {
+                        "nn" => InitStrategy::FromNearestNeighbor,
+                        "greedy" => InitStrategy::FromGreedy,
+                        value => InitStrategy::Constant(
+                            value
+                                .parse()
+                                .map_err(|_| "Invalid value for --init-pheromone")?,
+                        ),
+                    }
                 }
                 "-w" | "--elitist-weight" => {
                     config.elitist_weight = args
@@ -100,16 +358,776 @@ impl Config {
                         .parse()
                         .map_err(|_| "Invalid number for --min-pheromone-val")?
                 }
+                "--max-dimension" => {
+                    config.max_dimension = args
+                        .next()
+                        .ok_or("Missing value for --max-dimension")?
+                        .parse()
+                        .map_err(|_| "Invalid number for --max-dimension")?
+                }
+                "--max-memory" => {
+                    config.max_memory = Some(
+                        memory::parse_byte_size(
+                            &args.next().ok_or("Missing value for --max-memory")?,
+                        )
+                        .ok_or("Invalid size for --max-memory (e.g. 512M or 8G)")?,
+                    )
+                }
+                "--candidate-list" => {
+                    config.candidate_list_size = args
+                        .next()
+                        .ok_or("Missing value for --candidate-list")?
+                        .parse()
+                        .map_err(|_| "Invalid number for --candidate-list")?
+                }
+                "--spatial-pruning" => config.spatial_pruning = true,
+                "--distance-policy" => {
+                    let value = args.next().ok_or("Missing value for --distance-policy")?;
+                    config.distance_policy = Some(match value.as_str() {
+                        "exact" => DistancePolicy::Exact,
+                        "nint" => DistancePolicy::Nint,
+                        "ceil" => DistancePolicy::Ceil,
+                        "floor" => DistancePolicy::Floor,
+                        _ => match value.strip_prefix("scale:") {
+                            Some(factor) => DistancePolicy::Scale(
+                                factor
+                                    .parse()
+                                    .map_err(|_| "Invalid factor for --distance-policy")?,
+                            ),
+                            None => {
+                                return Err(
+                                    "Invalid value for --distance-policy (expected exact, nint, ceil, floor or scale:<f>)",
+                                );
+                            }
+                        },
+                    })
+                }
+                "--decompose" => {
+                    config.backend = Backend::Decomposed(
+                        args.next()
+                            .ok_or("Missing value for --decompose")?
+                            .parse()
+                            .map_err(|_| "Invalid number for --decompose")?,
+                    )
+                }
+                "--partition" => {
+                    config.backend = Backend::Partitioned(
+                        args.next()
+                            .ok_or("Missing value for --partition")?
+                            .parse()
+                            .map_err(|_| "Invalid number for --partition")?,
+                    )
+                }
+                "--multilevel" => {
+                    config.backend = Backend::Multilevel(
+                        args.next()
+                            .ok_or("Missing value for --multilevel")?
+                            .parse()
+                            .map_err(|_| "Invalid number for --multilevel")?,
+                    )
+                }
+                "--auto" => config.backend = Backend::Portfolio,
+                "--time-budget" => {
+                    let seconds: f64 = args
+                        .next()
+                        .ok_or("Missing value for --time-budget")?
+                        .parse()
+                        .map_err(|_| "Invalid number for --time-budget")?;
+                    config.time_budget = Some(
+                        Duration::try_from_secs_f64(seconds)
+                            .map_err(|_| "Invalid number for --time-budget")?,
+                    );
+                }
+                "--integer-costs" => config.integer_costs = true,
+                "--length-check-interval" => {
+                    config.length_check_interval = args
+                        .next()
+                        .ok_or("Missing value for --length-check-interval")?
+                        .parse()
+                        .map_err(|_| "Invalid number for --length-check-interval")?
+                }
+                "--verify-lengths" => config.verify_lengths = true,
+                "--export-pheromone" => {
+                    config.pheromone_export =
+                        Some(args.next().ok_or("Missing value for --export-pheromone")?)
+                }
+                "--coordinator" => {
+                    let addr = args.next().ok_or("Missing value for --coordinator")?;
+                    let workers = args
+                        .next()
+                        .ok_or("Missing worker count for --coordinator")?
+                        .parse()
+                        .map_err(|_| "Invalid worker count for --coordinator")?;
+                    config.distributed = Some(DistributedRole::Coordinator { addr, workers });
+                }
+                "--worker" => {
+                    let addr = args.next().ok_or("Missing value for --worker")?;
+                    config.distributed = Some(DistributedRole::Worker { addr });
+                }
+                "--dashboard" => {
+                    config.dashboard_addr =
+                        Some(args.next().ok_or("Missing value for --dashboard")?)
+                }
+                "--record" => {
+                    config.replay_path = Some(args.next().ok_or("Missing value for --record")?)
+                }
+                "--snapshots" => {
+                    config.snapshot_path = Some(args.next().ok_or("Missing value for --snapshots")?)
+                }
+                "--record-pheromone" => {
+                    config.replay_pheromone_interval = args
+                        .next()
+                        .ok_or("Missing value for --record-pheromone")?
+                        .parse()
+                        .map_err(|_| "Invalid number for --record-pheromone")?
+                }
+                "--record-ants" => {
+                    // "10" or "10:20": every 10 iterations, 20 ants (default 5).
+                    let value = args.next().ok_or("Missing value for --record-ants")?;
+                    let (interval, count) = match value.split_once(':') {
+                        Some((interval, count)) => (interval, Some(count)),
+                        None => (value.as_str(), None),
+                    };
+                    config.replay_ants_interval = interval
+                        .parse()
+                        .map_err(|_| "Invalid value for --record-ants (expected K or K:M)")?;
+                    if let Some(count) = count {
+                        config.replay_ants = count
+                            .parse()
+                            .map_err(|_| "Invalid value for --record-ants (expected K or K:M)")?;
+                    }
+                }
+                "--colony-ranges" => {
+                    // "alpha=0.5:2,beta=2:5,rho=0.02:0.2"; omitted parameters stay fixed.
+                    let value = args.next().ok_or("Missing value for --colony-ranges")?;
+                    let mut ranges = ColonyRanges::default();
+                    for entry in value.split(',') {
+                        let (name, range) = entry
+                            .split_once('=')
+                            .ok_or("Invalid entry in --colony-ranges (expected name=low:high)")?;
+                        let range = range
+                            .split_once(':')
+                            .and_then(|(low, high)| Some((low.parse().ok()?, high.parse().ok()?)))
+                            .filter(|(low, high): &(f64, f64)| low <= high)
+                            .ok_or("Invalid range in --colony-ranges (expected low:high)")?;
+                        match name.trim() {
+                            "alpha" => ranges.alpha = Some(range),
+                            "beta" => ranges.beta = Some(range),
+                            "rho" => ranges.evap_rate = Some(range),
+                            _ => {
+                                return Err(
+                                    "Unknown parameter in --colony-ranges (expected alpha, beta or rho)",
+                                );
+                            }
+                        }
+                    }
+                    config.colony_ranges = Some(ranges);
+                }
+                "--exchange-interval" => {
+                    config.exchange_interval = args
+                        .next()
+                        .ok_or("Missing value for --exchange-interval")?
+                        .parse()
+                        .map_err(|_| "Invalid number for --exchange-interval")?
+                }
+                "--export-interval" => {
+                    config.pheromone_export_interval = args
+                        .next()
+                        .ok_or("Missing value for --export-interval")?
+                        .parse()
+                        .map_err(|_| "Invalid number for --export-interval")?
+                }
+                "-s" | "--stagnation-limit" => {
+                    config.stagnation_limit = args
+                        .next()
+                        .ok_or("Missing value for --stagnation-limit")?
+                        .parse()
+                        .map_err(|_| "Invalid number for --stagnation-limit")?
+                }
+                "--start" => {
+                    config.start_policy =
+                        match args.next().ok_or("Missing value for --start")?.as_str() {
+                            "random" => StartPolicy::Random,
+                            "round-robin" => StartPolicy::RoundRobin,
+                            "one-per-node" => StartPolicy::OnePerNode,
+                            // 1-based like node ids: "--start 1" starts every ant at node 1.
+                            node => StartPolicy::FixedNode(
+                                node.parse::<usize>()
+                                    .ok()
+                                    .and_then(|node| node.checked_sub(1))
+                                    .ok_or(
+                                        "Invalid value for --start (expected random, round-robin, one-per-node or a node number)",
+                                    )?,
+                            ),
+                        }
+                }
+                "--ablation" => {
+                    config.ablation = Some(
+                        Ablation::from_name(&args.next().ok_or("Missing value for --ablation")?)
+                            .ok_or(
+                                "Invalid value for --ablation (expected heuristic-only, pheromone-only or random)",
+                            )?,
+                    )
+                }
+                "--ablations" => config.ablations = true,
+                "--evaporation" => {
+                    // "uniform", "usage" or "rank:5" (partial evaporation by the 5 best tours).
+                    let value = args.next().ok_or("Missing value for --evaporation")?;
+                    config.evaporation = match value.split_once(':') {
+                        None if value == "uniform" => EvaporationModel::Uniform,
+                        None if value == "usage" => EvaporationModel::Usage,
+                        Some(("rank", k)) => EvaporationModel::Rank(
+                            k.parse()
+                                .ok()
+                                .filter(|&k| k > 0)
+                                .ok_or("Invalid tour count for --evaporation rank")?,
+                        ),
+                        _ => {
+                            return Err(
+                                "Invalid value for --evaporation (expected uniform, usage or rank:K)",
+                            );
+                        }
+                    };
+                }
+                "--deposit" => {
+                    config.deposit_schedule = Some(
+                        match args.next().ok_or("Missing value for --deposit")?.as_str() {
+                            "iteration-best" => DepositSchedule::IterationBest,
+                            "global-best" => DepositSchedule::GlobalBest,
+                            "mmas" => DepositSchedule::Mmas,
+                            _ => {
+                                return Err(
+                                    "Invalid value for --deposit (expected iteration-best, global-best or mmas)",
+                                );
+                            }
+                        },
+                    )
+                }
+                "--reheat" => {
+                    // "blend:0.5" or "noise:0.3", on stagnation unless --reheat-every is given.
+                    let value = args.next().ok_or("Missing value for --reheat")?;
+                    let perturbation = match value.split_once(':') {
+                        Some(("blend", amount)) => amount.parse().ok().map(Perturbation::Blend),
+                        Some(("noise", amount)) => amount.parse().ok().map(Perturbation::Noise),
+                        _ => None,
+                    }
+                    .ok_or("Invalid value for --reheat (expected blend:F or noise:F)")?;
+                    let trigger = config
+                        .reheat
+                        .map_or(ReheatTrigger::OnStagnation, |schedule| schedule.trigger);
+                    config.reheat = Some(ReheatSchedule {
+                        perturbation,
+                        trigger,
+                    });
+                }
+                "--reheat-every" => {
+                    let iterations = args
+                        .next()
+                        .ok_or("Missing value for --reheat-every")?
+                        .parse()
+                        .map_err(|_| "Invalid number for --reheat-every")?;
+                    let perturbation = config
+                        .reheat
+                        .map_or(Perturbation::Blend(0.5), |schedule| schedule.perturbation);
+                    config.reheat = Some(ReheatSchedule {
+                        perturbation,
+                        trigger: ReheatTrigger::Every(iterations),
+                    });
+                }
+                "--time-limit" => {
+                    let seconds: f64 = args
+                        .next()
+                        .ok_or("Missing value for --time-limit")?
+                        .parse()
+                        .map_err(|_| "Invalid number for --time-limit")?;
+                    let limit = Duration::try_from_secs_f64(seconds)
+                        .map_err(|_| "Invalid number for --time-limit")?;
+                    config.stopping.push(Arc::new(TimeLimit(limit)));
+                }
+                "--target-length" => {
+                    let target = args
+                        .next()
+                        .ok_or("Missing value for --target-length")?
+                        .parse()
+                        .map_err(|_| "Invalid number for --target-length")?;
+                    config.stopping.push(Arc::new(TargetLength(target)));
+                }
+                "--no-improvement" => {
+                    let iterations = args
+                        .next()
+                        .ok_or("Missing value for --no-improvement")?
+                        .parse()
+                        .map_err(|_| "Invalid number for --no-improvement")?;
+                    config.stopping.push(Arc::new(NoImprovementFor(iterations)));
+                }
+                "--heuristic" => {
+                    let value = args.next().ok_or("Missing value for --heuristic")?;
+                    config.heuristic = match value.as_str() {
+                        "inverse" => HeuristicStrategy::InverseDistance,
+                        "savings" => HeuristicStrategy::Savings,
+                        _ => match value.strip_prefix("offset:") {
+                            Some(c) => HeuristicStrategy::InverseDistanceOffset(
+                                c.parse().map_err(|_| "Invalid offset for --heuristic")?,
+                            ),
+                            None => {
+                                return Err(
+                                    "Invalid value for --heuristic (expected inverse, offset:<c> or savings)",
+                                );
+                            }
+                        },
+                    }
+                }
+                "-l" | "--local-search" => {
+                    let names = args.next().ok_or("Missing value for --local-search")?;
+                    config.local_search = names
+                        .split(',')
+                        .map(|name| {
+                            local_search::local_search_by_name(name.trim())
+                                .ok_or("Unknown local search (expected 2opt, oropt, 3opt or popmusic[:size])")
+                        })
+                        .collect::<Result<_, _>>()?;
+                }
+                "--ls-target" => {
+                    config.local_search_target =
+                        match args.next().ok_or("Missing value for --ls-target")?.as_str() {
+                            "ants" => LocalSearchTarget::AntTours,
+                            "best" => LocalSearchTarget::GlobalBest,
+                            "both" => LocalSearchTarget::Both,
+                            _ => {
+                                return Err(
+                                    "Invalid value for --ls-target (expected ants, best or both)",
+                                );
+                            }
+                        }
+                }
+                "--seed" => {
+                    config.seed = Some(
+                        args.next()
+                            .ok_or("Missing value for --seed")?
+                            .parse()
+                            .map_err(|_| "Invalid number for --seed")?,
+                    )
+                }
+                "--seeds" => {
+                    config.num_seeds = args
+                        .next()
+                        .ok_or("Missing value for --seeds")?
+                        .parse()
+                        .map_err(|_| "Invalid number for --seeds")?
+                }
+                "--seed-list" => {
+                    config.seed_list = args
+                        .next()
+                        .ok_or("Missing value for --seed-list")?
+                        .split(',')
+                        .map(|seed| seed.trim().parse())
+                        .collect::<Result<_, _>>()
+                        .map_err(|_| "Invalid list for --seed-list (e.g. 1,2,3)")?
+                }
+                "--config" => {
+                    config.experiment_config =
+                        Some(args.next().ok_or("Missing value for --config")?)
+                }
+                "--output-dir" => {
+                    config.output_dir = args.next().ok_or("Missing value for --output-dir")?
+                }
+                "--noise-levels" => {
+                    config.noise_levels = args
+                        .next()
+                        .ok_or("Missing value for --noise-levels")?
+                        .split(',')
+                        .map(|level| level.trim().parse())
+                        .collect::<Result<_, _>>()
+                        .map_err(|_| "Invalid list for --noise-levels (e.g. 0.01,0.05,0.1)")?
+                }
+                "--pin" => {
+                    // 1-based on the command line: "--pin 7:3" visits node 7 third.
+                    let value = args.next().ok_or("Missing value for --pin")?;
+                    let (node, position) = value
+                        .split_once(':')
+                        .ok_or("--pin expects <node>:<position>")?;
+                    let node: usize = node.parse().map_err(|_| "Invalid node for --pin")?;
+                    let position: usize =
+                        position.parse().map_err(|_| "Invalid position for --pin")?;
+                    if node == 0 || position == 0 {
+                        return Err("--pin node and position are 1-based");
+                    }
+                    config.pinned_positions.push((node - 1, position - 1));
+                }
+                "--sequence" => {
+                    // 1-based node ids, e.g. "--sequence 4,9,2".
+                    let value = args.next().ok_or("Missing value for --sequence")?;
+                    let chain = value
+                        .split(',')
+                        .map(|id| id.trim().parse::<usize>().ok().filter(|&id| id > 0))
+                        .collect::<Option<Vec<_>>>()
+                        .ok_or("Invalid node list for --sequence")?;
+                    config
+                        .subsequences
+                        .push(chain.into_iter().map(|id| id - 1).collect());
+                }
+                "--depot" => {
+                    // "--depot 5:2" bases two salesmen at node 5; one by default.
+                    let value = args.next().ok_or("Missing value for --depot")?;
+                    let (node, salesmen) = value.split_once(':').unwrap_or((&value, "1"));
+                    let node: usize = node
+                        .parse()
+                        .ok()
+                        .filter(|&id| id > 0)
+                        .ok_or("Invalid node for --depot")?;
+                    let salesmen: usize = salesmen
+                        .parse()
+                        .map_err(|_| "Invalid salesmen count for --depot")?;
+                    config.depots.push(Depot {
+                        node: node - 1,
+                        salesmen,
+                    });
+                }
+                "--capacity" => {
+                    config.route_limits.capacity = Some(
+                        args.next()
+                            .ok_or("Missing value for --capacity")?
+                            .parse()
+                            .map_err(|_| "Invalid number for --capacity")?,
+                    )
+                }
+                "--max-route-length" => {
+                    config.route_limits.max_length = Some(
+                        args.next()
+                            .ok_or("Missing value for --max-route-length")?
+                            .parse()
+                            .map_err(|_| "Invalid number for --max-route-length")?,
+                    )
+                }
+                "--capacity-penalty" => {
+                    config.route_limits.capacity_penalty = args
+                        .next()
+                        .ok_or("Missing value for --capacity-penalty")?
+                        .parse()
+                        .map_err(|_| "Invalid number for --capacity-penalty")?
+                }
+                "--length-penalty" => {
+                    config.route_limits.length_penalty = args
+                        .next()
+                        .ok_or("Missing value for --length-penalty")?
+                        .parse()
+                        .map_err(|_| "Invalid number for --length-penalty")?
+                }
+                "--turn-penalty" => {
+                    config.turn_weight = Some(
+                        args.next()
+                            .ok_or("Missing value for --turn-penalty")?
+                            .parse()
+                            .map_err(|_| "Invalid number for --turn-penalty")?,
+                    )
+                }
+                "--turn-heuristic" => config.turn_in_heuristic = true,
+                "--metric" => {
+                    config.matrix_metric = match args.next().as_deref() {
+                        Some("distance") => RoadMetric::Distance,
+                        Some("duration") => RoadMetric::Duration,
+                        _ => return Err("--metric expects distance or duration"),
+                    }
+                }
+                "--legs" => config.print_legs = true,
+                "--visits" => {
+                    config.visits_path = Some(args.next().ok_or("Missing value for --visits")?)
+                }
+                "--node-data" => {
+                    config.node_data_path =
+                        Some(args.next().ok_or("Missing value for --node-data")?)
+                }
+                "--legs-csv" => {
+                    config.legs_path = Some(args.next().ok_or("Missing value for --legs-csv")?)
+                }
+                "--gap-precision" => {
+                    config.gap_precision = match args
+                        .next()
+                        .ok_or("Missing value for --gap-precision")?
+                        .as_str()
+                    {
+                        "rounded" => LengthPrecision::Rounded,
+                        "exact" => LengthPrecision::Exact,
+                        _ => {
+                            return Err(
+                                "Invalid value for --gap-precision (expected rounded or exact)",
+                            );
+                        }
+                    }
+                }
+                "--report" => {
+                    config.report_format = match args
+                        .next()
+                        .ok_or("Missing value for --report")?
+                        .as_str()
+                    {
+                        "console" => ReportFormat::Console,
+                        "json" => ReportFormat::Json,
+                        "ndjson" => ReportFormat::Ndjson,
+                        "silent" => ReportFormat::Silent,
+                        _ => {
+                            return Err(
+                                "Invalid value for --report (expected console, json, ndjson or silent)",
+                            );
+                        }
+                    }
+                }
+                "--tour-out" => {
+                    config.tour_path = Some(args.next().ok_or("Missing value for --tour-out")?)
+                }
+                "--save-solution" => {
+                    config.solution_path =
+                        Some(args.next().ok_or("Missing value for --save-solution")?)
+                }
+                "--cache" => {
+                    config.cache_dir = Some(args.next().ok_or("Missing value for --cache")?)
+                }
+                "--pair" => {
+                    // 1-based: "--pair 3:8" picks up at node 3 and delivers at node 8.
+                    let value = args.next().ok_or("Missing value for --pair")?;
+                    let (pickup, delivery) = value
+                        .split_once(':')
+                        .ok_or("--pair expects <pickup>:<delivery>")?;
+                    let pickup: usize = pickup.parse().map_err(|_| "Invalid pickup for --pair")?;
+                    let delivery: usize = delivery
+                        .parse()
+                        .map_err(|_| "Invalid delivery for --pair")?;
+                    if pickup == 0 || delivery == 0 {
+                        return Err("--pair nodes are 1-based");
+                    }
+                    config.pickup_delivery.push((pickup - 1, delivery - 1));
+                }
+                "--resume" => {
+                    config.resume_path = Some(args.next().ok_or("Missing value for --resume")?)
+                }
+                "--ant-chunk" => {
+                    config.ant_chunk = args
+                        .next()
+                        .ok_or("Missing value for --ant-chunk")?
+                        .parse()
+                        .map_err(|_| "Invalid number for --ant-chunk")?
+                }
+                "--evaporation-chunk" => {
+                    config.evaporation_chunk = Some(
+                        args.next()
+                            .ok_or("Missing value for --evaporation-chunk")?
+                            .parse()
+                            .map_err(|_| "Invalid number for --evaporation-chunk")?,
+                    )
+                }
+                "--pin-threads" => config.pin_threads = true,
+                "--checkpoint" => {
+                    config.checkpoint_path =
+                        Some(args.next().ok_or("Missing value for --checkpoint")?)
+                }
+                "--initial-tour" => {
+                    config.initial_tour =
+                        Some(args.next().ok_or("Missing value for --initial-tour")?)
+                }
+                "--candidates" => {
+                    config.tune_candidates = args
+                        .next()
+                        .ok_or("Missing value for --candidates")?
+                        .parse()
+                        .map_err(|_| "Invalid number for --candidates")?
+                }
+                "--budget" => {
+                    config.tune_budget = args
+                        .next()
+                        .ok_or("Missing value for --budget")?
+                        .parse()
+                        .map_err(|_| "Invalid number for --budget")?
+                }
+                "--plot" => config.plot_path = Some(args.next().ok_or("Missing value for --plot")?),
+                "--csv" => config.csv_path = Some(args.next().ok_or("Missing value for --csv")?),
+                "--edge-frequency" => {
+                    config.edge_frequency_path =
+                        Some(args.next().ok_or("Missing value for --edge-frequency")?)
+                }
+                "--parquet" => {
+                    if !cfg!(feature = "parquet") {
+                        return Err("--parquet requires building with the `parquet` feature");
+                    }
+                    config.parquet_path = Some(args.next().ok_or("Missing value for --parquet")?)
+                }
+                "--sqlite" => {
+                    if !cfg!(feature = "sqlite") {
+                        return Err("--sqlite requires building with the `sqlite` feature");
+                    }
+                    config.sqlite_path = Some(args.next().ok_or("Missing value for --sqlite")?)
+                }
+                // Subcommands are only recognized before the file path.
+                "replay" if config.file_path.is_none() && config.command == Command::Solve => {
+                    let replay = args.next().ok_or("Missing replay file for replay")?;
+                    let svg = args.next().ok_or("Missing output SVG path for replay")?;
+                    config.command = Command::Replay { replay, svg };
+                }
+                "diff-snapshots"
+                    if config.file_path.is_none() && config.command == Command::Solve =>
+                {
+                    let left = args.next().ok_or("Missing snapshot files for diff-snapshots")?;
+                    let right = args.next().ok_or("Missing second snapshot file for diff-snapshots")?;
+                    config.command = Command::DiffSnapshots { left, right };
+                }
+                "watch" if config.file_path.is_none() && config.command == Command::Solve => {
+                    config.command =
+                        Command::Watch(args.next().ok_or("Missing directory for watch")?)
+                }
+                "sensitivity" if config.file_path.is_none() && config.command == Command::Solve => {
+                    config.command = Command::Sensitivity
+                }
+                "tune" if config.file_path.is_none() && config.command == Command::Solve => {
+                    config.command = Command::Tune
+                }
+                "robustness" if config.file_path.is_none() && config.command == Command::Solve => {
+                    config.command = Command::Robustness
+                }
+                "bench" if config.file_path.is_none() && config.command == Command::Solve => {
+                    config.command = Command::Bench
+                }
+                "experiment" if config.file_path.is_none() && config.command == Command::Solve => {
+                    config.command = Command::Experiment(
+                        args.next().ok_or("Missing name for experiment")?,
+                    )
+                }
+                "--target-gaps" => {
+                    config.target_gaps = args
+                        .next()
+                        .ok_or("Missing value for --target-gaps")?
+                        .split(',')
+                        .map(|gap| gap.trim().parse())
+                        .collect::<Result<_, _>>()
+                        .map_err(|_| "Invalid list for --target-gaps (e.g. 5,2,1,0)")?
+                }
+                "--compare" => {
+                    let first = args.next().ok_or("Missing config files for --compare")?;
+                    let second = args
+                        .next()
+                        .ok_or("Missing second config file for --compare")?;
+                    config.compare_configs = Some((first, second));
+                }
                 _ if config.file_path.is_none() && !arg.starts_with('-') => {
                     config.file_path = Some(arg)
                 }
                 _ => return Err("Invalid option or unexpected argument"),
             }
         }
-        if config.file_path.is_none() {
-            return Err("TSPLIB file path not provided");
+        // After all flags, so that --ablation wins over -a and -b.
+        if let Some(ablation) = config.ablation {
+            ablation.apply(&mut config);
         }
-
         Ok(config)
     }
+
+    /// `self` with the settings of a configuration file applied on top. The
+    /// file holds TOML style `key = value` lines whose keys are long option
+    /// names (`beta = 4`, `local-search = "2opt"`, `integer-costs = true`);
+    /// comments and section headers are ignored.
+    pub fn with_file(&self, path: &str) -> Result<Config, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to open config file {}: {}", path, e))?;
+        let mut args = Vec::new();
+        for (line_num, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() || line.starts_with('[') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("{}:{}: expected key = value", path, line_num + 1))?;
+            let flag = format!("--{}", key.trim().replace('_', "-"));
+            match value.trim().trim_matches('"') {
+                "true" => args.push(flag),
+                "false" => {}
+                value => args.extend([flag, value.to_string()]),
+            }
+        }
+        self.clone()
+            .with_args(args.into_iter())
+            .map_err(|e| format!("{}: {}", path, e))
+    }
+
+    /// The settings that shape the search, as `key=value` pairs, for
+    /// recording alongside results. Paths, reporting and thread layout are
+    /// left out; pluggable parts appear as their `Debug` output.
+    pub fn settings(&self) -> String {
+        self.setting_pairs()
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// [`Config::settings`] as `(key, value)` pairs, e.g. for one column per
+    /// setting.
+    pub fn setting_pairs(&self) -> Vec<(&'static str, String)> {
+        let budget = match self.tour_budget {
+            Some(tours) => ("tour_budget", tours.to_string()),
+            None => ("iters", self.num_iters.to_string()),
+        };
+        let local_search: Vec<String> = self
+            .local_search
+            .iter()
+            .map(|search| format!("{:?}", search))
+            .collect();
+        let stopping: Vec<String> = self
+            .stopping
+            .iter()
+            .map(|criterion| format!("{:?}", criterion))
+            .collect();
+        vec![
+            ("ants", self.num_ants.to_string()),
+            budget,
+            ("alpha", self.alpha.to_string()),
+            ("beta", self.beta.to_string()),
+            ("rho", self.evap_rate.to_string()),
+            ("evaporation", format!("{:?}", self.evaporation)),
+            ("q", self.q_val.to_string()),
+            ("normalize_deposit", self.normalize_deposit.to_string()),
+            ("init_pheromone", format!("{:?}", self.init_pheromone)),
+            ("elitist_weight", self.elitist_weight.to_string()),
+            ("min_pheromone", self.min_pheromone_val.to_string()),
+            ("candidates", self.candidate_list_size.to_string()),
+            ("spatial_pruning", self.spatial_pruning.to_string()),
+            ("distance_policy", format!("{:?}", self.distance_policy)),
+            ("backend", format!("{:?}", self.backend)),
+            ("integer_costs", self.integer_costs.to_string()),
+            ("start", format!("{:?}", self.start_policy)),
+            ("heuristic", format!("{:?}", self.heuristic)),
+            ("deposit", format!("{:?}", self.deposit_schedule)),
+            ("reheat", format!("{:?}", self.reheat)),
+            ("local_search", format!("[{}]", local_search.join(" "))),
+            ("ls_target", format!("{:?}", self.local_search_target)),
+            ("stopping", format!("[{}]", stopping.join(" "))),
+            ("ablation", format!("{:?}", self.ablation)),
+        ]
+    }
+}
+
+impl From<&Config> for ParseOptions {
+    fn from(config: &Config) -> Self {
+        // Large-instance backends only ever solve small sub-problems, so the
+        // whole instance may exceed the dimension limit as long as its
+        // distances stay lazy.
+        let decompose = !matches!(config.backend, Backend::Aco | Backend::Portfolio);
+        // The portfolio does not know the size in advance; a memory budget
+        // keeps the matrix of huge instances lazy.
+        let max_memory = match config.backend {
+            Backend::Portfolio => config.max_memory.or(Some(PORTFOLIO_MEMORY_BUDGET)),
+            _ => config.max_memory,
+        };
+        ParseOptions {
+            // A memory budget replaces the dimension limit.
+            max_dimension: if decompose || max_memory.is_some() {
+                usize::MAX
+            } else {
+                config.max_dimension
+            },
+            distance_policy: config.distance_policy,
+            lazy_distances: decompose,
+            matrix_metric: config.matrix_metric,
+            max_memory,
+            special_distance: None,
+        }
+    }
 }
@@ -0,0 +1,930 @@
+use crate::config::Config;
+use crate::events::warn;
+use crate::json::JsonValue;
+use crate::parser::TspInstance;
+use std::fmt;
+
+/// Nodes fixed to tour positions, e.g. when only part of an existing route
+/// may be reoptimized. Position 0 is the first node of the tour.
+#[derive(Debug, Clone)]
+pub struct PositionLocks {
+    by_position: Vec<Option<usize>>,
+    locked: Vec<bool>,
+}
+
+impl PositionLocks {
+    /// Locks each of `locked_nodes` to the position it has in `tour`, which
+    /// must visit every node exactly once.
+    pub fn from_warm_start(tour: &[usize], locked_nodes: &[usize]) -> Result<Self, String> {
+        let n = tour.len();
+        validate_permutation(tour, n)?;
+        let mut position_of = vec![0; n];
+        for (position, &node) in tour.iter().enumerate() {
+            position_of[node] = position;
+        }
+        let mut locks = PositionLocks {
+            by_position: vec![None; n],
+            locked: vec![false; n],
+        };
+        for &node in locked_nodes {
+            if node >= n {
+                return Err(format!(
+                    "Locked node {} out of bounds for dimension {}",
+                    node, n
+                ));
+            }
+            locks.by_position[position_of[node]] = Some(node);
+            locks.locked[node] = true;
+        }
+        Ok(locks)
+    }
+
+    /// Pins each `(node, position)` pair of `pins` in a tour of `n` nodes.
+    pub fn from_pins(n: usize, pins: &[(usize, usize)]) -> Result<Self, String> {
+        let mut locks = PositionLocks {
+            by_position: vec![None; n],
+            locked: vec![false; n],
+        };
+        for &(node, position) in pins {
+            locks.pin(node, position)?;
+        }
+        Ok(locks)
+    }
+
+    /// Fixes `node` to `position`, failing if either is out of bounds or
+    /// already taken by a different lock.
+    pub fn pin(&mut self, node: usize, position: usize) -> Result<(), String> {
+        let n = self.dimension();
+        if node >= n || position >= n {
+            return Err(format!(
+                "Pin of node {} to position {} out of bounds for dimension {}",
+                node, position, n
+            ));
+        }
+        match self.by_position[position] {
+            Some(other) if other != node => {
+                return Err(format!(
+                    "Position {} is already pinned to node {}",
+                    position, other
+                ));
+            }
+            Some(_) => return Ok(()),
+            None if self.locked[node] => {
+                return Err(format!("Node {} is already pinned elsewhere", node));
+            }
+            None => {}
+        }
+        self.by_position[position] = Some(node);
+        self.locked[node] = true;
+        Ok(())
+    }
+
+    pub fn dimension(&self) -> usize {
+        self.locked.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.locked.iter().any(|&locked| locked)
+    }
+
+    /// Node that must occupy `position`, if any.
+    #[inline]
+    pub fn node_at(&self, position: usize) -> Option<usize> {
+        self.by_position.get(position).copied().flatten()
+    }
+
+    #[inline]
+    pub fn is_locked(&self, node: usize) -> bool {
+        self.locked[node]
+    }
+
+    pub fn is_satisfied(&self, tour: &[usize]) -> bool {
+        self.by_position
+            .iter()
+            .enumerate()
+            .all(|(position, lock)| lock.is_none_or(|node| tour.get(position) == Some(&node)))
+    }
+
+    /// Locked nodes away from their position. A node missing from `tour`
+    /// counts as a whole tour length away.
+    pub fn violations(&self, tour: &[usize]) -> Vec<Violation> {
+        let position_of = positions(tour, self.dimension());
+        self.by_position
+            .iter()
+            .enumerate()
+            .filter_map(|(position, lock)| {
+                let node = (*lock)?;
+                let offset = match position_of[node] {
+                    usize::MAX => self.dimension(),
+                    actual => actual.abs_diff(position),
+                };
+                (offset > 0).then_some(Violation::Lock {
+                    node,
+                    position,
+                    offset,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Ordered node chains that must appear contiguously in the tour, e.g. a
+/// mandatory corridor of stops. Ants enter a chain only at its head and then
+/// follow it to the end, so each chain behaves like a single super-node.
+#[derive(Debug, Clone)]
+pub struct Subsequences {
+    chains: Vec<Vec<usize>>,
+    next: Vec<Option<usize>>,
+    chain_of: Vec<Option<usize>>,
+}
+
+impl Subsequences {
+    /// Builds the constraint for a tour of `n` nodes. Each chain needs at
+    /// least two nodes and no node may belong to more than one chain.
+    pub fn new(n: usize, chains: &[Vec<usize>]) -> Result<Self, String> {
+        let mut subsequences = Subsequences {
+            chains: Vec::with_capacity(chains.len()),
+            next: vec![None; n],
+            chain_of: vec![None; n],
+        };
+        for (chain_idx, chain) in chains.iter().enumerate() {
+            if chain.len() < 2 {
+                return Err(format!(
+                    "Subsequence {} needs at least two nodes",
+                    chain_idx
+                ));
+            }
+            for &node in chain {
+                if node >= n {
+                    return Err(format!(
+                        "Subsequence node {} out of bounds for dimension {}",
+                        node, n
+                    ));
+                }
+                if subsequences.chain_of[node].is_some() {
+                    return Err(format!(
+                        "Node {} appears in more than one subsequence",
+                        node
+                    ));
+                }
+                subsequences.chain_of[node] = Some(chain_idx);
+            }
+            for pair in chain.windows(2) {
+                subsequences.next[pair[0]] = Some(pair[1]);
+            }
+            subsequences.chains.push(chain.clone());
+        }
+        Ok(subsequences)
+    }
+
+    pub fn dimension(&self) -> usize {
+        self.next.len()
+    }
+
+    pub fn chains(&self) -> &[Vec<usize>] {
+        &self.chains
+    }
+
+    /// Node that must directly follow `node`, if it is inside a chain.
+    #[inline]
+    pub fn successor(&self, node: usize) -> Option<usize> {
+        self.next[node]
+    }
+
+    /// Whether `node` may be visited freely, i.e. it is not a chain member
+    /// other than the head.
+    #[inline]
+    pub fn is_entry(&self, node: usize) -> bool {
+        self.chain_of[node].is_none_or(|chain| self.chains[chain][0] == node)
+    }
+
+    /// Number of nodes visited once `node` is entered.
+    #[inline]
+    pub fn span(&self, node: usize) -> usize {
+        self.chain_of[node].map_or(1, |chain| self.chains[chain].len())
+    }
+
+    #[inline]
+    pub fn contains(&self, node: usize) -> bool {
+        self.chain_of[node].is_some()
+    }
+
+    pub fn is_satisfied(&self, tour: &[usize]) -> bool {
+        let n = tour.len();
+        let position_of = positions(tour, self.dimension());
+        self.chains.iter().all(|chain| {
+            chain.windows(2).all(|pair| {
+                let (a, b) = (position_of[pair[0]], position_of[pair[1]]);
+                a < n && b < n && b == (a + 1) % n
+            })
+        })
+    }
+
+    /// Chains whose consecutive nodes are not adjacent in `tour`, with the
+    /// number of such breaks.
+    pub fn violations(&self, tour: &[usize]) -> Vec<Violation> {
+        let n = tour.len();
+        let position_of = positions(tour, self.dimension());
+        self.chains
+            .iter()
+            .enumerate()
+            .filter_map(|(chain, nodes)| {
+                let breaks = nodes
+                    .windows(2)
+                    .filter(|pair| {
+                        let (a, b) = (position_of[pair[0]], position_of[pair[1]]);
+                        !(a < n && b < n && b == (a + 1) % n)
+                    })
+                    .count();
+                (breaks > 0).then_some(Violation::Subsequence { chain, breaks })
+            })
+            .collect()
+    }
+}
+
+/// Pickup and delivery pairs: each pickup has to be visited before its
+/// delivery on the same route, counting from the route's first node.
+#[derive(Debug, Clone)]
+pub struct Precedences {
+    pairs: Vec<(usize, usize)>,
+    pickup_of: Vec<Option<usize>>,
+}
+
+impl Precedences {
+    /// Builds the constraint for `n` nodes from `(pickup, delivery)` pairs.
+    /// No node may take part in more than one pair.
+    pub fn new(n: usize, pairs: &[(usize, usize)]) -> Result<Self, String> {
+        let mut in_pair = vec![false; n];
+        let mut pickup_of = vec![None; n];
+        for &(pickup, delivery) in pairs {
+            if pickup >= n || delivery >= n {
+                return Err(format!(
+                    "Pair ({}, {}) out of bounds for dimension {}",
+                    pickup, delivery, n
+                ));
+            }
+            if pickup == delivery {
+                return Err(format!("Node {} cannot be its own delivery", pickup));
+            }
+            for node in [pickup, delivery] {
+                if in_pair[node] {
+                    return Err(format!("Node {} appears in more than one pair", node));
+                }
+                in_pair[node] = true;
+            }
+            pickup_of[delivery] = Some(pickup);
+        }
+        Ok(Precedences {
+            pairs: pairs.to_vec(),
+            pickup_of,
+        })
+    }
+
+    pub fn dimension(&self) -> usize {
+        self.pickup_of.len()
+    }
+
+    pub fn pairs(&self) -> &[(usize, usize)] {
+        &self.pairs
+    }
+
+    /// Pickup that has to come before `node`, if `node` is a delivery.
+    #[inline]
+    pub fn pickup_of(&self, node: usize) -> Option<usize> {
+        self.pickup_of[node]
+    }
+
+    /// Checks the pairs on one route, which need not visit every node: a
+    /// pair is fine if neither node is on it, or both in the right order.
+    pub fn is_satisfied(&self, route: &[usize]) -> bool {
+        let position_of = positions(route, self.dimension());
+        self.pairs.iter().all(|&(pickup, delivery)| {
+            match (position_of[pickup], position_of[delivery]) {
+                (usize::MAX, usize::MAX) => true,
+                (p, d) => p < d && d != usize::MAX,
+            }
+        })
+    }
+
+    /// Pairs broken on `route`: deliveries visited before their pickup,
+    /// with how many positions too early, and pairs with only one node on
+    /// the route, whose distance is the route length.
+    pub fn violations(&self, route: &[usize]) -> Vec<Violation> {
+        let position_of = positions(route, self.dimension());
+        self.pairs
+            .iter()
+            .filter_map(|&(pickup, delivery)| {
+                let positions = match (position_of[pickup], position_of[delivery]) {
+                    (usize::MAX, usize::MAX) => return None,
+                    (usize::MAX, _) | (_, usize::MAX) => route.len(),
+                    (p, d) if p < d => return None,
+                    (p, d) => p - d,
+                };
+                Some(Violation::Precedence {
+                    pickup,
+                    delivery,
+                    positions,
+                })
+            })
+            .collect()
+    }
+}
+
+/// General precedence relations, as given by sequential ordering (SOP)
+/// instances: every `(before, after)` pair requires `before` to be visited
+/// earlier than `after`. Unlike [`Precedences`] a node may take part in any
+/// number of pairs. Positions count from the tour's first node, which for
+/// SOP instances is the source node 0.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrecedenceGraph {
+    predecessors: Vec<Vec<usize>>,
+}
+
+impl PrecedenceGraph {
+    /// Builds the relation for `n` nodes from `(before, after)` pairs.
+    pub fn new(n: usize, pairs: &[(usize, usize)]) -> Result<Self, String> {
+        let mut predecessors = vec![Vec::new(); n];
+        for &(before, after) in pairs {
+            if before >= n || after >= n {
+                return Err(format!(
+                    "Precedence ({}, {}) out of bounds for dimension {}",
+                    before, after, n
+                ));
+            }
+            if before == after {
+                return Err(format!("Node {} cannot precede itself", before));
+            }
+            predecessors[after].push(before);
+        }
+        Ok(PrecedenceGraph { predecessors })
+    }
+
+    pub fn dimension(&self) -> usize {
+        self.predecessors.len()
+    }
+
+    /// Number of `(before, after)` pairs.
+    pub fn len(&self) -> usize {
+        self.predecessors.iter().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.predecessors.iter().all(Vec::is_empty)
+    }
+
+    /// Nodes that have to be visited before `node`.
+    pub fn predecessors(&self, node: usize) -> &[usize] {
+        &self.predecessors[node]
+    }
+
+    /// All `(before, after)` pairs, ordered by `after`.
+    pub fn pairs(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.predecessors
+            .iter()
+            .enumerate()
+            .flat_map(|(after, before)| before.iter().map(move |&before| (before, after)))
+    }
+
+    /// Whether `tour`, read from its first node, visits every node after
+    /// all of its predecessors. Nodes missing from the tour fail the check.
+    pub fn is_satisfied(&self, tour: &[usize]) -> bool {
+        let position_of = positions(tour, self.dimension());
+        self.pairs().all(|(before, after)| {
+            position_of[after] != usize::MAX && position_of[before] < position_of[after]
+        })
+    }
+
+    /// The relation among `nodes` only, node `k` of the result being
+    /// `nodes[k]` here; pairs with a node outside `nodes` are dropped.
+    pub fn restricted(&self, nodes: &[usize]) -> PrecedenceGraph {
+        let index_of = positions(nodes, self.dimension());
+        PrecedenceGraph {
+            predecessors: nodes
+                .iter()
+                .map(|&node| {
+                    self.predecessors[node]
+                        .iter()
+                        .map(|&before| index_of[before])
+                        .filter(|&index| index != usize::MAX)
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Edges the tour has to contain, from the FIXED_EDGES_SECTION of a TSPLIB
+/// file. They form paths; an ant entering a path at one end walks it to the
+/// other, so its inner nodes are never chosen freely. Edges are undirected.
+#[derive(Debug, Clone)]
+pub struct FixedEdges {
+    edges: Vec<(usize, usize)>,
+    neighbours: Vec<Vec<usize>>,
+}
+
+impl FixedEdges {
+    /// Builds the constraint for `n` nodes. No node may have more than two
+    /// fixed edges, and the edges may only close a cycle through all nodes.
+    pub fn new(n: usize, edges: &[(usize, usize)]) -> Result<Self, String> {
+        let mut fixed = FixedEdges {
+            edges: Vec::with_capacity(edges.len()),
+            neighbours: vec![Vec::new(); n],
+        };
+        for &(a, b) in edges {
+            if a >= n || b >= n {
+                return Err(format!(
+                    "Fixed edge ({}, {}) out of bounds for dimension {}",
+                    a, b, n
+                ));
+            }
+            if a == b {
+                return Err(format!("Fixed edge ({}, {}) is a loop", a, b));
+            }
+            if fixed.neighbours[a].contains(&b) {
+                continue;
+            }
+            if fixed.neighbours[a].len() == 2 || fixed.neighbours[b].len() == 2 {
+                return Err(format!(
+                    "Fixed edge ({}, {}) gives a node a third fixed edge",
+                    a, b
+                ));
+            }
+            fixed.neighbours[a].push(b);
+            fixed.neighbours[b].push(a);
+            fixed.edges.push((a, b));
+        }
+        // A cycle is only allowed if it is the whole tour.
+        let mut seen = vec![false; n];
+        for start in 0..n {
+            if seen[start] || fixed.neighbours[start].len() != 2 {
+                continue;
+            }
+            let (mut previous, mut node, mut length) = (start, fixed.neighbours[start][0], 1);
+            seen[start] = true;
+            while node != start && !seen[node] {
+                seen[node] = true;
+                length += 1;
+                let next = fixed.neighbours[node].iter().find(|&&m| m != previous);
+                match next {
+                    Some(&next) => (previous, node) = (node, next),
+                    None => break,
+                }
+            }
+            if node == start && length < n {
+                return Err(format!(
+                    "Fixed edges close a cycle of {} nodes through node {}",
+                    length, start
+                ));
+            }
+        }
+        Ok(fixed)
+    }
+
+    pub fn dimension(&self) -> usize {
+        self.neighbours.len()
+    }
+
+    pub fn edges(&self) -> &[(usize, usize)] {
+        &self.edges
+    }
+
+    /// Unvisited node `node` has to be followed by, if any.
+    #[inline]
+    pub fn successor(&self, node: usize, visited: &[bool]) -> Option<usize> {
+        self.neighbours[node].iter().copied().find(|&m| !visited[m])
+    }
+
+    /// Whether `node` may be visited freely, i.e. it is not inside a path.
+    #[inline]
+    pub fn is_entry(&self, node: usize) -> bool {
+        self.neighbours[node].len() < 2
+    }
+
+    pub fn is_satisfied(&self, tour: &[usize]) -> bool {
+        let n = tour.len();
+        let position_of = positions(tour, self.dimension());
+        self.edges
+            .iter()
+            .all(|&(a, b)| adjacent(position_of[a], position_of[b], n))
+    }
+
+    /// Fixed edges that are not part of `tour`.
+    pub fn violations(&self, tour: &[usize]) -> Vec<Violation> {
+        let n = tour.len();
+        let position_of = positions(tour, self.dimension());
+        self.edges
+            .iter()
+            .filter(|&&(a, b)| !adjacent(position_of[a], position_of[b], n))
+            .map(|&(a, b)| Violation::FixedEdge { a, b })
+            .collect()
+    }
+}
+
+// Whether two tour positions are neighbours on the closed tour of `n` nodes.
+fn adjacent(a: usize, b: usize, n: usize) -> bool {
+    a < n && b < n && (b == (a + 1) % n || a == (b + 1) % n)
+}
+
+/// All structural constraints a tour has to satisfy.
+#[derive(Debug, Clone, Default)]
+pub struct TourConstraints {
+    pub locks: Option<PositionLocks>,
+    pub subsequences: Option<Subsequences>,
+    pub precedences: Option<Precedences>,
+    pub fixed_edges: Option<FixedEdges>,
+}
+
+impl TourConstraints {
+    /// The constraints `config` asks for on a tour of `n_nodes` nodes:
+    /// locked nodes (with a valid warm start), pinned positions,
+    /// subsequences and pickup and delivery pairs. Invalid or conflicting
+    /// parts are ignored with a [`crate::SolverEvent::Warning`].
+    pub fn from_config(config: &Config, n_nodes: usize) -> Self {
+        let mut locks = None;
+        if let Some(warm_start) = &config.warm_start
+            && !config.locked_nodes.is_empty()
+            && validate_permutation(warm_start, n_nodes).is_ok()
+        {
+            match PositionLocks::from_warm_start(warm_start, &config.locked_nodes) {
+                Ok(l) => locks = Some(l),
+                Err(e) => warn(
+                    &config.event_sender,
+                    format!("ignoring locked nodes: {}", e),
+                ),
+            }
+        }
+        if !config.pinned_positions.is_empty() {
+            let pinned = match locks.take() {
+                Some(mut l) => config
+                    .pinned_positions
+                    .iter()
+                    .try_for_each(|&(node, position)| l.pin(node, position))
+                    .map(|()| l),
+                None => PositionLocks::from_pins(n_nodes, &config.pinned_positions),
+            };
+            match pinned {
+                Ok(l) => locks = Some(l),
+                Err(e) => warn(
+                    &config.event_sender,
+                    format!("ignoring pinned positions: {}", e),
+                ),
+            }
+        }
+        let mut subsequences = None;
+        if !config.subsequences.is_empty() {
+            match Subsequences::new(n_nodes, &config.subsequences) {
+                Ok(s)
+                    if locks
+                        .as_ref()
+                        .is_some_and(|l| (0..n_nodes).any(|i| l.is_locked(i) && s.contains(i))) =>
+                {
+                    warn(
+                        &config.event_sender,
+                        "ignoring subsequences that contain locked nodes".to_string(),
+                    );
+                }
+                Ok(s) => subsequences = Some(s),
+                Err(e) => warn(
+                    &config.event_sender,
+                    format!("ignoring subsequences: {}", e),
+                ),
+            }
+        }
+        let mut precedences = None;
+        if !config.pickup_delivery.is_empty() {
+            match Precedences::new(n_nodes, &config.pickup_delivery) {
+                Ok(p) => precedences = Some(p),
+                Err(e) => warn(
+                    &config.event_sender,
+                    format!("ignoring pickup and delivery pairs: {}", e),
+                ),
+            }
+        }
+        TourConstraints {
+            locks,
+            subsequences,
+            precedences,
+            fixed_edges: None,
+        }
+    }
+
+    /// [`TourConstraints::from_config`] plus the fixed edges of `instance`.
+    pub fn for_instance(instance: &TspInstance, config: &Config) -> Self {
+        let mut constraints = Self::from_config(config, instance.dimension);
+        if let Some(edges) = &instance.fixed_edges {
+            match FixedEdges::new(instance.dimension, edges) {
+                Ok(f) => constraints.fixed_edges = Some(f),
+                Err(e) => warn(&config.event_sender, format!("ignoring fixed edges: {}", e)),
+            }
+        }
+        constraints
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.locks.is_none()
+            && self.subsequences.is_none()
+            && self.precedences.is_none()
+            && self.fixed_edges.is_none()
+    }
+
+    /// Node that has to follow `node`: the next node of its chain or of its
+    /// fixed path, if not visited yet.
+    #[inline]
+    pub fn successor(&self, node: usize, visited: &[bool]) -> Option<usize> {
+        self.subsequences
+            .as_ref()
+            .and_then(|s| s.successor(node))
+            .or_else(|| {
+                self.fixed_edges
+                    .as_ref()
+                    .and_then(|f| f.successor(node, visited))
+            })
+    }
+
+    /// Whether `node` may be placed at `position` by choice rather than
+    /// being forced there, given the nodes already `visited`. Chain heads
+    /// additionally need room for the whole chain before the next pinned
+    /// position, and deliveries wait for their pickup.
+    #[inline]
+    pub fn can_enter(&self, node: usize, position: usize, visited: &[bool]) -> bool {
+        if self.locks.as_ref().is_some_and(|l| l.is_locked(node)) {
+            return false;
+        }
+        if let Some(pickup) = self.precedences.as_ref().and_then(|p| p.pickup_of(node))
+            && !visited[pickup]
+        {
+            return false;
+        }
+        if self.fixed_edges.as_ref().is_some_and(|f| !f.is_entry(node)) {
+            return false;
+        }
+        match &self.subsequences {
+            Some(s) if !s.is_entry(node) => false,
+            Some(s) => self.locks.as_ref().is_none_or(|l| {
+                (position..position + s.span(node))
+                    .all(|p| p < l.dimension() && l.node_at(p).is_none())
+            }),
+            None => true,
+        }
+    }
+
+    pub fn is_satisfied(&self, tour: &[usize]) -> bool {
+        self.locks.as_ref().is_none_or(|l| l.is_satisfied(tour))
+            && self
+                .subsequences
+                .as_ref()
+                .is_none_or(|s| s.is_satisfied(tour))
+            && self
+                .precedences
+                .as_ref()
+                .is_none_or(|p| p.is_satisfied(tour))
+            && self
+                .fixed_edges
+                .as_ref()
+                .is_none_or(|f| f.is_satisfied(tour))
+    }
+
+    /// Every way `tour` breaks the constraints, empty if it satisfies them.
+    pub fn violations(&self, tour: &[usize]) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        if let Some(locks) = &self.locks {
+            violations.extend(locks.violations(tour));
+        }
+        if let Some(subsequences) = &self.subsequences {
+            violations.extend(subsequences.violations(tour));
+        }
+        if let Some(precedences) = &self.precedences {
+            violations.extend(precedences.violations(tour));
+        }
+        if let Some(fixed_edges) = &self.fixed_edges {
+            violations.extend(fixed_edges.violations(tour));
+        }
+        violations
+    }
+}
+
+/// A constraint a result breaks, and by how much. Tour constraints are hard
+/// for the ACO solver but not for every backend; route limits are soft and
+/// only penalized.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Violation {
+    /// `node` is locked to `position` but visited `offset` positions away.
+    Lock {
+        node: usize,
+        position: usize,
+        offset: usize,
+    },
+    /// Subsequence `chain` has `breaks` consecutive pairs that are not
+    /// adjacent in the tour.
+    Subsequence { chain: usize, breaks: usize },
+    /// `delivery` is visited `positions` places before `pickup`, or without
+    /// it on the same route.
+    Precedence {
+        pickup: usize,
+        delivery: usize,
+        positions: usize,
+    },
+    /// The fixed edge between `a` and `b` is not part of the tour.
+    FixedEdge { a: usize, b: usize },
+    /// Route `route` of the depot at `depot` carries `excess` demand above
+    /// the capacity.
+    Capacity {
+        depot: usize,
+        route: usize,
+        excess: f64,
+    },
+    /// Route `route` of the depot at `depot` is `excess` longer than the
+    /// route length limit.
+    RouteLength {
+        depot: usize,
+        route: usize,
+        excess: f64,
+    },
+}
+
+impl Violation {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Violation::Lock { .. } => "lock",
+            Violation::Subsequence { .. } => "subsequence",
+            Violation::Precedence { .. } => "precedence",
+            Violation::FixedEdge { .. } => "fixed_edge",
+            Violation::Capacity { .. } => "capacity",
+            Violation::RouteLength { .. } => "route_length",
+        }
+    }
+
+    /// Size of the violation in the unit of its constraint: positions,
+    /// breaks, missing edges, demand or length.
+    pub fn magnitude(&self) -> f64 {
+        match *self {
+            Violation::Lock { offset, .. } => offset as f64,
+            Violation::Subsequence { breaks, .. } => breaks as f64,
+            Violation::Precedence { positions, .. } => positions as f64,
+            Violation::FixedEdge { .. } => 1.0,
+            Violation::Capacity { excess, .. } | Violation::RouteLength { excess, .. } => excess,
+        }
+    }
+
+    pub(crate) fn to_json_value(&self) -> JsonValue {
+        let number = |key: &str, value: f64| (key.to_string(), JsonValue::Number(value));
+        let mut fields = vec![(
+            "constraint".to_string(),
+            JsonValue::String(self.name().to_string()),
+        )];
+        fields.extend(match *self {
+            Violation::Lock { node, position, .. } => {
+                vec![
+                    number("node", node as f64),
+                    number("position", position as f64),
+                ]
+            }
+            Violation::Subsequence { chain, .. } => vec![number("chain", chain as f64)],
+            Violation::Precedence {
+                pickup, delivery, ..
+            } => vec![
+                number("pickup", pickup as f64),
+                number("delivery", delivery as f64),
+            ],
+            Violation::FixedEdge { a, b } => vec![number("a", a as f64), number("b", b as f64)],
+            Violation::Capacity { depot, route, .. }
+            | Violation::RouteLength { depot, route, .. } => {
+                vec![number("depot", depot as f64), number("route", route as f64)]
+            }
+        });
+        fields.push(number("magnitude", self.magnitude()));
+        JsonValue::Object(fields)
+    }
+
+    pub(crate) fn from_json_value(value: &JsonValue) -> Result<Violation, String> {
+        let number = |key: &str| {
+            value
+                .get(key)
+                .and_then(JsonValue::as_f64)
+                .ok_or_else(|| format!("Violation is missing '{}'", key))
+        };
+        let index = |key: &str| {
+            number(key).and_then(|x| {
+                (x >= 0.0 && x.fract() == 0.0)
+                    .then_some(x as usize)
+                    .ok_or_else(|| format!("Violation '{}' is not an index", key))
+            })
+        };
+        let constraint = value
+            .get("constraint")
+            .and_then(JsonValue::as_str)
+            .ok_or("Violation is missing 'constraint'")?;
+        Ok(match constraint {
+            "lock" => Violation::Lock {
+                node: index("node")?,
+                position: index("position")?,
+                offset: index("magnitude")?,
+            },
+            "subsequence" => Violation::Subsequence {
+                chain: index("chain")?,
+                breaks: index("magnitude")?,
+            },
+            "precedence" => Violation::Precedence {
+                pickup: index("pickup")?,
+                delivery: index("delivery")?,
+                positions: index("magnitude")?,
+            },
+            "fixed_edge" => Violation::FixedEdge {
+                a: index("a")?,
+                b: index("b")?,
+            },
+            "capacity" => Violation::Capacity {
+                depot: index("depot")?,
+                route: index("route")?,
+                excess: number("magnitude")?,
+            },
+            "route_length" => Violation::RouteLength {
+                depot: index("depot")?,
+                route: index("route")?,
+                excess: number("magnitude")?,
+            },
+            other => return Err(format!("Unknown constraint '{}'", other)),
+        })
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Violation::Lock {
+                node,
+                position,
+                offset,
+            } => write!(
+                f,
+                "node {} locked to position {} is {} positions away",
+                node, position, offset
+            ),
+            Violation::Subsequence { chain, breaks } => {
+                write!(f, "subsequence {} is broken {} times", chain, breaks)
+            }
+            Violation::Precedence {
+                pickup,
+                delivery,
+                positions,
+            } => write!(
+                f,
+                "delivery {} is {} positions ahead of pickup {}",
+                delivery, positions, pickup
+            ),
+            Violation::FixedEdge { a, b } => {
+                write!(f, "fixed edge ({}, {}) is not in the tour", a, b)
+            }
+            Violation::Capacity {
+                depot,
+                route,
+                excess,
+            } => write!(
+                f,
+                "route {} of depot {} exceeds the capacity by {:.2}",
+                route, depot, excess
+            ),
+            Violation::RouteLength {
+                depot,
+                route,
+                excess,
+            } => write!(
+                f,
+                "route {} of depot {} exceeds the length limit by {:.2}",
+                route, depot, excess
+            ),
+        }
+    }
+}
+
+/// Position of each of `n` nodes in `tour`, `usize::MAX` if absent.
+fn positions(tour: &[usize], n: usize) -> Vec<usize> {
+    let mut position_of = vec![usize::MAX; n];
+    for (position, &node) in tour.iter().enumerate() {
+        if let Some(slot) = position_of.get_mut(node) {
+            *slot = position;
+        }
+    }
+    position_of
+}
+
+/// Checks that `tour` visits each of the `n` nodes exactly once.
+pub fn validate_permutation(tour: &[usize], n: usize) -> Result<(), String> {
+    if tour.len() != n {
+        return Err(format!("Tour has {} nodes, expected {}", tour.len(), n));
+    }
+    let mut seen = vec![false; n];
+    for &node in tour {
+        if node >= n {
+            return Err(format!(
+                "Tour node {} out of bounds for dimension {}",
+                node, n
+            ));
+        }
+        if seen[node] {
+            return Err(format!("Tour visits node {} more than once", node));
+        }
+        seen[node] = true;
+    }
+    Ok(())
+}
@@ -0,0 +1,200 @@
+use crate::parser::{Node, TspInstance};
+use rand::Rng;
+use rand::seq::SliceRandom;
+
+/// Nearest-neighbor tour starting at `start`.
+pub fn nearest_neighbor_tour(instance: &TspInstance, start: usize) -> Vec<usize> {
+    let n = instance.dimension;
+    if n == 0 {
+        return Vec::new();
+    }
+    let mut visited = vec![false; n];
+    let mut tour = Vec::with_capacity(n);
+    let mut current = start;
+    visited[current] = true;
+    tour.push(current);
+    for _ in 1..n {
+        let next = (0..n)
+            .filter(|&j| !visited[j])
+            .min_by(|&a, &b| {
+                instance
+                    .dist_matrix
+                    .get(current, a)
+                    .total_cmp(&instance.dist_matrix.get(current, b))
+            })
+            .expect("unvisited node must exist");
+        visited[next] = true;
+        tour.push(next);
+        current = next;
+    }
+    tour
+}
+
+/// Uniformly random tour, the baseline any constructive heuristic has to
+/// beat.
+pub fn random_tour(n: usize, rng: &mut impl Rng) -> Vec<usize> {
+    let mut tour: Vec<usize> = (0..n).collect();
+    tour.shuffle(rng);
+    tour
+}
+
+/// Best of `samples` random tours and its length.
+pub fn random_baseline(
+    instance: &TspInstance,
+    samples: usize,
+    rng: &mut impl Rng,
+) -> (Vec<usize>, f64) {
+    let mut best = (Vec::new(), f64::MAX);
+    for _ in 0..samples.max(1) {
+        let tour = random_tour(instance.dimension, rng);
+        let length = instance.tour_length(&tour);
+        if length < best.1 {
+            best = (tour, length);
+        }
+    }
+    best
+}
+
+/// Position after which inserting `node` into the closed `tour` adds the
+/// least length (0 for tours of fewer than two nodes).
+pub fn cheapest_insertion_position(instance: &TspInstance, tour: &[usize], node: usize) -> usize {
+    let d = &instance.dist_matrix;
+    (0..tour.len())
+        .min_by(|&a, &b| {
+            let cost = |k: usize| {
+                let (p, q) = (tour[k], tour[(k + 1) % tour.len()]);
+                d.get(p, node) + d.get(node, q) - d.get(p, q)
+            };
+            cost(a).total_cmp(&cost(b))
+        })
+        .unwrap_or(0)
+}
+
+/// Turns an edited, possibly invalid tour into a valid one with as few
+/// changes as possible: out-of-range nodes and repeated visits are dropped
+/// (the first visit is kept), then every missing node is inserted at its
+/// cheapest position, in index order. Returns the tour and its length.
+pub fn repair_tour(instance: &TspInstance, tour: &[usize]) -> (Vec<usize>, f64) {
+    let n = instance.dimension;
+    let mut visited = vec![false; n];
+    let mut repaired: Vec<usize> = tour
+        .iter()
+        .copied()
+        .filter(|&node| node < n && !std::mem::replace(&mut visited[node], true))
+        .collect();
+    let missing: Vec<usize> = (0..n).filter(|&node| !visited[node]).collect();
+    for node in missing {
+        let position = cheapest_insertion_position(instance, &repaired, node);
+        repaired.insert((position + 1).min(repaired.len()), node);
+    }
+    let length = instance.tour_length(&repaired);
+    (repaired, length)
+}
+
+/// Greedy edge tour: repeatedly adds the shortest edge that keeps every node
+/// at degree <= 2 and does not close a cycle early.
+pub fn greedy_edge_tour(instance: &TspInstance) -> Vec<usize> {
+    let n = instance.dimension;
+    if n < 3 {
+        return (0..n).collect();
+    }
+    let mut edges: Vec<(usize, usize)> = Vec::with_capacity(n * (n - 1) / 2);
+    for i in 0..n {
+        for j in (i + 1)..n {
+            edges.push((i, j));
+        }
+    }
+    let d = &instance.dist_matrix;
+    edges.sort_by(|&(a, b), &(c, e)| d.get(a, b).total_cmp(&d.get(c, e)));
+
+    let mut parent: Vec<usize> = (0..n).collect();
+    fn find(parent: &mut [usize], mut x: usize) -> usize {
+        while parent[x] != x {
+            parent[x] = parent[parent[x]];
+            x = parent[x];
+        }
+        x
+    }
+
+    let mut adjacency: Vec<Vec<usize>> = vec![Vec::with_capacity(2); n];
+    let mut added = 0;
+    for (a, b) in edges {
+        if added == n - 1 {
+            break;
+        }
+        if adjacency[a].len() == 2 || adjacency[b].len() == 2 {
+            continue;
+        }
+        let (ra, rb) = (find(&mut parent, a), find(&mut parent, b));
+        if ra == rb {
+            continue;
+        }
+        parent[ra] = rb;
+        adjacency[a].push(b);
+        adjacency[b].push(a);
+        added += 1;
+    }
+
+    // The edges now form a Hamiltonian path; walk it from one of its ends.
+    let start = (0..n).find(|&i| adjacency[i].len() < 2).unwrap_or(0);
+    let mut tour = Vec::with_capacity(n);
+    let mut prev = usize::MAX;
+    let mut current = start;
+    loop {
+        tour.push(current);
+        match adjacency[current].iter().find(|&&next| next != prev) {
+            Some(&next) if tour.len() < n => {
+                prev = current;
+                current = next;
+            }
+            _ => break,
+        }
+    }
+    tour
+}
+
+/// Hilbert curve index of `(x, y)` on a `2^16 x 2^16` grid.
+fn hilbert_index(mut x: u32, mut y: u32) -> u64 {
+    let mut d = 0u64;
+    let mut s = 1u32 << 15;
+    while s > 0 {
+        let rx = u32::from(x & s > 0);
+        let ry = u32::from(y & s > 0);
+        d += u64::from(s) * u64::from(s) * u64::from((3 * rx) ^ ry);
+        if ry == 0 {
+            if rx == 1 {
+                x = 0xFFFF - x;
+                y = 0xFFFF - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        s >>= 1;
+    }
+    d
+}
+
+/// Orders nodes along a Hilbert space-filling curve. Costs O(n log n) and
+/// needs no distances, so it gives a usable tour (roughly 25% above optimal
+/// on uniform data) even for instances far too large for a matrix.
+pub fn space_filling_curve_order(nodes: &[Node]) -> Vec<usize> {
+    let (min_x, max_x) = nodes.iter().fold((f64::MAX, f64::MIN), |(lo, hi), node| {
+        (lo.min(node.x), hi.max(node.x))
+    });
+    let (min_y, max_y) = nodes.iter().fold((f64::MAX, f64::MIN), |(lo, hi), node| {
+        (lo.min(node.y), hi.max(node.y))
+    });
+    let scale = |value: f64, min: f64, max: f64| {
+        let span = (max - min).max(f64::EPSILON);
+        ((value - min) / span * 65535.0) as u32
+    };
+    let mut order: Vec<(u64, usize)> = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, node)| {
+            let key = hilbert_index(scale(node.x, min_x, max_x), scale(node.y, min_y, max_y));
+            (key, i)
+        })
+        .collect();
+    order.sort_unstable();
+    order.into_iter().map(|(_, i)| i).collect()
+}
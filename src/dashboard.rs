@@ -0,0 +1,525 @@
+//! Live web dashboard: a small static page that receives solver events over
+//! a WebSocket and draws the instance, the current best tour and the
+//! convergence curve while the run is in progress. The same server exposes
+//! Prometheus metrics of the run on `/metrics`.
+
+use crate::bench::gap_percent;
+use crate::events::SolverEvent;
+use crate::parser::Node;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::Receiver;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Progress messages are throttled; new-best tours are always sent.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+#[derive(Default)]
+struct DashboardState {
+    clients: Vec<TcpStream>,
+    // Messages replayed to clients connecting mid-run.
+    points: Option<String>,
+    best: Option<String>,
+    history: Vec<String>,
+    metrics: Metrics,
+}
+
+/// Progress of the run served by the dashboard, as Prometheus metrics.
+#[derive(Debug, Clone, Default)]
+pub struct Metrics {
+    job: String,
+    optimum: Option<f64>,
+    started: Option<Instant>,
+    finished: bool,
+    iterations: usize,
+    new_bests: usize,
+    best_length: Option<f64>,
+    connection_errors: usize,
+}
+
+impl Metrics {
+    /// Metrics of the run `job`; `optimum` is its best-known length, if any.
+    pub fn new(job: &str, optimum: Option<f64>) -> Self {
+        Metrics {
+            job: job.to_string(),
+            optimum,
+            ..Default::default()
+        }
+    }
+
+    /// Counts `event`; the run is running from its first event on.
+    pub fn record(&mut self, event: &SolverEvent) {
+        self.started.get_or_insert_with(Instant::now);
+        match event {
+            SolverEvent::IterationFinished {
+                iteration,
+                best_length,
+                ..
+            } => {
+                self.iterations = iteration + 1;
+                if *best_length < f64::MAX {
+                    self.best_length = Some(*best_length);
+                }
+            }
+            SolverEvent::NewBest { length, .. } => {
+                self.new_bests += 1;
+                self.best_length = Some(*length);
+            }
+            _ => {}
+        }
+    }
+
+    /// Counts a dashboard or metrics request that failed.
+    pub fn connection_failed(&mut self) {
+        self.connection_errors += 1;
+    }
+
+    /// Marks the run as finished; the event stream has ended.
+    pub fn finish(&mut self) {
+        self.finished = true;
+    }
+
+    /// The metrics in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let running = self.started.is_some() && !self.finished;
+        let rate = self.started.map_or(0.0, |started| {
+            let seconds = started.elapsed().as_secs_f64();
+            if seconds > 0.0 {
+                self.iterations as f64 / seconds
+            } else {
+                0.0
+            }
+        });
+        let job = format!("{{job=\"{}\"}}", escape_label(&self.job));
+        let mut text = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, labels: &str, value: f64| {
+            text.push_str(&format!(
+                "# HELP tsp_solver_{0} {1}\n# TYPE tsp_solver_{0} {2}\ntsp_solver_{0}{3} {4}\n",
+                name, help, kind, labels, value
+            ));
+        };
+        metric(
+            "jobs_running",
+            "gauge",
+            "Runs in progress.",
+            "",
+            f64::from(u8::from(running)),
+        );
+        metric(
+            "jobs_finished_total",
+            "counter",
+            "Runs that have finished.",
+            "",
+            f64::from(u8::from(self.finished)),
+        );
+        metric(
+            "dashboard_connection_errors_total",
+            "counter",
+            "Dashboard and metrics requests that failed.",
+            "",
+            self.connection_errors as f64,
+        );
+        metric(
+            "iterations_total",
+            "counter",
+            "Iterations completed.",
+            &job,
+            self.iterations as f64,
+        );
+        metric(
+            "iterations_per_second",
+            "gauge",
+            "Mean iteration rate since the run started.",
+            &job,
+            rate,
+        );
+        metric(
+            "new_best_total",
+            "counter",
+            "Improvements of the best tour.",
+            &job,
+            self.new_bests as f64,
+        );
+        if let Some(length) = self.best_length {
+            metric(
+                "best_length",
+                "gauge",
+                "Length of the best tour found.",
+                &job,
+                length,
+            );
+            if let Some(optimum) = self.optimum {
+                metric(
+                    "best_gap_percent",
+                    "gauge",
+                    "Percent above the best-known length.",
+                    &job,
+                    gap_percent(length, optimum),
+                );
+            }
+        }
+        text
+    }
+}
+
+// Label values escape backslashes, quotes and line feeds.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Serves the dashboard on `addr` in background threads and forwards every
+/// event from `events` to the connected browsers. `job` names the run and
+/// `optimum` is its best-known length for the gap metric. Returns once the
+/// listener is bound; the threads end with the process.
+pub fn serve(
+    addr: &str,
+    job: &str,
+    nodes: Option<&[Node]>,
+    optimum: Option<f64>,
+    events: Receiver<SolverEvent>,
+) -> Result<(), String> {
+    let listener =
+        TcpListener::bind(addr).map_err(|e| format!("Failed to bind {}: {}", addr, e))?;
+    let state = Arc::new(Mutex::new(DashboardState {
+        points: nodes.map(points_message),
+        metrics: Metrics::new(job, optimum),
+        ..Default::default()
+    }));
+
+    let accept_state = Arc::clone(&state);
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            // Browsers going away are routine; failures only show in the
+            // metrics.
+            if handle_connection(stream, &accept_state).is_err()
+                && let Ok(mut state) = accept_state.lock()
+            {
+                state.metrics.connection_failed();
+            }
+        }
+    });
+
+    thread::spawn(move || {
+        let mut last_progress = Instant::now() - PROGRESS_INTERVAL;
+        for event in events {
+            if let Ok(mut state) = state.lock() {
+                state.metrics.record(&event);
+            }
+            let message = match event {
+                SolverEvent::NewBest {
+                    iteration,
+                    tour,
+                    length,
+                } => {
+                    let message = format!(
+                        "{{\"type\":\"best\",\"iteration\":{},\"length\":{},\"tour\":{:?}}}",
+                        iteration, length, tour
+                    );
+                    if let Ok(mut state) = state.lock() {
+                        state.best = Some(message.clone());
+                    }
+                    message
+                }
+                SolverEvent::IterationFinished {
+                    iteration,
+                    best_length,
+                    edge_entropy,
+                    ..
+                } if last_progress.elapsed() >= PROGRESS_INTERVAL && best_length < f64::MAX => {
+                    last_progress = Instant::now();
+                    let message = format!(
+                        "{{\"type\":\"progress\",\"iteration\":{},\"best\":{},\"entropy\":{}}}",
+                        iteration, best_length, edge_entropy
+                    );
+                    if let Ok(mut state) = state.lock() {
+                        state.history.push(message.clone());
+                    }
+                    message
+                }
+                _ => continue,
+            };
+            broadcast(&state, &message);
+        }
+        if let Ok(mut state) = state.lock() {
+            state.metrics.finish();
+        }
+    });
+    Ok(())
+}
+
+fn handle_connection(stream: TcpStream, state: &Mutex<DashboardState>) -> Result<(), String> {
+    let mut reader = BufReader::new(stream.try_clone().map_err(|e| e.to_string())?);
+    let mut request_line = String::new();
+    reader
+        .read_line(&mut request_line)
+        .map_err(|e| e.to_string())?;
+    let mut websocket_key = None;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header).map_err(|e| e.to_string())?;
+        let header = header.trim();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':')
+            && name.trim().eq_ignore_ascii_case("sec-websocket-key")
+        {
+            websocket_key = Some(value.trim().to_string());
+        }
+    }
+
+    let mut stream = stream;
+    match (request_line.split_whitespace().nth(1), websocket_key) {
+        (Some("/ws"), Some(key)) => {
+            let accept = base64(&sha1(format!("{}{}", key, WEBSOCKET_GUID).as_bytes()));
+            write!(
+                stream,
+                "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+                accept
+            )
+            .map_err(|e| e.to_string())?;
+            let mut state = state.lock().map_err(|_| "Dashboard state poisoned")?;
+            let replay: Vec<String> = state
+                .points
+                .iter()
+                .chain(state.history.iter())
+                .chain(state.best.iter())
+                .cloned()
+                .collect();
+            for message in replay {
+                write_text_frame(&mut stream, &message).map_err(|e| e.to_string())?;
+            }
+            state.clients.push(stream);
+        }
+        (Some("/metrics"), _) => {
+            let body = state
+                .lock()
+                .map_err(|_| "Dashboard state poisoned")?
+                .metrics
+                .render();
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        (Some("/"), _) => {
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                DASHBOARD_HTML.len(),
+                DASHBOARD_HTML
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        _ => {
+            write!(
+                stream,
+                "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+            )
+            .map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+fn broadcast(state: &Mutex<DashboardState>, message: &str) {
+    if let Ok(mut state) = state.lock() {
+        // Clients that went away fail the write and are dropped.
+        state
+            .clients
+            .retain_mut(|client| write_text_frame(client, message).is_ok());
+    }
+}
+
+fn points_message(nodes: &[Node]) -> String {
+    let points: Vec<String> = nodes
+        .iter()
+        .map(|node| format!("[{},{}]", node.x, node.y))
+        .collect();
+    format!("{{\"type\":\"points\",\"points\":[{}]}}", points.join(","))
+}
+
+/// Writes an unmasked, unfragmented WebSocket text frame.
+fn write_text_frame(stream: &mut TcpStream, payload: &str) -> std::io::Result<()> {
+    let len = payload.len();
+    let mut frame = Vec::with_capacity(len + 10);
+    frame.push(0x81);
+    if len < 126 {
+        frame.push(len as u8);
+    } else if len <= u16::MAX as usize {
+        frame.push(126);
+        frame.extend_from_slice(&(len as u16).to_be_bytes());
+    } else {
+        frame.push(127);
+        frame.extend_from_slice(&(len as u64).to_be_bytes());
+    }
+    frame.extend_from_slice(payload.as_bytes());
+    stream.write_all(&frame)
+}
+
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for chunk in message.chunks(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in chunk.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, &word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+        for (state, value) in h.iter_mut().zip([a, b, c, d, e]) {
+            *state = state.wrapping_add(value);
+        }
+    }
+
+    let mut digest = [0u8; 20];
+    for (bytes, word) in digest.chunks_mut(4).zip(h) {
+        bytes.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let triple = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(triple >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+const DASHBOARD_HTML: &str = r##"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>RustACO live</title>
+<style>
+body { font-family: sans-serif; margin: 1em; background: #fafafa; }
+canvas { background: #fff; border: 1px solid #ccc; margin-right: 1em; }
+#status { margin: 0.5em 0; }
+</style>
+</head>
+<body>
+<h2>RustACO live</h2>
+<div id="status">Connecting...</div>
+<canvas id="tour" width="600" height="600"></canvas>
+<canvas id="curve" width="600" height="300"></canvas>
+<script>
+let points = [], tour = [], history = [];
+const status = document.getElementById("status");
+const tourCanvas = document.getElementById("tour");
+const curveCanvas = document.getElementById("curve");
+
+function drawTour() {
+  const ctx = tourCanvas.getContext("2d");
+  ctx.clearRect(0, 0, tourCanvas.width, tourCanvas.height);
+  if (points.length === 0) return;
+  const xs = points.map(p => p[0]), ys = points.map(p => p[1]);
+  const minX = Math.min(...xs), maxX = Math.max(...xs);
+  const minY = Math.min(...ys), maxY = Math.max(...ys);
+  const scale = 580 / Math.max(maxX - minX, maxY - minY, 1e-9);
+  const px = p => [10 + (p[0] - minX) * scale, 590 - (p[1] - minY) * scale];
+  if (tour.length > 1) {
+    ctx.strokeStyle = "#2a6fdb";
+    ctx.beginPath();
+    tour.concat([tour[0]]).forEach((idx, k) => {
+      const [x, y] = px(points[idx]);
+      if (k === 0) ctx.moveTo(x, y); else ctx.lineTo(x, y);
+    });
+    ctx.stroke();
+  }
+  ctx.fillStyle = "#d33";
+  const r = points.length > 2000 ? 1 : 2.5;
+  for (const p of points) {
+    const [x, y] = px(p);
+    ctx.fillRect(x - r / 2, y - r / 2, r, r);
+  }
+}
+
+function drawCurve() {
+  const ctx = curveCanvas.getContext("2d");
+  ctx.clearRect(0, 0, curveCanvas.width, curveCanvas.height);
+  if (history.length < 2) return;
+  const its = history.map(h => h[0]), vals = history.map(h => h[1]);
+  const minI = its[0], maxI = Math.max(its[its.length - 1], minI + 1);
+  const minV = Math.min(...vals), maxV = Math.max(...vals, minV + 1e-9);
+  ctx.strokeStyle = "#2a6fdb";
+  ctx.beginPath();
+  history.forEach(([it, v], k) => {
+    const x = 10 + (it - minI) / (maxI - minI) * 580;
+    const y = 290 - (v - minV) / (maxV - minV) * 280;
+    if (k === 0) ctx.moveTo(x, y); else ctx.lineTo(x, y);
+  });
+  ctx.stroke();
+}
+
+const ws = new WebSocket("ws://" + location.host + "/ws");
+ws.onmessage = ev => {
+  const msg = JSON.parse(ev.data);
+  if (msg.type === "points") { points = msg.points; drawTour(); }
+  if (msg.type === "best") {
+    tour = msg.tour;
+    history.push([msg.iteration, msg.length]);
+    status.textContent = "Iteration " + msg.iteration + ": best " + msg.length.toFixed(2);
+    drawTour(); drawCurve();
+  }
+  if (msg.type === "progress") {
+    history.push([msg.iteration, msg.best]);
+    status.textContent = "Iteration " + msg.iteration + ": best " + msg.best.toFixed(2) +
+      " (edge entropy " + msg.entropy.toFixed(3) + ")";
+    drawCurve();
+  }
+};
+ws.onclose = () => { status.textContent += " (run finished)"; };
+</script>
+</body>
+</html>
+"##;
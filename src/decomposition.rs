@@ -0,0 +1,349 @@
+use crate::config::Config;
+use crate::construction;
+use crate::distance::DistanceMatrix;
+use crate::local_search::{LocalSearch, TwoOpt};
+use crate::parser::{DistancePolicy, EdgeWeightType, Node, TspInstance};
+use crate::solver::solve_many;
+use rayon::prelude::*;
+
+const KMEANS_ITERATIONS: usize = 20;
+const REPAIR_WINDOW: usize = 50;
+const MERGE_CANDIDATES: usize = 32;
+
+/// Cluster-first, route-second solving for very large geometric instances.
+///
+/// Nodes are grouped by k-means into `num_clusters` clusters, every cluster is
+/// solved with ACO (clusters run in parallel, see [`solve_many`]), the
+/// clusters are visited in the order of a short tour over their centroids and
+/// the junctions between consecutive clusters are repaired with 2-opt. Works
+/// with lazy distances, so the full matrix never has to be stored.
+pub fn solve_decomposed(
+    instance: &TspInstance,
+    config: &Config,
+    num_clusters: usize,
+) -> Result<(Vec<usize>, f64), String> {
+    let nodes = instance
+        .node_coords
+        .as_ref()
+        .ok_or("Decomposition requires node coordinates")?;
+    let n = instance.dimension;
+    if n < 2 {
+        return Ok(((0..n).collect(), 0.0));
+    }
+
+    let clusters = kmeans(nodes, num_clusters.clamp(1, n));
+    let sub_instances: Vec<TspInstance> = clusters
+        .iter()
+        .enumerate()
+        .map(|(c, members)| {
+            instance.sub_instance(format!("{}-cluster{}", instance.name, c), members)
+        })
+        .collect();
+    let cluster_tours: Vec<Vec<usize>> = solve_many(&sub_instances, config)
+        .into_iter()
+        .zip(&clusters)
+        .map(|((tour, _), members)| tour.into_iter().map(|k| members[k]).collect())
+        .collect();
+
+    let order = cluster_order(nodes, &clusters);
+    let mut tour = Vec::with_capacity(n);
+    let mut junctions = Vec::with_capacity(clusters.len());
+    for &c in &order {
+        junctions.push(tour.len());
+        let prev = tour.last().copied();
+        append_cluster_tour(&mut tour, &cluster_tours[c], prev, &instance.dist_matrix);
+    }
+
+    for &junction in junctions.iter().skip(1) {
+        windowed_two_opt(&mut tour, &instance.dist_matrix, junction, REPAIR_WINDOW);
+    }
+    let length = instance.tour_length(&tour);
+    Ok((tour, length))
+}
+
+/// Parallel partition-and-merge solving for very large geometric instances.
+///
+/// The bounding box is cut into `tiles_per_side` x `tiles_per_side` tiles that
+/// are solved concurrently. Tiles are arranged in serpentine order so that
+/// consecutive tiles are neighbors, and neighboring tours are merged pairwise,
+/// level by level and in parallel, by exchanging the cheapest pair of edges
+/// between them. Every merge is followed by 2-opt around the new junctions.
+pub fn solve_partitioned(
+    instance: &TspInstance,
+    config: &Config,
+    tiles_per_side: usize,
+) -> Result<(Vec<usize>, f64), String> {
+    let nodes = instance
+        .node_coords
+        .as_ref()
+        .ok_or("Partitioning requires node coordinates")?;
+    let n = instance.dimension;
+    if n < 2 {
+        return Ok(((0..n).collect(), 0.0));
+    }
+
+    let tiles = grid_tiles(nodes, tiles_per_side.max(1));
+    let sub_instances: Vec<TspInstance> = tiles
+        .iter()
+        .enumerate()
+        .map(|(t, members)| instance.sub_instance(format!("{}-tile{}", instance.name, t), members))
+        .collect();
+    let mut tours: Vec<Vec<usize>> = solve_many(&sub_instances, config)
+        .into_iter()
+        .zip(&tiles)
+        .map(|((tour, _), members)| tour.into_iter().map(|k| members[k]).collect())
+        .collect();
+
+    while tours.len() > 1 {
+        tours = tours
+            .par_chunks(2)
+            .map(|pair| match pair {
+                [a, b] => merge_tours(a, b, nodes, &instance.dist_matrix),
+                [a] => a.clone(),
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+    let tour = tours.pop().unwrap_or_default();
+    let length = instance.tour_length(&tour);
+    Ok((tour, length))
+}
+
+/// Non-empty grid tiles in serpentine (boustrophedon) order.
+fn grid_tiles(nodes: &[Node], side: usize) -> Vec<Vec<usize>> {
+    let (min_x, max_x) = nodes.iter().fold((f64::MAX, f64::MIN), |(lo, hi), node| {
+        (lo.min(node.x), hi.max(node.x))
+    });
+    let (min_y, max_y) = nodes.iter().fold((f64::MAX, f64::MIN), |(lo, hi), node| {
+        (lo.min(node.y), hi.max(node.y))
+    });
+    let cell = |value: f64, min: f64, max: f64| {
+        let span = (max - min).max(f64::EPSILON);
+        (((value - min) / span * side as f64) as usize).min(side - 1)
+    };
+    let mut tiles = vec![Vec::new(); side * side];
+    for (i, node) in nodes.iter().enumerate() {
+        let row = cell(node.y, min_y, max_y);
+        let mut col = cell(node.x, min_x, max_x);
+        if row % 2 == 1 {
+            col = side - 1 - col;
+        }
+        tiles[row * side + col].push(i);
+    }
+    tiles.retain(|members| !members.is_empty());
+    tiles
+}
+
+/// Merges two cyclic tours into one by removing an edge from each and
+/// reconnecting them, choosing the cheapest exchange among the nodes of each
+/// tour closest to the other tour's centroid.
+fn merge_tours(a: &[usize], b: &[usize], nodes: &[Node], d: &DistanceMatrix) -> Vec<usize> {
+    if a.len() < 2 || b.len() < 2 {
+        let mut merged = a.to_vec();
+        merged.extend_from_slice(b);
+        return merged;
+    }
+    let centroid = |tour: &[usize]| {
+        let (sx, sy) = tour.iter().fold((0.0, 0.0), |(sx, sy), &i| {
+            (sx + nodes[i].x, sy + nodes[i].y)
+        });
+        (sx / tour.len() as f64, sy / tour.len() as f64)
+    };
+    let candidates = |tour: &[usize], (cx, cy): (f64, f64)| {
+        let mut positions: Vec<usize> = (0..tour.len()).collect();
+        let dist = |p: usize| (nodes[tour[p]].x - cx).powi(2) + (nodes[tour[p]].y - cy).powi(2);
+        if positions.len() > MERGE_CANDIDATES {
+            positions
+                .select_nth_unstable_by(MERGE_CANDIDATES, |&p, &q| dist(p).total_cmp(&dist(q)));
+            positions.truncate(MERGE_CANDIDATES);
+        }
+        positions
+    };
+    let candidates_a = candidates(a, centroid(b));
+    let candidates_b = candidates(b, centroid(a));
+
+    // (cost, position in a, position in b, walk b forward)
+    let mut best = (f64::MAX, 0, 0, false);
+    for &i in &candidates_a {
+        let (a1, a2) = (a[i], a[(i + 1) % a.len()]);
+        for &j in &candidates_b {
+            let (b1, b2) = (b[j], b[(j + 1) % b.len()]);
+            let removed = d.get(a1, a2) + d.get(b1, b2);
+            let backward = d.get(a1, b1) + d.get(b2, a2) - removed;
+            let forward = d.get(a1, b2) + d.get(b1, a2) - removed;
+            if backward < best.0 {
+                best = (backward, i, j, false);
+            }
+            if forward < best.0 {
+                best = (forward, i, j, true);
+            }
+        }
+    }
+
+    let (_, i, j, forward) = best;
+    let (m_a, m_b) = (a.len(), b.len());
+    // Path through `a` from a[i + 1] to a[i], then through `b`.
+    let mut merged: Vec<usize> = (0..m_a).map(|k| a[(i + 1 + k) % m_a]).collect();
+    if forward {
+        merged.extend((0..m_b).map(|k| b[(j + 1 + k) % m_b]));
+    } else {
+        merged.extend((0..m_b).map(|k| b[(j + m_b - k) % m_b]));
+    }
+    windowed_two_opt(&mut merged, d, m_a, REPAIR_WINDOW);
+    let last = merged.len() - 1;
+    windowed_two_opt(&mut merged, d, last, REPAIR_WINDOW);
+    merged
+}
+
+/// Lloyd's k-means on the node coordinates. Returns the non-empty clusters
+/// as lists of node indices.
+pub fn kmeans(nodes: &[Node], k: usize) -> Vec<Vec<usize>> {
+    let n = nodes.len();
+    let mut centers: Vec<(f64, f64)> = (0..k)
+        .map(|c| {
+            let node = &nodes[c * n / k];
+            (node.x, node.y)
+        })
+        .collect();
+    let mut assignment = vec![0; n];
+
+    for _ in 0..KMEANS_ITERATIONS {
+        let next: Vec<usize> = nodes
+            .par_iter()
+            .map(|node| {
+                (0..k)
+                    .min_by(|&a, &b| {
+                        let da = (node.x - centers[a].0).powi(2) + (node.y - centers[a].1).powi(2);
+                        let db = (node.x - centers[b].0).powi(2) + (node.y - centers[b].1).powi(2);
+                        da.total_cmp(&db)
+                    })
+                    .unwrap_or(0)
+            })
+            .collect();
+        let changed = next != assignment;
+        assignment = next;
+
+        let mut sums = vec![(0.0, 0.0, 0usize); k];
+        for (node, &c) in nodes.iter().zip(&assignment) {
+            sums[c].0 += node.x;
+            sums[c].1 += node.y;
+            sums[c].2 += 1;
+        }
+        for (center, &(sx, sy, count)) in centers.iter_mut().zip(&sums) {
+            if count > 0 {
+                *center = (sx / count as f64, sy / count as f64);
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    let mut clusters = vec![Vec::new(); k];
+    for (i, &c) in assignment.iter().enumerate() {
+        clusters[c].push(i);
+    }
+    clusters.retain(|members| !members.is_empty());
+    clusters
+}
+
+/// Visiting order of the clusters: nearest-neighbor tour over the centroids,
+/// improved with 2-opt.
+fn cluster_order(nodes: &[Node], clusters: &[Vec<usize>]) -> Vec<usize> {
+    let centroids: Vec<Node> = clusters
+        .iter()
+        .enumerate()
+        .map(|(c, members)| {
+            let (sx, sy) = members.iter().fold((0.0, 0.0), |(sx, sy), &i| {
+                (sx + nodes[i].x, sy + nodes[i].y)
+            });
+            let count = members.len() as f64;
+            Node {
+                id: c + 1,
+                x: sx / count,
+                y: sy / count,
+                z: 0.0,
+            }
+        })
+        .collect();
+    let centroid_instance = TspInstance {
+        name: "centroids".to_string(),
+        tsp_type: "TSP".to_string(),
+        comment: String::new(),
+        dimension: centroids.len(),
+        edge_weight_type: EdgeWeightType::Euc2D,
+        edge_weight_format: None,
+        distance_policy: DistancePolicy::Exact,
+        dist_matrix: DistanceMatrix::Lazy {
+            nodes: centroids.clone(),
+            metric: EdgeWeightType::Euc2D,
+            policy: DistancePolicy::Exact,
+        },
+        node_coords: Some(centroids),
+        node_metadata: None,
+        precedences: None,
+        capacity: None,
+        demands: None,
+        depots: None,
+        fixed_edges: None,
+    };
+    let mut order = construction::nearest_neighbor_tour(&centroid_instance, 0);
+    TwoOpt.improve(&mut order, &centroid_instance);
+    order
+}
+
+/// Appends the cyclic `cluster_tour` as a path, opening it at the edge and in
+/// the direction that make the entry from `prev` cheapest.
+fn append_cluster_tour(
+    tour: &mut Vec<usize>,
+    cluster_tour: &[usize],
+    prev: Option<usize>,
+    d: &DistanceMatrix,
+) {
+    let m = cluster_tour.len();
+    let Some(prev) = prev.filter(|_| m > 1) else {
+        tour.extend_from_slice(cluster_tour);
+        return;
+    };
+    let mut best = (f64::MAX, 0, false);
+    for p in 0..m {
+        let (a, b) = (cluster_tour[p], cluster_tour[(p + 1) % m]);
+        let removed = d.get(a, b);
+        let forward = d.get(prev, b) - removed;
+        let backward = d.get(prev, a) - removed;
+        if forward < best.0 {
+            best = (forward, p, false);
+        }
+        if backward < best.0 {
+            best = (backward, p, true);
+        }
+    }
+    let (_, p, reversed) = best;
+    if reversed {
+        tour.extend((0..m).map(|k| cluster_tour[(p + m - k) % m]));
+    } else {
+        tour.extend((0..m).map(|k| cluster_tour[(p + 1 + k) % m]));
+    }
+}
+
+/// 2-opt restricted to positions within `window` of `center`, cheap enough to
+/// repair cluster junctions of tours with 100k nodes.
+fn windowed_two_opt(tour: &mut [usize], d: &DistanceMatrix, center: usize, window: usize) {
+    let n = tour.len();
+    let lo = center.saturating_sub(window);
+    let hi = (center + window).min(n - 1);
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for i in lo..hi {
+            for j in (i + 2)..hi {
+                let (a, b) = (tour[i], tour[i + 1]);
+                let (c, e) = (tour[j], tour[j + 1]);
+                if d.get(a, c) + d.get(b, e) - d.get(a, b) - d.get(c, e) < -1e-9 {
+                    tour[i + 1..=j].reverse();
+                    improved = true;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,330 @@
+use crate::parser::{DistancePolicy, EdgeWeightType, Node};
+use std::collections::HashMap;
+
+/// Cost of an edge that cannot be travelled, e.g. a pair without a road
+/// route. Large enough that tours avoid it whenever possible, but finite so
+/// that tour lengths stay comparable.
+pub const FORBIDDEN_DISTANCE: f64 = 1e9;
+
+/// Distance storage of a [`TspInstance`](crate::parser::TspInstance).
+///
+/// Symmetric instances only keep the upper triangle (without the diagonal),
+/// which nearly halves the memory of big instances while keeping O(1) access.
+#[derive(Debug, Clone)]
+pub enum DistanceMatrix {
+    /// Row-major `n * n` matrix, used for asymmetric data.
+    Full { n: usize, data: Vec<f64> },
+    /// Upper triangle of a symmetric matrix, `n * (n - 1) / 2` entries.
+    Triangular { n: usize, data: Vec<f64> },
+    /// Per-edge cost deltas layered over another matrix without copying it.
+    /// Keys are `(i, j)` with `i < j` when the base stores symmetric data.
+    Adjusted {
+        base: Box<DistanceMatrix>,
+        deltas: HashMap<(usize, usize), f64>,
+    },
+    /// Distances computed from coordinates on every access. Needs no
+    /// quadratic memory, for instances too large to store a matrix.
+    Lazy {
+        nodes: Vec<Node>,
+        metric: EdgeWeightType,
+        policy: DistancePolicy,
+    },
+}
+
+impl DistanceMatrix {
+    pub fn full(n: usize) -> Self {
+        DistanceMatrix::Full {
+            n,
+            data: vec![0.0; n * n],
+        }
+    }
+
+    pub fn triangular(n: usize) -> Self {
+        DistanceMatrix::Triangular {
+            n,
+            data: vec![0.0; n * n.saturating_sub(1) / 2],
+        }
+    }
+
+    /// Distances computed from `nodes` under `metric` on every access.
+    /// Fails for metrics that are not computed from coordinates, so that
+    /// lookups cannot.
+    pub fn lazy(
+        nodes: Vec<Node>,
+        metric: EdgeWeightType,
+        policy: DistancePolicy,
+    ) -> Result<Self, String> {
+        if let EdgeWeightType::Explicit | EdgeWeightType::Unknown(_) = metric {
+            return Err(format!(
+                "{} distances cannot be computed from coordinates",
+                metric.keyword()
+            ));
+        }
+        Ok(DistanceMatrix::Lazy {
+            nodes,
+            metric,
+            policy,
+        })
+    }
+
+    // Entries are laid out column by column (`(0,1), (0,2), (1,2), (0,3), ...`)
+    // so appending a node only appends to `data`.
+    #[inline]
+    fn triangular_index(i: usize, j: usize) -> usize {
+        let (i, j) = if i < j { (i, j) } else { (j, i) };
+        j * (j - 1) / 2 + i
+    }
+
+    #[inline]
+    fn delta_key(symmetric: bool, i: usize, j: usize) -> (usize, usize) {
+        if symmetric && j < i { (j, i) } else { (i, j) }
+    }
+
+    /// Wraps `self` in an overlay adding `delta` to the cost of each listed
+    /// edge. Deltas for the same edge accumulate.
+    pub fn with_adjustments(
+        self,
+        adjustments: impl IntoIterator<Item = (usize, usize, f64)>,
+    ) -> Result<Self, String> {
+        let n = self.dimension();
+        let (base, mut deltas) = match self {
+            DistanceMatrix::Adjusted { base, deltas } => (base, deltas),
+            other => (Box::new(other), HashMap::new()),
+        };
+        let symmetric = base.is_symmetric_storage();
+        for (i, j, delta) in adjustments {
+            if i >= n || j >= n || i == j {
+                return Err(format!("Invalid edge ({}, {}) for dimension {}", i, j, n));
+            }
+            *deltas
+                .entry(Self::delta_key(symmetric, i, j))
+                .or_insert(0.0) += delta;
+        }
+        Ok(DistanceMatrix::Adjusted { base, deltas })
+    }
+
+    pub fn dimension(&self) -> usize {
+        match self {
+            DistanceMatrix::Full { n, .. } | DistanceMatrix::Triangular { n, .. } => *n,
+            DistanceMatrix::Adjusted { base, .. } => base.dimension(),
+            DistanceMatrix::Lazy { nodes, .. } => nodes.len(),
+        }
+    }
+
+    /// Whether setting `(i, j)` also sets `(j, i)`.
+    pub fn is_symmetric_storage(&self) -> bool {
+        match self {
+            DistanceMatrix::Full { .. } => false,
+            DistanceMatrix::Triangular { .. } | DistanceMatrix::Lazy { .. } => true,
+            DistanceMatrix::Adjusted { base, .. } => base.is_symmetric_storage(),
+        }
+    }
+
+    #[inline]
+    pub fn get(&self, i: usize, j: usize) -> f64 {
+        match self {
+            DistanceMatrix::Full { n, data } => {
+                debug_assert!(j < *n, "column {} out of bounds for dimension {}", j, n);
+                data[i * n + j]
+            }
+            DistanceMatrix::Triangular { data, .. } => {
+                if i == j {
+                    0.0
+                } else {
+                    data[Self::triangular_index(i, j)]
+                }
+            }
+            DistanceMatrix::Adjusted { base, deltas } => {
+                let key = Self::delta_key(base.is_symmetric_storage(), i, j);
+                base.get(i, j) + deltas.get(&key).copied().unwrap_or(0.0)
+            }
+            DistanceMatrix::Lazy {
+                nodes,
+                metric,
+                policy,
+            } => {
+                if i == j {
+                    0.0
+                } else {
+                    let distance = metric
+                        .distance(&nodes[i], &nodes[j])
+                        .expect("lazy matrices have a coordinate metric");
+                    policy.apply(distance)
+                }
+            }
+        }
+    }
+
+    /// Sets the distance from `i` to `j`. Triangular and lazy storage set
+    /// both directions and ignore the diagonal. On an adjusted matrix the
+    /// base cost is set, so the edge's delta still applies on top. A lazy
+    /// matrix becomes an adjusted one overriding the computed distance.
+    #[inline]
+    pub fn set(&mut self, i: usize, j: usize, value: f64) {
+        match self {
+            DistanceMatrix::Full { n, data } => data[i * *n + j] = value,
+            DistanceMatrix::Triangular { data, .. } => {
+                if i != j {
+                    data[Self::triangular_index(i, j)] = value;
+                }
+            }
+            DistanceMatrix::Adjusted { base, .. } => base.set(i, j, value),
+            DistanceMatrix::Lazy { .. } if i == j => {}
+            DistanceMatrix::Lazy { .. } => {
+                let delta = value - self.get(i, j);
+                let lazy = std::mem::replace(self, DistanceMatrix::full(0));
+                *self = lazy
+                    .with_adjustments([(i, j, delta)])
+                    .expect("off-diagonal edge within the dimension");
+            }
+        }
+    }
+
+    /// Sets the distance between `i` and `j` in both directions, ignoring
+    /// the diagonal, whatever the storage.
+    pub fn set_symmetric(&mut self, i: usize, j: usize, value: f64) {
+        if i == j {
+            return;
+        }
+        self.set(i, j, value);
+        if !self.is_symmetric_storage() {
+            self.set(j, i, value);
+        }
+    }
+
+    pub fn is_symmetric(&self) -> bool {
+        match self {
+            DistanceMatrix::Triangular { .. } => true,
+            _ => {
+                let n = self.dimension();
+                (0..n).all(|i| ((i + 1)..n).all(|j| self.get(i, j) == self.get(j, i)))
+            }
+        }
+    }
+
+    /// Converts a full matrix with symmetric values and a zero diagonal to
+    /// triangular storage; anything else is returned unchanged.
+    pub fn into_compact(self) -> Self {
+        let DistanceMatrix::Full { n, .. } = self else {
+            return self;
+        };
+        if !self.is_symmetric() || (0..n).any(|i| self.get(i, i) != 0.0) {
+            return self;
+        }
+        let mut compact = DistanceMatrix::triangular(n);
+        for i in 0..n {
+            for j in (i + 1)..n {
+                compact.set(i, j, self.get(i, j));
+            }
+        }
+        compact
+    }
+
+    /// Converts triangular storage to a full matrix, trading memory for
+    /// cheaper lookups; anything else is returned unchanged.
+    pub fn into_dense(self) -> Self {
+        let DistanceMatrix::Triangular { n, .. } = self else {
+            return self;
+        };
+        let mut dense = DistanceMatrix::full(n);
+        for i in 0..n {
+            for j in 0..n {
+                if i != j {
+                    dense.set(i, j, self.get(i, j));
+                }
+            }
+        }
+        dense
+    }
+
+    /// Appends `node`, whose distance to and from node `i` is `distances[i]`.
+    /// Lazy storage ignores `distances` and computes them from `node`.
+    pub fn push_node(&mut self, node: &Node, distances: &[f64]) {
+        match self {
+            DistanceMatrix::Triangular { n, data } => {
+                data.extend_from_slice(&distances[..*n]);
+                *n += 1;
+            }
+            DistanceMatrix::Full { n, data } => {
+                let old_n = *n;
+                let mut grown = Vec::with_capacity((old_n + 1) * (old_n + 1));
+                for i in 0..old_n {
+                    grown.extend_from_slice(&data[i * old_n..(i + 1) * old_n]);
+                    grown.push(distances[i]);
+                }
+                grown.extend_from_slice(&distances[..old_n]);
+                grown.push(0.0);
+                *data = grown;
+                *n += 1;
+            }
+            DistanceMatrix::Adjusted { base, .. } => base.push_node(node, distances),
+            DistanceMatrix::Lazy { nodes, .. } => nodes.push(node.clone()),
+        }
+    }
+
+    /// Replaces node `k`, whose distance to and from node `i` is now
+    /// `distances[i]`; only row and column `k` are written. Lazy storage
+    /// ignores `distances` and computes them from `node`, and edge deltas
+    /// of an adjusted matrix stay on top of the new distances.
+    pub fn update_node(&mut self, k: usize, node: &Node, distances: &[f64]) {
+        match self {
+            DistanceMatrix::Full { .. } | DistanceMatrix::Triangular { .. } => {
+                for (i, &distance) in distances.iter().enumerate() {
+                    if i != k {
+                        self.set(k, i, distance);
+                        self.set(i, k, distance);
+                    }
+                }
+            }
+            DistanceMatrix::Adjusted { base, .. } => base.update_node(k, node, distances),
+            DistanceMatrix::Lazy { nodes, .. } => nodes[k] = node.clone(),
+        }
+    }
+
+    /// Removes node `k`; nodes after it shift down by one index.
+    pub fn remove_node(&mut self, k: usize) {
+        let n = self.dimension();
+        let mut shrunk = match self {
+            DistanceMatrix::Full { .. } => DistanceMatrix::full(n - 1),
+            DistanceMatrix::Triangular { .. } => DistanceMatrix::triangular(n - 1),
+            DistanceMatrix::Adjusted { base, deltas } => {
+                base.remove_node(k);
+                *deltas = deltas
+                    .drain()
+                    .filter(|&((i, j), _)| i != k && j != k)
+                    .map(|((i, j), delta)| {
+                        let shift = |x: usize| if x > k { x - 1 } else { x };
+                        ((shift(i), shift(j)), delta)
+                    })
+                    .collect();
+                return;
+            }
+            DistanceMatrix::Lazy { nodes, .. } => {
+                nodes.remove(k);
+                return;
+            }
+        };
+        let old_index = |i: usize| if i < k { i } else { i + 1 };
+        for i in 0..n - 1 {
+            for j in 0..n - 1 {
+                if i != j {
+                    shrunk.set(i, j, self.get(old_index(i), old_index(j)));
+                }
+            }
+        }
+        *self = shrunk;
+    }
+
+    /// Bytes used by the stored distances.
+    pub fn memory_bytes(&self) -> usize {
+        match self {
+            DistanceMatrix::Full { data, .. } | DistanceMatrix::Triangular { data, .. } => {
+                data.len() * size_of::<f64>()
+            }
+            DistanceMatrix::Adjusted { base, deltas } => {
+                base.memory_bytes() + deltas.len() * size_of::<((usize, usize), f64)>()
+            }
+            DistanceMatrix::Lazy { nodes, .. } => nodes.len() * size_of::<Node>(),
+        }
+    }
+}
@@ -0,0 +1,321 @@
+//! Coordinator/worker mode: several processes (possibly on different
+//! machines) run colonies on the same instance and exchange elite tours
+//! through a coordinator over TCP.
+//!
+//! The protocol is line based. Every `exchange_interval` iterations a worker
+//! sends `BEST <n0> <n1> ...` (its best tour as 0-based indices) and the
+//! coordinator answers with the best tour any worker has reported, in the
+//! same form (`NONE` if there is none yet). A worker sends `DONE` when it
+//! has used up its iterations.
+//!
+//! With `Config::colony_ranges` each worker draws its own alpha, beta and
+//! evaporation rate and appends them to its reports as `PARAMS <alpha>
+//! <beta> <rho>`. The coordinator hands out the parameters of the colony
+//! behind the global best along with the tour, and a worker that adopts the
+//! tour switches to them, so successful settings spread without a separate
+//! tuning phase.
+
+use crate::config::Config;
+use crate::events::{self, SolverEvent};
+use crate::parser::TspInstance;
+use crate::prepared::PreparedInstance;
+use crate::solver::{AcoSolver, ant_rng};
+use rand::Rng;
+use std::fmt;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Role of this process in distributed solving.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DistributedRole {
+    /// Listen on `addr` and serve until `workers` workers have finished.
+    Coordinator { addr: String, workers: usize },
+    /// Connect to the coordinator at `addr`.
+    Worker { addr: String },
+}
+
+/// The parameters that differ between colonies.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColonyParams {
+    pub alpha: f64,
+    pub beta: f64,
+    pub evap_rate: f64,
+}
+
+impl ColonyParams {
+    pub fn of(config: &Config) -> Self {
+        ColonyParams {
+            alpha: config.alpha,
+            beta: config.beta,
+            evap_rate: config.evap_rate,
+        }
+    }
+
+    pub fn apply(&self, config: &mut Config) {
+        config.alpha = self.alpha;
+        config.beta = self.beta;
+        config.evap_rate = self.evap_rate;
+    }
+}
+
+impl fmt::Display for ColonyParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "alpha {:.3}, beta {:.3}, rho {:.3}",
+            self.alpha, self.beta, self.evap_rate
+        )
+    }
+}
+
+/// Ranges (inclusive) workers draw their [`ColonyParams`] from; `None`
+/// keeps the configured value.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ColonyRanges {
+    pub alpha: Option<(f64, f64)>,
+    pub beta: Option<(f64, f64)>,
+    pub evap_rate: Option<(f64, f64)>,
+}
+
+impl ColonyRanges {
+    pub fn sample(&self, config: &Config, rng: &mut impl Rng) -> ColonyParams {
+        let mut draw = |range: Option<(f64, f64)>, value: f64| match range {
+            Some((low, high)) if low < high => rng.random_range(low..=high),
+            Some((low, _)) => low,
+            None => value,
+        };
+        ColonyParams {
+            alpha: draw(self.alpha, config.alpha),
+            beta: draw(self.beta, config.beta),
+            evap_rate: draw(self.evap_rate, config.evap_rate),
+        }
+    }
+}
+
+struct Incumbent {
+    tour: Vec<usize>,
+    length: f64,
+    // Parameters of the colony that found `tour`, if it reported them.
+    params: Option<ColonyParams>,
+}
+
+/// Runs the coordinator. Reported tours are re-evaluated on the
+/// coordinator's own copy of the instance, so a misbehaving worker cannot
+/// claim a better length than its tour has. Returns the best tour once all
+/// `workers` workers have sent `DONE` or disconnected. Connections and new
+/// global bests are sent to `config.event_sender`.
+pub fn run_coordinator(
+    instance: &TspInstance,
+    config: &Config,
+    addr: &str,
+    workers: usize,
+) -> Result<(Vec<usize>, f64), String> {
+    let listener =
+        TcpListener::bind(addr).map_err(|e| format!("Failed to bind {}: {}", addr, e))?;
+    let incumbent = Arc::new(Mutex::new(Incumbent {
+        tour: Vec::new(),
+        length: f64::MAX,
+        params: None,
+    }));
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            let (stream, peer) = listener
+                .accept()
+                .map_err(|e| format!("Failed to accept worker: {}", e))?;
+            let events = &config.event_sender;
+            events::info(events, format!("  Worker connected from {}", peer));
+            let incumbent = Arc::clone(&incumbent);
+            scope.spawn(move || {
+                if let Err(e) = serve_worker(stream, instance, &incumbent, events) {
+                    events::warn(events, format!("Worker {} dropped: {}", peer, e));
+                }
+            });
+        }
+        Ok::<(), String>(())
+    })?;
+
+    let incumbent = incumbent.lock().map_err(|_| "Coordinator state poisoned")?;
+    if incumbent.tour.is_empty() {
+        return Err("No worker reported a tour".to_string());
+    }
+    Ok((incumbent.tour.clone(), incumbent.length.round()))
+}
+
+fn serve_worker(
+    stream: TcpStream,
+    instance: &TspInstance,
+    incumbent: &Mutex<Incumbent>,
+    events: &Option<Sender<SolverEvent>>,
+) -> Result<(), String> {
+    let mut writer = stream.try_clone().map_err(|e| e.to_string())?;
+    for line in BufReader::new(stream).lines() {
+        let line = line.map_err(|e| e.to_string())?;
+        let line = line.trim();
+        if line == "DONE" {
+            break;
+        }
+        let (tour, params) = parse_tour_message(line)?;
+        let mut state = incumbent.lock().map_err(|_| "Coordinator state poisoned")?;
+        if let Some(tour) = tour
+            && crate::constraints::validate_permutation(&tour, instance.dimension).is_ok()
+        {
+            let length = instance.tour_length(&tour);
+            if length < state.length {
+                let message = match params {
+                    Some(params) => {
+                        format!("  New global best from worker: {:.2} ({})", length, params)
+                    }
+                    None => format!("  New global best from worker: {:.2}", length),
+                };
+                events::info(events, message);
+                state.tour = tour;
+                state.length = length;
+                state.params = params;
+            }
+        }
+        let reply = format_tour_message(&state.tour, state.params);
+        drop(state);
+        writer
+            .write_all(reply.as_bytes())
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Runs a colony that exchanges its best tour with the coordinator at
+/// `addr` every `Config::exchange_interval` iterations, adopting the global
+/// best whenever it beats its own. With `Config::colony_ranges` the colony
+/// starts from parameters drawn from the ranges (with the configured seed,
+/// so give workers different seeds) and takes over the parameters that come
+/// with an adopted tour, keeping its trails.
+pub fn run_worker(
+    instance: &TspInstance,
+    config: &Config,
+    addr: &str,
+) -> Result<(Vec<usize>, f64), String> {
+    let stream =
+        TcpStream::connect(addr).map_err(|e| format!("Failed to connect to {}: {}", addr, e))?;
+    let mut writer = stream.try_clone().map_err(|e| e.to_string())?;
+    let mut reader = BufReader::new(stream);
+    let interval = config.exchange_interval.max(1);
+
+    let mut colony = config.clone();
+    if let Some(ranges) = &config.colony_ranges {
+        let params = ranges.sample(config, &mut ant_rng(config.seed, 0, usize::MAX - 1));
+        params.apply(&mut colony);
+        events::info(
+            &config.event_sender,
+            format!("  Colony parameters: {}", params),
+        );
+    }
+    let mut reply = String::new();
+    // Restarts with adopted parameters keep the heuristic table.
+    let prepared = PreparedInstance::new(instance);
+    loop {
+        let mut solver = AcoSolver::prepared(&prepared, &colony);
+        let mut switch_to = None;
+        while !solver.is_finished() && switch_to.is_none() {
+            solver.step();
+            if solver.iteration().is_multiple_of(interval) || solver.is_finished() {
+                let params = config.colony_ranges.map(|_| ColonyParams::of(&colony));
+                let message = format_tour_message(solver.best_tour(), params);
+                writer
+                    .write_all(message.as_bytes())
+                    .map_err(|e| format!("Lost connection to coordinator: {}", e))?;
+                reply.clear();
+                reader
+                    .read_line(&mut reply)
+                    .map_err(|e| format!("Lost connection to coordinator: {}", e))?;
+                let (tour, params) = parse_tour_message(reply.trim())?;
+                if let Some(tour) = tour
+                    && let Ok(true) = solver.offer_tour(&tour)
+                {
+                    events::info(
+                        &config.event_sender,
+                        format!(
+                            "Iter {}: Adopted global best {:.2}",
+                            solver.iteration(),
+                            solver.best_tour_length()
+                        ),
+                    );
+                    switch_to = params.filter(|&params| {
+                        config.colony_ranges.is_some() && params != ColonyParams::of(&colony)
+                    });
+                }
+            }
+        }
+        let Some(params) = switch_to else {
+            // The coordinator may already be gone; the local result is still valid.
+            let _ = writer.write_all(b"DONE\n");
+            return Ok(solver.run());
+        };
+        // Restart on the new parameters from where this colony stands.
+        let checkpoint = solver.checkpoint();
+        events::info(
+            &config.event_sender,
+            format!("Iter {}: Switched to {}", checkpoint.iteration, params),
+        );
+        colony.resume = Some(checkpoint);
+        params.apply(&mut colony);
+    }
+}
+
+fn format_tour_message(tour: &[usize], params: Option<ColonyParams>) -> String {
+    if tour.is_empty() {
+        return "NONE\n".to_string();
+    }
+    let nodes: Vec<String> = tour.iter().map(|node| node.to_string()).collect();
+    match params {
+        Some(p) => format!(
+            "BEST {} PARAMS {} {} {}\n",
+            nodes.join(" "),
+            p.alpha,
+            p.beta,
+            p.evap_rate
+        ),
+        None => format!("BEST {}\n", nodes.join(" ")),
+    }
+}
+
+fn parse_tour_message(line: &str) -> Result<(Option<Vec<usize>>, Option<ColonyParams>), String> {
+    if line == "NONE" {
+        return Ok((None, None));
+    }
+    let rest = line
+        .strip_prefix("BEST")
+        .ok_or_else(|| format!("Unexpected message: {}", line))?;
+    let (nodes, params) = match rest.split_once("PARAMS") {
+        Some((nodes, params)) => {
+            let values = params
+                .split_whitespace()
+                .map(|value| {
+                    value
+                        .parse()
+                        .map_err(|_| format!("Invalid parameter in message: {}", value))
+                })
+                .collect::<Result<Vec<f64>, _>>()?;
+            let [alpha, beta, evap_rate] = values[..] else {
+                return Err(format!("Expected three parameters in message: {}", line));
+            };
+            let params = ColonyParams {
+                alpha,
+                beta,
+                evap_rate,
+            };
+            (nodes, Some(params))
+        }
+        None => (rest, None),
+    };
+    let tour = nodes
+        .split_whitespace()
+        .map(|node| {
+            node.parse()
+                .map_err(|_| format!("Invalid node index in message: {}", node))
+        })
+        .collect::<Result<Vec<usize>, _>>()?;
+    Ok((Some(tour), params))
+}
@@ -0,0 +1,220 @@
+//! How often each edge appears in the best tours of repeated runs. Edges
+//! every run agrees on are robust parts of the route; edges only some runs
+//! use mark where the search has not settled.
+
+use crate::parser::TspInstance;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+const CANVAS_SIZE: f64 = 600.0;
+
+/// An undirected edge between node indices `from < to` and the number of
+/// runs whose best tour used it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdgeCount {
+    pub from: usize,
+    pub to: usize,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EdgeFrequencies {
+    pub instance: String,
+    /// Runs that contributed a tour.
+    pub runs: usize,
+    /// Most frequent edges first, ties by node indices.
+    pub edges: Vec<EdgeCount>,
+    // Of the instance, so the counts can be written without it.
+    node_ids: Vec<usize>,
+    coordinates: Option<Vec<(f64, f64)>>,
+}
+
+impl EdgeFrequencies {
+    /// Counts the edges of `tours` on `instance`, closing each tour. Tours
+    /// of fewer than two nodes have no edges and are not counted as runs.
+    pub fn new<'t>(instance: &TspInstance, tours: impl IntoIterator<Item = &'t [usize]>) -> Self {
+        let mut counts: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut runs = 0;
+        for tour in tours {
+            if tour.len() < 2 {
+                continue;
+            }
+            runs += 1;
+            let mut edges: Vec<(usize, usize)> = tour
+                .iter()
+                .zip(tour.iter().cycle().skip(1))
+                .map(|(&a, &b)| (a.min(b), a.max(b)))
+                .collect();
+            // A two-node tour uses its only edge twice; count it once.
+            edges.sort_unstable();
+            edges.dedup();
+            for edge in edges {
+                *counts.entry(edge).or_default() += 1;
+            }
+        }
+        let mut edges: Vec<EdgeCount> = counts
+            .into_iter()
+            .map(|((from, to), count)| EdgeCount { from, to, count })
+            .collect();
+        edges.sort_unstable_by(|a, b| {
+            b.count
+                .cmp(&a.count)
+                .then((a.from, a.to).cmp(&(b.from, b.to)))
+        });
+        let nodes = instance
+            .node_coords
+            .as_deref()
+            .filter(|nodes| nodes.len() == instance.dimension);
+        EdgeFrequencies {
+            instance: instance.name.clone(),
+            runs,
+            edges,
+            node_ids: (0..instance.dimension)
+                .map(|idx| nodes.map_or(idx + 1, |nodes| nodes[idx].id))
+                .collect(),
+            coordinates: nodes.map(|nodes| nodes.iter().map(|node| (node.x, node.y)).collect()),
+        }
+    }
+
+    /// Share of runs that used `edge`, in `[0, 1]`.
+    pub fn frequency(&self, edge: &EdgeCount) -> f64 {
+        edge.count as f64 / self.runs.max(1) as f64
+    }
+
+    /// Edges used by every run.
+    pub fn stable_edges(&self) -> usize {
+        self.edges
+            .iter()
+            .filter(|edge| edge.count == self.runs)
+            .count()
+    }
+
+    /// CSV lines `instance,from,to,count,frequency` with node ids, without
+    /// a header.
+    pub fn csv_rows(&self) -> String {
+        let mut csv = String::new();
+        for edge in &self.edges {
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{}",
+                self.instance,
+                self.node_ids[edge.from],
+                self.node_ids[edge.to],
+                edge.count,
+                self.frequency(edge)
+            );
+        }
+        csv
+    }
+
+    /// An SVG heatmap of the edges over the node coordinates: edges used by
+    /// every run are drawn blue, opaque and thick, rare ones red, faint and
+    /// thin.
+    pub fn to_svg(&self) -> Result<String, String> {
+        let nodes = self
+            .coordinates
+            .as_deref()
+            .filter(|nodes| !nodes.is_empty())
+            .ok_or_else(|| format!("{} has no node coordinates to draw", self.instance))?;
+        let (min_x, max_x) = nodes.iter().fold((f64::MAX, f64::MIN), |(lo, hi), p| {
+            (lo.min(p.0), hi.max(p.0))
+        });
+        let (min_y, max_y) = nodes.iter().fold((f64::MAX, f64::MIN), |(lo, hi), p| {
+            (lo.min(p.1), hi.max(p.1))
+        });
+        let scale = (CANVAS_SIZE - 20.0) / (max_x - min_x).max(max_y - min_y).max(f64::EPSILON);
+        let project = |idx: usize| {
+            let (x, y) = nodes[idx];
+            (
+                10.0 + (x - min_x) * scale,
+                CANVAS_SIZE - 10.0 - (y - min_y) * scale,
+            )
+        };
+
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">\n<title>{2}</title>\n<rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n",
+            CANVAS_SIZE,
+            CANVAS_SIZE + 30.0,
+            self.instance
+        );
+        // Rare edges first, so the robust ones are drawn on top.
+        for edge in self.edges.iter().rev() {
+            let frequency = self.frequency(edge);
+            let ((x1, y1), (x2, y2)) = (project(edge.from), project(edge.to));
+            let _ = writeln!(
+                svg,
+                "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"{}\" stroke-opacity=\"{:.2}\" stroke-width=\"{:.1}\"/>",
+                x1,
+                y1,
+                x2,
+                y2,
+                heat_colour(frequency),
+                0.25 + 0.75 * frequency,
+                0.5 + 2.0 * frequency
+            );
+        }
+        let radius = if nodes.len() > 2000 { 0.8 } else { 2.0 };
+        for idx in 0..nodes.len() {
+            let (x, y) = project(idx);
+            let _ = writeln!(
+                svg,
+                "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{}\" fill=\"#333\"/>",
+                x, y, radius
+            );
+        }
+        let _ = writeln!(
+            svg,
+            "<text x=\"10\" y=\"{:.0}\" font-family=\"sans-serif\" font-size=\"14\">{} runs: {} of {} edges in every best tour</text>",
+            CANVAS_SIZE + 20.0,
+            self.runs,
+            self.stable_edges(),
+            self.edges.len()
+        );
+        svg.push_str("</svg>\n");
+        Ok(svg)
+    }
+}
+
+/// Blue for edges every run used, through purple to red for rare ones.
+fn heat_colour(frequency: f64) -> String {
+    let frequency = frequency.clamp(0.0, 1.0);
+    format!(
+        "#{:02x}30{:02x}",
+        (220.0 * (1.0 - frequency)) as u8,
+        (220.0 * frequency) as u8
+    )
+}
+
+/// Writes the edge counts of every benchmarked instance to `path`: one CSV
+/// table, or with an `.svg` extension one heatmap per instance. Several
+/// instances get their name appended to the file stem, e.g. `heat-eil51.svg`.
+pub fn write_edge_frequencies(all: &[EdgeFrequencies], path: &str) -> Result<(), String> {
+    let write = |path: &str, contents: String| {
+        fs::write(path, contents).map_err(|e| format!("Failed to write {}: {}", path, e))
+    };
+    let target = Path::new(path);
+    let is_svg = target
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("svg"));
+    if !is_svg {
+        let mut csv = "instance,from,to,count,frequency\n".to_string();
+        for frequencies in all {
+            csv.push_str(&frequencies.csv_rows());
+        }
+        return write(path, csv);
+    }
+    if let [frequencies] = all {
+        return write(path, frequencies.to_svg()?);
+    }
+    let stem = target
+        .file_stem()
+        .map_or(String::new(), |stem| stem.to_string_lossy().into_owned());
+    for frequencies in all {
+        let file = target.with_file_name(format!("{}-{}.svg", stem, frequencies.instance));
+        write(&file.to_string_lossy(), frequencies.to_svg()?)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use std::sync::mpsc::Sender;
+
+/// Notifications emitted by the solver when `Config::event_sender` is set.
+///
+/// Sending never blocks the solver; if the receiving end has been dropped the
+/// events are silently discarded.
+#[derive(Debug, Clone)]
+pub enum SolverEvent {
+    /// `edge_entropy` is the normalized edge-usage entropy of this
+    /// iteration's ant tours, see [`crate::solver::edge_entropy`].
+    IterationFinished {
+        iteration: usize,
+        iteration_best_length: f64,
+        best_length: f64,
+        edge_entropy: f64,
+    },
+    NewBest {
+        iteration: usize,
+        tour: Vec<usize>,
+        length: f64,
+    },
+    /// No improvement for `iterations_without_improvement` iterations; sent
+    /// every time another `stagnation_limit` iterations pass without progress.
+    Stagnation {
+        iteration: usize,
+        iterations_without_improvement: usize,
+    },
+    CheckpointWritten {
+        iteration: usize,
+        path: String,
+    },
+    /// A recoverable problem, e.g. an ignored constraint or a failed write.
+    Warning {
+        message: String,
+    },
+    /// A line of progress for the user, e.g. a worker joining a distributed
+    /// run; see [`crate::Reporter::on_message`].
+    Message {
+        message: String,
+    },
+}
+
+pub(crate) fn emit(sender: &Option<Sender<SolverEvent>>, event: SolverEvent) {
+    if let Some(tx) = sender {
+        let _ = tx.send(event);
+    }
+}
+
+/// Sends `message` as a [`SolverEvent::Warning`], or prints it to stderr if
+/// nobody listens for events.
+pub(crate) fn warn(sender: &Option<Sender<SolverEvent>>, message: String) {
+    match sender {
+        Some(tx) => {
+            let _ = tx.send(SolverEvent::Warning { message });
+        }
+        None => eprintln!("Warning: {}", message),
+    }
+}
+
+/// Sends `message` as a [`SolverEvent::Message`]; without a listener it is
+/// dropped.
+pub(crate) fn info(sender: &Option<Sender<SolverEvent>>, message: String) {
+    emit(sender, SolverEvent::Message { message });
+}
@@ -0,0 +1,295 @@
+//! Experiment sessions: one configuration run over a set of instances and
+//! seeds, with everything it produced kept in a directory of its own.
+//!
+//! `<root>/<name>-<YYYYMMDD-HHMMSS>/` holds
+//! - `config/settings.txt`, the settings of the run, and a copy of the
+//!   configuration file, if one was given,
+//! - `experiment.log`, one line per run or skipped instance,
+//! - `runs/<instance>-seed<seed>.json`, the [`Solution`] of every run,
+//! - `tours/<instance>-seed<seed>.tour`, its tour in TSPLIB format,
+//! - `summary.csv` (one line per run) and `summary.txt` (per instance).
+
+use crate::bench::best_known_length;
+use crate::config::Config;
+use crate::export::tsplib_tour;
+use crate::parser::parse_tsp_file_with_options;
+use crate::provenance::VERSION;
+use crate::results::{RunRecord, history_channel, iteration_history};
+use crate::solution::Solution;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// A finished experiment: its directory and all runs, in instance and seed
+/// order. Instances that failed to parse are only recorded in the log.
+#[derive(Debug, Clone)]
+pub struct Experiment {
+    pub dir: PathBuf,
+    pub runs: Vec<RunRecord>,
+}
+
+/// The seeds of an experiment: `config.seed_list`, or `config.num_seeds`
+/// consecutive seeds from `config.seed` (or 1).
+pub fn experiment_seeds(config: &Config) -> Vec<u64> {
+    if !config.seed_list.is_empty() {
+        return config.seed_list.clone();
+    }
+    let base_seed = config.seed.unwrap_or(1);
+    (0..config.num_seeds.max(1) as u64)
+        .map(|s| base_seed + s)
+        .collect()
+}
+
+/// Solves every instance in `instances` once per seed with `config` and
+/// writes the session directory under `root`. `config_file` is copied into
+/// it; `solutions` holds best-known lengths besides the embedded optima.
+pub fn run_experiment(
+    name: &str,
+    root: &Path,
+    instances: &[PathBuf],
+    seeds: &[u64],
+    config: &Config,
+    config_file: Option<&str>,
+    solutions: &HashMap<String, f64>,
+) -> Result<Experiment, String> {
+    let started = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let dir = create_session_dir(root, name, &utc_timestamp(started))?;
+    for sub in ["config", "runs", "tours"] {
+        fs::create_dir(dir.join(sub))
+            .map_err(|e| format!("Failed to create {}: {}", dir.join(sub).display(), e))?;
+    }
+    write(
+        &dir.join("config/settings.txt"),
+        &format!(
+            "version={}\nseeds={:?}\n{}\n",
+            VERSION,
+            seeds,
+            config.settings().replace(", ", "\n")
+        ),
+    )?;
+    if let Some(path) = config_file {
+        let file_name = Path::new(path)
+            .file_name()
+            .ok_or_else(|| format!("Invalid config file path {}", path))?;
+        fs::copy(path, dir.join("config").join(file_name))
+            .map_err(|e| format!("Failed to copy config file {}: {}", path, e))?;
+    }
+    let log_path = dir.join("experiment.log");
+    let mut log = fs::File::create(&log_path)
+        .map_err(|e| format!("Failed to create {}: {}", log_path.display(), e))?;
+    let clock = Instant::now();
+    let mut log_line = |line: String| {
+        let _ = writeln!(log, "[{:>8.2}s] {}", clock.elapsed().as_secs_f64(), line);
+    };
+
+    let mut runs = Vec::new();
+    for path in instances {
+        let instance = match parse_tsp_file_with_options(&path.to_string_lossy(), &config.into()) {
+            Ok(instance) => instance,
+            Err(e) => {
+                log_line(format!("Skipping {}: {}", path.display(), e));
+                continue;
+            }
+        };
+        let label = path.file_stem().map_or(instance.name.clone(), |stem| {
+            stem.to_string_lossy().into_owned()
+        });
+        let best_known = best_known_length(&instance.name, solutions);
+        let instance_hash = instance.content_hash_hex();
+        for &seed in seeds {
+            let (event_sender, events) = history_channel(config);
+            let run_config = Config {
+                seed: Some(seed),
+                event_sender,
+                ..config.clone()
+            };
+            let mut solution = match crate::solve_instance(&instance, &run_config) {
+                Ok(solution) => solution,
+                Err(e) => {
+                    log_line(format!("{} seed {}: failed: {}", label, seed, e));
+                    continue;
+                }
+            };
+            if let Some(known) = best_known {
+                solution.set_optimum_with(known, config.gap_precision);
+            }
+            let stem = format!("{}-seed{}", label, seed);
+            write(
+                &dir.join("runs").join(format!("{}.json", stem)),
+                &(solution.to_json() + "\n"),
+            )?;
+            if solution.is_complete(instance.dimension) {
+                write(
+                    &dir.join("tours").join(format!("{}.tour", stem)),
+                    &tsplib_tour(&stem, &solution.node_ids),
+                )?;
+            }
+            let mut run = experiment_run(&label, &instance_hash, seed, &solution, config);
+            run.history = iteration_history(&events);
+            log_line(format!(
+                "{} seed {}: length {} ({}) in {:.2} s",
+                label,
+                seed,
+                run.length,
+                run.gap
+                    .map_or("no best-known length".to_string(), |gap| format!(
+                        "gap {:.2}%",
+                        gap
+                    )),
+                run.seconds
+            ));
+            runs.push(run);
+        }
+    }
+
+    write(&dir.join("summary.csv"), &summary_csv(&runs))?;
+    write(
+        &dir.join("summary.txt"),
+        &format!(
+            "Experiment {} ({} instances, {} seeds)\n\n{}",
+            name,
+            instances.len(),
+            seeds.len(),
+            format_summary(&runs)
+        ),
+    )?;
+    Ok(Experiment { dir, runs })
+}
+
+fn experiment_run(
+    label: &str,
+    instance_hash: &str,
+    seed: u64,
+    solution: &Solution,
+    config: &Config,
+) -> RunRecord {
+    RunRecord {
+        instance: label.to_string(),
+        instance_hash: instance_hash.to_string(),
+        seed,
+        length: solution.length_with(config.gap_precision),
+        gap: solution.gap,
+        seconds: solution.duration.as_secs_f64(),
+        tour: solution.tour.clone(),
+        history: Vec::new(),
+    }
+}
+
+fn write(path: &Path, contents: &str) -> Result<(), String> {
+    fs::write(path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+// `<root>/<name>-<timestamp>`, with a counter appended if that exists.
+fn create_session_dir(root: &Path, name: &str, timestamp: &str) -> Result<PathBuf, String> {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    fs::create_dir_all(root).map_err(|e| format!("Failed to create {}: {}", root.display(), e))?;
+    let base = format!("{}-{}", name, timestamp);
+    for attempt in 1.. {
+        let dir = match attempt {
+            1 => root.join(&base),
+            n => root.join(format!("{}-{}", base, n)),
+        };
+        match fs::create_dir(&dir) {
+            Ok(()) => return Ok(dir),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(format!("Failed to create {}: {}", dir.display(), e)),
+        }
+    }
+    unreachable!()
+}
+
+/// `secs` since the Unix epoch as a UTC `YYYYMMDD-HHMMSS` timestamp.
+pub fn utc_timestamp(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let time = secs % 86_400;
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm).
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}{:02}{:02}-{:02}{:02}{:02}",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+/// One CSV line per run; unknown gaps are left empty.
+pub fn summary_csv(runs: &[RunRecord]) -> String {
+    let mut csv = "instance,seed,length,gap,seconds\n".to_string();
+    for run in runs {
+        let _ = writeln!(
+            csv,
+            "{},{},{},{},{}",
+            run.instance,
+            run.seed,
+            run.length,
+            run.gap.map_or(String::new(), |gap| gap.to_string()),
+            run.seconds
+        );
+    }
+    csv
+}
+
+/// Plain text table with one line per instance: runs, best and mean length
+/// and gap, and mean run time.
+pub fn format_summary(runs: &[RunRecord]) -> String {
+    let mut table = format!(
+        "  {:<16} {:>4} {:>12} {:>12} {:>9} {:>9} {:>9}\n",
+        "Instance", "Runs", "Best", "Mean", "Best gap", "Mean gap", "Mean s"
+    );
+    let mut instances: Vec<&str> = Vec::new();
+    for run in runs {
+        if !instances.contains(&run.instance.as_str()) {
+            instances.push(&run.instance);
+        }
+    }
+    let gap = |gap: Option<f64>| gap.map_or("-".to_string(), |gap| format!("{:.2}%", gap));
+    for instance in instances {
+        let group: Vec<&RunRecord> = runs.iter().filter(|r| r.instance == instance).collect();
+        let count = group.len() as f64;
+        let best = group.iter().map(|r| r.length).fold(f64::MAX, f64::min);
+        let mean = group.iter().map(|r| r.length).sum::<f64>() / count;
+        let gaps: Option<Vec<f64>> = group.iter().map(|r| r.gap).collect();
+        let best_gap = gaps
+            .as_ref()
+            .map(|g| g.iter().copied().fold(f64::MAX, f64::min));
+        let mean_gap = gaps.as_ref().map(|g| g.iter().sum::<f64>() / count);
+        let seconds = group.iter().map(|r| r.seconds).sum::<f64>() / count;
+        let _ = writeln!(
+            table,
+            "  {:<16} {:>4} {:>12.2} {:>12.2} {:>9} {:>9} {:>9.2}",
+            instance,
+            group.len(),
+            best,
+            mean,
+            gap(best_gap),
+            gap(mean_gap),
+            seconds
+        );
+    }
+    table
+}
@@ -0,0 +1,239 @@
+use crate::parser::{EdgeWeightType, TspInstance};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// On-disk format for exported matrices.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MatrixFormat {
+    Csv,
+    /// NumPy `.npy` (version 1.0, little-endian `f64`, C order).
+    Npy,
+}
+
+impl MatrixFormat {
+    /// Picks the format from the file extension: `.npy` or anything else as CSV.
+    pub fn from_path(path: &str) -> MatrixFormat {
+        match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("npy") => MatrixFormat::Npy,
+            _ => MatrixFormat::Csv,
+        }
+    }
+}
+
+/// One leg of a closed tour between two consecutive stops.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteLeg {
+    pub from_id: usize,
+    pub to_id: usize,
+    pub distance: f64,
+    pub cumulative: f64, // Distance travelled including this leg
+}
+
+/// Breaks `tour` (0-based indices) into its legs, ending with the return
+/// to the first stop. Ids are the instance's node ids, or 1-based indices
+/// for explicit instances.
+pub fn route_legs(instance: &TspInstance, tour: &[usize]) -> Vec<RouteLeg> {
+    if tour.len() < 2 {
+        return Vec::new();
+    }
+    let id = |idx: usize| {
+        instance
+            .node_coords
+            .as_ref()
+            .and_then(|nodes| nodes.get(idx))
+            .map_or(idx + 1, |node| node.id)
+    };
+    let mut cumulative = 0.0;
+    tour.iter()
+        .zip(tour.iter().cycle().skip(1))
+        .map(|(&from, &to)| {
+            let distance = instance.dist_matrix.get(from, to);
+            cumulative += distance;
+            RouteLeg {
+                from_id: id(from),
+                to_id: id(to),
+                distance,
+                cumulative,
+            }
+        })
+        .collect()
+}
+
+/// Writes route legs as CSV with a header row.
+pub fn write_route_legs(legs: &[RouteLeg], path: &str) -> Result<(), String> {
+    let file =
+        File::create(path).map_err(|e| format!("Failed to create legs file {}: {}", path, e))?;
+    let mut writer = BufWriter::new(file);
+    let result = writeln!(writer, "leg,from_id,to_id,distance,cumulative")
+        .and_then(|_| {
+            legs.iter().enumerate().try_for_each(|(k, leg)| {
+                writeln!(
+                    writer,
+                    "{},{},{},{},{}",
+                    k + 1,
+                    leg.from_id,
+                    leg.to_id,
+                    leg.distance,
+                    leg.cumulative
+                )
+            })
+        })
+        .and_then(|_| writer.flush());
+    result.map_err(|e| format!("Failed to write legs file {}: {}", path, e))
+}
+
+/// A TSPLIB `TOUR` file visiting `node_ids` in order.
+pub fn tsplib_tour(name: &str, node_ids: &[usize]) -> String {
+    let mut contents = format!(
+        "NAME: {}.tour\nTYPE: TOUR\nDIMENSION: {}\nTOUR_SECTION\n",
+        name,
+        node_ids.len()
+    );
+    for id in node_ids {
+        contents.push_str(&format!("{}\n", id));
+    }
+    contents.push_str("-1\nEOF\n");
+    contents
+}
+
+/// The instance as a TSPLIB problem file that parses back to the same
+/// distances. Coordinate metrics are written as their coordinates (parse
+/// them with the same distance policy); everything else becomes an EXPLICIT
+/// FULL_MATRIX of the stored, already rounded weights.
+pub fn tsplib_instance(instance: &TspInstance) -> String {
+    let coords = instance.node_coords.as_deref().unwrap_or_default();
+    let coordinate_metric = !matches!(
+        instance.edge_weight_type,
+        EdgeWeightType::Explicit | EdgeWeightType::Special(_) | EdgeWeightType::Unknown(_)
+    ) && coords.len() == instance.dimension;
+    let tsp_type = match instance.tsp_type.as_str() {
+        "" => "TSP",
+        tsp_type => tsp_type,
+    };
+    let mut contents = format!("NAME: {}\nTYPE: {}\n", instance.name, tsp_type);
+    if !instance.comment.is_empty() {
+        contents.push_str(&format!("COMMENT: {}\n", instance.comment));
+    }
+    contents.push_str(&format!("DIMENSION: {}\n", instance.dimension));
+    if coordinate_metric {
+        contents.push_str(&format!(
+            "EDGE_WEIGHT_TYPE: {}\n",
+            instance.edge_weight_type.keyword()
+        ));
+    } else {
+        contents.push_str("EDGE_WEIGHT_TYPE: EXPLICIT\nEDGE_WEIGHT_FORMAT: FULL_MATRIX\n");
+    }
+    if !coords.is_empty() {
+        let three_d = instance.edge_weight_type.is_three_dimensional();
+        if three_d {
+            contents.push_str("NODE_COORD_TYPE: THREED_COORDS\n");
+        }
+        contents.push_str("NODE_COORD_SECTION\n");
+        for node in coords {
+            if three_d {
+                contents.push_str(&format!("{} {} {} {}\n", node.id, node.x, node.y, node.z));
+            } else {
+                contents.push_str(&format!("{} {} {}\n", node.id, node.x, node.y));
+            }
+        }
+    }
+    if !coordinate_metric {
+        contents.push_str("EDGE_WEIGHT_SECTION\n");
+        for i in 0..instance.dimension {
+            let row: Vec<String> = (0..instance.dimension)
+                .map(|j| instance.dist_matrix.get(i, j).to_string())
+                .collect();
+            contents.push_str(&row.join(" "));
+            contents.push('\n');
+        }
+    }
+    contents.push_str("EOF\n");
+    contents
+}
+
+/// Writes [`tsplib_instance`] to `path`.
+pub fn write_tsplib_instance(instance: &TspInstance, path: &str) -> Result<(), String> {
+    std::fs::write(path, tsplib_instance(instance))
+        .map_err(|e| format!("Failed to write instance {}: {}", path, e))
+}
+
+/// Writes a tour of node ids to `path`: in TSPLIB format for `.tour` files,
+/// one id per line otherwise.
+pub fn write_tour(name: &str, node_ids: &[usize], path: &str) -> Result<(), String> {
+    let contents = if Path::new(path)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("tour"))
+    {
+        tsplib_tour(name, node_ids)
+    } else {
+        node_ids.iter().map(|id| format!("{}\n", id)).collect()
+    };
+    std::fs::write(path, contents).map_err(|e| format!("Failed to write tour {}: {}", path, e))
+}
+
+/// Writes `tour` (0-based indices) of `instance` to `path` as a TSPLIB
+/// `TOUR` file of its node ids, readable by [`crate::parse_tour_file`] and
+/// external tools such as Concorde. The tour must visit every node once.
+pub fn write_tour_file(instance: &TspInstance, tour: &[usize], path: &str) -> Result<(), String> {
+    crate::constraints::validate_permutation(tour, instance.dimension)?;
+    let node_ids: Vec<usize> = tour.iter().map(|&idx| instance.node_id(idx)).collect();
+    std::fs::write(path, tsplib_tour(&instance.name, &node_ids))
+        .map_err(|e| format!("Failed to write tour {}: {}", path, e))
+}
+
+/// Writes a square matrix to `path`, as `f64` whatever the element type.
+pub fn write_matrix<T: Copy + Into<f64>>(
+    matrix: &[Vec<T>],
+    path: &str,
+    format: MatrixFormat,
+) -> Result<(), String> {
+    let file =
+        File::create(path).map_err(|e| format!("Failed to create matrix file {}: {}", path, e))?;
+    let mut writer = BufWriter::new(file);
+    let result = match format {
+        MatrixFormat::Csv => write_csv(matrix, &mut writer),
+        MatrixFormat::Npy => write_npy(matrix, &mut writer),
+    };
+    result
+        .and_then(|_| writer.flush())
+        .map_err(|e| format!("Failed to write matrix file {}: {}", path, e))
+}
+
+fn write_csv<T: Copy + Into<f64>>(
+    matrix: &[Vec<T>],
+    writer: &mut impl Write,
+) -> std::io::Result<()> {
+    for row in matrix {
+        let line: Vec<String> = row.iter().map(|&value| value.into().to_string()).collect();
+        writeln!(writer, "{}", line.join(","))?;
+    }
+    Ok(())
+}
+
+fn write_npy<T: Copy + Into<f64>>(
+    matrix: &[Vec<T>],
+    writer: &mut impl Write,
+) -> std::io::Result<()> {
+    let rows = matrix.len();
+    let cols = matrix.first().map_or(0, |row| row.len());
+    let mut header = format!(
+        "{{'descr': '<f8', 'fortran_order': False, 'shape': ({}, {}), }}",
+        rows, cols
+    );
+    // Magic (6) + version (2) + header length (2) + header must be a
+    // multiple of 64 bytes, the header ending in a newline.
+    let padding = (64 - (10 + header.len() + 1) % 64) % 64;
+    header.push_str(&" ".repeat(padding));
+    header.push('\n');
+
+    writer.write_all(b"\x93NUMPY\x01\x00")?;
+    writer.write_all(&(header.len() as u16).to_le_bytes())?;
+    writer.write_all(header.as_bytes())?;
+    for row in matrix {
+        for &value in row {
+            writer.write_all(&value.into().to_le_bytes())?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,158 @@
+//! C interface: solve a TSPLIB file and receive every new best tour through
+//! a function pointer while the run is in progress.
+
+use crate::config::Config;
+use crate::events::SolverEvent;
+use crate::parser::parse_tsp_file;
+use crate::solver::solve_tsp_aco;
+use std::ffi::{CStr, c_char, c_void};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Sender};
+use std::thread::{self, JoinHandle};
+
+/// Called with the host's `user_data`, the iteration, the tour as 0-based
+/// node indices (`tour_len` entries, valid only during the call) and its
+/// length.
+pub type IncumbentFn = unsafe extern "C" fn(
+    user_data: *mut c_void,
+    iteration: usize,
+    tour: *const usize,
+    tour_len: usize,
+    length: f64,
+);
+
+/// A host callback together with its user data. All calls are made from a
+/// single forwarding thread, one at a time, so the callback itself does not
+/// have to be reentrant; it must however tolerate being called from a
+/// thread other than the one that started the solve.
+pub struct IncumbentCallback {
+    callback: IncumbentFn,
+    user_data: *mut c_void,
+}
+
+// The pointer is only handed back to the host's callback, which the caller
+// of `IncumbentCallback::new` vouches may be used from another thread.
+unsafe impl Send for IncumbentCallback {}
+
+impl IncumbentCallback {
+    /// # Safety
+    ///
+    /// `callback` must be safe to call with `user_data` from another thread
+    /// until the handle returned by [`IncumbentCallback::spawn`] is joined.
+    pub unsafe fn new(callback: IncumbentFn, user_data: *mut c_void) -> Self {
+        IncumbentCallback {
+            callback,
+            user_data,
+        }
+    }
+
+    /// Starts the forwarding thread. Set the sender as
+    /// `Config::event_sender`; the thread ends once the solver (and every
+    /// clone of the sender) is dropped.
+    pub fn spawn(self) -> (Sender<SolverEvent>, JoinHandle<()>) {
+        let (sender, receiver) = mpsc::channel();
+        let handle = thread::spawn(move || {
+            let this = self;
+            for event in receiver {
+                if let SolverEvent::NewBest {
+                    iteration,
+                    tour,
+                    length,
+                } = event
+                {
+                    unsafe {
+                        (this.callback)(
+                            this.user_data,
+                            iteration,
+                            tour.as_ptr(),
+                            tour.len(),
+                            length,
+                        )
+                    };
+                }
+            }
+        });
+        (sender, handle)
+    }
+}
+
+/// Solves the TSPLIB file at `path` with default settings, calling
+/// `callback` (if not null) on every new best tour. Writes the best tour as
+/// 0-based indices into `tour_out` (if not null) and its length into
+/// `length_out` (if not null). Returns the instance dimension, -1 if the
+/// file cannot be read or the solver panics, or -2 if `tour_capacity` is
+/// smaller than the dimension; the tour is then not written, but the length
+/// still is.
+///
+/// # Safety
+///
+/// `path` must be a NUL-terminated string, `tour_out` must be valid for
+/// `tour_capacity` writes (or null) and `length_out` valid for one write (or
+/// null). `callback` and `user_data` must satisfy [`IncumbentCallback::new`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn tsp_solve_file(
+    path: *const c_char,
+    callback: Option<IncumbentFn>,
+    user_data: *mut c_void,
+    tour_out: *mut usize,
+    tour_capacity: usize,
+    length_out: *mut f64,
+) -> i64 {
+    // Unwinding into the host is undefined behaviour, so panics become -1.
+    panic::catch_unwind(AssertUnwindSafe(|| unsafe {
+        solve_file(
+            path,
+            callback,
+            user_data,
+            tour_out,
+            tour_capacity,
+            length_out,
+        )
+    }))
+    .unwrap_or(-1)
+}
+
+unsafe fn solve_file(
+    path: *const c_char,
+    callback: Option<IncumbentFn>,
+    user_data: *mut c_void,
+    tour_out: *mut usize,
+    tour_capacity: usize,
+    length_out: *mut f64,
+) -> i64 {
+    if path.is_null() {
+        return -1;
+    }
+    let Ok(path) = unsafe { CStr::from_ptr(path) }.to_str() else {
+        return -1;
+    };
+    let Ok(instance) = parse_tsp_file(path) else {
+        return -1;
+    };
+    let mut config = Config::default();
+    let forwarder = callback.map(|callback| {
+        let (sender, handle) = unsafe { IncumbentCallback::new(callback, user_data) }.spawn();
+        config.event_sender = Some(sender);
+        handle
+    });
+    // The forwarder is joined even if the solver panics, so the callback is
+    // never called after this function returns.
+    let solved = panic::catch_unwind(AssertUnwindSafe(|| solve_tsp_aco(&instance, &config)));
+    drop(config);
+    if let Some(handle) = forwarder {
+        let _ = handle.join();
+    }
+    let Ok((tour, length)) = solved else {
+        return -1;
+    };
+    if !length_out.is_null() {
+        unsafe { *length_out = length };
+    }
+    if !tour_out.is_null() {
+        if tour.len() > tour_capacity {
+            return -2;
+        }
+        unsafe { std::ptr::copy_nonoverlapping(tour.as_ptr(), tour_out, tour.len()) };
+    }
+    instance.dimension as i64
+}
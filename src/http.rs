@@ -0,0 +1,57 @@
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+/// Minimal HTTP/1.0 client, enough for a routing server on the local
+/// network. Returns the body of a 2xx response.
+pub(crate) fn http_request(method: &str, url: &str, body: Option<&str>) -> Result<String, String> {
+    let rest = url
+        .strip_prefix("http://")
+        .ok_or_else(|| format!("Only http:// URLs are supported: {}", url))?;
+    let (authority, path) = match rest.find('/') {
+        Some(slash) => (&rest[..slash], &rest[slash..]),
+        None => (rest, "/"),
+    };
+    let address = if authority.contains(':') {
+        authority.to_string()
+    } else {
+        format!("{}:80", authority)
+    };
+    let mut stream = TcpStream::connect(&address)
+        .map_err(|e| format!("Failed to connect to {}: {}", address, e))?;
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(120)));
+
+    let mut request = format!(
+        "{} {} HTTP/1.0\r\nHost: {}\r\nAccept: application/json\r\nConnection: close\r\n",
+        method, path, authority
+    );
+    if let Some(body) = body {
+        request.push_str(&format!(
+            "Content-Type: application/json\r\nContent-Length: {}\r\n",
+            body.len()
+        ));
+    }
+    request.push_str("\r\n");
+    request.push_str(body.unwrap_or(""));
+    stream
+        .write_all(request.as_bytes())
+        .map_err(|e| format!("Failed to send request to {}: {}", address, e))?;
+
+    let mut response = Vec::new();
+    stream
+        .read_to_end(&mut response)
+        .map_err(|e| format!("Failed to read response from {}: {}", address, e))?;
+    let response = String::from_utf8_lossy(&response);
+    let (head, body) = response
+        .split_once("\r\n\r\n")
+        .ok_or("Malformed HTTP response")?;
+    let status = head
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse::<u16>().ok())
+        .ok_or("Malformed HTTP status line")?;
+    if !(200..300).contains(&status) {
+        return Err(format!("{} returned HTTP {}: {}", url, status, body.trim()));
+    }
+    Ok(body.to_string())
+}
@@ -0,0 +1,112 @@
+//! Hot inner loops of the colony, compiled twice: once for the baseline
+//! target and once with AVX2/FMA enabled, picked at runtime with
+//! `is_x86_feature_detected!`. Distributed binaries thus get vectorized code
+//! without requiring `-C target-cpu=native`. On aarch64 NEON is part of the
+//! baseline, so the plain versions are already vectorized there.
+
+use crate::solver::{Pheromone, widen};
+
+/// Multiplies every entry of `row` by `retain` and clamps it to `min`.
+pub(crate) fn evaporate(row: &mut [Pheromone], retain: f64, min: f64) {
+    #[cfg(target_arch = "x86_64")]
+    if std::is_x86_feature_detected!("avx2") && std::is_x86_feature_detected!("fma") {
+        // SAFETY: the required CPU features were just detected.
+        return unsafe { evaporate_avx2(row, retain, min) };
+    }
+    evaporate_body(row, retain, min)
+}
+
+/// Writes `pheromone[j]^alpha * heuristic[j]^beta` into `out[j]`, the
+/// unnormalized transition weight of every candidate node.
+pub(crate) fn attractiveness(
+    pheromone: &[Pheromone],
+    heuristic: &[f64],
+    alpha: f64,
+    beta: f64,
+    out: &mut [f64],
+) {
+    #[cfg(target_arch = "x86_64")]
+    if std::is_x86_feature_detected!("avx2") && std::is_x86_feature_detected!("fma") {
+        // SAFETY: the required CPU features were just detected.
+        return unsafe { attractiveness_avx2(pheromone, heuristic, alpha, beta, out) };
+    }
+    attractiveness_body(pheromone, heuristic, alpha, beta, out)
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2,fma")]
+fn evaporate_avx2(row: &mut [Pheromone], retain: f64, min: f64) {
+    evaporate_body(row, retain, min)
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2,fma")]
+fn attractiveness_avx2(
+    pheromone: &[Pheromone],
+    heuristic: &[f64],
+    alpha: f64,
+    beta: f64,
+    out: &mut [f64],
+) {
+    attractiveness_body(pheromone, heuristic, alpha, beta, out)
+}
+
+#[inline(always)]
+fn evaporate_body(row: &mut [Pheromone], retain: f64, min: f64) {
+    for value in row.iter_mut() {
+        *value = (widen(*value) * retain).max(min) as Pheromone;
+    }
+}
+
+#[inline(always)]
+fn attractiveness_body(
+    pheromone: &[Pheromone],
+    heuristic: &[f64],
+    alpha: f64,
+    beta: f64,
+    out: &mut [f64],
+) {
+    // Ablations switch a factor off: x^0 = 1, even for infinite heuristics.
+    match (alpha == 0.0, beta == 0.0) {
+        (true, true) => return out.fill(1.0),
+        (true, false) => {
+            for (w, &eta) in out.iter_mut().zip(heuristic) {
+                *w = eta.powf(beta);
+            }
+            return;
+        }
+        (false, true) => {
+            for (w, &tau) in out.iter_mut().zip(pheromone) {
+                *w = widen(tau).powf(alpha);
+            }
+            return;
+        }
+        (false, false) => {}
+    }
+    // powf does not vectorize; small integer exponents (the common case,
+    // alpha = 1 and beta in 1..=5) become plain multiplications that do.
+    if alpha == 1.0 {
+        match beta {
+            1.0 => return weights_int_pow::<1>(pheromone, heuristic, out),
+            2.0 => return weights_int_pow::<2>(pheromone, heuristic, out),
+            3.0 => return weights_int_pow::<3>(pheromone, heuristic, out),
+            4.0 => return weights_int_pow::<4>(pheromone, heuristic, out),
+            5.0 => return weights_int_pow::<5>(pheromone, heuristic, out),
+            _ => {}
+        }
+    }
+    for ((w, &tau), &eta) in out.iter_mut().zip(pheromone).zip(heuristic) {
+        *w = widen(tau).powf(alpha) * eta.powf(beta);
+    }
+}
+
+#[inline(always)]
+fn weights_int_pow<const BETA: u32>(pheromone: &[Pheromone], heuristic: &[f64], out: &mut [f64]) {
+    for ((w, &tau), &eta) in out.iter_mut().zip(pheromone).zip(heuristic) {
+        let mut value = widen(tau);
+        for _ in 0..BETA {
+            value *= eta;
+        }
+        *w = value;
+    }
+}
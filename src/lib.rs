@@ -1,148 +1,826 @@
+pub mod bench;
+pub mod cache;
+pub mod checkpoint;
 pub mod config;
+pub mod constraints;
+pub mod construction;
+pub mod dashboard;
+pub mod decomposition;
+pub mod distance;
+pub mod distributed;
+pub mod edge_frequency;
+pub mod events;
+pub mod experiment;
+pub mod export;
+pub mod ffi;
+mod http;
+mod json;
+mod kernels;
+pub mod local_search;
+pub mod matrix_import;
+pub mod memory;
+pub mod metadata;
+pub mod multilevel;
+pub mod node_data;
+pub mod online;
+pub mod operators;
+#[cfg(feature = "parquet")]
+pub mod parquet_output;
 pub mod parser;
+pub mod portfolio;
+pub mod prepared;
+pub mod provenance;
+pub mod replay;
+pub mod report;
+pub mod results;
+#[cfg(feature = "road-network")]
+pub mod road_network;
+pub mod robustness;
+pub mod routing;
+pub mod sensitivity;
+pub mod snapshots;
+pub mod solution;
 pub mod solver;
+pub mod spatial;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_log;
+pub mod stopping;
+pub mod threads;
+pub mod time_budget;
+pub mod transform;
+pub mod tuning;
 pub mod utils;
+pub mod watch;
 
-pub use config::Config;
-pub use parser::{EdgeWeightFormat, EdgeWeightType, Node, TspInstance, parse_tsp_file};
-pub use solver::{Ant, solve_tsp_aco};
-pub use utils::{evaluate_solution, load_optimal_solutions};
+pub use bench::{BenchRow, BenchSummary};
+pub use cache::ResultCache;
+pub use checkpoint::Checkpoint;
+pub use config::{Backend, Command, Config};
+pub use constraints::{
+    FixedEdges, PositionLocks, PrecedenceGraph, Precedences, Subsequences, TourConstraints,
+    Violation,
+};
+pub use decomposition::{solve_decomposed, solve_partitioned};
+pub use distance::{DistanceMatrix, FORBIDDEN_DISTANCE};
+pub use distributed::{ColonyParams, ColonyRanges, DistributedRole, run_coordinator, run_worker};
+pub use events::SolverEvent;
+pub use export::{
+    MatrixFormat, RouteLeg, route_legs, tsplib_instance, tsplib_tour, write_matrix,
+    write_route_legs, write_tour, write_tour_file, write_tsplib_instance,
+};
+pub use local_search::{LocalSearch, LocalSearchTarget, OrOpt, Popmusic, ThreeOpt, TwoOpt};
+pub use matrix_import::{
+    MatrixResponseFormat, RoadMetric, import_matrix_response, parse_matrix_response,
+};
+pub use memory::StorageMode;
+pub use metadata::{BoundingBox, EdgeWeightStats};
+pub use multilevel::solve_multilevel;
+pub use node_data::{NodeMetadata, parse_csv_instance, write_visit_order};
+pub use online::OnlineSolver;
+pub use operators::{edge_recombination, order_crossover, partially_mapped_crossover, random_cut};
+pub use parser::{
+    DistancePolicy, EdgeWeightFormat, EdgeWeightType, Node, ParseOptions, SpecialDistance,
+    TspInstance, parse_npy_matrix, parse_tour_file, parse_tour_reader, parse_tsp_file,
+    parse_tsp_file_with_options, parse_tsp_reader,
+};
+pub use portfolio::{InstanceFeatures, PortfolioPlan, PortfolioStrategy};
+pub use prepared::PreparedInstance;
+pub use provenance::Provenance;
+pub use replay::{ReplayRecorder, render_svg};
+pub use report::{
+    ConsoleReporter, JsonReporter, NdjsonReporter, ReportFormat, Reporter, SilentReporter,
+};
+#[cfg(feature = "road-network")]
+pub use road_network::{RoutingService, fetch_road_matrix, road_network_instance};
+pub use robustness::{RobustnessRow, perturbed_instance, robustness_analysis};
+pub use routing::{Depot, DepotRoutes, RouteLimits, RouteSet, solve_multi_depot};
+pub use sensitivity::{SensitivityRow, sensitivity_analysis};
+pub use snapshots::{Divergence, Snapshot, diff_snapshots, read_snapshots};
+pub use solution::{LengthPrecision, Solution, Termination};
+pub use solver::{
+    Ablation, AcoSolver, Ant, BRUTE_FORCE_MAX_DIMENSION, DepositSchedule, EvaporationModel,
+    Improvement, Improvements, InitStrategy, Perturbation, Pheromone, ReheatSchedule,
+    ReheatTrigger, StartPolicy, TurnPenalty, edge_entropy, solve_brute_force, solve_many,
+    solve_random, solve_tsp_aco,
+};
+pub use stopping::{
+    Composite, MaxIterations, NoImprovementFor, SearchState, StoppingCriterion, TargetLength,
+    TimeLimit,
+};
+pub use transform::{atsp_to_stsp, stsp_tour_to_atsp};
+pub use tuning::{RaceResult, TuningCandidate, race};
+pub use utils::{embedded_optimum, evaluate_solution, load_optimal_solutions};
+pub use watch::watch_directory;
 
 use std::error::Error;
+use std::sync::mpsc;
+use std::thread;
 
-pub fn run(config: &Config) -> Result<(), Box<dyn Error>> {
-    println!("\nRustACO - Ant Colony Optimization for TSP");
-    println!("========================================");
-    println!("\n ACO Configuration:");
-    println!("  Iterations: {}", config.num_iters);
-    println!("  Number of Ants: {}", config.num_ants);
-    println!("  Alpha (pheromone influence): {:.2}", config.alpha);
-    println!("  Beta (heuristic influence): {:.2}", config.beta);
-    println!("  Evaporation Rate (rho): {:.2}", config.evap_rate);
-    println!("  Q Value (pheromone deposit factor): {:.2}", config.q_val);
-    println!("  Initial Pheromone: {:.2}", config.init_pheromone);
-    println!("  Elitist Weight: {:.2}", config.elitist_weight);
-    println!("  Min Pheromone Value: {:.0e}", config.min_pheromone_val);
+const SOLUTIONS_FILE_PATH: &str = "tsplib/solutions";
 
-    let file_path = config
-        .file_path
-        .as_deref()
-        .ok_or("File path not provided in config")?;
-    println!("\n Parsing TSP file: {}...", file_path);
-
-    let instance = match parse_tsp_file(file_path) {
-        Ok(inst) => {
-            println!("  Successfully parsed: {}", inst.name);
-            println!("  Problem Type: {}", inst.tsp_type);
-            if !inst.comment.is_empty() {
-                println!("  Comment: {}", inst.comment);
+/// Solves `instance` with the backend selected in `config` (plain ACO,
+/// decomposition, partitioning, multilevel or distributed).
+pub fn solve_instance(instance: &TspInstance, config: &Config) -> Result<Solution, String> {
+    solve_prepared(&PreparedInstance::new(instance), config)
+}
+
+/// Like [`solve_instance`], reusing what `prepared` has already built for
+/// earlier solves. Backends that solve sub-instances build their own.
+pub fn solve_prepared(prepared: &PreparedInstance, config: &Config) -> Result<Solution, String> {
+    let instance = prepared.instance();
+    let start_time = std::time::Instant::now();
+    let (algorithm, (tour, length)) = match (&config.distributed, config.backend) {
+        (Some(DistributedRole::Coordinator { addr, workers }), _) => (
+            "distributed",
+            run_coordinator(instance, config, addr, *workers)?,
+        ),
+        (Some(DistributedRole::Worker { addr }), _) => {
+            ("distributed", run_worker(instance, config, addr)?)
+        }
+        (None, Backend::Aco) if config.ablation == Some(Ablation::RandomTours) => {
+            ("random", solve_random(instance, config))
+        }
+        (None, Backend::Aco) => {
+            let budgeted = config
+                .time_budget
+                .map(|_| time_budget::budgeted_config(config, instance.dimension));
+            let mut solution =
+                AcoSolver::prepared(prepared, budgeted.as_ref().unwrap_or(config)).solve();
+            solution.provenance = Some(Provenance::new(instance, config));
+            return Ok(solution);
+        }
+        (None, Backend::Decomposed(clusters)) => {
+            ("decomposed", solve_decomposed(instance, config, clusters)?)
+        }
+        (None, Backend::Partitioned(tiles)) => {
+            ("partitioned", solve_partitioned(instance, config, tiles)?)
+        }
+        (None, Backend::Multilevel(coarsest)) => {
+            ("multilevel", solve_multilevel(instance, config, coarsest)?)
+        }
+        (None, Backend::Portfolio) => {
+            let plan = PortfolioPlan::new(instance, config);
+            if plan.strategy == PortfolioStrategy::AcoLocalSearch {
+                let mut solution = AcoSolver::prepared(prepared, &plan.config).solve();
+                solution.algorithm = plan.strategy.name().to_string();
+                solution.provenance = Some(Provenance::new(instance, config));
+                return Ok(solution);
             }
-            println!("  Dimension: {}", inst.dimension);
-            println!("  Edge Weight Type: {:?}", inst.edge_weight_type);
-            if let Some(format) = &inst.edge_weight_format {
-                if !matches!(format, EdgeWeightFormat::Unknown(_)) {
-                    println!("  Edge Weight Format: {:?}", format);
+            (plan.strategy.name(), plan.solve_without_aco(instance)?)
+        }
+    };
+    let mut solution = Solution::new(instance, tour, length, algorithm, Termination::Completed);
+    // Backends differ in whether they round; report both like the ACO solver.
+    if solution.is_complete(instance.dimension) {
+        solution.exact_length = instance.tour_length(&solution.tour);
+        solution.length = solution.exact_length.round();
+        // Only the ACO solver enforces the tour constraints.
+        solution.violations =
+            TourConstraints::for_instance(instance, config).violations(&solution.tour);
+    }
+    solution.seed = config.seed;
+    solution.duration = start_time.elapsed();
+    solution.provenance = Some(Provenance::new(instance, config));
+    Ok(solution)
+}
+
+/// What [`solve_instance`] is about to do, for backends other than plain ACO.
+fn backend_description(instance: &TspInstance, config: &Config) -> Option<String> {
+    match (&config.distributed, config.backend) {
+        (Some(DistributedRole::Coordinator { addr, workers }), _) => {
+            Some(format!("  Coordinating {} workers on {}...", workers, addr))
+        }
+        (Some(DistributedRole::Worker { addr }), _) => {
+            Some(format!("  Working for coordinator at {}...", addr))
+        }
+        (None, Backend::Aco) => config
+            .ablation
+            .map(|ablation| format!("  Ablation: {}", ablation.name())),
+        (None, Backend::Decomposed(clusters)) => {
+            Some(format!("  Decomposing into {} clusters...", clusters))
+        }
+        (None, Backend::Partitioned(tiles)) => {
+            Some(format!("  Partitioning into {0}x{0} tiles...", tiles))
+        }
+        (None, Backend::Multilevel(coarsest)) => Some(format!(
+            "  Multilevel solving, coarsening to {} nodes...",
+            coarsest
+        )),
+        (None, Backend::Portfolio) => Some(format!(
+            "  Portfolio: {}",
+            PortfolioPlan::new(instance, config)
+        )),
+    }
+}
+
+/// Runs the command selected in `config`, handing all output to `reporter`.
+/// [`solve_instance`] on a separate thread while the solver's events are
+/// passed on to `reporter` (and to `Config::event_sender`, if set).
+fn solve_reporting(
+    instance: &TspInstance,
+    config: &Config,
+    reporter: &mut dyn Reporter,
+) -> Result<Solution, String> {
+    with_reporting(config, reporter, |solving| {
+        solve_instance(instance, solving)
+    })
+    .unwrap_or_else(|| Err("Solver thread panicked".to_string()))
+}
+
+/// Runs `task` on a separate thread with a config whose events are passed
+/// on to `reporter` (and to `Config::event_sender`, if set). `None` if the
+/// task panicked.
+fn with_reporting<T: Send>(
+    config: &Config,
+    reporter: &mut dyn Reporter,
+    task: impl FnOnce(&Config) -> T + Send,
+) -> Option<T> {
+    let (sender, receiver) = mpsc::channel();
+    let forward = config.event_sender.clone();
+    let solving = Config {
+        event_sender: Some(sender),
+        ..config.clone()
+    };
+    thread::scope(|scope| {
+        // The receiver loop ends once the task drops `solving`.
+        let task = scope.spawn(move || task(&solving));
+        for event in receiver {
+            match &event {
+                SolverEvent::IterationFinished {
+                    iteration,
+                    best_length,
+                    edge_entropy,
+                    ..
+                } => reporter.on_iteration(*iteration, *best_length, *edge_entropy),
+                SolverEvent::NewBest {
+                    iteration,
+                    tour,
+                    length,
+                } => reporter.on_new_best(*iteration, tour, *length),
+                SolverEvent::CheckpointWritten { iteration, path } => {
+                    reporter.on_checkpoint(*iteration, path)
                 }
+                SolverEvent::Warning { message } => reporter.on_warning(message),
+                SolverEvent::Message { message } => reporter.on_message(message),
+                SolverEvent::Stagnation { .. } => {}
             }
-            if inst.dimension == 0 {
-                return Err("Problem dimension is 0. Cannot solve.".into());
+            if let Some(forward) = &forward {
+                let _ = forward.send(event);
             }
-            inst
         }
-        Err(e) => {
-            return Err(format!("Error parsing TSPLIB file: {}", e).into());
+        task.join().ok()
+    })
+}
+
+pub fn run(config: &Config, reporter: &mut dyn Reporter) -> Result<(), Box<dyn Error>> {
+    if let Command::Replay { replay, svg } = &config.command {
+        render_svg(replay, svg)?;
+        reporter.on_message(&format!("Rendered {} to {}", replay, svg));
+        return Ok(());
+    }
+    if let Command::DiffSnapshots { left, right } = &config.command {
+        let (left_run, right_run) = (read_snapshots(left)?, read_snapshots(right)?);
+        match diff_snapshots(&left_run, &right_run) {
+            Some(divergence) => reporter.on_message(&divergence.to_string()),
+            None => reporter.on_message(&format!(
+                "Runs are identical over {} iterations",
+                left_run.len()
+            )),
         }
-    };
+        return Ok(());
+    }
 
-    println!("\n Starting ACO to solve TSP for {}...", instance.name);
-    let start_time = std::time::Instant::now();
-    let (best_tour_indices, best_tour_length) = solve_tsp_aco(&instance, config);
-    let duration = start_time.elapsed();
-
-    println!("\n --- ACO Results for {} ---", instance.name);
-    println!("   Time taken: {:.2?}", duration);
-
-    if best_tour_length == 0.0 && (best_tour_indices.is_empty() || instance.dimension > 1) {
-        println!("   No tour found or tour length is zero for a multi-node problem.");
-    } else {
-        println!("   Best tour length found: {:.2}", best_tour_length);
-    }
-
-    if !best_tour_indices.is_empty() {
-        let valid_indices = best_tour_indices
-            .iter()
-            .all(|&idx| idx < instance.dimension);
-
-        if valid_indices && best_tour_indices.len() == instance.dimension {
-            if best_tour_indices.len() <= 30 {
-                if let Some(nodes) = &instance.node_coords {
-                    let display_tour: Vec<usize> = best_tour_indices.iter().map(|&idx| {
-                        nodes.get(idx).map_or_else(|| {
-                            eprintln!("Warning: Solver index {} out of bounds for node_coords (len {})", idx, nodes.len());
-                            idx + 1
-                        }, |node| node.id)
-                    }).collect();
-                    println!("   Route (Node IDs): {:?}", display_tour);
-                } else {
-                    let display_tour_indices: Vec<usize> =
-                        best_tour_indices.iter().map(|&idx| idx + 0).collect();
-                    println!(
-                        "   Route (0-based City Indices): {:?}",
-                        display_tour_indices
-                    );
+    reporter.on_message("\nRustACO - Ant Colony Optimization for TSP");
+    reporter.on_message("========================================");
+    reporter.on_message("\n ACO Configuration:");
+    match (config.tour_budget, config.time_budget) {
+        (Some(budget), _) => reporter.on_message(&format!("  Tour budget: {}", budget)),
+        (None, Some(budget)) => reporter.on_message(&format!(
+            "  Time budget: {:.1} s (iterations and ants planned as the run goes)",
+            budget.as_secs_f64()
+        )),
+        (None, None) => reporter.on_message(&format!("  Iterations: {}", config.num_iters)),
+    }
+    reporter.on_message(&format!("  Number of Ants: {}", config.num_ants));
+    reporter.on_message(&format!(
+        "  Alpha (pheromone influence): {:.2}",
+        config.alpha
+    ));
+    reporter.on_message(&format!("  Beta (heuristic influence): {:.2}", config.beta));
+    reporter.on_message(&format!("  Heuristic: {:?}", config.heuristic));
+    reporter.on_message(&format!(
+        "  Evaporation Rate (rho): {:.2}",
+        config.evap_rate
+    ));
+    if config.evaporation != EvaporationModel::Uniform {
+        reporter.on_message(&format!("  Evaporation Model: {:?}", config.evaporation));
+    }
+    reporter.on_message(&format!(
+        "  Q Value (pheromone deposit factor): {:.2}",
+        config.q_val
+    ));
+    if config.normalize_deposit {
+        reporter.on_message("  Deposit Normalization: nearest-neighbour tour length");
+    }
+    reporter.on_message(&format!("  Initial Pheromone: {}", config.init_pheromone));
+    match &config.deposit_schedule {
+        Some(schedule) => reporter.on_message(&format!("  Deposit Schedule: {:?}", schedule)),
+        None => reporter.on_message(&format!("  Elitist Weight: {:.2}", config.elitist_weight)),
+    }
+    reporter.on_message(&format!(
+        "  Min Pheromone Value: {:.0e}",
+        config.min_pheromone_val
+    ));
+    if config.integer_costs {
+        reporter.on_message("  Integer Costs: TSPLIB rounding");
+    }
+
+    if config.command == Command::Bench {
+        let path = config
+            .file_path
+            .as_deref()
+            .ok_or("Instance directory not provided in config")?;
+        let instances = bench::bench_instances(path)?;
+        if instances.is_empty() {
+            return Err(format!("No .tsp or .npy instances in {}", path).into());
+        }
+        // The solutions file is optional here, embedded optima cover TSPLIB.
+        let solutions = load_optimal_solutions(SOLUTIONS_FILE_PATH).unwrap_or_default();
+        if let Some((file_a, file_b)) = &config.compare_configs {
+            let config_a = config.with_file(file_a)?;
+            let config_b = config.with_file(file_b)?;
+            reporter.on_message(&format!(
+                "\n Comparing {} and {} on {} instances ({} seeds each)...",
+                file_a,
+                file_b,
+                instances.len(),
+                config.num_seeds.max(1)
+            ));
+            let mut rows = Vec::new();
+            let mut differences = Vec::new();
+            for instance_path in &instances {
+                match bench::compare_instance(instance_path, &config_a, &config_b, &solutions) {
+                    Ok((row, diffs)) => {
+                        rows.push(row);
+                        differences.extend(diffs);
+                    }
+                    Err(e) => {
+                        reporter.on_warning(&format!("Skipping {}: {}", instance_path.display(), e))
+                    }
                 }
-            } else {
-                println!(
-                    "  Tour is too long to print ({} cities).",
-                    best_tour_indices.len()
-                );
             }
-        } else if !best_tour_indices.is_empty() {
-            println!(
-                "   Partial or invalid tour found: {:?} (Length: {})",
-                best_tour_indices,
-                best_tour_indices.len()
-            );
+            let test = bench::wilcoxon_signed_rank(&differences);
+            reporter.on_message("");
+            reporter
+                .on_message(bench::format_comparison(&rows, &test, (file_a, file_b)).trim_end());
+            return Ok(());
+        }
+        if config.ablations {
+            reporter.on_message(&format!(
+                "\n Ablation study on {} instances ({} seeds each)...",
+                instances.len(),
+                config.num_seeds.max(1)
+            ));
+            let mut studies = Vec::new();
+            for instance_path in &instances {
+                match bench::ablation_study(instance_path, config, &solutions) {
+                    Ok(rows) => studies.push(rows),
+                    Err(e) => {
+                        reporter.on_warning(&format!("Skipping {}: {}", instance_path.display(), e))
+                    }
+                }
+            }
+            reporter.on_message("");
+            reporter.on_message(bench::format_ablations(&studies).trim_end());
+            return Ok(());
+        }
+        reporter.on_message(&format!(
+            "\n Benchmarking {} instances ({} seeds each)...",
+            instances.len(),
+            config.num_seeds.max(1)
+        ));
+        let mut rows = Vec::new();
+        for instance_path in &instances {
+            match bench::bench_instance(instance_path, config, &solutions) {
+                Ok(row) => rows.push(row),
+                Err(e) => {
+                    reporter.on_warning(&format!("Skipping {}: {}", instance_path.display(), e))
+                }
+            }
+        }
+        reporter.on_message("");
+        reporter.on_message(bench::format_table(&rows).trim_end());
+        if let Some(csv_path) = &config.csv_path {
+            bench::write_csv(&rows, csv_path)?;
+            reporter.on_message(&format!("  Results written to {}", csv_path));
+        }
+        #[cfg(any(feature = "parquet", feature = "sqlite"))]
+        let runs: Vec<_> = rows.iter().flat_map(|row| row.records.clone()).collect();
+        #[cfg(feature = "parquet")]
+        if let Some(path) = &config.parquet_path {
+            parquet_output::write_parquet(&runs, config, path)?;
+            reporter.on_message(&format!("  Runs written to {}", path));
+        }
+        #[cfg(feature = "sqlite")]
+        if let Some(path) = &config.sqlite_path {
+            sqlite_log::record_runs(&runs, config, "bench", path)?;
+            reporter.on_message(&format!("  Runs recorded in {}", path));
+        }
+        if let Some(path) = &config.edge_frequency_path {
+            let frequencies: Vec<_> = rows
+                .iter()
+                .map(|row| row.edge_frequencies.clone())
+                .collect();
+            edge_frequency::write_edge_frequencies(&frequencies, path)?;
+            reporter.on_message(&format!("  Edge frequencies written to {}", path));
+        }
+        return Ok(());
+    }
+
+    if let Command::Experiment(name) = &config.command {
+        let path = config
+            .file_path
+            .as_deref()
+            .ok_or("Instance directory not provided in config")?;
+        let instances = bench::bench_instances(path)?;
+        if instances.is_empty() {
+            return Err(format!("No .tsp or .npy instances in {}", path).into());
+        }
+        let run_config = match &config.experiment_config {
+            Some(file) => config.with_file(file)?,
+            None => config.clone(),
+        };
+        let seeds = experiment::experiment_seeds(&run_config);
+        reporter.on_message(&format!(
+            "\n Experiment {}: {} instances, seeds {:?}...",
+            name,
+            instances.len(),
+            seeds
+        ));
+        let solutions = load_optimal_solutions(SOLUTIONS_FILE_PATH).unwrap_or_default();
+        let experiment = experiment::run_experiment(
+            name,
+            std::path::Path::new(&config.output_dir),
+            &instances,
+            &seeds,
+            &run_config,
+            config.experiment_config.as_deref(),
+            &solutions,
+        )?;
+        reporter.on_message("");
+        reporter.on_message(experiment::format_summary(&experiment.runs).trim_end());
+        reporter.on_message(&format!(
+            "  Results written to {}",
+            experiment.dir.display()
+        ));
+        #[cfg(feature = "parquet")]
+        if let Some(path) = &config.parquet_path {
+            parquet_output::write_parquet(&experiment.runs, &run_config, path)?;
+            reporter.on_message(&format!("  Runs written to {}", path));
+        }
+        #[cfg(feature = "sqlite")]
+        if let Some(path) = &config.sqlite_path {
+            sqlite_log::record_runs(&experiment.runs, &run_config, name, path)?;
+            reporter.on_message(&format!("  Runs recorded in {}", path));
+        }
+        return Ok(());
+    }
+
+    if let Command::Watch(dir) = &config.command {
+        reporter.on_message(&format!("\n Watching {} for new instances...", dir));
+        return Ok(
+            with_reporting(config, reporter, |watching| watch_directory(dir, watching))
+                .ok_or("Watch thread panicked")??,
+        );
+    }
+
+    let file_path = config
+        .file_path
+        .as_deref()
+        .ok_or("File path not provided in config")?;
+    reporter.on_message(&format!("\n Parsing TSP file: {}...", file_path));
+
+    let instance = parse_tsp_file_with_options(file_path, &config.into())
+        .map_err(|e| format!("Error parsing TSPLIB file: {}", e))?;
+    let instance = match &config.node_data_path {
+        Some(path) => {
+            let metadata = NodeMetadata::read_csv(path, &instance)?;
+            instance.with_node_metadata(metadata)?
+        }
+        None => instance,
+    };
+    reporter.on_parse(&instance);
+    if instance.dimension == 0 {
+        return Err("Problem dimension is 0. Cannot solve.".into());
+    }
+
+    // Settings that only become known once the instance is parsed.
+    let mut overrides: Option<Config> = None;
+    if let Some(budget) = config.max_memory {
+        let candidates = match config.candidate_list_size {
+            0 => memory::DEFAULT_CANDIDATE_LIST_SIZE,
+            k => k,
+        };
+        let mode = StorageMode::of(&instance.dist_matrix, candidates);
+        reporter.on_message(&format!(
+            "  Storage: {} (about {} of the {} budget)",
+            mode,
+            memory::format_bytes(mode.estimated_bytes(instance.dimension)),
+            memory::format_bytes(budget)
+        ));
+        if matches!(mode, StorageMode::Lazy { .. })
+            && config.backend == Backend::Aco
+            && config.candidate_list_size == 0
+        {
+            overrides
+                .get_or_insert_with(|| config.clone())
+                .candidate_list_size = candidates;
+        }
+    }
+    if let Some(tour_path) = &config.initial_tour {
+        let tour = parse_tour_file(tour_path, &instance)
+            .map_err(|e| format!("Invalid initial tour: {}", e))?;
+        reporter.on_message(&format!(
+            "  Initial tour: {} (length {:.2})",
+            tour_path,
+            instance.tour_length(&tour)
+        ));
+        overrides.get_or_insert_with(|| config.clone()).warm_start = Some(tour);
+    }
+    if let Some(checkpoint_path) = &config.resume_path {
+        let resume = Checkpoint::read(checkpoint_path)?;
+        if resume.dimension() != instance.dimension {
+            return Err(format!(
+                "Checkpoint {} is for {} nodes, instance has {}",
+                checkpoint_path,
+                resume.dimension(),
+                instance.dimension
+            )
+            .into());
+        }
+        if let Some(provenance) = &resume.provenance
+            && provenance.instance_hash != instance.content_hash_hex()
+        {
+            return Err(format!(
+                "Checkpoint {} was written for instance {}, this one hashes to {}",
+                checkpoint_path,
+                provenance.instance_hash,
+                instance.content_hash_hex()
+            )
+            .into());
+        }
+        reporter.on_message(&format!(
+            "  Resuming from {} at iteration {}",
+            checkpoint_path, resume.iteration
+        ));
+        let resumed = overrides.get_or_insert_with(|| config.clone());
+        // Continue the seeded random streams of the interrupted run.
+        resumed.seed = resumed.seed.or(resume.seed);
+        resumed.resume = Some(resume);
+    }
+    if let Some(weight) = config.turn_weight {
+        let nodes = instance
+            .node_coords
+            .as_deref()
+            .ok_or("--turn-penalty requires node coordinates")?;
+        let mut turn = TurnPenalty::turn_angle(nodes, weight);
+        turn.in_heuristic = config.turn_in_heuristic;
+        reporter.on_message(&format!("  Turn penalty: {} per radian", weight));
+        overrides.get_or_insert_with(|| config.clone()).turn_penalty = Some(turn);
+    }
+    if let Some(addr) = &config.dashboard_addr {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let solutions = load_optimal_solutions(SOLUTIONS_FILE_PATH).unwrap_or_default();
+        let optimum = bench::best_known_length(&instance.name, &solutions);
+        dashboard::serve(
+            addr,
+            &instance.name,
+            instance.node_coords.as_deref(),
+            optimum,
+            receiver,
+        )?;
+        reporter.on_message(&format!(
+            "\n Live dashboard at http://{}/ (metrics at /metrics)",
+            addr
+        ));
+        overrides.get_or_insert_with(|| config.clone()).event_sender = Some(sender);
+    }
+    let config = overrides.as_ref().unwrap_or(config);
+
+    let solutions_file_path = SOLUTIONS_FILE_PATH;
+    let problem_base_name = instance.name.split('.').next().unwrap_or(&instance.name);
+    if config.command == Command::Sensitivity {
+        let optimum = load_optimal_solutions(solutions_file_path)
+            .ok()
+            .and_then(|solutions| solutions.get(&problem_base_name.to_lowercase()).copied());
+        reporter.on_message(&format!(
+            "\n Sensitivity analysis for {} ({} seeds per setting)...",
+            instance.name,
+            config.num_seeds.max(1)
+        ));
+        let (rows, reference) = sensitivity_analysis(&instance, config, optimum);
+        match optimum {
+            Some(_) => reporter.on_message(&format!(
+                "  Gaps relative to the known optimum {:.0}",
+                reference
+            )),
+            None => reporter.on_message(&format!(
+                "  Gaps relative to the best length found, {:.0}",
+                reference
+            )),
+        }
+        reporter.on_message(sensitivity::format_table(&rows).trim_end());
+        if let Some(plot_path) = &config.plot_path {
+            sensitivity::write_plot(&rows, plot_path)?;
+            reporter.on_message(&format!("  Plot written to {}", plot_path));
+        }
+        return Ok(());
+    }
+    if config.command == Command::Robustness {
+        reporter.on_message(&format!(
+            "\n Robustness analysis for {} ({} samples per noise level)...",
+            instance.name,
+            config.num_seeds.max(1)
+        ));
+        let (rows, (_, length)) = robustness_analysis(&instance, config);
+        reporter.on_message(&format!(
+            "  Original tour length {:.2}, re-solved on each perturbed instance",
+            length
+        ));
+        reporter.on_message(robustness::format_table(&rows).trim_end());
+        return Ok(());
+    }
+    if config.command == Command::Tune {
+        reporter.on_message(&format!(
+            "\n Racing {} candidate configurations on {} (budget {} runs)...",
+            config.tune_candidates, instance.name, config.tune_budget
+        ));
+        let survivors = with_reporting(config, reporter, |racing| {
+            race(
+                &instance,
+                racing,
+                config.tune_candidates,
+                config.tune_budget,
+            )
+        })
+        .ok_or("Tuning race panicked")?;
+        reporter.on_message(&format!("\n  {:<10} {:>5}  Parameters", "Mean", "Runs"));
+        for survivor in &survivors {
+            reporter.on_message(&format!(
+                "  {:<10.2} {:>5}  {}",
+                survivor.mean_length,
+                survivor.runs,
+                survivor.candidate.to_args()
+            ));
+        }
+        if let Some(best) = survivors.first() {
+            reporter.on_message(&format!(
+                "\n  Best configuration: {}",
+                best.candidate.to_args()
+            ));
+        }
+        return Ok(());
+    }
+
+    if !config.depots.is_empty() {
+        reporter.on_message(&format!(
+            "\n Starting multi-depot mTSP for {} ({} depots)...",
+            instance.name,
+            config.depots.len()
+        ));
+        let start_time = std::time::Instant::now();
+        let route_set = with_reporting(config, reporter, |routing| {
+            solve_multi_depot(&instance, routing, &config.depots)
+        })
+        .ok_or("Routing thread panicked")??;
+        reporter.on_message(&format!("\n --- mTSP Results for {} ---", instance.name));
+        reporter.on_message(&format!("   Time taken: {:.2?}", start_time.elapsed()));
+        reporter.on_message(&format!(
+            "   Total route length: {:.2}",
+            route_set.total_length
+        ));
+        if route_set.capacity_excess > 0.0 || route_set.length_excess > 0.0 {
+            reporter.on_message(&format!(
+                "   Limits violated: capacity by {:.2}, route length by {:.2}",
+                route_set.capacity_excess, route_set.length_excess
+            ));
+        }
+        for violation in &route_set.violations {
+            reporter.on_message(&format!("     - {}", violation));
+        }
+        let id = |idx: usize| {
+            instance
+                .node_coords
+                .as_ref()
+                .and_then(|nodes| nodes.get(idx))
+                .map_or(idx + 1, |node| node.id)
+        };
+        for depot in &route_set.depots {
+            reporter.on_message(&format!("   Depot {}:", id(depot.depot)));
+            for (k, route) in depot.routes.iter().enumerate() {
+                let ids: Vec<usize> = route.iter().map(|&idx| id(idx)).collect();
+                reporter.on_message(&format!(
+                    "     Salesman {} ({:.2}): {:?}",
+                    k + 1,
+                    instance.tour_length(route),
+                    ids
+                ));
+            }
         }
-    } else if instance.dimension > 0 {
-        println!("  No tour found by the solver.");
+        return Ok(());
     }
 
-    let solutions_file_path = "tsplib/solutions";
+    reporter.on_message(&format!(
+        "\n Starting ACO to solve TSP for {}...",
+        instance.name
+    ));
+    if let Some(description) = backend_description(&instance, config) {
+        reporter.on_message(&description);
+    }
+    let cache = config.cache_dir.as_deref().map(ResultCache::new);
+    let cached = cache
+        .as_ref()
+        .and_then(|cache| Some((cache.dir(), cache.get(&instance, config)?)));
+    let mut solution = match cached {
+        Some((dir, solution)) => {
+            reporter.on_message(&format!(
+                "  Reusing the cached solution in {}",
+                dir.display()
+            ));
+            solution
+        }
+        None => {
+            let solution = solve_reporting(&instance, config, reporter)?;
+            if let Some(cache) = &cache
+                && let Err(e) = cache.put(&instance, config, &solution)
+            {
+                reporter.on_warning(&format!("Could not cache the solution: {}", e));
+            }
+            solution
+        }
+    };
+
     match load_optimal_solutions(solutions_file_path) {
         Ok(optimal_solutions) => {
-            let problem_base_name = instance.name.split('.').next().unwrap_or(&instance.name);
-            let (optimal_len_opt, diff_opt) =
-                evaluate_solution(problem_base_name, best_tour_length, &optimal_solutions);
-
-            if let Some(optimal_len) = optimal_len_opt {
-                println!(
-                    "   Optimal solution for {}: {:.0}",
-                    problem_base_name, optimal_len
-                );
-                if let Some(percentage_diff) = diff_opt {
-                    if best_tour_length > 0.0 {
-                        println!(
-                            "   ACO solution is {:.2}% away from optimal.",
-                            percentage_diff
-                        );
-                    } else {
-                        println!(
-                            "   Cannot calculate deviation from optimal as no valid tour was found by ACO."
-                        );
-                    }
+            match evaluate_solution(problem_base_name, solution.length, &optimal_solutions).0 {
+                Some(optimal_len) => {
+                    reporter.on_message(&format!(
+                        "  Optimal solution for {}: {:.0}",
+                        problem_base_name, optimal_len
+                    ));
+                    solution.set_optimum_with(optimal_len, config.gap_precision);
                 }
-            } else {
-                println!(
+                None => reporter.on_message(&format!(
                     "  ℹ️ No optimal solution found in '{}' for '{}'",
                     solutions_file_path, problem_base_name
-                );
+                )),
             }
         }
-        Err(e) => {
-            eprintln!("   Could not load optimal solutions: {}", e);
+        Err(e) => reporter.on_warning(&format!("Could not load optimal solutions: {}", e)),
+    }
+    reporter.on_finish(&instance, &solution);
+
+    if (config.print_legs || config.legs_path.is_some()) && solution.is_complete(instance.dimension)
+    {
+        let legs = route_legs(&instance, &solution.tour);
+        if config.print_legs {
+            reporter.on_message(&format!(
+                "\n   {:>5} {:>8} {:>8} {:>12} {:>14}",
+                "Leg", "From", "To", "Distance", "Cumulative"
+            ));
+            for (k, leg) in legs.iter().enumerate() {
+                reporter.on_message(&format!(
+                    "   {:>5} {:>8} {:>8} {:>12.2} {:>14.2}",
+                    k + 1,
+                    leg.from_id,
+                    leg.to_id,
+                    leg.distance,
+                    leg.cumulative
+                ));
+            }
+        }
+        if let Some(path) = &config.legs_path {
+            write_route_legs(&legs, path)?;
+            reporter.on_message(&format!("   Route legs written to {}", path));
         }
     }
-    println!("========================================");
+
+    if let Some(path) = &config.tour_path
+        && !solution.tour.is_empty()
+    {
+        write_tour(&instance.name, &solution.node_ids, path)?;
+        reporter.on_message(&format!("   Tour written to {}", path));
+    }
+    if let Some(path) = &config.visits_path
+        && solution.is_complete(instance.dimension)
+    {
+        write_visit_order(&instance, &solution.tour, path)?;
+        reporter.on_message(&format!("   Visit order written to {}", path));
+    }
+    if let Some(path) = &config.solution_path {
+        solution.write_json(path)?;
+        reporter.on_message(&format!("   Solution written to {}", path));
+    }
+    reporter.on_message("========================================");
     Ok(())
 }
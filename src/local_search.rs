@@ -0,0 +1,339 @@
+use crate::distance::DistanceMatrix;
+use crate::parser::TspInstance;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+const EPS: f64 = 1e-9;
+
+/// Tour improvement heuristic that can be plugged into the solver.
+pub trait LocalSearch: Debug + Send + Sync {
+    /// Improves `tour` in place and returns its new length.
+    fn improve(&self, tour: &mut [usize], instance: &TspInstance) -> f64;
+}
+
+/// Which tours the configured local search pipeline is applied to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LocalSearchTarget {
+    AntTours,
+    GlobalBest,
+    Both,
+}
+
+impl LocalSearchTarget {
+    pub fn ant_tours(self) -> bool {
+        matches!(self, LocalSearchTarget::AntTours | LocalSearchTarget::Both)
+    }
+
+    pub fn global_best(self) -> bool {
+        matches!(
+            self,
+            LocalSearchTarget::GlobalBest | LocalSearchTarget::Both
+        )
+    }
+}
+
+/// Classic 2-opt: reverses a tour segment whenever that shortens the tour.
+/// On asymmetric instances the move also counts the reversed segment's arcs.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TwoOpt;
+
+/// Or-opt: relocates segments of up to three consecutive nodes.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OrOpt;
+
+/// Segment-exchange 3-opt: removes three edges and reconnects the segments
+/// without reversing any of them, the move 2-opt cannot express. O(n^3) per
+/// pass, so only worth it on small instances or on the global best.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ThreeOpt;
+
+/// POPMUSIC-style post-optimization: slides a window of `segment_size`
+/// nodes around the tour and reoptimizes each open segment between its two
+/// fixed neighbours, exactly (Held-Karp) when the segment is small enough and
+/// with path 2-opt otherwise. Repeats until no segment improves.
+#[derive(Debug, Clone, Copy)]
+pub struct Popmusic {
+    pub segment_size: usize,
+}
+
+// Largest segment solved exactly; Held-Karp costs O(2^s * s^2).
+const POPMUSIC_EXACT_LIMIT: usize = 12;
+
+impl Default for Popmusic {
+    fn default() -> Self {
+        Popmusic { segment_size: 10 }
+    }
+}
+
+impl LocalSearch for TwoOpt {
+    fn improve(&self, tour: &mut [usize], instance: &TspInstance) -> f64 {
+        let n = tour.len();
+        let d = &instance.dist_matrix;
+        if n < 4 {
+            return instance.tour_length(tour);
+        }
+        let directed = !d.is_symmetric_storage();
+        let mut improved = true;
+        while improved {
+            improved = false;
+            for i in 0..n - 2 {
+                let mut reversal = 0.0;
+                for j in (i + 2)..n {
+                    if directed {
+                        reversal += reversal_cost(d, tour[j - 1], tour[j]);
+                    }
+                    if i == 0 && j == n - 1 {
+                        continue;
+                    }
+                    let (a, b) = (tour[i], tour[i + 1]);
+                    let (c, e) = (tour[j], tour[(j + 1) % n]);
+                    let delta = d.get(a, c) + d.get(b, e) - d.get(a, b) - d.get(c, e) + reversal;
+                    if delta < -EPS {
+                        tour[i + 1..=j].reverse();
+                        reversal = -reversal;
+                        improved = true;
+                    }
+                }
+            }
+        }
+        instance.tour_length(tour)
+    }
+}
+
+impl LocalSearch for OrOpt {
+    fn improve(&self, tour: &mut [usize], instance: &TspInstance) -> f64 {
+        let n = tour.len();
+        let d = &instance.dist_matrix;
+        let mut improved = true;
+        while improved {
+            improved = false;
+            'search: for seg_len in 1..=3 {
+                if n < seg_len + 3 {
+                    break;
+                }
+                for i in 0..=n - seg_len {
+                    let first = tour[i];
+                    let last = tour[i + seg_len - 1];
+                    let prev = tour[(i + n - 1) % n];
+                    let next = tour[(i + seg_len) % n];
+                    let removal_gain = d.get(prev, first) + d.get(last, next) - d.get(prev, next);
+
+                    for j in 0..n - 1 {
+                        // Skip insertion points touching the segment itself.
+                        if j + 1 >= i && j < i + seg_len {
+                            continue;
+                        }
+                        let (p, q) = (tour[j], tour[j + 1]);
+                        let insert_cost = d.get(p, first) + d.get(last, q) - d.get(p, q);
+                        if removal_gain - insert_cost > EPS {
+                            if j >= i + seg_len {
+                                tour[i..=j].rotate_left(seg_len);
+                            } else {
+                                tour[j + 1..i + seg_len].rotate_right(seg_len);
+                            }
+                            improved = true;
+                            break 'search;
+                        }
+                    }
+                }
+            }
+        }
+        instance.tour_length(tour)
+    }
+}
+
+impl LocalSearch for ThreeOpt {
+    fn improve(&self, tour: &mut [usize], instance: &TspInstance) -> f64 {
+        let n = tour.len();
+        let d = &instance.dist_matrix;
+        if n < 6 {
+            return instance.tour_length(tour);
+        }
+        let mut improved = true;
+        while improved {
+            improved = false;
+            'search: for i in 0..n - 4 {
+                for j in (i + 2)..n - 2 {
+                    for k in (j + 2)..n {
+                        let (a, b) = (tour[i], tour[i + 1]);
+                        let (c, e) = (tour[j], tour[j + 1]);
+                        let (f, g) = (tour[k], tour[(k + 1) % n]);
+                        let delta = d.get(a, e) + d.get(f, b) + d.get(c, g)
+                            - d.get(a, b)
+                            - d.get(c, e)
+                            - d.get(f, g);
+                        if delta < -EPS {
+                            tour[i + 1..=k].rotate_left(j - i);
+                            improved = true;
+                            break 'search;
+                        }
+                    }
+                }
+            }
+        }
+        instance.tour_length(tour)
+    }
+}
+
+impl LocalSearch for Popmusic {
+    fn improve(&self, tour: &mut [usize], instance: &TspInstance) -> f64 {
+        let n = tour.len();
+        let size = self.segment_size.max(3);
+        if n < size + 2 {
+            return ThreeOpt.improve(tour, instance);
+        }
+        let step = (size / 2).max(1);
+        let mut improved = true;
+        while improved {
+            improved = false;
+            for start in (0..n).step_by(step) {
+                // Rotating keeps the segment contiguous: tour[0] and
+                // tour[size + 1] are its fixed endpoints.
+                tour.rotate_left(start);
+                improved |= reoptimize_path(&mut tour[..size + 2], instance);
+                tour.rotate_right(start);
+            }
+        }
+        instance.tour_length(tour)
+    }
+}
+
+fn path_length(path: &[usize], instance: &TspInstance) -> f64 {
+    path.windows(2)
+        .map(|edge| instance.dist_matrix.get(edge[0], edge[1]))
+        .sum()
+}
+
+/// Reorders the interior of `path` keeping both ends fixed. Returns whether
+/// the path got shorter.
+fn reoptimize_path(path: &mut [usize], instance: &TspInstance) -> bool {
+    let before = path_length(path, instance);
+    let interior = path.len() - 2;
+    let mut candidate = path.to_vec();
+    if interior <= POPMUSIC_EXACT_LIMIT {
+        held_karp_path(&mut candidate, instance);
+    } else {
+        two_opt_path(&mut candidate, instance);
+    }
+    if path_length(&candidate, instance) < before - EPS {
+        path.copy_from_slice(&candidate);
+        true
+    } else {
+        false
+    }
+}
+
+/// Exact shortest Hamiltonian path through the interior of `path` between
+/// its fixed endpoints.
+fn held_karp_path(path: &mut [usize], instance: &TspInstance) {
+    let first = path[0];
+    let last = path[path.len() - 1];
+    let inner: Vec<usize> = path[1..path.len() - 1].to_vec();
+    let m = inner.len();
+    let full = (1usize << m) - 1;
+    // cost[mask][j]: shortest path from `first` visiting `mask`, ending at inner[j].
+    let mut cost = vec![f64::INFINITY; (1 << m) * m];
+    let mut parent = vec![usize::MAX; (1 << m) * m];
+    for (j, &node) in inner.iter().enumerate() {
+        cost[(1 << j) * m + j] = instance.dist_matrix.get(first, node);
+    }
+    for mask in 1..=full {
+        for j in 0..m {
+            let here = cost[mask * m + j];
+            if mask & (1 << j) == 0 || here.is_infinite() {
+                continue;
+            }
+            for k in 0..m {
+                if mask & (1 << k) != 0 {
+                    continue;
+                }
+                let next = mask | (1 << k);
+                let value = here + instance.dist_matrix.get(inner[j], inner[k]);
+                if value < cost[next * m + k] {
+                    cost[next * m + k] = value;
+                    parent[next * m + k] = j;
+                }
+            }
+        }
+    }
+    // No interior (a path of two nodes) leaves nothing to reorder.
+    let Some(mut end) = (0..m).min_by(|&a, &b| {
+        let ca = cost[full * m + a] + instance.dist_matrix.get(inner[a], last);
+        let cb = cost[full * m + b] + instance.dist_matrix.get(inner[b], last);
+        ca.total_cmp(&cb)
+    }) else {
+        return;
+    };
+    let mut mask = full;
+    for slot in (1..=m).rev() {
+        path[slot] = inner[end];
+        let prev = parent[mask * m + end];
+        mask &= !(1 << end);
+        end = prev;
+    }
+}
+
+/// 2-opt on an open path; never moves the endpoints.
+fn two_opt_path(path: &mut [usize], instance: &TspInstance) {
+    let n = path.len();
+    let d = &instance.dist_matrix;
+    let directed = !d.is_symmetric_storage();
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for i in 0..n - 3 {
+            let mut reversal = 0.0;
+            for j in (i + 2)..n - 1 {
+                if directed {
+                    reversal += reversal_cost(d, path[j - 1], path[j]);
+                }
+                let (a, b) = (path[i], path[i + 1]);
+                let (c, e) = (path[j], path[j + 1]);
+                if d.get(a, c) + d.get(b, e) - d.get(a, b) - d.get(c, e) + reversal < -EPS {
+                    path[i + 1..=j].reverse();
+                    reversal = -reversal;
+                    improved = true;
+                }
+            }
+        }
+    }
+}
+
+// Change in length when the arc `from -> to` is traversed backwards. 2-opt
+// reverses the segment between its two new edges, so on asymmetric matrices
+// the sum of this over the segment's arcs is part of the move's delta.
+#[inline]
+fn reversal_cost(d: &DistanceMatrix, from: usize, to: usize) -> f64 {
+    d.get(to, from) - d.get(from, to)
+}
+
+/// Looks up a local search by its command line name. POPMUSIC takes an
+/// optional segment size, e.g. `popmusic:12`.
+pub fn local_search_by_name(name: &str) -> Option<Arc<dyn LocalSearch>> {
+    let name = name.to_lowercase();
+    if let Some(size) = name.strip_prefix("popmusic:") {
+        let segment_size = size.parse().ok().filter(|&s: &usize| s >= 3)?;
+        return Some(Arc::new(Popmusic { segment_size }));
+    }
+    match name.as_str() {
+        "2opt" | "2-opt" => Some(Arc::new(TwoOpt)),
+        "oropt" | "or-opt" => Some(Arc::new(OrOpt)),
+        "3opt" | "3-opt" => Some(Arc::new(ThreeOpt)),
+        "popmusic" => Some(Arc::new(Popmusic::default())),
+        _ => None,
+    }
+}
+
+/// Runs every local search of `pipeline` on `tour` in order and returns the
+/// resulting tour length.
+pub fn apply_pipeline(
+    pipeline: &[Arc<dyn LocalSearch>],
+    tour: &mut [usize],
+    instance: &TspInstance,
+) -> f64 {
+    let mut length = instance.tour_length(tour);
+    for ls in pipeline {
+        length = ls.improve(tour, instance);
+    }
+    length
+}
@@ -9,7 +9,17 @@ fn main() {
         process::exit(1);
     });
 
-    if let Err(e) = tsp_solver::run(&config) {
+    let mut reporter = config.report_format.reporter();
+    if config.pin_threads
+        && let Err(e) = tsp_solver::threads::pin_global_pool()
+    {
+        reporter.on_warning(&e);
+    }
+    if config.checkpoint_path.is_some() {
+        tsp_solver::checkpoint::install_shutdown_handler();
+    }
+
+    if let Err(e) = tsp_solver::run(&config, reporter.as_mut()) {
         println!("Application error: {e}");
         process::exit(1);
     };
@@ -0,0 +1,215 @@
+use crate::distance::{DistanceMatrix, FORBIDDEN_DISTANCE};
+use crate::http::http_request;
+use crate::json::{self, JsonValue};
+use crate::parser::{
+    DistancePolicy, EdgeWeightFormat, EdgeWeightType, ParseOptions, TspInstance,
+    dimension_limit_error,
+};
+
+/// Which annotation of a routing matrix becomes the edge weight.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RoadMetric {
+    /// Travel time in seconds.
+    Duration,
+    /// Driving distance in meters.
+    Distance,
+}
+
+/// JSON matrix responses of routing APIs that can be imported.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MatrixResponseFormat {
+    /// OpenRouteService `/v2/matrix`: `durations` / `distances` arrays,
+    /// `null` where no route exists.
+    OpenRouteService,
+    /// Google Distance Matrix: `rows[i].elements[j]` with a `status` and
+    /// `duration.value` / `distance.value`.
+    GoogleDistanceMatrix,
+}
+
+impl MatrixResponseFormat {
+    /// Recognizes the format from the response's top-level members.
+    pub fn detect(response: &JsonValue) -> Option<Self> {
+        if response.get("rows").is_some() {
+            Some(MatrixResponseFormat::GoogleDistanceMatrix)
+        } else if response.get("durations").is_some() || response.get("distances").is_some() {
+            Some(MatrixResponseFormat::OpenRouteService)
+        } else {
+            None
+        }
+    }
+}
+
+/// Reads a routing API matrix response from a file or an `http://` URL
+/// (for `https` APIs, save the response to a file first) and builds an
+/// explicit instance from it. `format` is detected when `None`. Missing or
+/// unreachable entries become forbidden edges.
+pub fn import_matrix_response(
+    source: &str,
+    format: Option<MatrixResponseFormat>,
+    metric: RoadMetric,
+    options: &ParseOptions,
+) -> Result<TspInstance, String> {
+    let (text, name) = if source.starts_with("http://") {
+        (http_request("GET", source, None)?, "matrix".to_string())
+    } else {
+        let text = std::fs::read_to_string(source)
+            .map_err(|e| format!("Failed to open file {}: {}", source, e))?;
+        let name = std::path::Path::new(source)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("matrix")
+            .to_string();
+        (text, name)
+    };
+    let response = json::parse(&text)?;
+    let format = match format {
+        Some(format) => format,
+        None => MatrixResponseFormat::detect(&response)
+            .ok_or("Unrecognized matrix response: expected OpenRouteService or Google JSON")?,
+    };
+    let matrix = parse_matrix_response(&response, format, metric)?;
+    if matrix.len() > options.max_dimension {
+        return Err(dimension_limit_error(matrix.len(), options.max_dimension));
+    }
+    Ok(instance_from_matrix(
+        &name,
+        format!(
+            "Imported from a routing API matrix ({:?}, {:?})",
+            format, metric
+        ),
+        &matrix,
+        options.distance_policy.unwrap_or(DistancePolicy::Exact),
+    ))
+}
+
+/// Extracts the square matrix of `metric` from a parsed response; `None`
+/// marks pairs without a route.
+pub fn parse_matrix_response(
+    response: &JsonValue,
+    format: MatrixResponseFormat,
+    metric: RoadMetric,
+) -> Result<Vec<Vec<Option<f64>>>, String> {
+    match format {
+        MatrixResponseFormat::OpenRouteService => {
+            let key = match metric {
+                RoadMetric::Duration => "durations",
+                RoadMetric::Distance => "distances",
+            };
+            let rows = response
+                .get(key)
+                .ok_or_else(|| format!("Matrix response has no {}", key))?;
+            let n = rows.as_array().map_or(0, |rows| rows.len());
+            read_matrix(rows, n, |cell| cell.as_f64())
+        }
+        MatrixResponseFormat::GoogleDistanceMatrix => {
+            if let Some(status) = response.get("status").and_then(JsonValue::as_str)
+                && status != "OK"
+            {
+                return Err(format!("Distance Matrix request failed: {}", status));
+            }
+            let key = match metric {
+                RoadMetric::Duration => "duration",
+                RoadMetric::Distance => "distance",
+            };
+            let rows = response
+                .get("rows")
+                .and_then(JsonValue::as_array)
+                .ok_or("Distance Matrix response has no rows")?;
+            let elements: Vec<JsonValue> = rows
+                .iter()
+                .map(|row| row.get("elements").cloned().unwrap_or(JsonValue::Null))
+                .collect();
+            read_matrix(&JsonValue::Array(elements), rows.len(), |element| {
+                let ok = element
+                    .get("status")
+                    .and_then(JsonValue::as_str)
+                    .is_none_or(|status| status == "OK");
+                ok.then(|| element.get(key)?.get("value")?.as_f64())
+                    .flatten()
+            })
+        }
+    }
+}
+
+/// Reads an `n x n` JSON array of arrays, mapping each cell with `cell_value`.
+pub(crate) fn read_matrix(
+    rows: &JsonValue,
+    n: usize,
+    cell_value: impl Fn(&JsonValue) -> Option<f64>,
+) -> Result<Vec<Vec<Option<f64>>>, String> {
+    let rows = rows.as_array().ok_or("Matrix is not an array")?;
+    if rows.len() != n {
+        return Err(format!("Expected {} matrix rows, got {}", n, rows.len()));
+    }
+    rows.iter()
+        .map(|row| {
+            let cells = row.as_array().ok_or("Matrix row is not an array")?;
+            if cells.len() != n {
+                return Err(format!(
+                    "Expected {} matrix columns, got {}",
+                    n,
+                    cells.len()
+                ));
+            }
+            Ok(cells.iter().map(&cell_value).collect())
+        })
+        .collect()
+}
+
+/// Builds an explicit instance from a square matrix, with `None` entries
+/// off the diagonal as forbidden edges, counted in the comment. Symmetric matrices are stored
+/// compactly; anything else is kept as a full asymmetric matrix.
+pub(crate) fn instance_from_matrix(
+    name: &str,
+    comment: String,
+    matrix: &[Vec<Option<f64>>],
+    distance_policy: DistancePolicy,
+) -> TspInstance {
+    let n = matrix.len();
+    let mut dist_matrix = DistanceMatrix::full(n);
+    let mut forbidden = 0;
+    for (i, row) in matrix.iter().enumerate() {
+        for (j, cell) in row.iter().enumerate() {
+            let cost = match cell {
+                _ if i == j => 0.0,
+                Some(cost) => distance_policy.apply(*cost),
+                None => {
+                    forbidden += 1;
+                    FORBIDDEN_DISTANCE
+                }
+            };
+            dist_matrix.set(i, j, cost);
+        }
+    }
+    // Kept with the instance, where reporters show the comment.
+    let comment = match forbidden {
+        0 => comment,
+        _ => format!(
+            "{}; {} pairs have no route and are forbidden",
+            comment, forbidden
+        ),
+    };
+    let dist_matrix = dist_matrix.into_compact();
+    let tsp_type = if dist_matrix.is_symmetric_storage() {
+        "TSP"
+    } else {
+        "ATSP"
+    };
+    TspInstance {
+        name: name.to_string(),
+        tsp_type: tsp_type.to_string(),
+        comment,
+        dimension: n,
+        edge_weight_type: EdgeWeightType::Explicit,
+        edge_weight_format: Some(EdgeWeightFormat::FullMatrix),
+        distance_policy,
+        node_coords: None,
+        dist_matrix,
+        node_metadata: None,
+        precedences: None,
+        capacity: None,
+        demands: None,
+        depots: None,
+        fixed_edges: None,
+    }
+}
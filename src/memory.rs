@@ -0,0 +1,131 @@
+//! Picks how an instance is stored from a memory budget (`--max-memory`), so
+//! that big instances run without choosing storage details by hand.
+//!
+//! The modes, from fastest to leanest:
+//!
+//! - dense: full distance matrix plus dense heuristic and pheromone matrices,
+//! - triangular: upper triangle of the distances, dense heuristic and
+//!   pheromone (the default without a budget),
+//! - lazy: distances computed from coordinates on demand and ants restricted
+//!   to candidate lists of nearest neighbours, with the heuristic only stored
+//!   for those. Only the pheromone matrix stays quadratic.
+
+use crate::distance::DistanceMatrix;
+use crate::parser::Node;
+use crate::solver::Pheromone;
+use std::fmt;
+
+/// Nearest neighbours per node in lazy mode when no candidate list size is
+/// configured.
+pub const DEFAULT_CANDIDATE_LIST_SIZE: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StorageMode {
+    Dense,
+    Triangular,
+    Lazy { candidates: usize },
+}
+
+impl StorageMode {
+    /// Storage in use for `dist_matrix`; `candidates` is the solver's
+    /// candidate list size for lazy matrices.
+    pub fn of(dist_matrix: &DistanceMatrix, candidates: usize) -> Self {
+        match dist_matrix {
+            DistanceMatrix::Full { .. } => StorageMode::Dense,
+            DistanceMatrix::Triangular { .. } => StorageMode::Triangular,
+            DistanceMatrix::Adjusted { base, .. } => StorageMode::of(base, candidates),
+            DistanceMatrix::Lazy { .. } => StorageMode::Lazy { candidates },
+        }
+    }
+
+    /// Approximate bytes the solver needs for `dimension` nodes in this mode.
+    pub fn estimated_bytes(&self, dimension: usize) -> usize {
+        let n = dimension;
+        let square = n.saturating_mul(n);
+        let pheromone = square.saturating_mul(size_of::<Pheromone>());
+        let stored = match self {
+            StorageMode::Dense => square.saturating_mul(2 * size_of::<f64>()),
+            StorageMode::Triangular => (n.saturating_mul(n.saturating_sub(1)) / 2)
+                .saturating_add(square)
+                .saturating_mul(size_of::<f64>()),
+            StorageMode::Lazy { candidates } => {
+                let per_candidate = size_of::<usize>() + size_of::<f64>();
+                n.saturating_mul(size_of::<Node>()).saturating_add(
+                    n.saturating_mul((*candidates).min(n.saturating_sub(1)))
+                        .saturating_mul(per_candidate),
+                )
+            }
+        };
+        stored.saturating_add(pheromone)
+    }
+
+    /// The fastest mode whose estimate fits `budget` bytes. Lazy mode needs
+    /// a coordinate based metric. Fails if not even the leanest mode fits.
+    pub fn select(dimension: usize, budget: usize, coordinates: bool) -> Result<Self, String> {
+        let mut modes = vec![StorageMode::Dense, StorageMode::Triangular];
+        if coordinates {
+            modes.push(StorageMode::Lazy {
+                candidates: DEFAULT_CANDIDATE_LIST_SIZE,
+            });
+        }
+        let leanest = *modes.last().expect("at least two modes");
+        modes
+            .into_iter()
+            .find(|mode| mode.estimated_bytes(dimension) <= budget)
+            .ok_or_else(|| {
+                format!(
+                    "{} nodes need at least {} even in the leanest mode ({}), but --max-memory is {}. Use --decompose, --partition or --multilevel for instances this large.",
+                    dimension,
+                    format_bytes(leanest.estimated_bytes(dimension)),
+                    leanest,
+                    format_bytes(budget)
+                )
+            })
+    }
+}
+
+impl fmt::Display for StorageMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageMode::Dense => write!(f, "dense"),
+            StorageMode::Triangular => write!(f, "triangular"),
+            StorageMode::Lazy { candidates } => {
+                write!(f, "lazy, {} candidates per node", candidates)
+            }
+        }
+    }
+}
+
+/// Parses a byte count with an optional binary suffix: `512M`, `8G`, `1.5G`
+/// or plain bytes.
+pub fn parse_byte_size(value: &str) -> Option<usize> {
+    let value = value.trim();
+    let (number, unit) = match value.char_indices().find(|(_, c)| c.is_ascii_alphabetic()) {
+        Some((k, _)) => value.split_at(k),
+        None => (value, ""),
+    };
+    let scale: f64 = match unit
+        .to_ascii_uppercase()
+        .trim_end_matches("IB")
+        .trim_end_matches('B')
+    {
+        "" => 1.0,
+        "K" => 1024.0,
+        "M" => 1024.0 * 1024.0,
+        "G" => 1024.0 * 1024.0 * 1024.0,
+        "T" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    let number: f64 = number.trim().parse().ok()?;
+    (number >= 0.0 && number.is_finite()).then_some((number * scale) as usize)
+}
+
+/// `bytes` in KiB, MiB or GiB, for log messages.
+pub fn format_bytes(bytes: usize) -> String {
+    let bytes = bytes as f64;
+    match bytes {
+        b if b >= 1024.0 * 1024.0 * 1024.0 => format!("{:.1} GiB", b / (1024.0 * 1024.0 * 1024.0)),
+        b if b >= 1024.0 * 1024.0 => format!("{:.1} MiB", b / (1024.0 * 1024.0)),
+        b => format!("{:.1} KiB", b / 1024.0),
+    }
+}
@@ -0,0 +1,147 @@
+//! Summary queries on an instance: extent of the coordinates, edge weight
+//! statistics and structural flags, for choosing parameters before a run.
+//!
+//! The edge based queries visit every pair of nodes, so they are O(n²) and
+//! compute each distance of a lazy matrix once more.
+
+use crate::distance::FORBIDDEN_DISTANCE;
+use crate::parser::TspInstance;
+
+/// Smallest axis-aligned rectangle containing all node coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub min_x: f64,
+    pub min_y: f64,
+    pub max_x: f64,
+    pub max_y: f64,
+}
+
+impl BoundingBox {
+    pub fn width(&self) -> f64 {
+        self.max_x - self.min_x
+    }
+
+    pub fn height(&self) -> f64 {
+        self.max_y - self.min_y
+    }
+
+    /// Length of the diagonal, a scale for coordinate distances.
+    pub fn diagonal(&self) -> f64 {
+        self.width().hypot(self.height())
+    }
+}
+
+/// Statistics over the usable edges of an instance: finite weights below
+/// [`FORBIDDEN_DISTANCE`], one direction per pair for symmetric instances.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EdgeWeightStats {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    /// Edges that went into the statistics.
+    pub edges: usize,
+}
+
+impl TspInstance {
+    /// Whether nodes have coordinates (for coordinate metrics and EXPLICIT
+    /// files with display coordinates).
+    pub fn has_coordinates(&self) -> bool {
+        self.node_coords
+            .as_ref()
+            .is_some_and(|nodes| !nodes.is_empty())
+    }
+
+    /// Extent of the coordinates, ignoring NaN values; `None` without
+    /// coordinates.
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        let nodes = self
+            .node_coords
+            .as_ref()
+            .filter(|nodes| !nodes.is_empty())?;
+        let empty = BoundingBox {
+            min_x: f64::INFINITY,
+            min_y: f64::INFINITY,
+            max_x: f64::NEG_INFINITY,
+            max_y: f64::NEG_INFINITY,
+        };
+        Some(nodes.iter().fold(empty, |bbox, node| BoundingBox {
+            min_x: bbox.min_x.min(node.x),
+            min_y: bbox.min_y.min(node.y),
+            max_x: bbox.max_x.max(node.x),
+            max_y: bbox.max_y.max(node.y),
+        }))
+    }
+
+    /// Mean of the coordinates; `None` without coordinates.
+    pub fn centroid(&self) -> Option<(f64, f64)> {
+        let nodes = self
+            .node_coords
+            .as_ref()
+            .filter(|nodes| !nodes.is_empty())?;
+        let (sx, sy) = nodes
+            .iter()
+            .fold((0.0, 0.0), |(sx, sy), node| (sx + node.x, sy + node.y));
+        let count = nodes.len() as f64;
+        Some((sx / count, sy / count))
+    }
+
+    /// Whether every edge weighs the same in both directions.
+    pub fn is_symmetric(&self) -> bool {
+        self.dist_matrix.is_symmetric()
+    }
+
+    /// Min, max and mean of the usable edge weights; `None` if there are
+    /// none (fewer than two nodes, or every edge forbidden).
+    pub fn edge_weight_stats(&self) -> Option<EdgeWeightStats> {
+        let mut stats = EdgeWeightStats {
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            mean: 0.0,
+            edges: 0,
+        };
+        let mut sum = 0.0;
+        for weight in self.edge_weights().filter(|&w| usable(w)) {
+            stats.min = stats.min.min(weight);
+            stats.max = stats.max.max(weight);
+            sum += weight;
+            stats.edges += 1;
+        }
+        if stats.edges == 0 {
+            return None;
+        }
+        stats.mean = sum / stats.edges as f64;
+        Some(stats)
+    }
+
+    /// Fraction of node pairs joined by a usable edge: 1 for complete
+    /// graphs, lower when edges are forbidden (e.g. no road route) or
+    /// missing. Instances with fewer than two nodes count as complete.
+    pub fn density(&self) -> f64 {
+        let (usable_edges, edges) = self
+            .edge_weights()
+            .fold((0usize, 0usize), |(usable_edges, edges), w| {
+                (usable_edges + usable(w) as usize, edges + 1)
+            });
+        if edges == 0 {
+            1.0
+        } else {
+            usable_edges as f64 / edges as f64
+        }
+    }
+
+    // Off-diagonal weights, `(i, j)` with `i < j` only if symmetric.
+    fn edge_weights(&self) -> impl Iterator<Item = f64> + '_ {
+        let n = self.dimension;
+        let symmetric = self.is_symmetric();
+        (0..n).flat_map(move |i| {
+            let first = if symmetric { i + 1 } else { 0 };
+            (first..n)
+                .filter(move |&j| j != i)
+                .map(move |j| self.dist_matrix.get(i, j))
+        })
+    }
+}
+
+fn usable(weight: f64) -> bool {
+    weight.is_finite() && weight < FORBIDDEN_DISTANCE
+}
@@ -0,0 +1,181 @@
+use crate::config::Config;
+use crate::construction;
+use crate::distance::DistanceMatrix;
+use crate::parser::{Node, TspInstance};
+use crate::solver::solve_tsp_aco;
+
+// Candidate partners per node, taken from its successors on a Hilbert curve.
+const MATCH_CANDIDATES: usize = 8;
+const REFINE_WINDOW: usize = 24;
+
+/// One coarsening level: `children[k]` are the (one or two) nodes of the next
+/// finer level merged into coarse node `k`.
+struct Level {
+    nodes: Vec<Node>,
+    children: Vec<Vec<usize>>,
+}
+
+/// Multilevel solving: repeatedly coarsens the instance by merging nearest
+/// pairs of nodes into their midpoint until at most `coarsest_size` nodes
+/// remain, solves that instance with ACO, then expands the tour level by
+/// level, refining it with 2-opt after every expansion.
+pub fn solve_multilevel(
+    instance: &TspInstance,
+    config: &Config,
+    coarsest_size: usize,
+) -> Result<(Vec<usize>, f64), String> {
+    let nodes = instance
+        .node_coords
+        .as_ref()
+        .ok_or("Multilevel solving requires node coordinates")?;
+    let n = instance.dimension;
+    if n < 2 {
+        return Ok(((0..n).collect(), 0.0));
+    }
+
+    let mut levels: Vec<Level> = Vec::new();
+    let mut current = nodes.clone();
+    while current.len() > coarsest_size.max(3) {
+        let level = coarsen(&current);
+        if level.nodes.len() == current.len() {
+            break;
+        }
+        current = level.nodes.clone();
+        levels.push(level);
+    }
+
+    let coarsest = level_instance(instance, &current)?;
+    let (mut tour, _) = solve_tsp_aco(&coarsest, config);
+
+    for (depth, level) in levels.iter().enumerate().rev() {
+        let finer_nodes = if depth == 0 {
+            nodes
+        } else {
+            &levels[depth - 1].nodes
+        };
+        let finer = level_instance(instance, finer_nodes)?;
+        tour = expand(&tour, level, &finer.dist_matrix);
+        refine(&mut tour, &finer.dist_matrix);
+    }
+
+    let length = instance.tour_length(&tour);
+    Ok((tour, length))
+}
+
+/// Lazy-distance instance over the nodes of one level, using the metric and
+/// rounding of `instance` (the finest level uses `instance` itself).
+fn level_instance(instance: &TspInstance, nodes: &[Node]) -> Result<TspInstance, String> {
+    if nodes.len() == instance.dimension {
+        return Ok(instance.clone());
+    }
+    Ok(TspInstance {
+        name: format!("{}-coarse{}", instance.name, nodes.len()),
+        tsp_type: instance.tsp_type.clone(),
+        comment: String::new(),
+        dimension: nodes.len(),
+        edge_weight_type: instance.edge_weight_type.clone(),
+        edge_weight_format: None,
+        distance_policy: instance.distance_policy,
+        dist_matrix: DistanceMatrix::lazy(
+            nodes.to_vec(),
+            instance.edge_weight_type.clone(),
+            instance.distance_policy,
+        )?,
+        node_coords: Some(nodes.to_vec()),
+        node_metadata: None,
+        precedences: None,
+        capacity: None,
+        demands: None,
+        depots: None,
+        fixed_edges: None,
+    })
+}
+
+/// Greedy matching of close pairs. Candidate pairs come from neighborhoods
+/// along a Hilbert curve and are matched shortest first.
+fn coarsen(nodes: &[Node]) -> Level {
+    let order = construction::space_filling_curve_order(nodes);
+    let dist = |a: usize, b: usize| {
+        let (na, nb) = (&nodes[a], &nodes[b]);
+        (na.x - nb.x).powi(2) + (na.y - nb.y).powi(2)
+    };
+    let mut candidates: Vec<(f64, usize, usize)> = Vec::new();
+    for (pos, &a) in order.iter().enumerate() {
+        for &b in order.iter().skip(pos + 1).take(MATCH_CANDIDATES) {
+            candidates.push((dist(a, b), a, b));
+        }
+    }
+    candidates.sort_by(|x, y| x.0.total_cmp(&y.0));
+
+    let mut matched = vec![false; nodes.len()];
+    let mut children: Vec<Vec<usize>> = Vec::with_capacity(nodes.len() / 2 + 1);
+    for (_, a, b) in candidates {
+        if !matched[a] && !matched[b] {
+            matched[a] = true;
+            matched[b] = true;
+            children.push(vec![a, b]);
+        }
+    }
+    children.extend((0..nodes.len()).filter(|&i| !matched[i]).map(|i| vec![i]));
+
+    let coarse_nodes = children
+        .iter()
+        .enumerate()
+        .map(|(k, members)| {
+            let count = members.len() as f64;
+            Node {
+                id: k + 1,
+                x: members.iter().map(|&i| nodes[i].x).sum::<f64>() / count,
+                y: members.iter().map(|&i| nodes[i].y).sum::<f64>() / count,
+                z: members.iter().map(|&i| nodes[i].z).sum::<f64>() / count,
+            }
+        })
+        .collect();
+    Level {
+        nodes: coarse_nodes,
+        children,
+    }
+}
+
+/// Replaces every coarse node of `tour` by its children, orienting pairs so
+/// that the connection from the previous node is the shorter one.
+fn expand(tour: &[usize], level: &Level, d: &DistanceMatrix) -> Vec<usize> {
+    let mut fine: Vec<usize> = Vec::with_capacity(tour.len() * 2);
+    for &coarse in tour {
+        match level.children[coarse].as_slice() {
+            [a, b] => match fine.last() {
+                Some(&prev) if d.get(prev, *b) < d.get(prev, *a) => fine.extend([*b, *a]),
+                _ => fine.extend([*a, *b]),
+            },
+            members => fine.extend_from_slice(members),
+        }
+    }
+    fine
+}
+
+/// 2-opt over overlapping windows sweeping the whole tour, O(n * w^2).
+fn refine(tour: &mut [usize], d: &DistanceMatrix) {
+    let n = tour.len();
+    if n < 4 {
+        return;
+    }
+    let mut start = 0;
+    while start + 2 < n {
+        let end = (start + 2 * REFINE_WINDOW).min(n - 1);
+        let mut improved = true;
+        while improved {
+            improved = false;
+            for i in start..end {
+                for j in (i + 2)..end {
+                    let (a, b) = (tour[i], tour[i + 1]);
+                    let (c, e) = (tour[j], tour[j + 1]);
+                    if d.get(a, c) + d.get(b, e) - d.get(a, b) - d.get(c, e) < -1e-9 {
+                        tour[i + 1..=j].reverse();
+                        improved = true;
+                    }
+                }
+            }
+        }
+        start += REFINE_WINDOW;
+    }
+}
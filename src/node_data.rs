@@ -0,0 +1,455 @@
+//! Metadata of the nodes (names, addresses, ...) carried from the input to
+//! the exports, so results can be read without looking up node ids.
+//!
+//! Metadata comes from the extra columns of a CSV instance, from a CSV file
+//! joined on node ids (`--node-data`), or is attached programmatically with
+//! [`TspInstance::with_node_metadata`]. Solutions keep the metadata of their
+//! stops in visit order, and [`write_visit_order`] lists the stops as CSV
+//! or GeoJSON.
+
+use crate::json::JsonValue;
+use crate::parser::{EdgeWeightType, ParseOptions, TspInstance};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+/// Text columns with one row per node, in node index order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NodeMetadata {
+    columns: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl NodeMetadata {
+    /// Metadata for `n` nodes with the given columns, all values empty.
+    pub fn new(columns: Vec<String>, n: usize) -> Self {
+        let width = columns.len();
+        NodeMetadata {
+            columns,
+            rows: vec![vec![String::new(); width]; n],
+        }
+    }
+
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    /// Number of nodes.
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Values of `node`, one per column.
+    pub fn row(&self, node: usize) -> Option<&[String]> {
+        self.rows.get(node).map(Vec::as_slice)
+    }
+
+    pub fn get(&self, node: usize, column: &str) -> Option<&str> {
+        let k = self.columns.iter().position(|c| c == column)?;
+        Some(self.rows.get(node)?[k].as_str())
+    }
+
+    /// Sets the value of `column` for `node`, adding the column if it is
+    /// new.
+    pub fn set(
+        &mut self,
+        node: usize,
+        column: &str,
+        value: impl Into<String>,
+    ) -> Result<(), String> {
+        if node >= self.rows.len() {
+            return Err(format!(
+                "Node {} out of bounds for {} nodes of metadata",
+                node,
+                self.rows.len()
+            ));
+        }
+        let k = match self.columns.iter().position(|c| c == column) {
+            Some(k) => k,
+            None => {
+                self.columns.push(column.to_string());
+                self.rows.iter_mut().for_each(|row| row.push(String::new()));
+                self.columns.len() - 1
+            }
+        };
+        self.rows[node][k] = value.into();
+        Ok(())
+    }
+
+    /// The rows of `order` (node indices), e.g. a tour's stops in visit
+    /// order. Out of range indices get empty rows.
+    pub fn reordered(&self, order: &[usize]) -> NodeMetadata {
+        NodeMetadata {
+            columns: self.columns.clone(),
+            rows: order
+                .iter()
+                .map(|&node| {
+                    self.rows
+                        .get(node)
+                        .cloned()
+                        .unwrap_or_else(|| vec![String::new(); self.columns.len()])
+                })
+                .collect(),
+        }
+    }
+
+    /// Joins CSV `text` on the node ids of `instance`: the header names an
+    /// `id` column and the metadata columns, and each line gives the values
+    /// of one node. Nodes without a line keep empty values.
+    pub fn from_csv(text: &str, instance: &TspInstance) -> Result<NodeMetadata, String> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .filter(|(_, l)| !l.trim().is_empty());
+        let header = csv_fields(lines.next().ok_or("Node data has no header")?.1);
+        let id_column = header
+            .iter()
+            .position(|column| column.eq_ignore_ascii_case("id"))
+            .ok_or("Node data has no 'id' column")?;
+        let columns: Vec<String> = header
+            .iter()
+            .enumerate()
+            .filter(|&(k, _)| k != id_column)
+            .map(|(_, column)| column.clone())
+            .collect();
+        let index_of: HashMap<usize, usize> = (0..instance.dimension)
+            .map(|idx| (instance.node_id(idx), idx))
+            .collect();
+        let mut metadata = NodeMetadata::new(columns, instance.dimension);
+        for (line_num, line) in lines {
+            let fields = csv_fields(line);
+            if fields.len() != header.len() {
+                return Err(format!(
+                    "L{}: Expected {} fields, got {}",
+                    line_num + 1,
+                    header.len(),
+                    fields.len()
+                ));
+            }
+            let id: usize = fields[id_column].trim().parse().map_err(|_| {
+                format!("L{}: Invalid node id '{}'", line_num + 1, fields[id_column])
+            })?;
+            let &idx = index_of
+                .get(&id)
+                .ok_or_else(|| format!("L{}: Unknown node id {}", line_num + 1, id))?;
+            metadata.rows[idx] = fields
+                .into_iter()
+                .enumerate()
+                .filter(|&(k, _)| k != id_column)
+                .map(|(_, value)| value)
+                .collect();
+        }
+        Ok(metadata)
+    }
+
+    pub fn read_csv(path: &str, instance: &TspInstance) -> Result<NodeMetadata, String> {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read node data {}: {}", path, e))?;
+        NodeMetadata::from_csv(&text, instance)
+    }
+
+    pub(crate) fn to_json_value(&self) -> JsonValue {
+        let strings = |values: &[String]| {
+            JsonValue::Array(values.iter().cloned().map(JsonValue::String).collect())
+        };
+        JsonValue::Object(vec![
+            ("columns".to_string(), strings(&self.columns)),
+            (
+                "rows".to_string(),
+                JsonValue::Array(self.rows.iter().map(|row| strings(row)).collect()),
+            ),
+        ])
+    }
+
+    pub(crate) fn from_json_value(value: &JsonValue) -> Result<NodeMetadata, String> {
+        let strings = |value: &JsonValue| -> Option<Vec<String>> {
+            value
+                .as_array()?
+                .iter()
+                .map(|v| v.as_str().map(str::to_string))
+                .collect()
+        };
+        let columns = value
+            .get("columns")
+            .and_then(strings)
+            .ok_or("Node metadata 'columns' is not a list of strings")?;
+        let rows: Vec<Vec<String>> = value
+            .get("rows")
+            .and_then(JsonValue::as_array)
+            .and_then(|rows| rows.iter().map(strings).collect())
+            .ok_or("Node metadata 'rows' is not a list of string lists")?;
+        if rows.iter().any(|row| row.len() != columns.len()) {
+            return Err("Node metadata rows do not match the columns".to_string());
+        }
+        Ok(NodeMetadata { columns, rows })
+    }
+}
+
+impl TspInstance {
+    /// Id of the node at `idx`: its coordinate id, or `idx + 1` for
+    /// explicit instances.
+    pub fn node_id(&self, idx: usize) -> usize {
+        self.node_coords
+            .as_ref()
+            .and_then(|nodes| nodes.get(idx))
+            .map_or(idx + 1, |node| node.id)
+    }
+
+    /// The instance with `metadata` attached, one row per node.
+    pub fn with_node_metadata(mut self, metadata: NodeMetadata) -> Result<TspInstance, String> {
+        if metadata.len() != self.dimension {
+            return Err(format!(
+                "Metadata for {} nodes does not fit dimension {}",
+                metadata.len(),
+                self.dimension
+            ));
+        }
+        self.node_metadata = Some(metadata);
+        Ok(self)
+    }
+}
+
+/// Parses a CSV instance: a header row, then one node per line. `x` and `y`
+/// columns give Euclidean coordinates, `lat` and `lon` (or `latitude` and
+/// `longitude`) geodesic ones; an `id` column sets the node ids (default
+/// 1..=n). All other columns become node metadata.
+pub fn parse_csv_instance(
+    name: &str,
+    text: &str,
+    options: &ParseOptions,
+) -> Result<TspInstance, String> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty());
+    let header = csv_fields(lines.next().ok_or("CSV instance has no header")?.1);
+    let find = |names: &[&str]| {
+        header
+            .iter()
+            .position(|column| names.iter().any(|name| column.eq_ignore_ascii_case(name)))
+    };
+    let (metric, x_column, y_column) = match (find(&["x"]), find(&["y"])) {
+        (Some(x), Some(y)) => (EdgeWeightType::Euc2D, x, y),
+        _ => match (
+            find(&["lat", "latitude"]),
+            find(&["lon", "lng", "longitude"]),
+        ) {
+            (Some(lat), Some(lon)) => (EdgeWeightType::Geodesic, lat, lon),
+            _ => return Err("CSV instance needs x and y, or lat and lon columns".to_string()),
+        },
+    };
+    let id_column = find(&["id"]);
+    let metadata_columns: Vec<usize> = (0..header.len())
+        .filter(|&k| k != x_column && k != y_column && Some(k) != id_column)
+        .collect();
+
+    let mut coords = Vec::new();
+    let mut ids = Vec::new();
+    let mut rows = Vec::new();
+    for (line_num, line) in lines {
+        let fields = csv_fields(line);
+        if fields.len() != header.len() {
+            return Err(format!(
+                "L{}: Expected {} fields, got {}",
+                line_num + 1,
+                header.len(),
+                fields.len()
+            ));
+        }
+        if coords.len() >= options.max_dimension {
+            return Err(format!(
+                "CSV instance has more than {} nodes",
+                options.max_dimension
+            ));
+        }
+        let number = |k: usize| {
+            fields[k]
+                .trim()
+                .parse::<f64>()
+                .map_err(|_| format!("L{}: Invalid {} '{}'", line_num + 1, header[k], fields[k]))
+        };
+        coords.push((number(x_column)?, number(y_column)?));
+        if let Some(k) = id_column {
+            ids.push(
+                fields[k]
+                    .trim()
+                    .parse::<usize>()
+                    .map_err(|_| format!("L{}: Invalid node id '{}'", line_num + 1, fields[k]))?,
+            );
+        }
+        rows.push(
+            metadata_columns
+                .iter()
+                .map(|&k| fields[k].clone())
+                .collect(),
+        );
+    }
+
+    let mut instance =
+        TspInstance::from_coordinates(name, &coords, metric, options.distance_policy)?;
+    if let Some(nodes) = instance.node_coords.as_mut() {
+        for (node, id) in nodes.iter_mut().zip(ids) {
+            node.id = id;
+        }
+    }
+    if !metadata_columns.is_empty() {
+        instance.node_metadata = Some(NodeMetadata {
+            columns: metadata_columns
+                .iter()
+                .map(|&k| header[k].clone())
+                .collect(),
+            rows,
+        });
+    }
+    Ok(instance)
+}
+
+/// Writes the stops of `tour` in visit order to `path`: GeoJSON for a
+/// `.geojson` or `.json` extension, otherwise CSV with the columns
+/// `position,id`, the node metadata, `distance` (from the previous stop)
+/// and `cumulative`.
+pub fn write_visit_order(instance: &TspInstance, tour: &[usize], path: &str) -> Result<(), String> {
+    let geojson = Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("geojson") || ext.eq_ignore_ascii_case("json"));
+    let contents = if geojson {
+        visit_order_geojson(instance, tour)?
+    } else {
+        visit_order_csv(instance, tour)
+    };
+    fs::write(path, contents).map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+fn visit_order_csv(instance: &TspInstance, tour: &[usize]) -> String {
+    let metadata = instance.node_metadata.as_ref();
+    let mut csv = "position,id".to_string();
+    for column in metadata.map_or(&[][..], NodeMetadata::columns) {
+        csv.push(',');
+        csv.push_str(&csv_field(column));
+    }
+    csv.push_str(",distance,cumulative\n");
+    let mut cumulative = 0.0;
+    for (position, &node) in tour.iter().enumerate() {
+        let distance = match position {
+            0 => 0.0,
+            _ => instance.dist_matrix.get(tour[position - 1], node),
+        };
+        cumulative += distance;
+        let _ = write!(csv, "{},{}", position + 1, instance.node_id(node));
+        for value in metadata.and_then(|m| m.row(node)).unwrap_or_default() {
+            csv.push(',');
+            csv.push_str(&csv_field(value));
+        }
+        let _ = writeln!(csv, ",{},{}", distance, cumulative);
+    }
+    csv
+}
+
+fn visit_order_geojson(instance: &TspInstance, tour: &[usize]) -> Result<String, String> {
+    let nodes = instance
+        .node_coords
+        .as_deref()
+        .filter(|nodes| nodes.len() == instance.dimension)
+        .ok_or_else(|| format!("{} has no node coordinates for GeoJSON", instance.name))?;
+    // GeoJSON positions are [longitude, latitude]; geodesic instances hold
+    // (latitude, longitude) pairs.
+    let position = |idx: usize| {
+        let node = &nodes[idx];
+        let (a, b) = match instance.edge_weight_type {
+            EdgeWeightType::Geodesic => (node.y, node.x),
+            _ => (node.x, node.y),
+        };
+        JsonValue::Array(vec![JsonValue::Number(a), JsonValue::Number(b)])
+    };
+    let feature = |geometry: JsonValue, properties: Vec<(String, JsonValue)>| {
+        JsonValue::Object(vec![
+            ("type".to_string(), JsonValue::String("Feature".to_string())),
+            ("geometry".to_string(), geometry),
+            ("properties".to_string(), JsonValue::Object(properties)),
+        ])
+    };
+    let geometry = |kind: &str, coordinates: JsonValue| {
+        JsonValue::Object(vec![
+            ("type".to_string(), JsonValue::String(kind.to_string())),
+            ("coordinates".to_string(), coordinates),
+        ])
+    };
+    let metadata = instance.node_metadata.as_ref();
+    let mut features: Vec<JsonValue> = tour
+        .iter()
+        .enumerate()
+        .map(|(k, &node)| {
+            let mut properties = vec![
+                ("position".to_string(), JsonValue::Number((k + 1) as f64)),
+                (
+                    "id".to_string(),
+                    JsonValue::Number(instance.node_id(node) as f64),
+                ),
+            ];
+            if let Some(metadata) = metadata {
+                properties.extend(
+                    metadata
+                        .columns()
+                        .iter()
+                        .zip(metadata.row(node).unwrap_or_default())
+                        .map(|(column, value)| (column.clone(), JsonValue::String(value.clone()))),
+                );
+            }
+            feature(geometry("Point", position(node)), properties)
+        })
+        .collect();
+    if !tour.is_empty() {
+        let route = tour.iter().chain(tour.first()).map(|&node| position(node));
+        features.push(feature(
+            geometry("LineString", JsonValue::Array(route.collect())),
+            vec![(
+                "length".to_string(),
+                JsonValue::Number(instance.tour_length(tour)),
+            )],
+        ));
+    }
+    let collection = JsonValue::Object(vec![
+        (
+            "type".to_string(),
+            JsonValue::String("FeatureCollection".to_string()),
+        ),
+        ("features".to_string(), JsonValue::Array(features)),
+    ]);
+    Ok(collection.to_string() + "\n")
+}
+
+/// Splits a CSV line, honouring double-quoted fields with `""` escapes.
+fn csv_fields(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.trim_end_matches('\r').chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+/// `value` quoted for CSV if it needs to be.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
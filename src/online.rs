@@ -0,0 +1,91 @@
+use crate::config::Config;
+use crate::parser::TspInstance;
+use crate::solver::AcoSolver;
+use std::sync::mpsc::{Receiver, TryRecvError};
+
+/// Online solving for streaming city arrivals (e.g. dispatching).
+///
+/// Cities sent as `(x, y)` on the arrival channel are queued and incorporated
+/// every `Config::online_interval` iterations: each one is patched into the
+/// current best tour by cheapest insertion and the colony keeps refining.
+/// Every incorporation grants another `num_iters` iterations, so the run ends
+/// once the sender is dropped and the colony has had its full budget on the
+/// final set of cities.
+pub struct OnlineSolver<'a> {
+    solver: AcoSolver<'a>,
+    arrivals: Receiver<(f64, f64)>,
+    interval: usize,
+    disconnected: bool,
+}
+
+impl<'a> OnlineSolver<'a> {
+    /// The instance must have node coordinates for arrivals to be inserted.
+    pub fn new(
+        instance: &'a TspInstance,
+        config: &'a Config,
+        arrivals: Receiver<(f64, f64)>,
+    ) -> Self {
+        OnlineSolver {
+            solver: AcoSolver::new(instance, config),
+            arrivals,
+            interval: config.online_interval.max(1),
+            disconnected: false,
+        }
+    }
+
+    pub fn solver(&self) -> &AcoSolver<'a> {
+        &self.solver
+    }
+
+    /// Inserts all queued cities, blocking for the next one if `block` is set
+    /// and nothing is queued. Returns the indices of the inserted nodes.
+    fn incorporate(&mut self, block: bool) -> Result<Vec<usize>, String> {
+        let mut inserted = Vec::new();
+        if block && !self.disconnected {
+            match self.arrivals.recv() {
+                Ok((x, y)) => inserted.push(self.solver.insert_node(x, y)?),
+                Err(_) => self.disconnected = true,
+            }
+        }
+        while !self.disconnected {
+            match self.arrivals.try_recv() {
+                Ok((x, y)) => inserted.push(self.solver.insert_node(x, y)?),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => self.disconnected = true,
+            }
+        }
+        if !inserted.is_empty() {
+            self.solver
+                .extend_iterations(self.solver.config().num_iters);
+        }
+        Ok(inserted)
+    }
+
+    /// Runs one iteration, first incorporating queued arrivals if this is an
+    /// incorporation iteration. Returns the indices of newly inserted nodes.
+    pub fn step(&mut self) -> Result<Vec<usize>, String> {
+        let inserted = if self.solver.iteration().is_multiple_of(self.interval) {
+            self.incorporate(false)?
+        } else {
+            Vec::new()
+        };
+        self.solver.step();
+        Ok(inserted)
+    }
+
+    /// Solves until the arrival channel is closed and the iteration budget of
+    /// the last incorporation is used up. While idle it waits for arrivals.
+    pub fn run(mut self) -> Result<(Vec<usize>, f64), String> {
+        loop {
+            if self.solver.is_finished() {
+                if self.disconnected {
+                    break;
+                }
+                self.incorporate(true)?;
+                continue;
+            }
+            self.step()?;
+        }
+        Ok(self.solver.run())
+    }
+}
@@ -0,0 +1,142 @@
+//! Crossover operators on tours, for genetic algorithms and hybrids that
+//! recombine tours found by the ACO solver. Parents are permutations of the
+//! same nodes; the child is always a valid tour.
+
+use crate::constraints::validate_permutation;
+use rand::Rng;
+use rand::seq::IndexedRandom;
+use std::ops::Range;
+
+fn validate_parents(parent1: &[usize], parent2: &[usize]) -> Result<(), String> {
+    validate_permutation(parent1, parent1.len())
+        .and_then(|()| validate_permutation(parent2, parent1.len()))
+        .map_err(|e| format!("Invalid parent: {}", e))
+}
+
+fn validate_cut(cut: &Range<usize>, n: usize) -> Result<(), String> {
+    if cut.start > cut.end || cut.end > n {
+        return Err(format!("Cut {:?} out of bounds for {} nodes", cut, n));
+    }
+    Ok(())
+}
+
+/// Random non-empty cut of a tour of `n` nodes for [`order_crossover`] and
+/// [`partially_mapped_crossover`]; empty for `n == 0`.
+pub fn random_cut(n: usize, rng: &mut impl Rng) -> Range<usize> {
+    if n == 0 {
+        return 0..0;
+    }
+    let a = rng.random_range(0..n);
+    let b = rng.random_range(0..n);
+    a.min(b)..a.max(b) + 1
+}
+
+/// Order crossover (OX): the child keeps `parent1[cut]` in place and fills
+/// the other positions, starting after the cut and wrapping around, with
+/// the remaining nodes in the order they follow the cut in `parent2`.
+pub fn order_crossover(
+    parent1: &[usize],
+    parent2: &[usize],
+    cut: Range<usize>,
+) -> Result<Vec<usize>, String> {
+    validate_parents(parent1, parent2)?;
+    let n = parent1.len();
+    validate_cut(&cut, n)?;
+    let mut taken = vec![false; n];
+    for &node in &parent1[cut.clone()] {
+        taken[node] = true;
+    }
+    let mut child = parent1.to_vec();
+    let mut fill = (cut.end..n).chain(0..cut.start);
+    for k in 0..n {
+        let node = parent2[(cut.end + k) % n];
+        if !taken[node] {
+            child[fill.next().expect("one free position per remaining node")] = node;
+        }
+    }
+    Ok(child)
+}
+
+/// Partially mapped crossover (PMX): the child keeps `parent1[cut]` in
+/// place and takes the other positions from `parent2`; a node already in
+/// the cut is replaced by following the mapping the cut defines between the
+/// parents until a node outside it is reached.
+pub fn partially_mapped_crossover(
+    parent1: &[usize],
+    parent2: &[usize],
+    cut: Range<usize>,
+) -> Result<Vec<usize>, String> {
+    validate_parents(parent1, parent2)?;
+    let n = parent1.len();
+    validate_cut(&cut, n)?;
+    // Node of parent2 at the position of each node of parent1's cut.
+    let mut mapped = vec![None; n];
+    for position in cut.clone() {
+        mapped[parent1[position]] = Some(parent2[position]);
+    }
+    let mut child = parent1.to_vec();
+    for position in (0..cut.start).chain(cut.end..n) {
+        let mut node = parent2[position];
+        while let Some(next) = mapped[node] {
+            node = next;
+        }
+        child[position] = node;
+    }
+    Ok(child)
+}
+
+/// Edge recombination (ERX): builds the child from the union of both
+/// parents' edges, starting at `parent1[0]` and always moving to the
+/// neighbour with the fewest remaining neighbours (ties broken at random).
+/// When the current node has no unvisited neighbour left, a random
+/// unvisited node follows. Most child edges are inherited from a parent.
+pub fn edge_recombination(
+    parent1: &[usize],
+    parent2: &[usize],
+    rng: &mut impl Rng,
+) -> Result<Vec<usize>, String> {
+    validate_parents(parent1, parent2)?;
+    let n = parent1.len();
+    if n == 0 {
+        return Ok(Vec::new());
+    }
+    let mut neighbours: Vec<Vec<usize>> = vec![Vec::with_capacity(4); n];
+    for parent in [parent1, parent2] {
+        for (&a, &b) in parent.iter().zip(parent.iter().cycle().skip(1)) {
+            if a != b && !neighbours[a].contains(&b) {
+                neighbours[a].push(b);
+                neighbours[b].push(a);
+            }
+        }
+    }
+    let mut visited = vec![false; n];
+    let mut child = Vec::with_capacity(n);
+    let mut current = parent1[0];
+    loop {
+        child.push(current);
+        visited[current] = true;
+        if child.len() == n {
+            break;
+        }
+        // Lists only hold unvisited nodes: each visited one leaves them.
+        let candidates = std::mem::take(&mut neighbours[current]);
+        for &m in &candidates {
+            neighbours[m].retain(|&x| x != current);
+        }
+        let fewest = candidates.iter().map(|&m| neighbours[m].len()).min();
+        let best: Vec<usize> = candidates
+            .into_iter()
+            .filter(|&m| Some(neighbours[m].len()) == fewest)
+            .collect();
+        current = match best.choose(rng) {
+            Some(&next) => next,
+            None => {
+                let unvisited: Vec<usize> = (0..n).filter(|&m| !visited[m]).collect();
+                *unvisited
+                    .choose(rng)
+                    .expect("the child is not complete yet")
+            }
+        };
+    }
+    Ok(child)
+}
@@ -0,0 +1,75 @@
+//! Parquet output of bench and experiment runs (`--parquet`): one row per
+//! run with its result columns followed by one column per setting of the
+//! configuration it ran with, so sweeps load straight into dataframe tools.
+
+use crate::config::Config;
+use crate::provenance::VERSION;
+use crate::results::RunRecord;
+use arrow_array::{ArrayRef, BooleanArray, Float64Array, RecordBatch, StringArray, UInt64Array};
+use arrow_schema::{DataType, Field, Schema};
+use parquet::arrow::ArrowWriter;
+use std::fs::File;
+use std::sync::Arc;
+
+/// Writes `runs`, all made with `config`, to a Parquet file at `path`.
+/// Numeric and boolean settings get typed columns, the others strings.
+pub fn write_parquet(runs: &[RunRecord], config: &Config, path: &str) -> Result<(), String> {
+    let mut fields = vec![
+        Field::new("instance", DataType::Utf8, false),
+        Field::new("instance_hash", DataType::Utf8, false),
+        Field::new("seed", DataType::UInt64, false),
+        Field::new("length", DataType::Float64, false),
+        Field::new("gap", DataType::Float64, true),
+        Field::new("seconds", DataType::Float64, false),
+        Field::new("version", DataType::Utf8, false),
+    ];
+    let mut columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from_iter_values(
+            runs.iter().map(|run| &run.instance),
+        )),
+        Arc::new(StringArray::from_iter_values(
+            runs.iter().map(|run| &run.instance_hash),
+        )),
+        Arc::new(UInt64Array::from_iter_values(
+            runs.iter().map(|run| run.seed),
+        )),
+        Arc::new(Float64Array::from_iter_values(
+            runs.iter().map(|run| run.length),
+        )),
+        Arc::new(runs.iter().map(|run| run.gap).collect::<Float64Array>()),
+        Arc::new(Float64Array::from_iter_values(
+            runs.iter().map(|run| run.seconds),
+        )),
+        Arc::new(StringArray::from_iter_values(runs.iter().map(|_| VERSION))),
+    ];
+    for (key, value) in config.setting_pairs() {
+        let (data_type, column): (DataType, ArrayRef) = if let Ok(number) = value.parse::<f64>() {
+            (
+                DataType::Float64,
+                Arc::new(Float64Array::from(vec![number; runs.len()])),
+            )
+        } else if let Ok(flag) = value.parse::<bool>() {
+            (
+                DataType::Boolean,
+                Arc::new(BooleanArray::from(vec![flag; runs.len()])),
+            )
+        } else {
+            (
+                DataType::Utf8,
+                Arc::new(StringArray::from(vec![value; runs.len()])),
+            )
+        };
+        fields.push(Field::new(key, data_type, false));
+        columns.push(column);
+    }
+
+    let batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)
+        .map_err(|e| format!("Failed to build Parquet rows: {}", e))?;
+    let file = File::create(path).map_err(|e| format!("Failed to create {}: {}", path, e))?;
+    let mut writer = ArrowWriter::try_new(file, batch.schema(), None)
+        .map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    writer
+        .write(&batch)
+        .and_then(|_| writer.close().map(|_| ()))
+        .map_err(|e| format!("Failed to write {}: {}", path, e))
+}
@@ -1,6 +1,13 @@
+use crate::constraints::PrecedenceGraph;
+use crate::distance::{DistanceMatrix, FORBIDDEN_DISTANCE};
+use crate::matrix_import::{RoadMetric, import_matrix_response};
+use crate::memory::StorageMode;
+use crate::node_data::{NodeMetadata, parse_csv_instance};
 use std::f64::consts::PI;
 use std::fs::File as StdFile;
 use std::io::{BufRead, BufReader as StdBufReader};
+use std::path::Path;
+use std::sync::Arc;
 
 #[inline]
 fn to_radians(degrees: f64) -> f64 {
@@ -19,11 +26,6 @@ fn calc_euc_2d_dist(n1: &Node, n2: &Node) -> f64 {
     dist_sq(n1, n2).sqrt()
 }
 
-#[inline]
-fn calc_ceil_2d_dist(n1: &Node, n2: &Node) -> f64 {
-    (dist_sq(n1, n2).sqrt()).ceil()
-}
-
 #[inline]
 fn calc_geo_dist(n1: &Node, n2: &Node) -> f64 {
     const RRR: f64 = 6378.388; // Earth radius in km
@@ -38,8 +40,117 @@ fn calc_geo_dist(n1: &Node, n2: &Node) -> f64 {
     let q2 = (lat1_rad - lat2_rad).cos();
     let q3 = (lat1_rad + lat2_rad).cos();
 
-    let distance = RRR * (0.5 * ((1.0 + q1) * q2 - (1.0 - q1) * q3)).acos() + 1.0;
-    distance
+    RRR * (0.5 * ((1.0 + q1) * q2 - (1.0 - q1) * q3)).acos() + 1.0
+}
+
+/// Distance in meters on the WGS84 ellipsoid between `(latitude, longitude)`
+/// points in decimal degrees (`x` = latitude, `y` = longitude), by Vincenty's
+/// inverse formula. Accurate to well below a millimeter; for the rare nearly
+/// antipodal pairs where the iteration does not converge, the great-circle
+/// distance on the mean-radius sphere is used instead.
+fn calc_geodesic_dist(n1: &Node, n2: &Node) -> f64 {
+    const A: f64 = 6_378_137.0; // WGS84 semi-major axis in meters
+    const F: f64 = 1.0 / 298.257_223_563; // WGS84 flattening
+    const B: f64 = A * (1.0 - F);
+
+    let (lat1, lat2) = (to_radians(n1.x), to_radians(n2.x));
+    let l = to_radians(n2.y - n1.y);
+    if lat1 == lat2 && l == 0.0 {
+        return 0.0;
+    }
+    let u1 = ((1.0 - F) * lat1.tan()).atan();
+    let u2 = ((1.0 - F) * lat2.tan()).atan();
+    let (sin_u1, cos_u1) = u1.sin_cos();
+    let (sin_u2, cos_u2) = u2.sin_cos();
+
+    let mut lambda = l;
+    for _ in 0..200 {
+        let (sin_lambda, cos_lambda) = lambda.sin_cos();
+        let sin_sigma = ((cos_u2 * sin_lambda).powi(2)
+            + (cos_u1 * sin_u2 - sin_u1 * cos_u2 * cos_lambda).powi(2))
+        .sqrt();
+        if sin_sigma == 0.0 {
+            return 0.0; // Coincident points
+        }
+        let cos_sigma = sin_u1 * sin_u2 + cos_u1 * cos_u2 * cos_lambda;
+        let sigma = sin_sigma.atan2(cos_sigma);
+        let sin_alpha = cos_u1 * cos_u2 * sin_lambda / sin_sigma;
+        let cos_sq_alpha = 1.0 - sin_alpha * sin_alpha;
+        // Equatorial lines have cos^2(alpha) = 0.
+        let cos_2sigma_m = if cos_sq_alpha != 0.0 {
+            cos_sigma - 2.0 * sin_u1 * sin_u2 / cos_sq_alpha
+        } else {
+            0.0
+        };
+        let c = F / 16.0 * cos_sq_alpha * (4.0 + F * (4.0 - 3.0 * cos_sq_alpha));
+        let previous = lambda;
+        lambda = l
+            + (1.0 - c)
+                * F
+                * sin_alpha
+                * (sigma
+                    + c * sin_sigma
+                        * (cos_2sigma_m + c * cos_sigma * (-1.0 + 2.0 * cos_2sigma_m.powi(2))));
+        if (lambda - previous).abs() < 1e-12 {
+            let u_sq = cos_sq_alpha * (A * A - B * B) / (B * B);
+            let a =
+                1.0 + u_sq / 16384.0 * (4096.0 + u_sq * (-768.0 + u_sq * (320.0 - 175.0 * u_sq)));
+            let b = u_sq / 1024.0 * (256.0 + u_sq * (-128.0 + u_sq * (74.0 - 47.0 * u_sq)));
+            let delta_sigma = b
+                * sin_sigma
+                * (cos_2sigma_m
+                    + b / 4.0
+                        * (cos_sigma * (-1.0 + 2.0 * cos_2sigma_m.powi(2))
+                            - b / 6.0
+                                * cos_2sigma_m
+                                * (-3.0 + 4.0 * sin_sigma.powi(2))
+                                * (-3.0 + 4.0 * cos_2sigma_m.powi(2))));
+            return B * a * (sigma - delta_sigma);
+        }
+    }
+
+    const MEAN_RADIUS: f64 = (2.0 * A + B) / 3.0;
+    let central_angle = (lat1.sin() * lat2.sin() + lat1.cos() * lat2.cos() * l.cos())
+        .clamp(-1.0, 1.0)
+        .acos();
+    MEAN_RADIUS * central_angle
+}
+
+#[inline]
+fn calc_man_2d_dist(n1: &Node, n2: &Node) -> f64 {
+    (n1.x - n2.x).abs() + (n1.y - n2.y).abs()
+}
+
+#[inline]
+fn calc_max_2d_dist(n1: &Node, n2: &Node) -> f64 {
+    (n1.x - n2.x).abs().max((n1.y - n2.y).abs())
+}
+
+#[inline]
+fn calc_euc_3d_dist(n1: &Node, n2: &Node) -> f64 {
+    let dz = n1.z - n2.z;
+    (dist_sq(n1, n2) + dz * dz).sqrt()
+}
+
+#[inline]
+fn calc_man_3d_dist(n1: &Node, n2: &Node) -> f64 {
+    (n1.x - n2.x).abs() + (n1.y - n2.y).abs() + (n1.z - n2.z).abs()
+}
+
+/// Time for a diffractometer to turn from `n1` to `n2`, with `x`, `y` and
+/// `z` the phi, chi and two-theta angles in degrees and `speeds` their
+/// motor speeds. The motors run in parallel, so the slowest one decides;
+/// phi wraps around. Scaled by 100 like the TSPLIB reference code.
+#[inline]
+fn calc_xray_dist(n1: &Node, n2: &Node, speeds: [f64; 3]) -> f64 {
+    let phi = (n1.x - n2.x).abs();
+    let phi = phi.min((phi - 360.0).abs());
+    let chi = (n1.y - n2.y).abs();
+    let two_theta = (n1.z - n2.z).abs();
+    100.0
+        * (phi / speeds[0])
+            .max(chi / speeds[1])
+            .max(two_theta / speeds[2])
 }
 
 #[inline]
@@ -51,16 +162,190 @@ fn calc_att_dist(n1: &Node, n2: &Node) -> f64 {
     if tij < rij { tij + 1.0 } else { tij }
 }
 
+/// A caller supplied metric for `EDGE_WEIGHT_TYPE: SPECIAL` instances,
+/// registered with [`ParseOptions::special_distance`]. Two instances are
+/// equal only if they share the very same function.
+#[derive(Clone)]
+pub struct SpecialDistance(pub Arc<SpecialDistanceFn>);
+
+pub type SpecialDistanceFn = dyn Fn(&Node, &Node) -> f64 + Send + Sync;
+
+impl SpecialDistance {
+    pub fn new(distance: impl Fn(&Node, &Node) -> f64 + Send + Sync + 'static) -> Self {
+        SpecialDistance(Arc::new(distance))
+    }
+}
+
+impl PartialEq for SpecialDistance {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl std::fmt::Debug for SpecialDistance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SpecialDistance(..)")
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum EdgeWeightType {
-    Euc2D,    // berlin52
-    Ceil2D,   // dsj1000
-    Geo,      // ulysses16
-    Att,      // att48
+    Euc2D, // berlin52
+    Euc3D,
+    Man2D,
+    Max2D,
+    Man3D,
+    /// Diffractometer rotation times of crystallography instances: XRAY1
+    /// with equally fast motors, XRAY2 with TSPLIB's slower chi and
+    /// two-theta motors.
+    Xray1,
+    Xray2,
+    Ceil2D, // dsj1000
+    /// Great-circle distance in km, `x` = longitude and `y` = latitude.
+    Geo, // ulysses16
+    Att,    // att48
+    /// WGS84 ellipsoid distance in meters, `x` = latitude and `y` =
+    /// longitude in decimal degrees. `GEODESIC` in TSPLIB headers. Note the
+    /// axes are swapped with respect to [`EdgeWeightType::Geo`], which reads
+    /// `x` as longitude, so the same coordinates differ under the two.
+    Geodesic,
+    /// `SPECIAL` in TSPLIB headers, measured by a caller supplied function.
+    Special(SpecialDistance),
     Explicit, // gr17, bayg29, bays29
     Unknown(String),
 }
 
+impl EdgeWeightType {
+    /// Raw distance between two nodes for coordinate based metrics, before
+    /// any [`DistancePolicy`] is applied. `None` for explicit or unknown types.
+    pub fn distance(&self, n1: &Node, n2: &Node) -> Option<f64> {
+        match self {
+            EdgeWeightType::Euc2D | EdgeWeightType::Ceil2D => Some(calc_euc_2d_dist(n1, n2)),
+            EdgeWeightType::Euc3D => Some(calc_euc_3d_dist(n1, n2)),
+            EdgeWeightType::Man2D => Some(calc_man_2d_dist(n1, n2)),
+            EdgeWeightType::Max2D => Some(calc_max_2d_dist(n1, n2)),
+            EdgeWeightType::Man3D => Some(calc_man_3d_dist(n1, n2)),
+            EdgeWeightType::Xray1 => Some(calc_xray_dist(n1, n2, [1.0, 1.0, 1.0])),
+            EdgeWeightType::Xray2 => Some(calc_xray_dist(n1, n2, [1.25, 1.5, 1.15])),
+            EdgeWeightType::Geo => Some(calc_geo_dist(n1, n2)),
+            EdgeWeightType::Att => Some(calc_att_dist(n1, n2)),
+            EdgeWeightType::Geodesic => Some(calc_geodesic_dist(n1, n2)),
+            EdgeWeightType::Special(distance) => Some((distance.0)(n1, n2)),
+            EdgeWeightType::Explicit | EdgeWeightType::Unknown(_) => None,
+        }
+    }
+
+    /// `EDGE_WEIGHT_TYPE` keyword of this metric in TSPLIB headers.
+    pub fn keyword(&self) -> &str {
+        match self {
+            EdgeWeightType::Euc2D => "EUC_2D",
+            EdgeWeightType::Euc3D => "EUC_3D",
+            EdgeWeightType::Man2D => "MAN_2D",
+            EdgeWeightType::Max2D => "MAX_2D",
+            EdgeWeightType::Man3D => "MAN_3D",
+            EdgeWeightType::Xray1 => "XRAY1",
+            EdgeWeightType::Xray2 => "XRAY2",
+            EdgeWeightType::Ceil2D => "CEIL_2D",
+            EdgeWeightType::Geo => "GEO",
+            EdgeWeightType::Att => "ATT",
+            EdgeWeightType::Geodesic => "GEODESIC",
+            EdgeWeightType::Special(_) => "SPECIAL",
+            EdgeWeightType::Explicit => "EXPLICIT",
+            EdgeWeightType::Unknown(keyword) => keyword,
+        }
+    }
+
+    /// Whether distances use the `z` coordinate, i.e. nodes are given as
+    /// THREED_COORDS.
+    pub fn is_three_dimensional(&self) -> bool {
+        matches!(
+            self,
+            EdgeWeightType::Euc3D
+                | EdgeWeightType::Man3D
+                | EdgeWeightType::Xray1
+                | EdgeWeightType::Xray2
+        )
+    }
+
+    /// Policy used when none is requested explicitly: CEIL_2D rounds up, all
+    /// other metrics keep exact distances.
+    pub fn default_policy(&self) -> DistancePolicy {
+        match self {
+            EdgeWeightType::Ceil2D => DistancePolicy::Ceil,
+            _ => DistancePolicy::Exact,
+        }
+    }
+
+    /// Converts a raw distance to the integer TSPLIB defines for this metric:
+    /// nearest integer for EUC_2D, ceiling for CEIL_2D, truncation for GEO.
+    /// ATT and EXPLICIT weights are already integral.
+    pub fn tsplib_round(&self, dist: f64) -> i64 {
+        match self {
+            EdgeWeightType::Ceil2D => dist.ceil() as i64,
+            EdgeWeightType::Geo => dist.trunc() as i64,
+            _ => dist.round() as i64,
+        }
+    }
+}
+
+/// Rounding applied to every distance when the matrix is built, uniformly
+/// for all metric types (including EXPLICIT weights).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DistancePolicy {
+    Exact,
+    /// TSPLIB `nint`, i.e. `(int)(d + 0.5)`.
+    Nint,
+    Ceil,
+    Floor,
+    Scale(f64),
+}
+
+impl DistancePolicy {
+    #[inline]
+    pub fn apply(&self, dist: f64) -> f64 {
+        match self {
+            DistancePolicy::Exact => dist,
+            DistancePolicy::Nint => (dist + 0.5).floor(),
+            DistancePolicy::Ceil => dist.ceil(),
+            DistancePolicy::Floor => dist.floor(),
+            DistancePolicy::Scale(factor) => dist * factor,
+        }
+    }
+}
+
+/// Options controlling how a TSPLIB file is turned into an instance.
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// Fail as soon as the header declares more nodes than this, before any
+    /// large allocation happens.
+    pub max_dimension: usize,
+    /// Rounding for all distances; `None` uses the metric's default policy.
+    pub distance_policy: Option<DistancePolicy>,
+    /// Compute coordinate based distances on demand instead of storing them.
+    pub lazy_distances: bool,
+    /// Weight taken from routing API matrices (`.json` input).
+    pub matrix_metric: RoadMetric,
+    /// Memory budget in bytes; picks dense, triangular or lazy storage for
+    /// TSPLIB files (see [`StorageMode::select`]).
+    pub max_memory: Option<usize>,
+    /// Metric of `EDGE_WEIGHT_TYPE: SPECIAL` files, which fail to parse
+    /// without one.
+    pub special_distance: Option<SpecialDistance>,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            max_dimension: usize::MAX,
+            distance_policy: None,
+            lazy_distances: false,
+            matrix_metric: RoadMetric::Distance,
+            max_memory: None,
+            special_distance: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum EdgeWeightFormat {
     Function,
@@ -69,16 +354,45 @@ pub enum EdgeWeightFormat {
     LowerRow,
     LowerDiagRow,
     UpperDiagRow,
+    UpperCol,
+    LowerCol,
+    UpperDiagCol,
+    LowerDiagCol,
     Unknown(String),
 }
 
+impl EdgeWeightFormat {
+    /// `EDGE_WEIGHT_FORMAT` keyword of this format in TSPLIB headers.
+    pub fn keyword(&self) -> &str {
+        match self {
+            EdgeWeightFormat::Function => "FUNCTION",
+            EdgeWeightFormat::FullMatrix => "FULL_MATRIX",
+            EdgeWeightFormat::UpperRow => "UPPER_ROW",
+            EdgeWeightFormat::LowerRow => "LOWER_ROW",
+            EdgeWeightFormat::LowerDiagRow => "LOWER_DIAG_ROW",
+            EdgeWeightFormat::UpperDiagRow => "UPPER_DIAG_ROW",
+            EdgeWeightFormat::UpperCol => "UPPER_COL",
+            EdgeWeightFormat::LowerCol => "LOWER_COL",
+            EdgeWeightFormat::UpperDiagCol => "UPPER_DIAG_COL",
+            EdgeWeightFormat::LowerDiagCol => "LOWER_DIAG_COL",
+            EdgeWeightFormat::Unknown(s) => s,
+        }
+    }
+}
+
+/// A city. For geographic metrics `x` and `y` are degrees: longitude and
+/// latitude under [`EdgeWeightType::Geo`], but latitude and longitude under
+/// [`EdgeWeightType::Geodesic`].
 #[derive(Debug, Clone)]
 pub struct Node {
     pub id: usize,
     pub x: f64,
     pub y: f64,
+    /// Third coordinate of THREED_COORDS instances, 0 for planar ones.
+    pub z: f64,
 }
 
+#[derive(Clone)]
 pub struct TspInstance {
     pub name: String,
     pub tsp_type: String,
@@ -86,28 +400,233 @@ pub struct TspInstance {
     pub dimension: usize,
     pub edge_weight_type: EdgeWeightType,
     pub edge_weight_format: Option<EdgeWeightFormat>,
+    pub distance_policy: DistancePolicy,
     pub node_coords: Option<Vec<Node>>,
-    pub dist_matrix: Vec<Vec<f64>>,
+    pub dist_matrix: DistanceMatrix,
+    /// Names, addresses etc. of the nodes, carried into the exports.
+    pub node_metadata: Option<NodeMetadata>,
+    /// Nodes that have to be visited before others, from the `-1` entries
+    /// of SOP files. Those arcs are forbidden in `dist_matrix`.
+    pub precedences: Option<PrecedenceGraph>,
+    /// Vehicle capacity of CVRP instances (CAPACITY).
+    pub capacity: Option<f64>,
+    /// Demand of every node from the DEMAND_SECTION, by node index.
+    pub demands: Option<Vec<f64>>,
+    /// Depot node indices from the DEPOT_SECTION.
+    pub depots: Option<Vec<usize>>,
+    /// Edges every tour has to contain, from the FIXED_EDGES_SECTION, as
+    /// node index pairs. The ACO solver enforces them.
+    pub fixed_edges: Option<Vec<(usize, usize)>>,
 }
 
 impl TspInstance {
-    #[allow(dead_code)]
-    pub fn get_dist(&self, node1_idx: usize, node2_idx: usize) -> f64 {
+    /// Distance between two nodes, checking both indices; hot loops with
+    /// known valid indices read `dist_matrix` directly.
+    pub fn get_dist(&self, node1_idx: usize, node2_idx: usize) -> Result<f64, String> {
         if node1_idx >= self.dimension || node2_idx >= self.dimension {
-            panic!(
+            return Err(format!(
                 "Node index out of bounds ({} or {} for dimension {})",
                 node1_idx, node2_idx, self.dimension
-            );
+            ));
         }
-        // Assumes dist_matrix is always square and valid if dimension > 0
-        unsafe {
-            *self
-                .dist_matrix
-                .get_unchecked(node1_idx)
-                .get_unchecked(node2_idx)
+        Ok(self.dist_matrix.get(node1_idx, node2_idx))
+    }
+
+    /// Returns the instance with `delta` added to the cost of each listed
+    /// `(i, j)` edge, e.g. to model tolls, closures or preferred roads. The
+    /// adjustments are layered over the existing distances without copying
+    /// them, and are seen by everything reading distances (heuristic, tour
+    /// evaluation, local search).
+    pub fn with_edge_adjustments(
+        mut self,
+        adjustments: impl IntoIterator<Item = (usize, usize, f64)>,
+    ) -> Result<Self, String> {
+        let dist_matrix = std::mem::replace(&mut self.dist_matrix, DistanceMatrix::full(0));
+        self.dist_matrix = dist_matrix.with_adjustments(adjustments)?;
+        Ok(self)
+    }
+
+    /// Moves the node with id `id` to `(x, y)` and returns its index. Only
+    /// the node's row and column of distances are recomputed, O(n) instead
+    /// of rebuilding the matrix; edge adjustments stay on top. Structures
+    /// built from the old distances (heuristic tables, prepared instances,
+    /// content hashes) are stale afterwards, [`AcoSolver::move_node`]
+    /// rebuilds those of a running colony.
+    ///
+    /// [`AcoSolver::move_node`]: crate::AcoSolver::move_node
+    pub fn update_node_coord(&mut self, id: usize, x: f64, y: f64) -> Result<usize, String> {
+        let metric = self.edge_weight_type.clone();
+        let policy = self.distance_policy;
+        let coords = self
+            .node_coords
+            .as_mut()
+            .ok_or("Moving nodes requires node coordinates")?;
+        let k = coords
+            .iter()
+            .position(|node| node.id == id)
+            .ok_or_else(|| format!("No node with id {}", id))?;
+        let node = Node {
+            id,
+            x,
+            y,
+            z: coords[k].z,
+        };
+        let distances = coords
+            .iter()
+            .map(|other| metric.distance(other, &node).map(|dist| policy.apply(dist)))
+            .collect::<Option<Vec<f64>>>()
+            .ok_or_else(|| format!("Cannot compute distances for {:?}", metric))?;
+        self.dist_matrix.update_node(k, &node, &distances);
+        coords[k] = node;
+        Ok(k)
+    }
+
+    /// Instance restricted to `nodes` (indices into this instance), with its
+    /// own dense distances. Node `k` of the result is `nodes[k]` here.
+    pub fn sub_instance(&self, name: String, nodes: &[usize]) -> TspInstance {
+        let m = nodes.len();
+        let mut dist_matrix = if self.dist_matrix.is_symmetric_storage() {
+            DistanceMatrix::triangular(m)
+        } else {
+            DistanceMatrix::full(m)
+        };
+        for (a, &i) in nodes.iter().enumerate() {
+            for (b, &j) in nodes.iter().enumerate() {
+                if a != b {
+                    dist_matrix.set(a, b, self.dist_matrix.get(i, j));
+                }
+            }
         }
-        // Safer version
-        // self.dist_matrix[node1_idx][node2_idx]
+        TspInstance {
+            name,
+            tsp_type: self.tsp_type.clone(),
+            comment: String::new(),
+            dimension: m,
+            edge_weight_type: self.edge_weight_type.clone(),
+            edge_weight_format: self.edge_weight_format.clone(),
+            distance_policy: self.distance_policy,
+            node_coords: self
+                .node_coords
+                .as_ref()
+                .map(|coords| nodes.iter().map(|&i| coords[i].clone()).collect()),
+            dist_matrix,
+            node_metadata: self
+                .node_metadata
+                .as_ref()
+                .map(|metadata| metadata.reordered(nodes)),
+            precedences: self
+                .precedences
+                .as_ref()
+                .map(|precedences| precedences.restricted(nodes)),
+            capacity: self.capacity,
+            demands: self
+                .demands
+                .as_ref()
+                .map(|demands| nodes.iter().map(|&i| demands[i]).collect()),
+            depots: self.depots.as_ref().map(|depots| {
+                nodes
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, i)| depots.contains(i))
+                    .map(|(k, _)| k)
+                    .collect()
+            }),
+            fixed_edges: self.fixed_edges.as_ref().map(|edges| {
+                let index_of = |node| nodes.iter().position(|&i| i == node);
+                edges
+                    .iter()
+                    .filter_map(|&(a, b)| index_of(a).zip(index_of(b)))
+                    .collect()
+            }),
+        }
+    }
+
+    /// Instance over `coords` (ids 1..=n) with distances from the coordinate
+    /// metric `metric`, e.g. [`EdgeWeightType::Geodesic`] for `(latitude,
+    /// longitude)` pairs. `policy` defaults to the metric's own rounding.
+    pub fn from_coordinates(
+        name: &str,
+        coords: &[(f64, f64)],
+        metric: EdgeWeightType,
+        policy: Option<DistancePolicy>,
+    ) -> Result<TspInstance, String> {
+        let nodes: Vec<Node> = coords
+            .iter()
+            .enumerate()
+            .map(|(i, &(x, y))| Node {
+                id: i + 1,
+                x,
+                y,
+                z: 0.0,
+            })
+            .collect();
+        if nodes.len() > 1 && metric.distance(&nodes[0], &nodes[1]).is_none() {
+            return Err(format!("{:?} is not a coordinate metric", metric));
+        }
+        let distance_policy = policy.unwrap_or_else(|| metric.default_policy());
+        let n = nodes.len();
+        // SPECIAL functions may be asymmetric; compact them afterwards.
+        let special = matches!(metric, EdgeWeightType::Special(_));
+        let mut dist_matrix = if special {
+            DistanceMatrix::full(n)
+        } else {
+            DistanceMatrix::triangular(n)
+        };
+        for i in 0..n {
+            let others = if special { 0..n } else { (i + 1)..n };
+            for j in others.filter(|&j| j != i) {
+                let dist = metric.distance(&nodes[i], &nodes[j]).unwrap_or(0.0);
+                dist_matrix.set(i, j, distance_policy.apply(dist));
+            }
+        }
+        let dist_matrix = dist_matrix.into_compact();
+        Ok(TspInstance {
+            name: name.to_string(),
+            tsp_type: "TSP".to_string(),
+            comment: String::new(),
+            dimension: n,
+            edge_weight_type: metric,
+            edge_weight_format: None,
+            distance_policy,
+            node_coords: Some(nodes),
+            dist_matrix,
+            node_metadata: None,
+            precedences: None,
+            capacity: None,
+            demands: None,
+            depots: None,
+            fixed_edges: None,
+        })
+    }
+
+    /// Length of the closed tour visiting `tour` in order.
+    ///
+    /// # Panics
+    ///
+    /// If a node index is not below `dimension`; see
+    /// [`TspInstance::try_tour_length`] for tours from untrusted input.
+    pub fn tour_length(&self, tour: &[usize]) -> f64 {
+        self.try_tour_length(tour)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like [`TspInstance::tour_length`], returning an error for node indices
+    /// out of bounds.
+    pub fn try_tour_length(&self, tour: &[usize]) -> Result<f64, String> {
+        if let Some(&k) = tour.iter().find(|&&k| k >= self.dimension) {
+            return Err(format!(
+                "Tour node {} out of bounds for dimension {}",
+                k, self.dimension
+            ));
+        }
+        if tour.len() < 2 {
+            return Ok(0.0);
+        }
+        Ok(tour
+            .iter()
+            .zip(tour.iter().cycle().skip(1))
+            .map(|(&a, &b)| self.dist_matrix.get(a, b))
+            .sum())
     }
 }
 
@@ -116,31 +635,140 @@ enum ParsingSection {
     Header,
     NodeCoordSection,
     EdgeWeightSection,
+    EdgeDataSection,
+    DemandSection,
+    DepotSection,
+    FixedEdgesSection,
+}
+
+/// Error for an instance over `limit` nodes, with the memory the default
+/// (triangular) storage would need for it.
+pub(crate) fn dimension_limit_error(dimension: usize, limit: usize) -> String {
+    format!(
+        "DIMENSION {} exceeds the limit of {} nodes; solving it would need about {:.1} MiB for the distance, heuristic and pheromone matrices. Raise --max-dimension to try anyway.",
+        dimension,
+        limit,
+        StorageMode::Triangular.estimated_bytes(dimension) as f64 / (1024.0 * 1024.0)
+    )
+}
+
+/// Appends the whitespace separated numbers of `line` to `weights`. Plain
+/// integers, the bulk of TSPLIB weight sections, are converted straight
+/// from the bytes; anything else goes through `str::parse`.
+fn scan_weights(line: &[u8], weights: &mut Vec<f64>) -> Result<(), String> {
+    for token in line
+        .split(u8::is_ascii_whitespace)
+        .filter(|token| !token.is_empty())
+    {
+        let weight = match scan_integer(token) {
+            Some(weight) => weight,
+            None => {
+                let text = String::from_utf8_lossy(token);
+                text.parse::<f64>()
+                    .map_err(|e| format!("Invalid edge weight number: '{}', error: {}", text, e))?
+            }
+        };
+        weights.push(weight);
+    }
+    Ok(())
+}
+
+// Optionally signed integers of up to 15 digits, which f64 holds exactly.
+fn scan_integer(token: &[u8]) -> Option<f64> {
+    let (negative, digits) = match token {
+        [b'-', rest @ ..] => (true, rest),
+        [b'+', rest @ ..] => (false, rest),
+        _ => (false, token),
+    };
+    if digits.is_empty() || digits.len() > 15 {
+        return None;
+    }
+    let mut value = 0u64;
+    for &digit in digits {
+        if !digit.is_ascii_digit() {
+            return None;
+        }
+        value = value * 10 + u64::from(digit - b'0');
+    }
+    let value = value as f64;
+    Some(if negative { -value } else { value })
 }
 
 pub fn parse_tsp_file(file_path: &str) -> Result<TspInstance, String> {
+    parse_tsp_file_with_options(file_path, &ParseOptions::default())
+}
+
+pub fn parse_tsp_file_with_options(
+    file_path: &str,
+    options: &ParseOptions,
+) -> Result<TspInstance, String> {
+    if file_path.to_lowercase().ends_with(".npy") {
+        return parse_npy_matrix(file_path, options);
+    }
+    if file_path.to_lowercase().ends_with(".json") {
+        return import_matrix_response(file_path, None, options.matrix_metric, options);
+    }
+    if file_path.to_lowercase().ends_with(".csv") {
+        let text = std::fs::read_to_string(file_path)
+            .map_err(|e| format!("Failed to open file {}: {}", file_path, e))?;
+        let name = Path::new(file_path)
+            .file_stem()
+            .map_or(String::new(), |stem| stem.to_string_lossy().into_owned());
+        return parse_csv_instance(&name, &text, options);
+    }
     let file = StdFile::open(file_path)
         .map_err(|e| format!("Failed to open file {}: {}", file_path, e))?;
-    let reader = StdBufReader::new(file);
+    parse_tsp_reader(StdBufReader::new(file), options)
+}
 
+/// Parses TSPLIB text from `reader`, e.g. a byte slice. Malformed input is
+/// reported as an error, never a panic.
+pub fn parse_tsp_reader(
+    mut reader: impl BufRead,
+    options: &ParseOptions,
+) -> Result<TspInstance, String> {
     let mut name = String::new();
     let mut tsp_type = String::new();
     let mut comment = String::new();
     let mut dimension = 0;
     let mut edge_weight_type_str = String::new();
     let mut edge_weight_format_str: Option<String> = None;
+    let mut node_coord_type_str = String::new();
     let mut node_coords_vec: Vec<Node> = Vec::new();
     let mut explicit_weights_data: Vec<f64> = Vec::new();
+    let mut edge_data_format_str = String::new();
+    let mut edge_data: Vec<f64> = Vec::new();
+    let mut capacity = None;
+    let mut demand_data: Vec<(usize, f64)> = Vec::new();
+    let mut depot_data: Vec<f64> = Vec::new();
+    let mut fixed_edge_data: Vec<f64> = Vec::new();
 
     let mut current_section = ParsingSection::Header;
     let mut current_line_num = 0;
 
-    for line_result in reader.lines() {
+    // One buffer for all lines; weight section lines are scanned as bytes
+    // without building strings.
+    let mut buffer = Vec::new();
+    loop {
+        buffer.clear();
         current_line_num += 1;
-        let line = line_result
+        let read = reader
+            .read_until(b'\n', &mut buffer)
+            .map_err(|e| format!("Error reading line {}: {}", current_line_num, e))?;
+        if read == 0 {
+            break;
+        }
+        let bytes = buffer.trim_ascii();
+        if current_section == ParsingSection::EdgeWeightSection
+            && bytes.first().is_some_and(|b| !b.is_ascii_uppercase())
+        {
+            scan_weights(bytes, &mut explicit_weights_data)
+                .map_err(|e| format!("L{}: {}", current_line_num, e))?;
+            continue;
+        }
+        let line = std::str::from_utf8(bytes)
             .map_err(|e| format!("Error reading line {}: {}", current_line_num, e))?
-            .trim()
-            .to_string();
+            .trim();
 
         if line == "EOF" {
             break;
@@ -155,6 +783,18 @@ pub fn parse_tsp_file(file_path: &str) -> Result<TspInstance, String> {
         } else if line == "EDGE_WEIGHT_SECTION" {
             current_section = ParsingSection::EdgeWeightSection;
             continue;
+        } else if line == "EDGE_DATA_SECTION" {
+            current_section = ParsingSection::EdgeDataSection;
+            continue;
+        } else if line == "DEMAND_SECTION" {
+            current_section = ParsingSection::DemandSection;
+            continue;
+        } else if line == "DEPOT_SECTION" {
+            current_section = ParsingSection::DepotSection;
+            continue;
+        } else if line == "FIXED_EDGES_SECTION" {
+            current_section = ParsingSection::FixedEdgesSection;
+            continue;
         } else if line == "DISPLAY_DATA_SECTION" || line == "TOUR_SECTION" {
             if current_section == ParsingSection::NodeCoordSection
                 && node_coords_vec.len() != dimension
@@ -194,9 +834,25 @@ pub fn parse_tsp_file(file_path: &str) -> Result<TspInstance, String> {
                                     current_line_num, e, line
                                 )
                             })?;
+                            if dimension > options.max_dimension {
+                                return Err(dimension_limit_error(
+                                    dimension,
+                                    options.max_dimension,
+                                ));
+                            }
                         }
                         "EDGE_WEIGHT_TYPE" => edge_weight_type_str = value.to_string(),
                         "EDGE_WEIGHT_FORMAT" => edge_weight_format_str = Some(value.to_string()),
+                        "NODE_COORD_TYPE" => node_coord_type_str = value.to_uppercase(),
+                        "EDGE_DATA_FORMAT" => edge_data_format_str = value.to_uppercase(),
+                        "CAPACITY" => {
+                            capacity = Some(value.parse::<f64>().map_err(|e| {
+                                format!(
+                                    "L{}: Invalid capacity: {} on line '{}'",
+                                    current_line_num, e, line
+                                )
+                            })?);
+                        }
                         _ => {} // Ignore other keywords
                     }
                 }
@@ -209,6 +865,17 @@ pub fn parse_tsp_file(file_path: &str) -> Result<TspInstance, String> {
                     ));
                 }
                 let parts: Vec<&str> = line.split_whitespace().collect();
+                let three_d = node_coord_type_str == "THREED_COORDS"
+                    || matches!(
+                        edge_weight_type_str.to_uppercase().as_str(),
+                        "EUC_3D" | "MAN_3D" | "XRAY1" | "XRAY2"
+                    );
+                if three_d && parts.len() < 4 {
+                    return Err(format!(
+                        "L{}: Malformed node coord line (expected id x y z): {}",
+                        current_line_num, line
+                    ));
+                }
                 if parts.len() >= 3 {
                     let id = parts[0].parse::<usize>().map_err(|e| {
                         format!(
@@ -228,7 +895,17 @@ pub fn parse_tsp_file(file_path: &str) -> Result<TspInstance, String> {
                             current_line_num, e, line
                         )
                     })?;
-                    node_coords_vec.push(Node { id, x, y });
+                    let z = if three_d {
+                        parts[3].parse::<f64>().map_err(|e| {
+                            format!(
+                                "L{}: Invalid z coord: {} on line '{}'",
+                                current_line_num, e, line
+                            )
+                        })?
+                    } else {
+                        0.0
+                    };
+                    node_coords_vec.push(Node { id, x, y, z });
                 } else {
                     return Err(format!(
                         "L{}: Malformed node coord line (expected id x y): {}",
@@ -237,17 +914,34 @@ pub fn parse_tsp_file(file_path: &str) -> Result<TspInstance, String> {
                 }
             }
             ParsingSection::EdgeWeightSection => {
-                let nums_str: Vec<&str> = line.split_whitespace().collect();
-                for s_num in nums_str {
-                    if !s_num.is_empty() {
-                        explicit_weights_data.push(s_num.parse::<f64>().map_err(|e| {
-                            format!(
-                                "L{}: Invalid edge weight number: '{}', error: {}",
-                                current_line_num, s_num, e
-                            )
-                        })?);
-                    }
-                }
+                scan_weights(line.as_bytes(), &mut explicit_weights_data)
+                    .map_err(|e| format!("L{}: {}", current_line_num, e))?;
+            }
+            ParsingSection::EdgeDataSection => {
+                scan_weights(line.as_bytes(), &mut edge_data)
+                    .map_err(|e| format!("L{}: {}", current_line_num, e))?;
+            }
+            ParsingSection::DemandSection => {
+                let parts: Vec<&str> = line.split_whitespace().collect();
+                let parsed = match parts[..] {
+                    [id, demand] => id.parse::<usize>().ok().zip(demand.parse::<f64>().ok()),
+                    _ => None,
+                };
+                let entry = parsed.ok_or_else(|| {
+                    format!(
+                        "L{}: Malformed demand line (expected id demand): {}",
+                        current_line_num, line
+                    )
+                })?;
+                demand_data.push(entry);
+            }
+            ParsingSection::DepotSection => {
+                scan_weights(line.as_bytes(), &mut depot_data)
+                    .map_err(|e| format!("L{}: {}", current_line_num, e))?;
+            }
+            ParsingSection::FixedEdgesSection => {
+                scan_weights(line.as_bytes(), &mut fixed_edge_data)
+                    .map_err(|e| format!("L{}: {}", current_line_num, e))?;
             }
         }
     }
@@ -256,12 +950,42 @@ pub fn parse_tsp_file(file_path: &str) -> Result<TspInstance, String> {
         return Err("DIMENSION not found or is zero.".to_string());
     }
 
+    // HCP graphs have no weights: they become an EXPLICIT instance whose
+    // graph edges cost 0 and all other pairs 1.
+    if tsp_type.eq_ignore_ascii_case("HCP") || !edge_data.is_empty() {
+        if !edge_weight_type_str.is_empty() {
+            return Err(format!(
+                "EDGE_DATA_SECTION with EDGE_WEIGHT_TYPE {} is not supported",
+                edge_weight_type_str
+            ));
+        }
+        explicit_weights_data = edge_data_weights(dimension, &edge_data_format_str, &edge_data)?;
+        edge_weight_type_str = "EXPLICIT".to_string();
+        edge_weight_format_str = Some("UPPER_ROW".to_string());
+    }
+
     let ewt = match edge_weight_type_str.to_uppercase().as_str() {
         "EUC_2D" => EdgeWeightType::Euc2D,
+        "EUC_3D" => EdgeWeightType::Euc3D,
+        "MAN_2D" => EdgeWeightType::Man2D,
+        "MAX_2D" => EdgeWeightType::Max2D,
+        "MAN_3D" => EdgeWeightType::Man3D,
+        "XRAY1" => EdgeWeightType::Xray1,
+        "XRAY2" => EdgeWeightType::Xray2,
         "GEO" => EdgeWeightType::Geo,
         "ATT" => EdgeWeightType::Att,
         "EXPLICIT" => EdgeWeightType::Explicit,
         "CEIL_2D" => EdgeWeightType::Ceil2D,
+        "GEODESIC" => EdgeWeightType::Geodesic,
+        "SPECIAL" => match &options.special_distance {
+            Some(distance) => EdgeWeightType::Special(distance.clone()),
+            None => {
+                return Err(
+                    "EDGE_WEIGHT_TYPE SPECIAL needs a distance function (ParseOptions::special_distance)"
+                        .to_string(),
+                );
+            }
+        },
         s => EdgeWeightType::Unknown(s.to_string()),
     };
 
@@ -270,8 +994,13 @@ pub fn parse_tsp_file(file_path: &str) -> Result<TspInstance, String> {
             match edge_weight_format_str.as_deref().map(|s| s.to_uppercase()) {
                 Some(s) if s == "FULL_MATRIX" => Some(EdgeWeightFormat::FullMatrix),
                 Some(s) if s == "UPPER_ROW" => Some(EdgeWeightFormat::UpperRow),
+                Some(s) if s == "LOWER_ROW" => Some(EdgeWeightFormat::LowerRow),
                 Some(s) if s == "LOWER_DIAG_ROW" => Some(EdgeWeightFormat::LowerDiagRow),
-                // TODO: Add other formats like
+                Some(s) if s == "UPPER_DIAG_ROW" => Some(EdgeWeightFormat::UpperDiagRow),
+                Some(s) if s == "UPPER_COL" => Some(EdgeWeightFormat::UpperCol),
+                Some(s) if s == "LOWER_COL" => Some(EdgeWeightFormat::LowerCol),
+                Some(s) if s == "UPPER_DIAG_COL" => Some(EdgeWeightFormat::UpperDiagCol),
+                Some(s) if s == "LOWER_DIAG_COL" => Some(EdgeWeightFormat::LowerDiagCol),
                 Some(s) => Some(EdgeWeightFormat::Unknown(s)),
                 None => return Err("EDGE_WEIGHT_FORMAT missing for EXPLICIT type.".to_string()),
             }
@@ -280,12 +1009,39 @@ pub fn parse_tsp_file(file_path: &str) -> Result<TspInstance, String> {
             .clone()
             .map(EdgeWeightFormat::Unknown),
     };
+    // Triangular formats would mirror the weights, losing the asymmetry.
+    let sop = tsp_type.eq_ignore_ascii_case("SOP");
+    if (sop || tsp_type.eq_ignore_ascii_case("ATSP"))
+        && ewt == EdgeWeightType::Explicit
+        && ewf != Some(EdgeWeightFormat::FullMatrix)
+    {
+        return Err(format!(
+            "TYPE {} needs EDGE_WEIGHT_FORMAT FULL_MATRIX, got {:?}",
+            tsp_type, edge_weight_format_str
+        ));
+    }
+    // SOP files repeat the dimension as the first number of the section.
+    if sop
+        && dimension.checked_mul(dimension).map(|cells| cells + 1)
+            == Some(explicit_weights_data.len())
+        && explicit_weights_data.first() == Some(&(dimension as f64))
+    {
+        explicit_weights_data.remove(0);
+    }
 
     match ewt {
         EdgeWeightType::Euc2D
+        | EdgeWeightType::Euc3D
+        | EdgeWeightType::Man2D
+        | EdgeWeightType::Max2D
+        | EdgeWeightType::Man3D
+        | EdgeWeightType::Xray1
+        | EdgeWeightType::Xray2
         | EdgeWeightType::Geo
         | EdgeWeightType::Att
-        | EdgeWeightType::Ceil2D => {
+        | EdgeWeightType::Ceil2D
+        | EdgeWeightType::Geodesic
+        | EdgeWeightType::Special(_) => {
             if node_coords_vec.len() != dimension {
                 return Err(format!(
                     "Mismatch: DIMENSION ({}) vs found node coordinates ({}). Type: {:?}",
@@ -302,23 +1058,128 @@ pub fn parse_tsp_file(file_path: &str) -> Result<TspInstance, String> {
             }
         }
         EdgeWeightType::Explicit => {
-            if ewf.is_none() || matches!(ewf, Some(EdgeWeightFormat::Unknown(_))) {
-                return Err(format!(
-                    "Unsupported or missing EDGE_WEIGHT_FORMAT for EXPLICIT type: {:?}",
-                    edge_weight_format_str
-                ));
+            // Counted before anything is allocated, so a huge DIMENSION
+            // over a short section fails instead of exhausting memory.
+            let (format, expected_weights) = match &ewf {
+                Some(format @ EdgeWeightFormat::FullMatrix) => {
+                    (format.keyword(), dimension.checked_mul(dimension))
+                }
+                Some(
+                    format @ (EdgeWeightFormat::UpperRow
+                    | EdgeWeightFormat::LowerRow
+                    | EdgeWeightFormat::UpperCol
+                    | EdgeWeightFormat::LowerCol),
+                ) => (
+                    format.keyword(),
+                    dimension.checked_mul(dimension - 1).map(|w| w / 2),
+                ),
+                Some(
+                    format @ (EdgeWeightFormat::LowerDiagRow
+                    | EdgeWeightFormat::UpperDiagRow
+                    | EdgeWeightFormat::UpperDiagCol
+                    | EdgeWeightFormat::LowerDiagCol),
+                ) => (
+                    format.keyword(),
+                    dimension
+                        .checked_add(1)
+                        .and_then(|d| d.checked_mul(dimension))
+                        .map(|w| w / 2),
+                ),
+                _ => {
+                    return Err(format!(
+                        "Unsupported or missing EDGE_WEIGHT_FORMAT for EXPLICIT type: {:?}",
+                        edge_weight_format_str
+                    ));
+                }
+            };
+            match expected_weights {
+                Some(expected) if expected == explicit_weights_data.len() => {}
+                Some(expected) => {
+                    return Err(format!(
+                        "EXPLICIT {}: Expected {} weights, got {}.",
+                        format,
+                        expected,
+                        explicit_weights_data.len()
+                    ));
+                }
+                None => {
+                    return Err(format!(
+                        "EXPLICIT {}: DIMENSION {} is too large.",
+                        format, dimension
+                    ));
+                }
             }
         }
         EdgeWeightType::Unknown(ref s) => return Err(format!("Unknown edge weight type: {}", s)),
     }
 
-    let mut dist_matrix = vec![vec![0.0; dimension]; dimension];
+    let precedences = if sop && ewt == EdgeWeightType::Explicit {
+        Some(sop_precedences(dimension, &mut explicit_weights_data)?)
+    } else {
+        None
+    };
 
-    match ewt {
+    let distance_policy = options
+        .distance_policy
+        .unwrap_or_else(|| ewt.default_policy());
+    if distance_policy != DistancePolicy::Exact {
+        for weight in &mut explicit_weights_data {
+            *weight = distance_policy.apply(*weight);
+        }
+    }
+
+    let storage = match options.max_memory {
+        Some(budget) => {
+            let coordinates = !matches!(
+                ewt,
+                EdgeWeightType::Explicit | EdgeWeightType::Special(_) | EdgeWeightType::Unknown(_)
+            );
+            Some(StorageMode::select(dimension, budget, coordinates)?)
+        }
+        None => None,
+    };
+    let lazy_distances =
+        options.lazy_distances || matches!(storage, Some(StorageMode::Lazy { .. }));
+
+    // Each branch allocates only the storage it fills: symmetric weights
+    // go into a triangle, or straight into a full matrix in dense mode.
+    let dense = storage == Some(StorageMode::Dense);
+    let symmetric_matrix = || {
+        if dense {
+            DistanceMatrix::full(dimension)
+        } else {
+            DistanceMatrix::triangular(dimension)
+        }
+    };
+    let compact = |full: DistanceMatrix| if dense { full } else { full.into_compact() };
+
+    let dist_matrix = match ewt {
         EdgeWeightType::Euc2D
+        | EdgeWeightType::Euc3D
+        | EdgeWeightType::Man2D
+        | EdgeWeightType::Max2D
+        | EdgeWeightType::Man3D
+        | EdgeWeightType::Xray1
+        | EdgeWeightType::Xray2
         | EdgeWeightType::Ceil2D
         | EdgeWeightType::Geo
-        | EdgeWeightType::Att => {
+        | EdgeWeightType::Att
+        | EdgeWeightType::Geodesic
+            if lazy_distances =>
+        {
+            DistanceMatrix::lazy(node_coords_vec.clone(), ewt.clone(), distance_policy)?
+        }
+        EdgeWeightType::Euc2D
+        | EdgeWeightType::Euc3D
+        | EdgeWeightType::Man2D
+        | EdgeWeightType::Max2D
+        | EdgeWeightType::Man3D
+        | EdgeWeightType::Xray1
+        | EdgeWeightType::Xray2
+        | EdgeWeightType::Ceil2D
+        | EdgeWeightType::Geo
+        | EdgeWeightType::Att
+        | EdgeWeightType::Geodesic => {
             let coords = &node_coords_vec;
             if coords.len() != dimension {
                 return Err(format!(
@@ -328,82 +1189,92 @@ pub fn parse_tsp_file(file_path: &str) -> Result<TspInstance, String> {
                     ewt
                 ));
             }
+            let mut dist_matrix = symmetric_matrix();
             for i in 0..dimension {
-                for j in 0..dimension {
-                    if i == j {
-                        dist_matrix[i][j] = 0.0;
-                        continue;
-                    }
+                for j in (i + 1)..dimension {
                     let n1 = &coords[i];
                     let n2 = &coords[j];
-                    dist_matrix[i][j] = match ewt {
-                        EdgeWeightType::Euc2D => calc_euc_2d_dist(n1, n2),
-                        EdgeWeightType::Ceil2D => calc_ceil_2d_dist(n1, n2),
-                        EdgeWeightType::Geo => calc_geo_dist(n1, n2),
-                        EdgeWeightType::Att => calc_att_dist(n1, n2),
-                        _ => unreachable!(),
-                    };
+                    let dist = ewt
+                        .distance(n1, n2)
+                        .ok_or_else(|| format!("{:?} is not a coordinate metric", ewt))?;
+                    dist_matrix.set_symmetric(i, j, distance_policy.apply(dist));
                 }
             }
+            dist_matrix
         }
-        EdgeWeightType::Explicit => match ewf.as_ref().unwrap() {
-            EdgeWeightFormat::FullMatrix => {
-                if explicit_weights_data.len() != dimension * dimension {
-                    return Err(format!(
-                        "EXPLICIT FULL_MATRIX: Expected {} weights ({}*{}_DIM), got {}.",
-                        dimension * dimension,
-                        dimension,
-                        dimension,
-                        explicit_weights_data.len()
-                    ));
+        // May be asymmetric, so both directions are computed and stored
+        // densely unless they turn out equal; never lazy.
+        EdgeWeightType::Special(ref special) => {
+            let mut full = DistanceMatrix::full(dimension);
+            for (i, n1) in node_coords_vec.iter().enumerate() {
+                for (j, n2) in node_coords_vec.iter().enumerate() {
+                    if i != j {
+                        full.set(i, j, distance_policy.apply((special.0)(n1, n2)));
+                    }
                 }
+            }
+            compact(full)
+        }
+        EdgeWeightType::Explicit => match ewf.as_ref() {
+            None => return Err("EDGE_WEIGHT_FORMAT missing for EXPLICIT type.".to_string()),
+            Some(EdgeWeightFormat::FullMatrix) => {
+                let mut full = DistanceMatrix::full(dimension);
                 let mut k = 0;
                 for i in 0..dimension {
                     for j in 0..dimension {
-                        dist_matrix[i][j] = explicit_weights_data[k];
+                        full.set(i, j, explicit_weights_data[k]);
                         k += 1;
                     }
                 }
+                compact(full)
             }
-            EdgeWeightFormat::UpperRow => {
-                let expected_weights = dimension * (dimension - 1) / 2;
-                if explicit_weights_data.len() != expected_weights {
-                    return Err(format!(
-                        "EXPLICIT UPPER_ROW: Expected {} weights, got {}.",
-                        expected_weights,
-                        explicit_weights_data.len()
-                    ));
-                }
+            // The matrix is symmetric, so a column-wise triangle lists the
+            // same weights in the same order as the opposite row-wise one.
+            Some(EdgeWeightFormat::UpperRow | EdgeWeightFormat::LowerCol) => {
+                let mut dist_matrix = symmetric_matrix();
                 let mut k = 0;
                 for i in 0..dimension {
                     for j in (i + 1)..dimension {
-                        dist_matrix[i][j] = explicit_weights_data[k];
-                        dist_matrix[j][i] = explicit_weights_data[k];
+                        dist_matrix.set_symmetric(i, j, explicit_weights_data[k]);
                         k += 1;
                     }
                 }
+                dist_matrix
             }
-            EdgeWeightFormat::LowerDiagRow => {
-                let expected_weights = dimension * (dimension + 1) / 2;
-                if explicit_weights_data.len() != expected_weights {
-                    return Err(format!(
-                        "EXPLICIT LOWER_DIAG_ROW: Expected {} weights, got {}.",
-                        expected_weights,
-                        explicit_weights_data.len()
-                    ));
+            Some(EdgeWeightFormat::LowerRow | EdgeWeightFormat::UpperCol) => {
+                let mut dist_matrix = symmetric_matrix();
+                let mut k = 0;
+                for i in 0..dimension {
+                    for j in 0..i {
+                        dist_matrix.set_symmetric(i, j, explicit_weights_data[k]);
+                        k += 1;
+                    }
                 }
+                dist_matrix
+            }
+            Some(EdgeWeightFormat::LowerDiagRow | EdgeWeightFormat::UpperDiagCol) => {
+                let mut dist_matrix = symmetric_matrix();
                 let mut k = 0;
                 for i in 0..dimension {
                     for j in 0..=i {
-                        dist_matrix[i][j] = explicit_weights_data[k];
-                        if i != j {
-                            dist_matrix[j][i] = explicit_weights_data[k];
-                        }
+                        dist_matrix.set_symmetric(i, j, explicit_weights_data[k]);
                         k += 1;
                     }
                 }
+                dist_matrix
             }
-            EdgeWeightFormat::Unknown(s) => {
+            Some(EdgeWeightFormat::UpperDiagRow | EdgeWeightFormat::LowerDiagCol) => {
+                let mut dist_matrix = symmetric_matrix();
+                let mut k = 0;
+                for i in 0..dimension {
+                    for j in i..dimension {
+                        dist_matrix.set_symmetric(i, j, explicit_weights_data[k]);
+                        k += 1;
+                    }
+                }
+                dist_matrix
+            }
+            Some(EdgeWeightFormat::Unknown(s)) => {
                 return Err(format!("Unsupported EXPLICIT format: {}", s));
             }
             _ => return Err("Unhandled EXPLICIT format during matrix population.".to_string()),
@@ -414,7 +1285,69 @@ pub fn parse_tsp_file(file_path: &str) -> Result<TspInstance, String> {
                 s
             ));
         }
-    }
+    };
+
+    // Demand and depot ids are node ids, as in the coordinate section.
+    let index_of: std::collections::HashMap<usize, usize> = match &node_coords_vec[..] {
+        [] => (0..dimension).map(|idx| (idx + 1, idx)).collect(),
+        nodes => nodes
+            .iter()
+            .enumerate()
+            .map(|(idx, node)| (node.id, idx))
+            .collect(),
+    };
+    let demands = if demand_data.is_empty() {
+        None
+    } else {
+        let mut demands = vec![None; dimension];
+        for (id, demand) in demand_data {
+            let idx = *index_of
+                .get(&id)
+                .ok_or_else(|| format!("DEMAND_SECTION: node id {} does not exist", id))?;
+            if demands[idx].replace(demand).is_some() {
+                return Err(format!("DEMAND_SECTION: node id {} listed twice", id));
+            }
+        }
+        let demands: Option<Vec<f64>> = demands.into_iter().collect();
+        Some(demands.ok_or("DEMAND_SECTION: not every node has a demand")?)
+    };
+    let node_index = |section: &str, id: f64| {
+        index_of
+            .get(&(id as usize))
+            .copied()
+            .filter(|_| id.fract() == 0.0 && id >= 0.0)
+            .ok_or_else(|| format!("{}: node id {} does not exist", section, id))
+    };
+    let depots = if depot_data.is_empty() {
+        None
+    } else {
+        let ids = depot_data.iter().take_while(|&&id| id != -1.0);
+        let depots = ids
+            .map(|&id| node_index("DEPOT_SECTION", id))
+            .collect::<Result<Vec<usize>, String>>()?;
+        Some(depots)
+    };
+    let fixed_edges = if fixed_edge_data.is_empty() {
+        None
+    } else {
+        let ids: Vec<f64> = fixed_edge_data
+            .into_iter()
+            .take_while(|&id| id != -1.0)
+            .collect();
+        if !ids.len().is_multiple_of(2) {
+            return Err("FIXED_EDGES_SECTION ends in the middle of an edge".to_string());
+        }
+        let edges = ids
+            .chunks(2)
+            .map(|pair| {
+                Ok((
+                    node_index("FIXED_EDGES_SECTION", pair[0])?,
+                    node_index("FIXED_EDGES_SECTION", pair[1])?,
+                ))
+            })
+            .collect::<Result<Vec<(usize, usize)>, String>>()?;
+        Some(edges)
+    };
 
     Ok(TspInstance {
         name,
@@ -423,11 +1356,288 @@ pub fn parse_tsp_file(file_path: &str) -> Result<TspInstance, String> {
         dimension,
         edge_weight_type: ewt,
         edge_weight_format: ewf,
+        distance_policy,
         node_coords: if node_coords_vec.is_empty() {
             None
         } else {
             Some(node_coords_vec)
         },
         dist_matrix,
+        node_metadata: None,
+        precedences,
+        capacity,
+        demands,
+        depots,
+        fixed_edges,
     })
 }
+
+/// UPPER_ROW weights of the graph in an EDGE_DATA_SECTION: 0 for the edges,
+/// 1 for all other pairs. A tour of length 0 is a Hamiltonian cycle; in
+/// general the length counts the pairs the tour needs outside the graph.
+fn edge_data_weights(n: usize, format: &str, data: &[f64]) -> Result<Vec<f64>, String> {
+    let node = |value: f64| {
+        if value.fract() == 0.0 && value >= 1.0 && value <= n as f64 {
+            Ok(value as usize - 1)
+        } else {
+            Err(format!(
+                "EDGE_DATA_SECTION: invalid node id {} (DIMENSION {})",
+                value, n
+            ))
+        }
+    };
+    let mut edges = Vec::new();
+    match format {
+        "EDGE_LIST" => {
+            let mut values = data.iter().copied().take_while(|&value| value != -1.0);
+            while let Some(a) = values.next() {
+                let b = values
+                    .next()
+                    .ok_or("EDGE_DATA_SECTION: EDGE_LIST ends in the middle of an edge")?;
+                edges.push((node(a)?, node(b)?));
+            }
+        }
+        // Lines of a node, its neighbours and -1, closed by another -1.
+        "ADJ_LIST" => {
+            let mut lists = data.split(|&value| value == -1.0);
+            while let Some(list) = lists.next().filter(|list| !list.is_empty()) {
+                let a = node(list[0])?;
+                for &b in &list[1..] {
+                    edges.push((a, node(b)?));
+                }
+            }
+        }
+        "" => return Err("EDGE_DATA_FORMAT missing for EDGE_DATA_SECTION".to_string()),
+        s => return Err(format!("Unsupported EDGE_DATA_FORMAT: {}", s)),
+    }
+    // Row i of the upper triangle starts after the rows of all lower nodes.
+    let row_start = |i: usize| i * (2 * n - i - 1) / 2;
+    let mut weights = vec![1.0; n * (n - 1) / 2];
+    for (a, b) in edges {
+        let (i, j) = (a.min(b), a.max(b));
+        if i != j {
+            weights[row_start(i) + j - i - 1] = 0.0;
+        }
+    }
+    Ok(weights)
+}
+
+/// Takes the precedences out of a SOP weight matrix (row-major, `n * n`):
+/// `-1` at `(i, j)` means `j` has to come before `i`, so the arc `i -> j`
+/// becomes forbidden. The path from the source node 0 to the sink node
+/// `n - 1` is closed into a tour by a free arc back to the source.
+fn sop_precedences(n: usize, weights: &mut [f64]) -> Result<PrecedenceGraph, String> {
+    let mut pairs = Vec::new();
+    for i in 0..n {
+        for j in 0..n {
+            if i != j && weights[i * n + j] == -1.0 {
+                pairs.push((j, i));
+                weights[i * n + j] = FORBIDDEN_DISTANCE;
+            }
+        }
+    }
+    if n > 1 {
+        weights[(n - 1) * n] = 0.0;
+    }
+    PrecedenceGraph::new(n, &pairs)
+}
+
+/// Reads a TSPLIB `.tour` file for `instance` and returns the tour as
+/// 0-based indices. Node ids are matched against the instance's coordinate
+/// ids (or taken as 1-based indices for explicit instances); the tour must
+/// visit every node exactly once.
+pub fn parse_tour_file(file_path: &str, instance: &TspInstance) -> Result<Vec<usize>, String> {
+    let file = StdFile::open(file_path)
+        .map_err(|e| format!("Failed to open tour file {}: {}", file_path, e))?;
+    parse_tour_reader(StdBufReader::new(file), instance)
+}
+
+/// [`parse_tour_file`] reading TSPLIB tour text from `reader`.
+pub fn parse_tour_reader(
+    reader: impl BufRead,
+    instance: &TspInstance,
+) -> Result<Vec<usize>, String> {
+    let index_of: std::collections::HashMap<usize, usize> = match &instance.node_coords {
+        Some(nodes) => nodes
+            .iter()
+            .enumerate()
+            .map(|(idx, node)| (node.id, idx))
+            .collect(),
+        None => (0..instance.dimension).map(|idx| (idx + 1, idx)).collect(),
+    };
+
+    let mut tour = Vec::with_capacity(instance.dimension);
+    let mut in_section = false;
+    'lines: for (line_num, line) in reader.lines().enumerate() {
+        let line = line.map_err(|e| format!("Error reading tour line: {}", e))?;
+        let line = line.trim();
+        if !in_section {
+            if let Some((key, value)) = line.split_once(':')
+                && key.trim() == "DIMENSION"
+            {
+                let dimension: usize = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("L{}: Invalid tour DIMENSION", line_num + 1))?;
+                if dimension != instance.dimension {
+                    return Err(format!(
+                        "Tour DIMENSION {} does not match the instance ({})",
+                        dimension, instance.dimension
+                    ));
+                }
+            }
+            in_section = line == "TOUR_SECTION";
+            continue;
+        }
+        for token in line.split_whitespace() {
+            if token == "-1" || token == "EOF" {
+                break 'lines;
+            }
+            let id: usize = token
+                .parse()
+                .map_err(|_| format!("L{}: Invalid node id in tour: {}", line_num + 1, token))?;
+            let idx = *index_of
+                .get(&id)
+                .ok_or_else(|| format!("Tour node id {} does not exist in the instance", id))?;
+            tour.push(idx);
+        }
+    }
+    if !in_section {
+        return Err("TOUR_SECTION not found in tour file".to_string());
+    }
+    crate::constraints::validate_permutation(&tour, instance.dimension)?;
+    Ok(tour)
+}
+
+/// Reads a square NumPy `.npy` distance matrix (e.g. written by
+/// `numpy.save`) as an EXPLICIT instance named after the file. Supports
+/// little-endian `f8`, `f4`, `i8` and `i4` arrays in C or Fortran order.
+pub fn parse_npy_matrix(file_path: &str, options: &ParseOptions) -> Result<TspInstance, String> {
+    let bytes = std::fs::read(file_path)
+        .map_err(|e| format!("Failed to open file {}: {}", file_path, e))?;
+    if bytes.len() < 10 || &bytes[..6] != b"\x93NUMPY" {
+        return Err(format!("{} is not a NumPy .npy file", file_path));
+    }
+    let (header_len, header_start) = match bytes[6] {
+        1 => (u16::from_le_bytes([bytes[8], bytes[9]]) as usize, 10),
+        2 | 3 if bytes.len() >= 12 => (
+            u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]) as usize,
+            12,
+        ),
+        version => return Err(format!("Unsupported .npy version {}", version)),
+    };
+    let data_start = header_start + header_len;
+    let header = bytes
+        .get(header_start..data_start)
+        .and_then(|h| std::str::from_utf8(h).ok())
+        .ok_or("Truncated or invalid .npy header")?;
+
+    let descr = npy_header_value(header, "descr")
+        .ok_or("Missing 'descr' in .npy header")?
+        .trim_matches(|c| c == '\'' || c == '"');
+    let fortran_order = npy_header_value(header, "fortran_order") == Some("True");
+    let shape: Vec<usize> = npy_header_value(header, "shape")
+        .ok_or("Missing 'shape' in .npy header")?
+        .trim_matches(|c| c == '(' || c == ')')
+        .split(',')
+        .map(str::trim)
+        .filter(|dim| !dim.is_empty())
+        .map(|dim| {
+            dim.parse()
+                .map_err(|_| format!("Invalid .npy shape entry: {}", dim))
+        })
+        .collect::<Result<_, _>>()?;
+    let dimension = match shape.as_slice() {
+        [rows, cols] if rows == cols => *rows,
+        _ => {
+            return Err(format!(
+                "Expected a square 2-D matrix, got shape {:?}",
+                shape
+            ));
+        }
+    };
+    if dimension == 0 {
+        return Err("DIMENSION not found or is zero.".to_string());
+    }
+    if dimension > options.max_dimension {
+        return Err(dimension_limit_error(dimension, options.max_dimension));
+    }
+
+    let (width, decode): (usize, fn(&[u8]) -> f64) = match descr {
+        // Chunks always have the dtype's width; NaN only guards the types.
+        "<f8" => (8, |b| b.try_into().map_or(f64::NAN, f64::from_le_bytes)),
+        "<f4" => (4, |b| {
+            b.try_into()
+                .map_or(f64::NAN, |b| f32::from_le_bytes(b) as f64)
+        }),
+        "<i8" => (8, |b| {
+            b.try_into()
+                .map_or(f64::NAN, |b| i64::from_le_bytes(b) as f64)
+        }),
+        "<i4" => (4, |b| {
+            b.try_into()
+                .map_or(f64::NAN, |b| i32::from_le_bytes(b) as f64)
+        }),
+        other => return Err(format!("Unsupported .npy dtype: {}", other)),
+    };
+    let data = &bytes[data_start..];
+    let expected_bytes = dimension
+        .checked_mul(dimension)
+        .and_then(|cells| cells.checked_mul(width))
+        .ok_or_else(|| format!("Matrix shape {:?} is too large", shape))?;
+    if data.len() != expected_bytes {
+        return Err(format!(
+            "Expected {} bytes of matrix data, found {}",
+            expected_bytes,
+            data.len()
+        ));
+    }
+
+    let distance_policy = options.distance_policy.unwrap_or(DistancePolicy::Exact);
+    let mut full = DistanceMatrix::full(dimension);
+    for (k, chunk) in data.chunks_exact(width).enumerate() {
+        let (row, col) = (k / dimension, k % dimension);
+        let (i, j) = if fortran_order {
+            (col, row)
+        } else {
+            (row, col)
+        };
+        full.set(i, j, distance_policy.apply(decode(chunk)));
+    }
+
+    let name = std::path::Path::new(file_path)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("npy")
+        .to_string();
+    Ok(TspInstance {
+        name,
+        tsp_type: "TSP".to_string(),
+        comment: "Imported from a NumPy distance matrix".to_string(),
+        dimension,
+        edge_weight_type: EdgeWeightType::Explicit,
+        edge_weight_format: Some(EdgeWeightFormat::FullMatrix),
+        distance_policy,
+        node_coords: None,
+        dist_matrix: full.into_compact(),
+        node_metadata: None,
+        precedences: None,
+        capacity: None,
+        demands: None,
+        depots: None,
+        fixed_edges: None,
+    })
+}
+
+/// Raw value of `key` in a .npy header dict such as
+/// `{'descr': '<f8', 'fortran_order': False, 'shape': (3, 3), }`.
+fn npy_header_value<'h>(header: &'h str, key: &str) -> Option<&'h str> {
+    let start = header.find(&format!("'{}'", key))? + key.len() + 2;
+    let rest = header[start..].trim_start().strip_prefix(':')?.trim_start();
+    let end = if rest.starts_with('(') {
+        rest.find(')')? + 1
+    } else {
+        rest.find(',').unwrap_or(rest.len())
+    };
+    Some(rest[..end].trim())
+}
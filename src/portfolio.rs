@@ -0,0 +1,184 @@
+//! A "just solve it" entry point: picks a backend and its parameters from
+//! the features of the instance instead of asking the user. Tiny instances
+//! are solved exactly, medium ones with ACO plus local search and huge ones
+//! by ordering the nodes along a space-filling curve and improving that
+//! tour with POPMUSIC. All stages share one wall clock budget.
+
+use crate::config::Config;
+use crate::construction::space_filling_curve_order;
+use crate::local_search::{LocalSearch, OrOpt, Popmusic, TwoOpt};
+use crate::parser::{EdgeWeightType, TspInstance};
+use crate::solver::{BRUTE_FORCE_MAX_DIMENSION, solve_brute_force};
+use crate::stopping::{NoImprovementFor, TimeLimit};
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Budget of a portfolio run when `Config::time_budget` is not set.
+pub const DEFAULT_TIME_BUDGET: Duration = Duration::from_secs(10);
+
+/// Largest instance the portfolio still gives to the ant colony; its
+/// pheromone matrix alone needs `n²` entries.
+pub const PORTFOLIO_ACO_LIMIT: usize = 5_000;
+
+/// Memory budget the portfolio parses instances with unless
+/// `Config::max_memory` is set; larger matrices are computed lazily.
+pub const PORTFOLIO_MEMORY_BUDGET: usize = 1 << 30;
+
+// Iterations without a better tour after which ACO stops before the budget.
+const PATIENCE: usize = 500;
+
+/// What the portfolio looks at when choosing a backend.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstanceFeatures {
+    pub dimension: usize,
+    pub metric: EdgeWeightType,
+    pub symmetric: bool,
+    /// Node coordinates for every node, required by the space-filling curve.
+    pub coordinates: bool,
+}
+
+impl InstanceFeatures {
+    pub fn of(instance: &TspInstance) -> Self {
+        let matrix = &instance.dist_matrix;
+        InstanceFeatures {
+            dimension: instance.dimension,
+            metric: instance.edge_weight_type.clone(),
+            symmetric: matrix.is_symmetric_storage() || matrix.is_symmetric(),
+            coordinates: instance
+                .node_coords
+                .as_ref()
+                .is_some_and(|nodes| nodes.len() == instance.dimension),
+        }
+    }
+}
+
+/// Backend chosen by the portfolio.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PortfolioStrategy {
+    /// Brute force, at most [`BRUTE_FORCE_MAX_DIMENSION`] nodes.
+    Exact,
+    /// ACO with 2-opt on the global best, Or-opt for asymmetric instances
+    /// where reversing a segment changes its length.
+    AcoLocalSearch,
+    /// Hilbert curve order improved with POPMUSIC.
+    SpaceFillingCurve,
+}
+
+impl PortfolioStrategy {
+    pub fn name(self) -> &'static str {
+        match self {
+            PortfolioStrategy::Exact => "portfolio:exact",
+            PortfolioStrategy::AcoLocalSearch => "portfolio:aco",
+            PortfolioStrategy::SpaceFillingCurve => "portfolio:sfc+popmusic",
+        }
+    }
+}
+
+/// A backend together with the configuration it runs with.
+#[derive(Debug, Clone)]
+pub struct PortfolioPlan {
+    pub features: InstanceFeatures,
+    pub strategy: PortfolioStrategy,
+    /// `config` with the ant colony parameters the portfolio picked: ants,
+    /// candidate lists, local search and stopping at the budget.
+    pub config: Config,
+    pub budget: Duration,
+}
+
+impl PortfolioPlan {
+    /// Chooses how to solve `instance`. Settings the portfolio does not
+    /// pick (seed, alpha, beta, evaporation, ...) are taken from `config`;
+    /// a local search pipeline or candidate list size set there wins over
+    /// the portfolio's own.
+    pub fn new(instance: &TspInstance, config: &Config) -> Self {
+        let features = InstanceFeatures::of(instance);
+        let budget = config.time_budget.unwrap_or(DEFAULT_TIME_BUDGET);
+        let n = features.dimension;
+        let strategy = if n <= BRUTE_FORCE_MAX_DIMENSION {
+            PortfolioStrategy::Exact
+        } else if n > PORTFOLIO_ACO_LIMIT && features.coordinates {
+            PortfolioStrategy::SpaceFillingCurve
+        } else {
+            PortfolioStrategy::AcoLocalSearch
+        };
+
+        let mut config = config.clone();
+        if strategy == PortfolioStrategy::AcoLocalSearch {
+            config.num_ants = n.clamp(10, 50);
+            config.num_iters = usize::MAX;
+            if config.candidate_list_size == 0 && n > 500 {
+                config.candidate_list_size = 20;
+            }
+            if config.local_search.is_empty() {
+                let local_search: Arc<dyn LocalSearch> = if features.symmetric {
+                    Arc::new(TwoOpt)
+                } else {
+                    Arc::new(OrOpt)
+                };
+                config.local_search = vec![local_search];
+            }
+            config.stopping.push(Arc::new(TimeLimit(budget)));
+            config.stopping.push(Arc::new(NoImprovementFor(PATIENCE)));
+        }
+        PortfolioPlan {
+            features,
+            strategy,
+            config,
+            budget,
+        }
+    }
+
+    /// Runs the exact or space-filling curve strategy; the ant colony is
+    /// run by the caller with `self.config`, which knows how to report it.
+    pub fn solve_without_aco(&self, instance: &TspInstance) -> Result<(Vec<usize>, f64), String> {
+        let start = Instant::now();
+        match self.strategy {
+            PortfolioStrategy::Exact => solve_brute_force(instance),
+            PortfolioStrategy::SpaceFillingCurve => {
+                let nodes = instance
+                    .node_coords
+                    .as_ref()
+                    .ok_or("The space-filling curve requires node coordinates")?;
+                let mut tour = space_filling_curve_order(nodes);
+                // POPMUSIC cannot be interrupted; skip it if the curve
+                // already used up the budget.
+                let length = if start.elapsed() < self.budget {
+                    Popmusic::default().improve(&mut tour, instance)
+                } else {
+                    instance.tour_length(&tour)
+                };
+                Ok((tour, length))
+            }
+            PortfolioStrategy::AcoLocalSearch => {
+                Err("The portfolio's ACO strategy runs through AcoSolver".to_string())
+            }
+        }
+    }
+}
+
+impl fmt::Display for PortfolioPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let method = match self.strategy {
+            PortfolioStrategy::Exact => "exact search".to_string(),
+            PortfolioStrategy::AcoLocalSearch => format!(
+                "ACO with {} ants and {:?}",
+                self.config.num_ants, self.config.local_search
+            ),
+            PortfolioStrategy::SpaceFillingCurve => "space-filling curve + POPMUSIC".to_string(),
+        };
+        write!(
+            f,
+            "{} for {} nodes ({} {}), {:.1} s budget",
+            method,
+            self.features.dimension,
+            if self.features.symmetric {
+                "symmetric"
+            } else {
+                "asymmetric"
+            },
+            self.features.metric.keyword(),
+            self.budget.as_secs_f64()
+        )
+    }
+}
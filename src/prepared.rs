@@ -0,0 +1,65 @@
+//! An instance together with the structures each ACO run derives from it:
+//! the heuristic matrix or, with candidate lists, the nearest-neighbour
+//! lists and their heuristic values. Building them is O(n²), so repeated
+//! solves of one instance (tuning, sensitivity analysis, benchmark seeds,
+//! distributed restarts) prepare it once and share the result.
+
+use crate::config::Config;
+use crate::parser::TspInstance;
+use crate::solver::{HeuristicStrategy, HeuristicTable};
+use std::sync::{Arc, Mutex};
+
+// Heuristic, candidate list size and spatial pruning a table was built for.
+type CachedTable = (HeuristicStrategy, usize, bool, Arc<HeuristicTable>);
+
+pub struct PreparedInstance<'a> {
+    instance: &'a TspInstance,
+    // Built on first use, one per setting.
+    heuristics: Mutex<Vec<CachedTable>>,
+}
+
+impl<'a> PreparedInstance<'a> {
+    pub fn new(instance: &'a TspInstance) -> Self {
+        PreparedInstance {
+            instance,
+            heuristics: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn instance(&self) -> &'a TspInstance {
+        self.instance
+    }
+
+    /// Heuristic tables built so far.
+    pub fn cached_tables(&self) -> usize {
+        self.heuristics
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .len()
+    }
+
+    /// The heuristic table for `config`'s heuristic, candidate list size
+    /// and spatial pruning, built on the first request. Concurrent requests wait for the
+    /// first one instead of building their own.
+    pub(crate) fn heuristic(&self, config: &Config) -> Arc<HeuristicTable> {
+        let mut heuristics = self.heuristics.lock().unwrap_or_else(|e| e.into_inner());
+        let cached = heuristics
+            .iter()
+            .find(|(heuristic, candidates, pruned, _)| {
+                heuristic.same_as(&config.heuristic)
+                    && *candidates == config.candidate_list_size
+                    && *pruned == config.spatial_pruning
+            });
+        if let Some((_, _, _, table)) = cached {
+            return Arc::clone(table);
+        }
+        let table = Arc::new(HeuristicTable::build(self.instance, config));
+        heuristics.push((
+            config.heuristic.clone(),
+            config.candidate_list_size,
+            config.spatial_pruning,
+            Arc::clone(&table),
+        ));
+        table
+    }
+}
@@ -0,0 +1,150 @@
+//! Where a result came from: a content hash of the instance, the crate
+//! version and the settings of the run, embedded in solutions, checkpoints
+//! and benchmark tables so a result can be traced back to its input.
+
+use crate::config::Config;
+use crate::distance::DistanceMatrix;
+use crate::json::JsonValue;
+use crate::parser::{EdgeWeightType, TspInstance};
+use std::fmt;
+
+/// Version of this crate, recorded with every result.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Provenance {
+    /// [`TspInstance::content_hash`] as 16 hex digits.
+    pub instance_hash: String,
+    pub version: String,
+    /// [`Config::settings`] of the run.
+    pub config: String,
+    pub seed: Option<u64>,
+}
+
+impl Provenance {
+    pub fn new(instance: &TspInstance, config: &Config) -> Self {
+        Provenance {
+            instance_hash: instance.content_hash_hex(),
+            version: VERSION.to_string(),
+            config: config.settings(),
+            seed: config.seed,
+        }
+    }
+
+    pub(crate) fn to_json_value(&self) -> JsonValue {
+        JsonValue::Object(vec![
+            (
+                "instance_hash".to_string(),
+                JsonValue::String(self.instance_hash.clone()),
+            ),
+            (
+                "version".to_string(),
+                JsonValue::String(self.version.clone()),
+            ),
+            ("config".to_string(), JsonValue::String(self.config.clone())),
+            (
+                "seed".to_string(),
+                self.seed
+                    .map_or(JsonValue::Null, |seed| JsonValue::String(seed.to_string())),
+            ),
+        ])
+    }
+
+    pub(crate) fn from_json_value(value: &JsonValue) -> Result<Provenance, String> {
+        let text = |key: &str| {
+            value
+                .get(key)
+                .and_then(JsonValue::as_str)
+                .map(str::to_string)
+                .ok_or_else(|| format!("Provenance is missing '{}'", key))
+        };
+        let seed = match value.get("seed") {
+            None | Some(JsonValue::Null) => None,
+            Some(seed) => Some(
+                seed.as_str()
+                    .and_then(|s| s.parse().ok())
+                    .ok_or("Provenance 'seed' is not a number string")?,
+            ),
+        };
+        Ok(Provenance {
+            instance_hash: text("instance_hash")?,
+            version: text("version")?,
+            config: text("config")?,
+            seed,
+        })
+    }
+}
+
+impl fmt::Display for Provenance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "instance {}, tsp-solver {}, seed {}",
+            self.instance_hash,
+            self.version,
+            self.seed
+                .map_or("none".to_string(), |seed| seed.to_string())
+        )
+    }
+}
+
+impl TspInstance {
+    /// FNV-1a hash of what defines the tours' costs, independent of the
+    /// name, comment and how distances are stored. Coordinate instances hash
+    /// their metric, distance policy and coordinates (O(n)); explicit,
+    /// SPECIAL and modified instances hash every distance (O(n²)).
+    pub fn content_hash(&self) -> u64 {
+        let mut hash = Fnv::default();
+        hash.write(&(self.dimension as u64).to_le_bytes());
+        let coordinate_metric = !matches!(
+            self.edge_weight_type,
+            EdgeWeightType::Explicit | EdgeWeightType::Special(_) | EdgeWeightType::Unknown(_)
+        );
+        let adjusted = matches!(self.dist_matrix, DistanceMatrix::Adjusted { .. });
+        match self.node_coords.as_deref() {
+            Some(nodes) if coordinate_metric && !adjusted && nodes.len() == self.dimension => {
+                hash.write(self.edge_weight_type.keyword().as_bytes());
+                hash.write(format!("{:?}", self.distance_policy).as_bytes());
+                for node in nodes {
+                    hash.write(&(node.id as u64).to_le_bytes());
+                    hash.write(&node.x.to_bits().to_le_bytes());
+                    hash.write(&node.y.to_bits().to_le_bytes());
+                    if self.edge_weight_type.is_three_dimensional() {
+                        hash.write(&node.z.to_bits().to_le_bytes());
+                    }
+                }
+            }
+            _ => {
+                hash.write(b"WEIGHTS");
+                for i in 0..self.dimension {
+                    for j in 0..self.dimension {
+                        hash.write(&self.dist_matrix.get(i, j).to_bits().to_le_bytes());
+                    }
+                }
+            }
+        }
+        hash.0
+    }
+
+    /// [`TspInstance::content_hash`] as 16 hex digits.
+    pub fn content_hash_hex(&self) -> String {
+        format!("{:016x}", self.content_hash())
+    }
+}
+
+/// FNV-1a, stable across platforms and releases unlike `DefaultHasher`.
+pub(crate) struct Fnv(pub(crate) u64);
+
+impl Default for Fnv {
+    fn default() -> Self {
+        Fnv(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Fnv {
+    pub(crate) fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
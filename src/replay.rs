@@ -0,0 +1,337 @@
+//! Run replays: a compact text recording of a run's progression (every new
+//! best tour with its iteration and elapsed time, plus optional pheromone
+//! snapshots and samples of the ants' own tours) and a renderer turning it
+//! into an animated SVG.
+//!
+//! File format, one record per line:
+//!
+//! ```text
+//! REPLAY 1
+//! NAME <instance name>
+//! NODE <x> <y>                                     (one per node, in index order)
+//! BEST <iteration> <elapsed ms> <length> <tour...>
+//! PHEROMONE <iteration> <i>:<j>:<level> ...        (strongest edges only)
+//! ANT <iteration> <elapsed ms> <ant> <length> <tour...>  (sampled ants)
+//! ```
+
+use crate::parser::TspInstance;
+use crate::solver::{Pheromone, widen};
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::time::Instant;
+
+// Edges kept per pheromone snapshot, as a multiple of the dimension.
+const SNAPSHOT_EDGES_PER_NODE: usize = 2;
+const FRAME_SECONDS: f64 = 0.4;
+const CANVAS_SIZE: f64 = 600.0;
+
+/// Writes a replay file while a run progresses.
+pub struct ReplayRecorder {
+    writer: BufWriter<File>,
+    start: Instant,
+}
+
+impl ReplayRecorder {
+    pub fn create(path: &str, instance: &TspInstance) -> Result<Self, String> {
+        let file =
+            File::create(path).map_err(|e| format!("Failed to create replay {}: {}", path, e))?;
+        let mut recorder = ReplayRecorder {
+            writer: BufWriter::new(file),
+            start: Instant::now(),
+        };
+        let mut header = format!("REPLAY 1\nNAME {}\n", instance.name);
+        for node in instance.node_coords.iter().flatten() {
+            let _ = writeln!(header, "NODE {} {}", node.x, node.y);
+        }
+        recorder.write_line(&header)?;
+        Ok(recorder)
+    }
+
+    pub fn record_best(
+        &mut self,
+        iteration: usize,
+        length: f64,
+        tour: &[usize],
+    ) -> Result<(), String> {
+        let mut line = format!(
+            "BEST {} {} {}",
+            iteration,
+            self.start.elapsed().as_millis(),
+            length
+        );
+        for node in tour {
+            let _ = write!(line, " {}", node);
+        }
+        line.push('\n');
+        self.write_line(&line)
+    }
+
+    /// Records the tour of ant `ant` in `iteration`.
+    pub fn record_ant(
+        &mut self,
+        iteration: usize,
+        ant: usize,
+        length: f64,
+        tour: &[usize],
+    ) -> Result<(), String> {
+        let mut line = format!(
+            "ANT {} {} {} {}",
+            iteration,
+            self.start.elapsed().as_millis(),
+            ant,
+            length
+        );
+        for node in tour {
+            let _ = write!(line, " {}", node);
+        }
+        line.push('\n');
+        self.write_line(&line)
+    }
+
+    /// Records the strongest edges of `pheromone_matrix`.
+    pub fn record_pheromone(
+        &mut self,
+        iteration: usize,
+        pheromone_matrix: &[Vec<Pheromone>],
+    ) -> Result<(), String> {
+        let n = pheromone_matrix.len();
+        let mut edges: Vec<(f64, usize, usize)> = pheromone_matrix
+            .iter()
+            .enumerate()
+            .flat_map(|(i, row)| {
+                row.iter()
+                    .enumerate()
+                    .skip(i + 1)
+                    .map(move |(j, &v)| (widen(v), i, j))
+            })
+            .collect();
+        let keep = (SNAPSHOT_EDGES_PER_NODE * n).min(edges.len());
+        if keep < edges.len() {
+            edges.select_nth_unstable_by(keep, |a, b| b.0.total_cmp(&a.0));
+            edges.truncate(keep);
+        }
+        let mut line = format!("PHEROMONE {}", iteration);
+        for (level, i, j) in edges {
+            let _ = write!(line, " {}:{}:{}", i, j, level);
+        }
+        line.push('\n');
+        self.write_line(&line)
+    }
+
+    fn write_line(&mut self, line: &str) -> Result<(), String> {
+        self.writer
+            .write_all(line.as_bytes())
+            .and_then(|_| self.writer.flush())
+            .map_err(|e| format!("Failed to write replay: {}", e))
+    }
+}
+
+struct BestFrame {
+    iteration: usize,
+    elapsed_ms: u64,
+    length: f64,
+    tour: Vec<usize>,
+}
+
+struct AntFrame {
+    iteration: usize,
+    elapsed_ms: u64,
+    tour: Vec<usize>,
+}
+
+struct PheromoneFrame {
+    iteration: usize,
+    edges: Vec<(usize, usize, f64)>,
+}
+
+/// Renders the replay at `replay_path` into an animated SVG showing each new
+/// best tour in turn (over the latest pheromone snapshot, if recorded).
+/// Iterations with sampled ants get a frame of their own, drawing the ants'
+/// tours under the best tour known at that point.
+pub fn render_svg(replay_path: &str, svg_path: &str) -> Result<(), String> {
+    let contents = fs::read_to_string(replay_path)
+        .map_err(|e| format!("Failed to read replay {}: {}", replay_path, e))?;
+    let mut name = String::new();
+    let mut nodes: Vec<(f64, f64)> = Vec::new();
+    let mut bests: Vec<BestFrame> = Vec::new();
+    let mut snapshots: Vec<PheromoneFrame> = Vec::new();
+    let mut ants: Vec<AntFrame> = Vec::new();
+
+    for (line_num, line) in contents.lines().enumerate() {
+        let bad = || format!("L{}: Malformed replay line: {}", line_num + 1, line);
+        let mut fields = line.split_whitespace();
+        match fields.next() {
+            Some("REPLAY") | None => {}
+            Some("NAME") => name = fields.collect::<Vec<_>>().join(" "),
+            Some("NODE") => {
+                let x = fields.next().and_then(|v| v.parse().ok()).ok_or_else(bad)?;
+                let y = fields.next().and_then(|v| v.parse().ok()).ok_or_else(bad)?;
+                nodes.push((x, y));
+            }
+            Some("BEST") => {
+                let iteration = fields.next().and_then(|v| v.parse().ok()).ok_or_else(bad)?;
+                let elapsed_ms = fields.next().and_then(|v| v.parse().ok()).ok_or_else(bad)?;
+                let length = fields.next().and_then(|v| v.parse().ok()).ok_or_else(bad)?;
+                let tour = fields
+                    .map(|v| v.parse().ok().filter(|&idx: &usize| idx < nodes.len()))
+                    .collect::<Option<Vec<usize>>>()
+                    .ok_or_else(bad)?;
+                bests.push(BestFrame {
+                    iteration,
+                    elapsed_ms,
+                    length,
+                    tour,
+                });
+            }
+            Some("ANT") => {
+                let iteration = fields.next().and_then(|v| v.parse().ok()).ok_or_else(bad)?;
+                let elapsed_ms = fields.next().and_then(|v| v.parse().ok()).ok_or_else(bad)?;
+                let _ant: usize = fields.next().and_then(|v| v.parse().ok()).ok_or_else(bad)?;
+                let _length: f64 = fields.next().and_then(|v| v.parse().ok()).ok_or_else(bad)?;
+                let tour = fields
+                    .map(|v| v.parse().ok().filter(|&idx: &usize| idx < nodes.len()))
+                    .collect::<Option<Vec<usize>>>()
+                    .ok_or_else(bad)?;
+                ants.push(AntFrame {
+                    iteration,
+                    elapsed_ms,
+                    tour,
+                });
+            }
+            Some("PHEROMONE") => {
+                let iteration = fields.next().and_then(|v| v.parse().ok()).ok_or_else(bad)?;
+                let edges = fields
+                    .map(|edge| {
+                        let mut parts = edge.split(':');
+                        let i = parts.next()?.parse().ok().filter(|&i| i < nodes.len())?;
+                        let j = parts.next()?.parse().ok().filter(|&j| j < nodes.len())?;
+                        let level = parts.next()?.parse().ok()?;
+                        Some((i, j, level))
+                    })
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or_else(bad)?;
+                snapshots.push(PheromoneFrame { iteration, edges });
+            }
+            Some(_) => return Err(bad()),
+        }
+    }
+    if nodes.is_empty() {
+        return Err("Replay has no node coordinates to draw".to_string());
+    }
+    if bests.is_empty() {
+        return Err("Replay contains no tours".to_string());
+    }
+
+    let (min_x, max_x) = nodes.iter().fold((f64::MAX, f64::MIN), |(lo, hi), p| {
+        (lo.min(p.0), hi.max(p.0))
+    });
+    let (min_y, max_y) = nodes.iter().fold((f64::MAX, f64::MIN), |(lo, hi), p| {
+        (lo.min(p.1), hi.max(p.1))
+    });
+    let scale = (CANVAS_SIZE - 20.0) / (max_x - min_x).max(max_y - min_y).max(f64::EPSILON);
+    let project = |idx: usize| {
+        let (x, y) = nodes[idx];
+        (
+            10.0 + (x - min_x) * scale,
+            CANVAS_SIZE - 10.0 - (y - min_y) * scale,
+        )
+    };
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">\n<title>{2}</title>\n<rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n",
+        CANVAS_SIZE,
+        CANVAS_SIZE + 30.0,
+        name
+    );
+    let points = |tour: &[usize]| {
+        tour.iter()
+            .map(|&idx| {
+                let (x, y) = project(idx);
+                format!("{:.1},{:.1}", x, y)
+            })
+            .collect::<Vec<String>>()
+            .join(" ")
+    };
+    // One frame per new best and per iteration with sampled ants, once a
+    // best tour exists.
+    let mut frames: Vec<(usize, u64)> = bests
+        .iter()
+        .map(|best| (best.iteration, best.elapsed_ms))
+        .chain(ants.iter().map(|ant| (ant.iteration, ant.elapsed_ms)))
+        .filter(|&(iteration, _)| bests[0].iteration <= iteration)
+        .collect();
+    frames.sort_unstable();
+    frames.dedup_by_key(|frame| frame.0);
+    let frame_count = frames.len();
+    for (k, &(iteration, elapsed_ms)) in frames.iter().enumerate() {
+        let Some(best) = bests.iter().rev().find(|best| best.iteration <= iteration) else {
+            continue;
+        };
+        let begin = k as f64 * FRAME_SECONDS;
+        let timing = if k + 1 == frame_count {
+            format!("begin=\"{:.2}s\" fill=\"freeze\"", begin)
+        } else {
+            format!("begin=\"{:.2}s\" dur=\"{:.2}s\"", begin, FRAME_SECONDS)
+        };
+        let _ = writeln!(
+            svg,
+            "<g visibility=\"hidden\"><set attributeName=\"visibility\" to=\"visible\" {}/>",
+            timing
+        );
+        let snapshot = snapshots
+            .iter()
+            .rev()
+            .find(|snapshot| snapshot.iteration <= iteration);
+        if let Some(snapshot) = snapshot {
+            let max_level = snapshot
+                .edges
+                .iter()
+                .map(|edge| edge.2)
+                .fold(f64::MIN_POSITIVE, f64::max);
+            for &(i, j, level) in &snapshot.edges {
+                let ((x1, y1), (x2, y2)) = (project(i), project(j));
+                let _ = writeln!(
+                    svg,
+                    "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"#e8a33d\" stroke-opacity=\"{:.2}\"/>",
+                    x1,
+                    y1,
+                    x2,
+                    y2,
+                    0.1 + 0.5 * level / max_level
+                );
+            }
+        }
+        for ant in ants.iter().filter(|ant| ant.iteration == iteration) {
+            let _ = writeln!(
+                svg,
+                "<polygon points=\"{}\" fill=\"none\" stroke=\"#777\" stroke-opacity=\"0.35\" stroke-width=\"0.8\"/>",
+                points(&ant.tour)
+            );
+        }
+        let _ = writeln!(
+            svg,
+            "<polygon points=\"{}\" fill=\"none\" stroke=\"#2a6fdb\" stroke-width=\"1.5\"/>",
+            points(&best.tour)
+        );
+        let _ = writeln!(
+            svg,
+            "<text x=\"10\" y=\"{:.0}\" font-family=\"sans-serif\" font-size=\"14\">Iteration {} ({:.1} s): length {:.2}</text>\n</g>",
+            CANVAS_SIZE + 20.0,
+            iteration,
+            elapsed_ms as f64 / 1000.0,
+            best.length
+        );
+    }
+    let radius = if nodes.len() > 2000 { 0.8 } else { 2.0 };
+    for idx in 0..nodes.len() {
+        let (x, y) = project(idx);
+        let _ = writeln!(
+            svg,
+            "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{}\" fill=\"#d33\"/>",
+            x, y, radius
+        );
+    }
+    svg.push_str("</svg>\n");
+    fs::write(svg_path, svg).map_err(|e| format!("Failed to write {}: {}", svg_path, e))
+}
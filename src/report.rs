@@ -0,0 +1,243 @@
+//! Presentation of a run, kept apart from solving: [`crate::run`] hands
+//! everything it would print to a [`Reporter`].
+
+use crate::json::JsonValue;
+use crate::parser::{EdgeWeightFormat, TspInstance};
+use crate::solution::Solution;
+use std::io::{self, Write};
+
+// Iterations between progress lines of the console reporter.
+const PROGRESS_INTERVAL: usize = 100;
+
+/// Receives the progress of a run. All methods do nothing by default.
+pub trait Reporter {
+    /// A line of status output: configuration, other modes' tables, files
+    /// written.
+    fn on_message(&mut self, _message: &str) {}
+    /// Something went wrong but the run goes on: an input that was skipped,
+    /// an ignored constraint, a file that could not be written.
+    fn on_warning(&mut self, _message: &str) {}
+    /// The instance was parsed and is about to be solved.
+    fn on_parse(&mut self, _instance: &TspInstance) {}
+    /// The solver finished an iteration; `best_length` is `f64::MAX` while
+    /// no complete tour was found.
+    fn on_iteration(&mut self, _iteration: usize, _best_length: f64, _edge_entropy: f64) {}
+    fn on_new_best(&mut self, _iteration: usize, _tour: &[usize], _length: f64) {}
+    /// The solver wrote a checkpoint to `path` after `iteration`.
+    fn on_checkpoint(&mut self, _iteration: usize, _path: &str) {}
+    fn on_finish(&mut self, _instance: &TspInstance, _solution: &Solution) {}
+}
+
+/// Which reporter the binary uses (`--report`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReportFormat {
+    Console,
+    /// The solution as a single JSON object.
+    Json,
+    /// One JSON object per event.
+    Ndjson,
+    Silent,
+}
+
+impl ReportFormat {
+    /// A reporter of this format writing to stdout.
+    pub fn reporter(self) -> Box<dyn Reporter> {
+        match self {
+            ReportFormat::Console => Box::new(ConsoleReporter),
+            ReportFormat::Json => Box::new(JsonReporter::new(io::stdout())),
+            ReportFormat::Ndjson => Box::new(NdjsonReporter::new(io::stdout())),
+            ReportFormat::Silent => Box::new(SilentReporter),
+        }
+    }
+}
+
+/// Human readable output on stdout, warnings on stderr.
+#[derive(Debug, Default)]
+pub struct ConsoleReporter;
+
+impl Reporter for ConsoleReporter {
+    fn on_message(&mut self, message: &str) {
+        println!("{}", message);
+    }
+
+    fn on_warning(&mut self, message: &str) {
+        eprintln!("Warning: {}", message);
+    }
+
+    fn on_parse(&mut self, instance: &TspInstance) {
+        println!("  Successfully parsed: {}", instance.name);
+        println!("  Problem Type: {}", instance.tsp_type);
+        if !instance.comment.is_empty() {
+            println!("  Comment: {}", instance.comment);
+        }
+        println!("  Dimension: {}", instance.dimension);
+        println!("  Edge Weight Type: {:?}", instance.edge_weight_type);
+        println!("  Distance Policy: {:?}", instance.distance_policy);
+        if let Some(format) = &instance.edge_weight_format
+            && !matches!(format, EdgeWeightFormat::Unknown(_))
+        {
+            println!("  Edge Weight Format: {:?}", format);
+        }
+    }
+
+    fn on_iteration(&mut self, iteration: usize, best_length: f64, edge_entropy: f64) {
+        if !iteration.is_multiple_of(PROGRESS_INTERVAL) {
+            return;
+        }
+        if best_length == f64::MAX {
+            println!("Iter {}: No complete tour found yet.", iteration);
+        } else {
+            println!(
+                "Iter {}: Best tour length so far: {:.2} (edge entropy {:.3})",
+                iteration, best_length, edge_entropy
+            );
+        }
+    }
+
+    fn on_checkpoint(&mut self, iteration: usize, path: &str) {
+        println!(
+            "Stopped after iteration {}, checkpoint written to {}",
+            iteration, path
+        );
+    }
+
+    fn on_finish(&mut self, instance: &TspInstance, solution: &Solution) {
+        println!("\n --- ACO Results for {} ---", instance.name);
+        print!("{}", solution.report(instance.dimension));
+    }
+}
+
+/// Writes the solution as one JSON object when the run finishes.
+#[derive(Debug)]
+pub struct JsonReporter<W: Write> {
+    out: W,
+}
+
+impl<W: Write> JsonReporter<W> {
+    pub fn new(out: W) -> Self {
+        JsonReporter { out }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+impl<W: Write> Reporter for JsonReporter<W> {
+    fn on_finish(&mut self, _instance: &TspInstance, solution: &Solution) {
+        let _ = writeln!(self.out, "{}", solution.to_json());
+    }
+}
+
+/// Writes every event as a JSON object on its own line, tagged by `event`.
+#[derive(Debug)]
+pub struct NdjsonReporter<W: Write> {
+    out: W,
+}
+
+impl<W: Write> NdjsonReporter<W> {
+    pub fn new(out: W) -> Self {
+        NdjsonReporter { out }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+
+    fn emit(&mut self, event: &str, mut members: Vec<(String, JsonValue)>) {
+        members.insert(
+            0,
+            ("event".to_string(), JsonValue::String(event.to_string())),
+        );
+        let _ = writeln!(self.out, "{}", JsonValue::Object(members));
+    }
+}
+
+fn number(value: f64) -> JsonValue {
+    JsonValue::Number(value)
+}
+
+impl<W: Write> Reporter for NdjsonReporter<W> {
+    fn on_message(&mut self, message: &str) {
+        let message = message.trim();
+        if !message.is_empty() {
+            self.emit(
+                "message",
+                vec![("text".to_string(), JsonValue::String(message.to_string()))],
+            );
+        }
+    }
+
+    fn on_warning(&mut self, message: &str) {
+        self.emit(
+            "warning",
+            vec![("text".to_string(), JsonValue::String(message.to_string()))],
+        );
+    }
+
+    fn on_parse(&mut self, instance: &TspInstance) {
+        self.emit(
+            "parse",
+            vec![
+                ("name".to_string(), JsonValue::String(instance.name.clone())),
+                ("dimension".to_string(), number(instance.dimension as f64)),
+            ],
+        );
+    }
+
+    fn on_iteration(&mut self, iteration: usize, best_length: f64, edge_entropy: f64) {
+        self.emit(
+            "iteration",
+            vec![
+                ("iteration".to_string(), number(iteration as f64)),
+                // Written as null while no tour was found.
+                (
+                    "best_length".to_string(),
+                    number(if best_length == f64::MAX {
+                        f64::NAN
+                    } else {
+                        best_length
+                    }),
+                ),
+                ("edge_entropy".to_string(), number(edge_entropy)),
+            ],
+        );
+    }
+
+    fn on_new_best(&mut self, iteration: usize, tour: &[usize], length: f64) {
+        self.emit(
+            "new_best",
+            vec![
+                ("iteration".to_string(), number(iteration as f64)),
+                ("length".to_string(), number(length)),
+                (
+                    "tour".to_string(),
+                    JsonValue::Array(tour.iter().map(|&idx| number(idx as f64)).collect()),
+                ),
+            ],
+        );
+    }
+
+    fn on_checkpoint(&mut self, iteration: usize, path: &str) {
+        self.emit(
+            "checkpoint",
+            vec![
+                ("iteration".to_string(), number(iteration as f64)),
+                ("path".to_string(), JsonValue::String(path.to_string())),
+            ],
+        );
+    }
+
+    fn on_finish(&mut self, _instance: &TspInstance, solution: &Solution) {
+        self.emit(
+            "finish",
+            vec![("solution".to_string(), solution.to_json_value())],
+        );
+    }
+}
+
+/// Reports nothing, for embedders that only want the return value.
+#[derive(Debug, Default)]
+pub struct SilentReporter;
+
+impl Reporter for SilentReporter {}
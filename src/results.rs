@@ -0,0 +1,68 @@
+//! Per-run records shared by the bench and experiment modes, the rows of
+//! their optional sinks (`--parquet`, `--sqlite`).
+
+use crate::config::Config;
+use crate::events::{self, SolverEvent};
+use std::sync::mpsc::{self, Receiver, Sender};
+
+/// Outcome of one seeded run.
+#[derive(Debug, Clone)]
+pub struct RunRecord {
+    pub instance: String,
+    /// [`crate::TspInstance::content_hash_hex`] of the instance solved.
+    pub instance_hash: String,
+    pub seed: u64,
+    pub length: f64,
+    /// Percent above the best-known length, if there is one.
+    pub gap: Option<f64>,
+    pub seconds: f64,
+    /// 0-based node indices; empty if no complete tour was found.
+    pub tour: Vec<usize>,
+    /// One entry per iteration, only collected for sinks that store it.
+    pub history: Vec<IterationRecord>,
+}
+
+/// Lengths reported at the end of one iteration, see
+/// [`SolverEvent::IterationFinished`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IterationRecord {
+    pub iteration: usize,
+    pub iteration_best_length: f64,
+    pub best_length: f64,
+    pub edge_entropy: f64,
+}
+
+/// An event sender for one run, if `config` has a sink for iteration
+/// histories, and the receiver to drain with [`iteration_history`].
+pub(crate) fn history_channel(
+    config: &Config,
+) -> (Option<Sender<SolverEvent>>, Receiver<SolverEvent>) {
+    let (sender, receiver) = mpsc::channel();
+    (config.sqlite_path.is_some().then_some(sender), receiver)
+}
+
+/// The iterations reported on `events` so far. Warnings are printed as
+/// they would have been without the channel.
+pub(crate) fn iteration_history(events: &Receiver<SolverEvent>) -> Vec<IterationRecord> {
+    events
+        .try_iter()
+        .filter_map(|event| match event {
+            SolverEvent::IterationFinished {
+                iteration,
+                iteration_best_length,
+                best_length,
+                edge_entropy,
+            } => Some(IterationRecord {
+                iteration,
+                iteration_best_length,
+                best_length,
+                edge_entropy,
+            }),
+            SolverEvent::Warning { message } => {
+                events::warn(&None, message);
+                None
+            }
+            _ => None,
+        })
+        .collect()
+}
@@ -0,0 +1,96 @@
+use crate::http::http_request;
+use crate::json::{self, JsonValue};
+use crate::matrix_import::{RoadMetric, instance_from_matrix, read_matrix};
+use crate::parser::{DistancePolicy, TspInstance};
+
+/// Routing engine serving a table (many-to-many) endpoint. `base_url` is
+/// the server root, e.g. `http://localhost:5000` for a local OSRM; only
+/// plain `http://` is supported.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RoutingService {
+    /// OSRM `GET /table/v1/<profile>/<coordinates>`.
+    Osrm { base_url: String, profile: String },
+    /// Valhalla `POST /sources_to_targets`.
+    Valhalla { base_url: String, costing: String },
+}
+
+/// Fetches the road matrix between `points`, given as `(latitude,
+/// longitude)`. Entry `[i][j]` is the cost from `i` to `j`, `None` where the
+/// service found no route.
+pub fn fetch_road_matrix(
+    points: &[(f64, f64)],
+    service: &RoutingService,
+    metric: RoadMetric,
+) -> Result<Vec<Vec<Option<f64>>>, String> {
+    if points.len() < 2 {
+        return Err("At least two points are needed for a road matrix".to_string());
+    }
+    match service {
+        RoutingService::Osrm { base_url, profile } => {
+            let coordinates: Vec<String> = points
+                .iter()
+                .map(|(lat, lon)| format!("{},{}", lon, lat))
+                .collect();
+            let url = format!(
+                "{}/table/v1/{}/{}?annotations=duration,distance",
+                base_url.trim_end_matches('/'),
+                profile,
+                coordinates.join(";")
+            );
+            let response = json::parse(&http_request("GET", &url, None)?)?;
+            if let Some(code) = response.get("code").and_then(JsonValue::as_str)
+                && code != "Ok"
+            {
+                return Err(format!("OSRM returned {}", code));
+            }
+            let key = match metric {
+                RoadMetric::Duration => "durations",
+                RoadMetric::Distance => "distances",
+            };
+            let rows = response
+                .get(key)
+                .ok_or_else(|| format!("OSRM response has no {}", key))?;
+            read_matrix(rows, points.len(), |cell| cell.as_f64())
+        }
+        RoutingService::Valhalla { base_url, costing } => {
+            let locations: Vec<String> = points
+                .iter()
+                .map(|(lat, lon)| format!("{{\"lat\":{},\"lon\":{}}}", lat, lon))
+                .collect();
+            let locations = locations.join(",");
+            let body = format!(
+                "{{\"sources\":[{}],\"targets\":[{}],\"costing\":\"{}\",\"units\":\"kilometers\"}}",
+                locations, locations, costing
+            );
+            let url = format!("{}/sources_to_targets", base_url.trim_end_matches('/'));
+            let response = json::parse(&http_request("POST", &url, Some(&body))?)?;
+            let rows = response
+                .get("sources_to_targets")
+                .ok_or("Valhalla response has no sources_to_targets")?;
+            read_matrix(rows, points.len(), |cell| match metric {
+                RoadMetric::Duration => cell.get("time").and_then(JsonValue::as_f64),
+                RoadMetric::Distance => cell
+                    .get("distance")
+                    .and_then(JsonValue::as_f64)
+                    .map(|km| km * 1000.0),
+            })
+        }
+    }
+}
+
+/// Builds an explicit (generally asymmetric) instance from the road matrix
+/// between `points`. Pairs without a route become forbidden edges.
+pub fn road_network_instance(
+    name: &str,
+    points: &[(f64, f64)],
+    service: &RoutingService,
+    metric: RoadMetric,
+) -> Result<TspInstance, String> {
+    let matrix = fetch_road_matrix(points, service, metric)?;
+    Ok(instance_from_matrix(
+        name,
+        format!("Road network matrix ({:?})", metric),
+        &matrix,
+        DistancePolicy::Exact,
+    ))
+}
@@ -0,0 +1,189 @@
+//! Robustness of a solution to uncertain distances: the instance is
+//! perturbed with random relative noise, and the tour found on the original
+//! data is compared with tours re-optimized on the perturbed data.
+
+use crate::config::Config;
+use crate::distance::{DistanceMatrix, FORBIDDEN_DISTANCE};
+use crate::parser::{DistancePolicy, EdgeWeightFormat, EdgeWeightType, TspInstance};
+use crate::prepared::PreparedInstance;
+use crate::solver::solve_quiet;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+use std::collections::HashSet;
+use std::fmt::Write as _;
+
+/// Copy of `instance` with every distance multiplied by a factor drawn
+/// uniformly from `[1 - noise, 1 + noise]` (`noise` is clamped to `[0, 1]`).
+/// Symmetric instances stay symmetric; forbidden edges stay forbidden. The
+/// result is an EXPLICIT instance, its coordinates are kept for display.
+pub fn perturbed_instance(instance: &TspInstance, noise: f64, seed: u64) -> TspInstance {
+    let n = instance.dimension;
+    let noise = noise.clamp(0.0, 1.0);
+    let symmetric = instance.dist_matrix.is_symmetric_storage() || instance.is_symmetric();
+    let mut dist_matrix = if symmetric {
+        DistanceMatrix::triangular(n)
+    } else {
+        DistanceMatrix::full(n)
+    };
+    let mut rng = StdRng::seed_from_u64(seed);
+    for i in 0..n {
+        let others = if symmetric { (i + 1)..n } else { 0..n };
+        for j in others.filter(|&j| j != i) {
+            let dist = instance.dist_matrix.get(i, j);
+            let factor = rng.random_range(1.0 - noise..=1.0 + noise);
+            let perturbed = if dist >= FORBIDDEN_DISTANCE {
+                dist
+            } else {
+                dist * factor
+            };
+            dist_matrix.set(i, j, perturbed);
+        }
+    }
+    TspInstance {
+        name: format!("{}_noise{}", instance.name, noise),
+        tsp_type: instance.tsp_type.clone(),
+        comment: format!("{} with {}% distance noise", instance.name, noise * 100.0),
+        dimension: n,
+        edge_weight_type: EdgeWeightType::Explicit,
+        edge_weight_format: Some(if symmetric {
+            EdgeWeightFormat::UpperRow
+        } else {
+            EdgeWeightFormat::FullMatrix
+        }),
+        distance_policy: DistancePolicy::Exact,
+        node_coords: instance.node_coords.clone(),
+        dist_matrix,
+        node_metadata: instance.node_metadata.clone(),
+        precedences: instance.precedences.clone(),
+        capacity: instance.capacity,
+        demands: instance.demands.clone(),
+        depots: instance.depots.clone(),
+        fixed_edges: instance.fixed_edges.clone(),
+    }
+}
+
+/// Effect of one noise level, averaged over the samples.
+#[derive(Debug, Clone)]
+pub struct RobustnessRow {
+    pub noise: f64,
+    /// Change of the original tour's length on the perturbed data, in
+    /// percent of its original length.
+    pub length_change: f64,
+    /// How much longer the original tour is than the tour re-optimized on
+    /// the perturbed data, in percent: the price of not re-planning.
+    pub mean_regret: f64,
+    pub max_regret: f64,
+    /// Fraction of the original tour's edges the re-optimized tour keeps.
+    pub shared_edges: f64,
+}
+
+/// Solves `instance`, then for every level in `config.noise_levels` draws
+/// `config.num_seeds` perturbed instances (seeds from `config.seed`, or 1)
+/// and re-solves each. Returns the rows and the original tour with its
+/// length.
+pub fn robustness_analysis(
+    instance: &TspInstance,
+    config: &Config,
+) -> (Vec<RobustnessRow>, (Vec<usize>, f64)) {
+    let base_seed = config.seed.unwrap_or(1);
+    let samples = config.num_seeds.max(1) as u64;
+    let quiet = Config {
+        seed: Some(base_seed),
+        event_sender: None,
+        ..config.clone()
+    };
+    let (tour, _) = solve_quiet(&PreparedInstance::new(instance), &quiet);
+    let length = instance.tour_length(&tour);
+    let directed = !instance.dist_matrix.is_symmetric_storage() && !instance.is_symmetric();
+    let original_edges = edges(&tour, directed);
+
+    let runs: Vec<(f64, u64)> = config
+        .noise_levels
+        .iter()
+        .flat_map(|&noise| (0..samples).map(move |s| (noise, s)))
+        .collect();
+    // (length change, regret, shared edges) of every run.
+    let results: Vec<(f64, f64, f64)> = runs
+        .par_iter()
+        .map(|&(noise, s)| {
+            let perturbed = perturbed_instance(instance, noise, base_seed + s);
+            let run_config = Config {
+                seed: Some(base_seed + s),
+                event_sender: None,
+                ..config.clone()
+            };
+            let (reoptimized, _) = solve_quiet(&PreparedInstance::new(&perturbed), &run_config);
+            let kept = perturbed.tour_length(&tour);
+            let best = perturbed.tour_length(&reoptimized).min(kept);
+            let shared = edges(&reoptimized, directed)
+                .intersection(&original_edges)
+                .count();
+            (
+                percent(kept - length, length),
+                percent(kept - best, best),
+                shared as f64 / original_edges.len().max(1) as f64,
+            )
+        })
+        .collect();
+
+    let rows = results
+        .chunks(samples as usize)
+        .zip(&config.noise_levels)
+        .map(|(results, &noise)| {
+            let mean = |value: fn(&(f64, f64, f64)) -> f64| {
+                results.iter().map(value).sum::<f64>() / results.len() as f64
+            };
+            RobustnessRow {
+                noise,
+                length_change: mean(|r| r.0),
+                mean_regret: mean(|r| r.1),
+                max_regret: results.iter().map(|r| r.1).fold(0.0, f64::max),
+                shared_edges: mean(|r| r.2),
+            }
+        })
+        .collect();
+    (rows, (tour, length))
+}
+
+fn percent(difference: f64, reference: f64) -> f64 {
+    if reference > 0.0 {
+        difference / reference * 100.0
+    } else {
+        0.0
+    }
+}
+
+// Edges of the closed tour, as `(min, max)` pairs unless `directed`.
+fn edges(tour: &[usize], directed: bool) -> HashSet<(usize, usize)> {
+    tour.iter()
+        .zip(tour.iter().cycle().skip(1))
+        .map(|(&a, &b)| {
+            if directed {
+                (a, b)
+            } else {
+                (a.min(b), a.max(b))
+            }
+        })
+        .collect()
+}
+
+/// Plain text table of the results, one line per noise level.
+pub fn format_table(rows: &[RobustnessRow]) -> String {
+    let mut table = format!(
+        "  {:>7} {:>12} {:>11} {:>11} {:>13}\n",
+        "Noise", "Length chg", "Mean regret", "Max regret", "Shared edges"
+    );
+    for row in rows {
+        let _ = writeln!(
+            table,
+            "  {:>6.1}% {:>11.2}% {:>10.2}% {:>10.2}% {:>12.1}%",
+            row.noise * 100.0,
+            row.length_change,
+            row.mean_regret,
+            row.max_regret,
+            row.shared_edges * 100.0
+        );
+    }
+    table
+}
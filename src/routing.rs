@@ -0,0 +1,461 @@
+use crate::config::Config;
+use crate::constraints::{Precedences, Violation};
+use crate::events;
+use crate::local_search;
+use crate::parser::TspInstance;
+use crate::solver::ant_rng;
+use rand::Rng;
+use rand::rngs::StdRng;
+use rayon::prelude::*;
+
+/// A depot node together with the number of salesmen based there.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Depot {
+    pub node: usize,
+    pub salesmen: usize,
+}
+
+/// Routes of the salesmen of one depot. Every route starts at the depot and
+/// implicitly returns to it; a salesman without cities has just `[depot]`.
+#[derive(Debug, Clone)]
+pub struct DepotRoutes {
+    pub depot: usize,
+    pub routes: Vec<Vec<usize>>,
+}
+
+/// Solution of a multi-depot mTSP instance.
+#[derive(Debug, Clone)]
+pub struct RouteSet {
+    pub depots: Vec<DepotRoutes>,
+    pub total_length: f64,
+    pub capacity_excess: f64, // Summed load above capacity over all routes
+    pub length_excess: f64,   // Summed length above the route length limit
+    /// Each route above a limit, and pickup and delivery pairs broken on
+    /// a route; empty for a feasible route set.
+    pub violations: Vec<Violation>,
+}
+
+/// Per-route restrictions. Routes are built to respect them while possible;
+/// leftover violations are added to the objective, weighted by the penalties.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteLimits {
+    pub capacity: Option<f64>,   // Maximum summed demand per route
+    pub max_length: Option<f64>, // Maximum length (or duration) per route
+    pub capacity_penalty: f64,   // Cost per unit of load above capacity
+    pub length_penalty: f64,     // Cost per unit of length above max_length
+}
+
+impl Default for RouteLimits {
+    fn default() -> Self {
+        RouteLimits {
+            capacity: None,
+            max_length: None,
+            capacity_penalty: 100.0,
+            length_penalty: 100.0,
+        }
+    }
+}
+
+struct AntSolution {
+    assignment: Vec<usize>, // Depot index per node (usize::MAX for depots)
+    depots: Vec<DepotRoutes>,
+    length: f64,
+    capacity_excess: f64,
+    length_excess: f64,
+    cost: f64, // Length plus penalties
+}
+
+/// Multi-depot multiple TSP with ACO. Each ant first assigns every city to a
+/// depot, weighing a depot-assignment pheromone against the nearest-depot
+/// heuristic, and then builds the routes of each depot by always extending
+/// the currently shortest one with the usual pheromone/heuristic choice.
+/// Minimizes the total length of all routes plus the penalties for
+/// violating `Config::route_limits`; city demands come from
+/// `Config::demands` (one per city when empty). Pickup and delivery pairs
+/// from `Config::pickup_delivery` are kept on the same route, pickup first.
+/// Progress is sent to `Config::event_sender`.
+pub fn solve_multi_depot(
+    instance: &TspInstance,
+    config: &Config,
+    depots: &[Depot],
+) -> Result<RouteSet, String> {
+    let n = instance.dimension;
+    validate_depots(depots, n)?;
+    if !config.demands.is_empty() && config.demands.len() != n {
+        return Err(format!(
+            "Expected {} demands, got {}",
+            n,
+            config.demands.len()
+        ));
+    }
+    let mut depot_of = vec![usize::MAX; n];
+    for (d, depot) in depots.iter().enumerate() {
+        depot_of[depot.node] = d;
+    }
+    let cities: Vec<usize> = (0..n).filter(|&i| depot_of[i] == usize::MAX).collect();
+    let precedences = if config.pickup_delivery.is_empty() {
+        None
+    } else {
+        if let Some(&(pickup, delivery)) =
+            config.pickup_delivery.iter().find(|&&(pickup, delivery)| {
+                depot_of.get(pickup).is_some_and(|&d| d != usize::MAX)
+                    || depot_of.get(delivery).is_some_and(|&d| d != usize::MAX)
+            })
+        {
+            return Err(format!("Pair ({}, {}) includes a depot", pickup, delivery));
+        }
+        Some(Precedences::new(n, &config.pickup_delivery)?)
+    };
+
+    let heuristic = config.heuristic.heuristic_matrix(instance);
+    let init = config.q_val / n.max(1) as f64;
+    let mut pheromone = vec![vec![init; n]; n];
+    let mut assign_pheromone = vec![vec![init; depots.len()]; n];
+    let mut best: Option<AntSolution> = None;
+
+    for iteration in 0..config.num_iters {
+        let ants: Vec<AntSolution> = (0..config.num_ants.max(1))
+            .into_par_iter()
+            .map(|ant_idx| {
+                let mut rng = ant_rng(config.seed, iteration, ant_idx);
+                construct(
+                    instance,
+                    config,
+                    depots,
+                    &cities,
+                    &heuristic,
+                    &pheromone,
+                    &assign_pheromone,
+                    precedences.as_ref(),
+                    &mut rng,
+                )
+            })
+            .collect();
+
+        let retain = 1.0 - config.evap_rate;
+        for row in pheromone.iter_mut().chain(assign_pheromone.iter_mut()) {
+            for level in row {
+                *level = (*level * retain).max(config.min_pheromone_val);
+            }
+        }
+        for ant in &ants {
+            reinforce(&mut pheromone, &mut assign_pheromone, ant, config.q_val);
+        }
+
+        let iteration_best = ants.into_iter().min_by(|a, b| a.cost.total_cmp(&b.cost));
+        if let Some(candidate) = iteration_best
+            && best.as_ref().is_none_or(|b| candidate.cost < b.cost)
+        {
+            best = Some(candidate);
+        }
+        if let Some(best) = &best
+            && config.elitist_weight > 0.0
+        {
+            reinforce(
+                &mut pheromone,
+                &mut assign_pheromone,
+                best,
+                config.elitist_weight * config.q_val,
+            );
+        }
+
+        if (iteration.is_multiple_of(100) || iteration + 1 == config.num_iters)
+            && let Some(best) = &best
+        {
+            events::info(
+                &config.event_sender,
+                format!(
+                    "Iter {}: Best total route length so far: {:.2} (cost {:.2})",
+                    iteration, best.length, best.cost
+                ),
+            );
+        }
+    }
+
+    let best = best.ok_or("No routes constructed (num_iters is 0)")?;
+    Ok(RouteSet {
+        violations: route_violations(instance, config, precedences.as_ref(), &best.depots),
+        depots: best.depots,
+        total_length: best.length,
+        capacity_excess: best.capacity_excess,
+        length_excess: best.length_excess,
+    })
+}
+
+fn route_violations(
+    instance: &TspInstance,
+    config: &Config,
+    precedences: Option<&Precedences>,
+    depots: &[DepotRoutes],
+) -> Vec<Violation> {
+    let limits = &config.route_limits;
+    let demand = |node: usize| config.demands.get(node).copied().unwrap_or(1.0);
+    let mut violations = Vec::new();
+    for depot in depots {
+        for (route, nodes) in depot.routes.iter().enumerate() {
+            let load: f64 = nodes.iter().skip(1).map(|&node| demand(node)).sum();
+            if let Some(capacity) = limits.capacity
+                && load > capacity
+            {
+                violations.push(Violation::Capacity {
+                    depot: depot.depot,
+                    route,
+                    excess: load - capacity,
+                });
+            }
+            let length = instance.tour_length(nodes);
+            if let Some(max_length) = limits.max_length
+                && length > max_length
+            {
+                violations.push(Violation::RouteLength {
+                    depot: depot.depot,
+                    route,
+                    excess: length - max_length,
+                });
+            }
+            if let Some(precedences) = precedences {
+                violations.extend(precedences.violations(nodes));
+            }
+        }
+    }
+    violations
+}
+
+fn validate_depots(depots: &[Depot], n: usize) -> Result<(), String> {
+    if depots.is_empty() {
+        return Err("At least one depot is required".to_string());
+    }
+    let mut seen = vec![false; n];
+    for depot in depots {
+        if depot.node >= n {
+            return Err(format!(
+                "Depot {} out of bounds for dimension {}",
+                depot.node, n
+            ));
+        }
+        if seen[depot.node] {
+            return Err(format!("Depot {} listed more than once", depot.node));
+        }
+        if depot.salesmen == 0 {
+            return Err(format!("Depot {} needs at least one salesman", depot.node));
+        }
+        seen[depot.node] = true;
+    }
+    Ok(())
+}
+
+fn roulette(weights: &[f64], rng: &mut StdRng) -> Option<usize> {
+    let total: f64 = weights.iter().filter(|w| w.is_finite()).sum();
+    if total <= 1e-300 {
+        return None;
+    }
+    let mut target = rng.random::<f64>() * total;
+    for (idx, &w) in weights.iter().enumerate() {
+        if !w.is_finite() {
+            continue;
+        }
+        target -= w;
+        if target <= 0.0 {
+            return Some(idx);
+        }
+    }
+    weights.iter().rposition(|w| w.is_finite() && *w > 0.0)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn construct(
+    instance: &TspInstance,
+    config: &Config,
+    depots: &[Depot],
+    cities: &[usize],
+    heuristic: &[Vec<f64>],
+    pheromone: &[Vec<f64>],
+    assign_pheromone: &[Vec<f64>],
+    precedences: Option<&Precedences>,
+    rng: &mut StdRng,
+) -> AntSolution {
+    let d = &instance.dist_matrix;
+    let n = instance.dimension;
+    let limits = &config.route_limits;
+    let demand = |node: usize| config.demands.get(node).copied().unwrap_or(1.0);
+    let pickup_of = |node: usize| precedences.and_then(|p| p.pickup_of(node));
+
+    // Depot assignment: pheromone against closeness to the depot, skipping
+    // depots whose salesmen are already full while others still have room.
+    let mut assignment = vec![usize::MAX; n];
+    let mut members: Vec<Vec<usize>> = vec![Vec::new(); depots.len()];
+    let mut depot_load = vec![0.0; depots.len()];
+    let mut weights = vec![0.0; depots.len()];
+    for &city in cities {
+        // Deliveries follow their pickup to its depot below.
+        if pickup_of(city).is_some() {
+            continue;
+        }
+        let fits = |dep: usize| {
+            limits.capacity.is_none_or(|capacity| {
+                depot_load[dep] + demand(city) <= capacity * depots[dep].salesmen as f64
+            })
+        };
+        let any_fits = (0..depots.len()).any(fits);
+        for (dep, (w, (depot, &tau))) in weights
+            .iter_mut()
+            .zip(depots.iter().zip(&assign_pheromone[city]))
+            .enumerate()
+        {
+            let eta = 1.0 / (d.get(depot.node, city) + 1e-9);
+            *w = if !any_fits || fits(dep) {
+                tau.powf(config.alpha) * eta.powf(config.beta)
+            } else {
+                0.0
+            };
+        }
+        let chosen = roulette(&weights, rng).unwrap_or_else(|| rng.random_range(0..depots.len()));
+        assignment[city] = chosen;
+        depot_load[chosen] += demand(city);
+        members[chosen].push(city);
+    }
+    for &city in cities {
+        if let Some(pickup) = pickup_of(city) {
+            let chosen = assignment[pickup];
+            assignment[city] = chosen;
+            depot_load[chosen] += demand(city);
+            members[chosen].push(city);
+        }
+    }
+
+    let mut length = 0.0;
+    let mut capacity_excess = 0.0;
+    let mut length_excess = 0.0;
+    let mut result = Vec::with_capacity(depots.len());
+    let mut weights = Vec::new();
+    let mut route_of = vec![usize::MAX; n];
+    for (depot, mut remaining) in depots.iter().zip(members) {
+        let mut routes = vec![vec![depot.node]; depot.salesmen];
+        let mut route_lengths = vec![0.0_f64; depot.salesmen];
+        let mut loads = vec![0.0; depot.salesmen];
+        // Routes stay open while some remaining city still fits them.
+        let mut open = vec![true; depot.salesmen];
+        while !remaining.is_empty() {
+            let shortest = |only_open: bool| {
+                (0..routes.len())
+                    .filter(|&k| !only_open || open[k])
+                    .min_by(|&a, &b| route_lengths[a].total_cmp(&route_lengths[b]))
+            };
+            let (k, enforce) = match shortest(true) {
+                Some(k) => (k, true),
+                None => (shortest(false).unwrap_or(0), false),
+            };
+            let current = *routes[k].last().unwrap_or(&depot.node);
+            weights.clear();
+            weights.extend(remaining.iter().map(|&next| {
+                let released = pickup_of(next).is_none_or(|pickup| route_of[pickup] == k);
+                let feasible = !enforce
+                    || (limits
+                        .capacity
+                        .is_none_or(|capacity| loads[k] + demand(next) <= capacity)
+                        && limits.max_length.is_none_or(|max_length| {
+                            route_lengths[k] + d.get(current, next) + d.get(next, depot.node)
+                                <= max_length
+                        }));
+                if released && feasible {
+                    pheromone[current][next].powf(config.alpha)
+                        * heuristic[current][next].powf(config.beta)
+                } else {
+                    0.0
+                }
+            }));
+            let (k, pick) = match roulette(&weights, rng) {
+                Some(pick) => (k, pick),
+                None if enforce => {
+                    open[k] = false;
+                    continue;
+                }
+                // Limits are violated anyway: take any city whose pickup is
+                // placed, on the route of that pickup.
+                None => remaining
+                    .iter()
+                    .enumerate()
+                    .find_map(|(pick, &city)| match pickup_of(city) {
+                        None => Some((k, pick)),
+                        Some(pickup) => {
+                            (route_of[pickup] != usize::MAX).then_some((route_of[pickup], pick))
+                        }
+                    })
+                    .unwrap_or((k, 0)),
+            };
+            let current = *routes[k].last().unwrap_or(&depot.node);
+            let next = remaining.swap_remove(pick);
+            route_of[next] = k;
+            route_lengths[k] += d.get(current, next);
+            loads[k] += demand(next);
+            routes[k].push(next);
+        }
+        for (route, load) in routes.iter_mut().zip(&loads) {
+            let route_length = if config.local_search.is_empty() || route.len() < 4 {
+                instance.tour_length(route)
+            } else {
+                let original = precedences.map(|_| route.clone());
+                let mut improved =
+                    local_search::apply_pipeline(&config.local_search, route, instance);
+                // Keep the depot first after the search may have rotated it.
+                if let Some(pos) = route.iter().position(|&node| node == depot.node) {
+                    route.rotate_left(pos);
+                }
+                if let (Some(p), Some(original)) = (precedences, original)
+                    && !p.is_satisfied(route)
+                {
+                    *route = original;
+                    improved = instance.tour_length(route);
+                }
+                improved
+            };
+            length += route_length;
+            if let Some(capacity) = limits.capacity {
+                capacity_excess += (load - capacity).max(0.0);
+            }
+            if let Some(max_length) = limits.max_length {
+                length_excess += (route_length - max_length).max(0.0);
+            }
+        }
+        result.push(DepotRoutes {
+            depot: depot.node,
+            routes,
+        });
+    }
+    AntSolution {
+        assignment,
+        depots: result,
+        length,
+        capacity_excess,
+        length_excess,
+        cost: length
+            + limits.capacity_penalty * capacity_excess
+            + limits.length_penalty * length_excess,
+    }
+}
+
+fn reinforce(
+    pheromone: &mut [Vec<f64>],
+    assign_pheromone: &mut [Vec<f64>],
+    ant: &AntSolution,
+    amount: f64,
+) {
+    if ant.cost <= 1e-9 {
+        return;
+    }
+    let delta = amount / ant.cost;
+    for depot in &ant.depots {
+        for route in depot.routes.iter().filter(|route| route.len() > 1) {
+            for (k, &a) in route.iter().enumerate() {
+                let b = route[(k + 1) % route.len()];
+                pheromone[a][b] += delta;
+                pheromone[b][a] += delta;
+            }
+        }
+    }
+    for (city, &depot) in ant.assignment.iter().enumerate() {
+        if depot != usize::MAX {
+            assign_pheromone[city][depot] += delta;
+        }
+    }
+}
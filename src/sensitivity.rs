@@ -0,0 +1,206 @@
+//! One-at-a-time parameter sensitivity analysis: each parameter is scaled
+//! around the base configuration while all others stay fixed, and every
+//! setting is run over several seeds to estimate its effect on the gap.
+
+use crate::config::Config;
+use crate::parser::TspInstance;
+use crate::prepared::PreparedInstance;
+use crate::solver::solve_quiet;
+use rayon::prelude::*;
+use std::fmt::Write as _;
+
+/// Factors applied to the base value of each parameter.
+pub const SENSITIVITY_FACTORS: [f64; 5] = [0.5, 0.75, 1.0, 1.25, 1.5];
+
+struct Parameter {
+    name: &'static str,
+    get: fn(&Config) -> f64,
+    set: fn(&mut Config, f64),
+}
+
+const PARAMETERS: [Parameter; 6] = [
+    Parameter {
+        name: "alpha",
+        get: |c| c.alpha,
+        set: |c, v| c.alpha = v,
+    },
+    Parameter {
+        name: "beta",
+        get: |c| c.beta,
+        set: |c, v| c.beta = v,
+    },
+    Parameter {
+        name: "evap_rate",
+        get: |c| c.evap_rate,
+        set: |c, v| c.evap_rate = v.min(1.0),
+    },
+    Parameter {
+        name: "num_ants",
+        get: |c| c.num_ants as f64,
+        set: |c, v| c.num_ants = (v.round() as usize).max(1),
+    },
+    Parameter {
+        name: "q_val",
+        get: |c| c.q_val,
+        set: |c, v| c.q_val = v,
+    },
+    Parameter {
+        name: "elitist_weight",
+        get: |c| c.elitist_weight,
+        set: |c, v| c.elitist_weight = v,
+    },
+];
+
+/// Result of one parameter setting over all seeds.
+#[derive(Debug, Clone)]
+pub struct SensitivityRow {
+    pub parameter: &'static str,
+    pub factor: f64,
+    pub value: f64,
+    /// Mean and standard deviation of the gap to the reference, in percent.
+    pub mean_gap: f64,
+    pub std_dev: f64,
+}
+
+/// Runs the analysis with `config.num_seeds` seeds per setting (starting at
+/// `config.seed`, or 1). Gaps are measured against `reference`, typically the
+/// known optimum; without one the best length found by any run is used.
+/// Returns the rows and the reference length.
+pub fn sensitivity_analysis(
+    instance: &TspInstance,
+    config: &Config,
+    reference: Option<f64>,
+) -> (Vec<SensitivityRow>, f64) {
+    let base_seed = config.seed.unwrap_or(1);
+    let seeds = config.num_seeds.max(1) as u64;
+    let runs: Vec<(usize, usize, u64)> = (0..PARAMETERS.len())
+        .flat_map(|p| {
+            (0..SENSITIVITY_FACTORS.len()).flat_map(move |f| (0..seeds).map(move |s| (p, f, s)))
+        })
+        .collect();
+    let prepared = PreparedInstance::new(instance);
+    let lengths: Vec<f64> = runs
+        .par_iter()
+        .map(|&(p, f, s)| {
+            let parameter = &PARAMETERS[p];
+            let mut run_config = Config {
+                seed: Some(base_seed + s),
+                event_sender: None,
+                ..config.clone()
+            };
+            (parameter.set)(
+                &mut run_config,
+                (parameter.get)(config) * SENSITIVITY_FACTORS[f],
+            );
+            solve_quiet(&prepared, &run_config).1
+        })
+        .collect();
+
+    let reference = reference.unwrap_or_else(|| lengths.iter().copied().fold(f64::MAX, f64::min));
+    let rows = lengths
+        .chunks(seeds as usize)
+        .enumerate()
+        .map(|(setting, lengths)| {
+            let parameter = &PARAMETERS[setting / SENSITIVITY_FACTORS.len()];
+            let factor = SENSITIVITY_FACTORS[setting % SENSITIVITY_FACTORS.len()];
+            let gaps: Vec<f64> = lengths
+                .iter()
+                .map(|&length| (length - reference) / reference * 100.0)
+                .collect();
+            let mean_gap = gaps.iter().sum::<f64>() / gaps.len() as f64;
+            let variance =
+                gaps.iter().map(|g| (g - mean_gap).powi(2)).sum::<f64>() / gaps.len() as f64;
+            let mut scaled = config.clone();
+            (parameter.set)(&mut scaled, (parameter.get)(config) * factor);
+            SensitivityRow {
+                parameter: parameter.name,
+                factor,
+                value: (parameter.get)(&scaled),
+                mean_gap,
+                std_dev: variance.sqrt(),
+            }
+        })
+        .collect();
+    (rows, reference)
+}
+
+/// Plain text table of the results, one line per setting.
+pub fn format_table(rows: &[SensitivityRow]) -> String {
+    let mut table = format!(
+        "  {:<15} {:>7} {:>10} {:>10} {:>9}\n",
+        "Parameter", "Factor", "Value", "Mean gap", "Std dev"
+    );
+    for row in rows {
+        let _ = writeln!(
+            table,
+            "  {:<15} {:>7.2} {:>10.4} {:>9.2}% {:>8.2}%",
+            row.parameter, row.factor, row.value, row.mean_gap, row.std_dev
+        );
+    }
+    table
+}
+
+/// Writes an SVG line chart of mean gap against the scaling factor, one line
+/// per parameter.
+pub fn write_plot(rows: &[SensitivityRow], path: &str) -> Result<(), String> {
+    const COLORS: [&str; 6] = ["#2a6fdb", "#d33", "#2a9d4b", "#e8a33d", "#8e44ad", "#555"];
+    let (width, height, margin) = (640.0, 400.0, 50.0);
+    let max_gap = rows
+        .iter()
+        .map(|r| r.mean_gap)
+        .fold(0.0, f64::max)
+        .max(1e-9);
+    let (min_f, max_f) = (SENSITIVITY_FACTORS[0], SENSITIVITY_FACTORS[4]);
+    let x = |factor: f64| margin + (factor - min_f) / (max_f - min_f) * (width - 2.0 * margin);
+    let y = |gap: f64| height - margin - gap / max_gap * (height - 2.0 * margin);
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\" font-family=\"sans-serif\" font-size=\"12\">\n<rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n",
+        width, height
+    );
+    let _ = writeln!(
+        svg,
+        "<line x1=\"{m}\" y1=\"{b}\" x2=\"{r}\" y2=\"{b}\" stroke=\"black\"/>\n<line x1=\"{m}\" y1=\"{m}\" x2=\"{m}\" y2=\"{b}\" stroke=\"black\"/>",
+        m = margin,
+        b = height - margin,
+        r = width - margin
+    );
+    for &factor in &SENSITIVITY_FACTORS {
+        let _ = writeln!(
+            svg,
+            "<text x=\"{:.1}\" y=\"{}\" text-anchor=\"middle\">x{}</text>",
+            x(factor),
+            height - margin + 18.0,
+            factor
+        );
+    }
+    let _ = writeln!(
+        svg,
+        "<text x=\"{}\" y=\"{}\" text-anchor=\"end\">{:.2}%</text>\n<text x=\"{}\" y=\"{}\" text-anchor=\"end\">0%</text>",
+        margin - 5.0,
+        margin + 4.0,
+        max_gap,
+        margin - 5.0,
+        height - margin + 4.0
+    );
+    for (k, parameter) in PARAMETERS.iter().enumerate() {
+        let points: Vec<String> = rows
+            .iter()
+            .filter(|row| row.parameter == parameter.name)
+            .map(|row| format!("{:.1},{:.1}", x(row.factor), y(row.mean_gap)))
+            .collect();
+        let color = COLORS[k % COLORS.len()];
+        let _ = writeln!(
+            svg,
+            "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"2\"/>\n<text x=\"{}\" y=\"{}\" fill=\"{}\">{}</text>",
+            points.join(" "),
+            color,
+            width - margin + 5.0 - 100.0,
+            margin + 15.0 * k as f64,
+            color,
+            parameter.name
+        );
+    }
+    svg.push_str("</svg>\n");
+    std::fs::write(path, svg).map_err(|e| format!("Failed to write {}: {}", path, e))
+}
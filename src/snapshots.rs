@@ -0,0 +1,185 @@
+//! Per-iteration fingerprints of a run for debugging nondeterminism: after
+//! every iteration the solver hashes its pheromone matrix and best tour.
+//! Two seeded runs that should be identical (e.g. before and after a
+//! refactor, or with different thread counts) can then be diffed to find
+//! the first iteration where they part ways.
+//!
+//! File format, one record per line:
+//!
+//! ```text
+//! SNAPSHOTS 1
+//! <iteration> <pheromone hash> <best tour hash> <best length>
+//! ```
+
+use crate::provenance::Fnv;
+use crate::solver::Pheromone;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Snapshot {
+    pub iteration: usize,
+    /// FNV-1a over the bits of every pheromone level, row by row.
+    pub pheromone_hash: u64,
+    /// FNV-1a over the node indices of the best tour.
+    pub tour_hash: u64,
+    pub best_length: f64,
+}
+
+impl Snapshot {
+    pub fn new(
+        iteration: usize,
+        pheromone_matrix: &[Vec<Pheromone>],
+        best_tour: &[usize],
+        best_length: f64,
+    ) -> Self {
+        let mut pheromone = Fnv::default();
+        for row in pheromone_matrix {
+            for level in row {
+                pheromone.write(&level.to_bits().to_le_bytes());
+            }
+        }
+        let mut tour = Fnv::default();
+        for &node in best_tour {
+            tour.write(&(node as u64).to_le_bytes());
+        }
+        Snapshot {
+            iteration,
+            pheromone_hash: pheromone.0,
+            tour_hash: tour.0,
+            best_length,
+        }
+    }
+}
+
+/// Appends a [`Snapshot`] per iteration to a file while a run progresses.
+pub struct SnapshotRecorder {
+    writer: BufWriter<File>,
+}
+
+impl SnapshotRecorder {
+    pub fn create(path: &str) -> Result<Self, String> {
+        let file = File::create(path)
+            .map_err(|e| format!("Failed to create snapshots {}: {}", path, e))?;
+        let mut recorder = SnapshotRecorder {
+            writer: BufWriter::new(file),
+        };
+        recorder.write_line("SNAPSHOTS 1\n")?;
+        Ok(recorder)
+    }
+
+    pub fn record(&mut self, snapshot: &Snapshot) -> Result<(), String> {
+        self.write_line(&format!(
+            "{} {:016x} {:016x} {}\n",
+            snapshot.iteration, snapshot.pheromone_hash, snapshot.tour_hash, snapshot.best_length
+        ))
+    }
+
+    fn write_line(&mut self, line: &str) -> Result<(), String> {
+        self.writer
+            .write_all(line.as_bytes())
+            .and_then(|_| self.writer.flush())
+            .map_err(|e| format!("Failed to write snapshots: {}", e))
+    }
+}
+
+/// Reads a file written by [`SnapshotRecorder`].
+pub fn read_snapshots(path: &str) -> Result<Vec<Snapshot>, String> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read snapshots {}: {}", path, e))?;
+    let mut lines = text.lines();
+    if lines.next() != Some("SNAPSHOTS 1") {
+        return Err(format!("{} is not a snapshot file", path));
+    }
+    lines
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(k, line)| {
+            let invalid = || format!("{}:{}: Invalid snapshot '{}'", path, k + 2, line);
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let [iteration, pheromone, tour, length] = fields[..] else {
+                return Err(invalid());
+            };
+            Ok(Snapshot {
+                iteration: iteration.parse().map_err(|_| invalid())?,
+                pheromone_hash: u64::from_str_radix(pheromone, 16).map_err(|_| invalid())?,
+                tour_hash: u64::from_str_radix(tour, 16).map_err(|_| invalid())?,
+                best_length: length.parse().map_err(|_| invalid())?,
+            })
+        })
+        .collect()
+}
+
+/// First iteration where two runs differ. `None` on either side means that
+/// run had already ended.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    pub iteration: usize,
+    pub left: Option<Snapshot>,
+    pub right: Option<Snapshot>,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.left, &self.right) {
+            (Some(left), Some(right)) => {
+                let mut parts = Vec::new();
+                if left.pheromone_hash != right.pheromone_hash {
+                    parts.push("pheromone matrices");
+                }
+                if left.tour_hash != right.tour_hash {
+                    parts.push("best tours");
+                }
+                if left.best_length.to_bits() != right.best_length.to_bits() {
+                    parts.push("best lengths");
+                }
+                write!(
+                    f,
+                    "Runs diverge at iteration {}: {} differ (best length {} vs {})",
+                    self.iteration,
+                    parts.join(", "),
+                    left.best_length,
+                    right.best_length
+                )
+            }
+            (Some(_), None) => write!(
+                f,
+                "Runs agree until the second ends before iteration {}",
+                self.iteration
+            ),
+            _ => write!(
+                f,
+                "Runs agree until the first ends before iteration {}",
+                self.iteration
+            ),
+        }
+    }
+}
+
+/// The first iteration in which `left` and `right` differ, or `None` if
+/// they are identical.
+pub fn diff_snapshots(left: &[Snapshot], right: &[Snapshot]) -> Option<Divergence> {
+    let differs = |a: &Snapshot, b: &Snapshot| {
+        a.iteration != b.iteration
+            || a.pheromone_hash != b.pheromone_hash
+            || a.tour_hash != b.tour_hash
+            || a.best_length.to_bits() != b.best_length.to_bits()
+    };
+    if let Some((a, b)) = left.iter().zip(right).find(|(a, b)| differs(a, b)) {
+        return Some(Divergence {
+            iteration: a.iteration.min(b.iteration),
+            left: Some(*a),
+            right: Some(*b),
+        });
+    }
+    let common = left.len().min(right.len());
+    (left.len() != right.len()).then(|| Divergence {
+        iteration: left
+            .get(common)
+            .or(right.get(common))
+            .map_or(common, |snapshot| snapshot.iteration),
+        left: left.get(common).copied(),
+        right: right.get(common).copied(),
+    })
+}
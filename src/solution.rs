@@ -0,0 +1,410 @@
+//! The result of solving an instance, whichever backend produced it, with the
+//! metadata needed to report, compare or store it.
+
+use crate::bench::gap_percent;
+use crate::constraints::Violation;
+use crate::json::{self, JsonValue};
+use crate::node_data::NodeMetadata;
+use crate::parser::TspInstance;
+use crate::provenance::Provenance;
+use std::fmt::{self, Write as _};
+use std::time::Duration;
+
+// Longer tours are previewed by their first and last node ids.
+const MAX_PRINTED_TOUR: usize = 30;
+const PREVIEW_LENGTH: usize = 10;
+
+/// Why the search stopped.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Termination {
+    /// The configured number of iterations was used up.
+    IterationLimit,
+    /// The configured number of constructed tours was used up.
+    TourBudget,
+    /// The time limit was reached.
+    TimeLimit,
+    /// A tour at least as short as the target length was found.
+    TargetReached,
+    /// The best tour did not improve for the configured number of iterations.
+    NoImprovement,
+    /// A signal asked the run to stop early (see `--checkpoint`).
+    Interrupted,
+    /// The backend has no iteration budget of its own and ran to its end.
+    Completed,
+}
+
+impl Termination {
+    fn name(self) -> &'static str {
+        match self {
+            Termination::IterationLimit => "iteration_limit",
+            Termination::TourBudget => "tour_budget",
+            Termination::TimeLimit => "time_limit",
+            Termination::TargetReached => "target_reached",
+            Termination::NoImprovement => "no_improvement",
+            Termination::Interrupted => "interrupted",
+            Termination::Completed => "completed",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        [
+            Termination::IterationLimit,
+            Termination::TourBudget,
+            Termination::TimeLimit,
+            Termination::TargetReached,
+            Termination::NoImprovement,
+            Termination::Interrupted,
+            Termination::Completed,
+        ]
+        .into_iter()
+        .find(|termination| termination.name() == name)
+    }
+}
+
+impl fmt::Display for Termination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name().replace('_', " "))
+    }
+}
+
+/// Which tour length gaps and comparisons use. TSPLIB optima are sums of
+/// rounded edge weights, so `Rounded` matches them; `Exact` suits instances
+/// whose distances are kept unrounded.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum LengthPrecision {
+    /// The length rounded to the nearest integer.
+    #[default]
+    Rounded,
+    /// The length as summed, without rounding.
+    Exact,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solution {
+    /// 0-based node indices; empty if no complete tour was found.
+    pub tour: Vec<usize>,
+    /// The tour as node ids (coordinate ids, or 1-based indices for explicit
+    /// instances).
+    pub node_ids: Vec<usize>,
+    /// Metadata of the stops in visit order, if the instance has any.
+    pub stop_metadata: Option<NodeMetadata>,
+    /// Rounded tour length, 0 if no tour was found.
+    pub length: f64,
+    /// The tour length before rounding, 0 if no tour was found.
+    pub exact_length: f64,
+    /// Percent above the known optimum, if there is one.
+    pub gap: Option<f64>,
+    pub algorithm: String,
+    pub seed: Option<u64>,
+    pub duration: Duration,
+    /// Ant tours constructed, 0 for backends that construct none.
+    pub tours: usize,
+    pub termination: Termination,
+    /// Tour constraints of the configuration the tour breaks, empty for a
+    /// feasible tour.
+    pub violations: Vec<Violation>,
+    /// Instance hash, version and settings of the run, set by
+    /// `solve_instance`.
+    pub provenance: Option<Provenance>,
+}
+
+impl Solution {
+    /// A solution for `instance`; gap, seed and duration are left for the
+    /// caller to fill in.
+    pub fn new(
+        instance: &TspInstance,
+        tour: Vec<usize>,
+        length: f64,
+        algorithm: impl Into<String>,
+        termination: Termination,
+    ) -> Self {
+        let node_ids = tour
+            .iter()
+            .map(|&idx| {
+                instance
+                    .node_coords
+                    .as_ref()
+                    .and_then(|nodes| nodes.get(idx))
+                    .map_or(idx + 1, |node| node.id)
+            })
+            .collect();
+        let stop_metadata = instance
+            .node_metadata
+            .as_ref()
+            .map(|metadata| metadata.reordered(&tour));
+        Solution {
+            tour,
+            node_ids,
+            stop_metadata,
+            length,
+            exact_length: length,
+            gap: None,
+            algorithm: algorithm.into(),
+            seed: None,
+            duration: Duration::ZERO,
+            tours: 0,
+            termination,
+            violations: Vec::new(),
+            provenance: None,
+        }
+    }
+
+    /// Sets the gap of the rounded length relative to a known optimum.
+    pub fn set_optimum(&mut self, optimum: f64) {
+        self.set_optimum_with(optimum, LengthPrecision::Rounded);
+    }
+
+    /// Sets the gap of the length at `precision` relative to a known
+    /// optimum.
+    pub fn set_optimum_with(&mut self, optimum: f64, precision: LengthPrecision) {
+        self.gap = Some(gap_percent(self.length_with(precision), optimum));
+    }
+
+    /// The tour length at `precision`.
+    pub fn length_with(&self, precision: LengthPrecision) -> f64 {
+        match precision {
+            LengthPrecision::Rounded => self.length,
+            LengthPrecision::Exact => self.exact_length,
+        }
+    }
+
+    /// Constructed tours per second of run time, `None` if no tours were
+    /// counted.
+    pub fn tours_per_second(&self) -> Option<f64> {
+        let seconds = self.duration.as_secs_f64();
+        (self.tours > 0 && seconds > 0.0).then(|| self.tours as f64 / seconds)
+    }
+
+    /// Whether the tour visits all `dimension` nodes.
+    pub fn is_complete(&self, dimension: usize) -> bool {
+        self.tour.len() == dimension && self.tour.iter().all(|&idx| idx < dimension)
+    }
+
+    /// Whether the tour is complete and breaks none of the constraints.
+    pub fn is_feasible(&self, dimension: usize) -> bool {
+        self.is_complete(dimension) && self.violations.is_empty()
+    }
+
+    /// Human readable summary: time, length, route and gap.
+    pub fn report(&self, dimension: usize) -> String {
+        let mut report = format!(
+            "   Algorithm: {}\n   Time taken: {:.2?} ({})\n",
+            self.algorithm, self.duration, self.termination
+        );
+        if let Some(rate) = self.tours_per_second() {
+            let _ = writeln!(
+                report,
+                "   Tours constructed: {} ({:.0} tours/s)",
+                self.tours, rate
+            );
+        }
+        if self.length == 0.0 && (self.tour.is_empty() || dimension > 1) {
+            report.push_str("   No tour found or tour length is zero for a multi-node problem.\n");
+        } else {
+            let _ = write!(report, "   Best tour length found: {:.2}", self.length);
+            if self.exact_length != self.length {
+                let _ = write!(report, " (exact {:.4})", self.exact_length);
+            }
+            report.push('\n');
+        }
+        if self.tour.is_empty() {
+            if dimension > 0 {
+                report.push_str("  No tour found by the solver.\n");
+            }
+        } else if !self.is_complete(dimension) {
+            let _ = writeln!(
+                report,
+                "   Partial or invalid tour found: {:?} (Length: {})",
+                self.tour,
+                self.tour.len()
+            );
+        } else if self.tour.len() <= MAX_PRINTED_TOUR {
+            let _ = writeln!(report, "   Route (Node IDs): {:?}", self.node_ids);
+        } else {
+            let ids = &self.node_ids;
+            let _ = writeln!(
+                report,
+                "   Route (Node IDs, {} cities): {:?} ... {:?}",
+                ids.len(),
+                &ids[..PREVIEW_LENGTH],
+                &ids[ids.len() - PREVIEW_LENGTH..]
+            );
+        }
+        if let Some(gap) = self.gap {
+            if self.length > 0.0 {
+                let _ = writeln!(report, "   ACO solution is {:.2}% away from optimal.", gap);
+            } else {
+                report.push_str(
+                    "   Cannot calculate deviation from optimal as no valid tour was found by ACO.\n",
+                );
+            }
+        }
+        if !self.violations.is_empty() {
+            let _ = writeln!(
+                report,
+                "   Best-effort tour, {} constraint violations:",
+                self.violations.len()
+            );
+            for violation in &self.violations {
+                let _ = writeln!(report, "     - {}", violation);
+            }
+        }
+        if let Some(provenance) = &self.provenance {
+            let _ = writeln!(report, "   Provenance: {}", provenance);
+        }
+        report
+    }
+
+    pub fn to_json(&self) -> String {
+        self.to_json_value().to_string()
+    }
+
+    pub(crate) fn to_json_value(&self) -> JsonValue {
+        let numbers = |values: &[usize]| {
+            JsonValue::Array(
+                values
+                    .iter()
+                    .map(|&v| JsonValue::Number(v as f64))
+                    .collect(),
+            )
+        };
+        let optional = |value: Option<f64>| value.map_or(JsonValue::Null, JsonValue::Number);
+        JsonValue::Object(vec![
+            ("tour".to_string(), numbers(&self.tour)),
+            ("node_ids".to_string(), numbers(&self.node_ids)),
+            (
+                "stop_metadata".to_string(),
+                self.stop_metadata
+                    .as_ref()
+                    .map_or(JsonValue::Null, NodeMetadata::to_json_value),
+            ),
+            ("length".to_string(), JsonValue::Number(self.length)),
+            (
+                "exact_length".to_string(),
+                JsonValue::Number(self.exact_length),
+            ),
+            ("gap".to_string(), optional(self.gap)),
+            (
+                "algorithm".to_string(),
+                JsonValue::String(self.algorithm.clone()),
+            ),
+            (
+                "seed".to_string(),
+                // Seeds above 2^53 do not survive a JSON number.
+                self.seed
+                    .map_or(JsonValue::Null, |seed| JsonValue::String(seed.to_string())),
+            ),
+            (
+                "duration_seconds".to_string(),
+                JsonValue::Number(self.duration.as_secs_f64()),
+            ),
+            ("tours".to_string(), JsonValue::Number(self.tours as f64)),
+            (
+                "termination".to_string(),
+                JsonValue::String(self.termination.name().to_string()),
+            ),
+            (
+                "violations".to_string(),
+                JsonValue::Array(
+                    self.violations
+                        .iter()
+                        .map(Violation::to_json_value)
+                        .collect(),
+                ),
+            ),
+            (
+                "provenance".to_string(),
+                self.provenance
+                    .as_ref()
+                    .map_or(JsonValue::Null, Provenance::to_json_value),
+            ),
+        ])
+    }
+
+    pub fn from_json(text: &str) -> Result<Solution, String> {
+        let value = json::parse(text)?;
+        let field = |key: &str| {
+            value
+                .get(key)
+                .ok_or_else(|| format!("Solution is missing '{}'", key))
+        };
+        let indices = |key: &str| -> Result<Vec<usize>, String> {
+            field(key)?
+                .as_array()
+                .ok_or_else(|| format!("'{}' is not an array", key))?
+                .iter()
+                .map(|v| {
+                    v.as_f64()
+                        .filter(|x| *x >= 0.0 && x.fract() == 0.0)
+                        .map(|x| x as usize)
+                        .ok_or_else(|| format!("Invalid entry in '{}'", key))
+                })
+                .collect()
+        };
+        let number = |key: &str| {
+            field(key)?
+                .as_f64()
+                .ok_or_else(|| format!("'{}' is not a number", key))
+        };
+        let text_field = |key: &str| {
+            field(key)?
+                .as_str()
+                .ok_or_else(|| format!("'{}' is not a string", key))
+        };
+        let seed = match field("seed")? {
+            JsonValue::Null => None,
+            seed => Some(
+                seed.as_str()
+                    .and_then(|s| s.parse().ok())
+                    .ok_or("'seed' is not a number string")?,
+            ),
+        };
+        let termination = text_field("termination")?;
+        let length = number("length")?;
+        Ok(Solution {
+            tour: indices("tour")?,
+            node_ids: indices("node_ids")?,
+            stop_metadata: match value.get("stop_metadata") {
+                None | Some(JsonValue::Null) => None,
+                Some(metadata) => Some(NodeMetadata::from_json_value(metadata)?),
+            },
+            length,
+            // Absent in solutions written before exact lengths were kept.
+            exact_length: value
+                .get("exact_length")
+                .and_then(JsonValue::as_f64)
+                .unwrap_or(length),
+            gap: field("gap")?.as_f64(),
+            algorithm: text_field("algorithm")?.to_string(),
+            seed,
+            duration: Duration::try_from_secs_f64(number("duration_seconds")?)
+                .map_err(|_| "Invalid 'duration_seconds'")?,
+            // Absent in solutions written before tours were counted.
+            tours: value
+                .get("tours")
+                .and_then(JsonValue::as_f64)
+                .map_or(0, |tours| tours as usize),
+            termination: Termination::from_name(termination)
+                .ok_or_else(|| format!("Unknown termination '{}'", termination))?,
+            // Absent in solutions written before violations were reported.
+            violations: match value.get("violations") {
+                None => Vec::new(),
+                Some(violations) => violations
+                    .as_array()
+                    .ok_or("'violations' is not an array")?
+                    .iter()
+                    .map(Violation::from_json_value)
+                    .collect::<Result<_, _>>()?,
+            },
+            provenance: match value.get("provenance") {
+                None | Some(JsonValue::Null) => None,
+                Some(provenance) => Some(Provenance::from_json_value(provenance)?),
+            },
+        })
+    }
+
+    pub fn write_json(&self, path: &str) -> Result<(), String> {
+        std::fs::write(path, self.to_json() + "\n")
+            .map_err(|e| format!("Failed to write solution {}: {}", path, e))
+    }
+}
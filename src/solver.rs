@@ -1,14 +1,442 @@
+use crate::checkpoint::{self, Checkpoint};
 use crate::config::Config;
-use crate::parser::TspInstance;
-use rand::Rng;
+use crate::constraints::{self, TourConstraints};
+use crate::construction;
+use crate::distance::DistanceMatrix;
+use crate::events::{self, SolverEvent};
+use crate::export::{self, MatrixFormat};
+use crate::kernels;
+use crate::local_search;
+use crate::parser::{DistancePolicy, Node, TspInstance};
+use crate::prepared::PreparedInstance;
+use crate::provenance::Provenance;
+use crate::replay::ReplayRecorder;
+use crate::snapshots::{Snapshot, SnapshotRecorder};
+use crate::solution::{Solution, Termination};
+use crate::spatial;
+use crate::stopping::{Composite, MaxIterations, SearchState, StoppingCriterion};
+use crate::threads;
+use crate::time_budget::BudgetPlanner;
 use rand::prelude::IndexedRandom;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use rayon::prelude::*;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// How the pheromone matrix is initialized.
+///
+/// The tour based strategies use the Ant System rule `tau_0 = m / (rho * L)`
+/// with `m` ants and a reference tour of length `L`, which adapts the initial
+/// level to the scale of the instance.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InitStrategy {
+    Constant(f64),
+    FromNearestNeighbor,
+    FromGreedy,
+    FromTour(Vec<usize>),
+}
+
+impl InitStrategy {
+    const FALLBACK: f64 = 0.1;
+
+    pub fn initial_pheromone(&self, instance: &TspInstance, config: &Config) -> f64 {
+        let reference_length = match self {
+            InitStrategy::Constant(value) => return *value,
+            InitStrategy::FromNearestNeighbor => {
+                instance.tour_length(&construction::nearest_neighbor_tour(instance, 0))
+            }
+            InitStrategy::FromGreedy => {
+                instance.tour_length(&construction::greedy_edge_tour(instance))
+            }
+            InitStrategy::FromTour(tour) => instance.tour_length(tour),
+        };
+        let num_ants = config.start_policy.num_ants(config, instance.dimension) as f64;
+        let denominator = config.evap_rate * reference_length;
+        if denominator > 1e-9 && denominator.is_finite() {
+            num_ants / denominator
+        } else {
+            Self::FALLBACK
+        }
+    }
+}
+
+/// Where the ants of an iteration start their tours. Tour constraints take
+/// precedence: a node locked to position 0 is always the start, and starts
+/// the constraints rule out are replaced by a random allowed node.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StartPolicy {
+    /// A uniformly random node per ant.
+    Random,
+    /// Every ant starts at this node (0-based); random if out of range.
+    FixedNode(usize),
+    /// Consecutive ants start at consecutive nodes, continuing across
+    /// iterations, so all nodes are used equally often.
+    RoundRobin,
+    /// One ant per node each iteration, whatever `num_ants` says.
+    OnePerNode,
+}
+
+impl StartPolicy {
+    /// Ants per iteration on `n_nodes` nodes.
+    pub fn num_ants(self, config: &Config, n_nodes: usize) -> usize {
+        match self {
+            StartPolicy::OnePerNode => n_nodes,
+            _ => config.num_ants.min(n_nodes),
+        }
+    }
+
+    /// Start of ant `ant_idx` in `iteration`, `None` for a random one.
+    pub fn start_node(
+        self,
+        ant_idx: usize,
+        iteration: usize,
+        num_ants: usize,
+        n_nodes: usize,
+    ) -> Option<usize> {
+        match self {
+            StartPolicy::Random => None,
+            StartPolicy::FixedNode(node) => (node < n_nodes).then_some(node),
+            StartPolicy::RoundRobin => Some((iteration * num_ants + ant_idx) % n_nodes),
+            StartPolicy::OnePerNode => Some(ant_idx),
+        }
+    }
+}
+
+/// Colony variants with a part switched off, for teaching and for checking
+/// what each part contributes. [`Ablation::apply`] sets the exponents.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Ablation {
+    /// `alpha = 0`: ants follow the heuristic only, trails are ignored.
+    HeuristicOnly,
+    /// `beta = 0`: ants follow the trails only, distances are ignored.
+    PheromoneOnly,
+    /// Best of as many uniformly random tours as the colony would construct
+    /// (`num_ants * num_iters`, or `tour_budget`), without any learning.
+    RandomTours,
+}
+
+impl Ablation {
+    pub const ALL: [Ablation; 3] = [
+        Ablation::HeuristicOnly,
+        Ablation::PheromoneOnly,
+        Ablation::RandomTours,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Ablation::HeuristicOnly => "heuristic-only",
+            Ablation::PheromoneOnly => "pheromone-only",
+            Ablation::RandomTours => "random",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Ablation::ALL
+            .into_iter()
+            .find(|ablation| ablation.name() == name)
+    }
+
+    /// Switches `config` to this variant.
+    pub fn apply(self, config: &mut Config) {
+        match self {
+            Ablation::HeuristicOnly => config.alpha = 0.0,
+            Ablation::PheromoneOnly => config.beta = 0.0,
+            Ablation::RandomTours => (config.alpha, config.beta) = (0.0, 0.0),
+        }
+        config.ablation = Some(self);
+    }
+}
+
+/// Disturbs converged pheromone trails so the colony explores again; applied
+/// with [`AcoSolver::perturb_pheromone`] or on a [`ReheatSchedule`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Perturbation {
+    /// Moves every level the given fraction (0 to 1) of the way back to the
+    /// initial level; 1 is a full restart of the trails.
+    Blend(f64),
+    /// Scales every level by an independent random factor in
+    /// `[1 - amplitude, 1 + amplitude]`.
+    Noise(f64),
+}
+
+/// When the solver reheats its trails by itself.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReheatTrigger {
+    /// After every given number of iterations.
+    Every(usize),
+    /// Whenever a [`SolverEvent::Stagnation`] is due (`Config::stagnation_limit`).
+    OnStagnation,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReheatSchedule {
+    pub perturbation: Perturbation,
+    pub trigger: ReheatTrigger,
+}
+
+/// How the trails evaporate each iteration, before the deposit.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum EvaporationModel {
+    /// Every trail loses the fraction `evap_rate`.
+    #[default]
+    Uniform,
+    /// Trails evaporate in proportion to how many of the iteration's ants
+    /// used the edge, at `evap_rate` for the most used edges; edges no ant
+    /// took keep their trail. Counters early convergence on a few edges.
+    Usage,
+    /// Partial evaporation by rank: edges of the `k` best tours of the
+    /// iteration evaporate at `evap_rate * r / k`, `r` being the rank
+    /// (from 0) of the best tour containing them; all others at `evap_rate`.
+    Rank(usize),
+}
+
+/// Single-tour pheromone update of MAX-MIN Ant System: instead of every ant
+/// plus the elitist global best, one tour deposits per iteration, either the
+/// iteration best or the global best.
+#[derive(Clone)]
+pub enum DepositSchedule {
+    IterationBest,
+    GlobalBest,
+    /// Stützle and Hoos' schedule: iteration best for the first 25
+    /// iterations, then global best every 5th iteration up to 75, every 3rd
+    /// up to 125, every 2nd up to 250 and always afterwards.
+    Mmas,
+    /// Whether the global best deposits in the given iteration.
+    Custom(Arc<dyn Fn(usize) -> bool + Send + Sync>),
+}
+
+impl DepositSchedule {
+    pub fn uses_global_best(&self, iteration: usize) -> bool {
+        match self {
+            DepositSchedule::IterationBest => false,
+            DepositSchedule::GlobalBest => true,
+            DepositSchedule::Mmas => {
+                let every = match iteration {
+                    0..25 => return false,
+                    25..75 => 5,
+                    75..125 => 3,
+                    125..250 => 2,
+                    _ => 1,
+                };
+                iteration.is_multiple_of(every)
+            }
+            DepositSchedule::Custom(schedule) => schedule(iteration),
+        }
+    }
+}
+
+impl fmt::Debug for DepositSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DepositSchedule::IterationBest => write!(f, "IterationBest"),
+            DepositSchedule::GlobalBest => write!(f, "GlobalBest"),
+            DepositSchedule::Mmas => write!(f, "Mmas"),
+            DepositSchedule::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
+}
+
+/// Heuristic desirability `eta_ij` used alongside the pheromone when ants
+/// pick their next node.
+#[derive(Clone)]
+pub enum HeuristicStrategy {
+    /// `1 / d_ij`, with zero distances clamped to a tiny epsilon.
+    InverseDistance,
+    /// `1 / (d_ij + c)`, well-defined for matrices containing zeros.
+    InverseDistanceOffset(f64),
+    /// Clarke-Wright savings relative to node 0, `d_i0 + d_0j - d_ij`. Edges
+    /// touching node 0 have no savings and fall back to `1 / d_ij`.
+    Savings,
+    /// Arbitrary function of `(i, j, d_ij)`.
+    Custom(Arc<dyn Fn(usize, usize, f64) -> f64 + Send + Sync>),
+}
+
+impl HeuristicStrategy {
+    pub fn heuristic_matrix(&self, instance: &TspInstance) -> Vec<Vec<f64>> {
+        let n_nodes = instance.dimension;
+        let mut matrix = vec![vec![0.0f64; n_nodes]; n_nodes];
+        for (i, row) in matrix.iter_mut().enumerate() {
+            for (j, eta) in row.iter_mut().enumerate() {
+                if i != j {
+                    *eta = self.eta(&instance.dist_matrix, i, j);
+                }
+            }
+        }
+        matrix
+    }
+
+    /// Heuristic value of the single edge `i`-`j` (`i != j`).
+    pub fn eta(&self, dist_matrix: &DistanceMatrix, i: usize, j: usize) -> f64 {
+        let inverse = |dist: f64| if dist > 1e-9 { 1.0 / dist } else { 1.0 / 1e-9 };
+        let dist = dist_matrix.get(i, j);
+        match self {
+            HeuristicStrategy::InverseDistance => inverse(dist),
+            HeuristicStrategy::InverseDistanceOffset(c) => inverse(dist + c),
+            HeuristicStrategy::Savings if i == 0 || j == 0 => inverse(dist),
+            HeuristicStrategy::Savings => {
+                (dist_matrix.get(i, 0) + dist_matrix.get(0, j) - dist).max(1e-9)
+            }
+            HeuristicStrategy::Custom(f) => f(i, j, dist),
+        }
+    }
+
+    /// Whether both produce the same values: equal variants and parameters,
+    /// or the very same custom function.
+    pub(crate) fn same_as(&self, other: &HeuristicStrategy) -> bool {
+        match (self, other) {
+            (HeuristicStrategy::InverseDistance, HeuristicStrategy::InverseDistance)
+            | (HeuristicStrategy::Savings, HeuristicStrategy::Savings) => true,
+            (
+                HeuristicStrategy::InverseDistanceOffset(a),
+                HeuristicStrategy::InverseDistanceOffset(b),
+            ) => a.to_bits() == b.to_bits(),
+            (HeuristicStrategy::Custom(f), HeuristicStrategy::Custom(g)) => Arc::ptr_eq(f, g),
+            _ => false,
+        }
+    }
+}
+
+/// Heuristic values the ants choose by. With candidate lists only the
+/// listed neighbours of each node are stored: `values[i][c]` belongs to the
+/// edge from `i` to `candidates[i][c]`. Otherwise `values` is the dense
+/// heuristic matrix.
+pub(crate) struct HeuristicTable {
+    values: Vec<Vec<f64>>,
+    candidates: Option<Vec<Vec<usize>>>,
+}
+
+impl HeuristicTable {
+    pub(crate) fn build(instance: &TspInstance, config: &Config) -> Self {
+        if config.candidate_list_size == 0 {
+            return HeuristicTable {
+                values: config.heuristic.heuristic_matrix(instance),
+                candidates: None,
+            };
+        }
+        let dist_matrix = &instance.dist_matrix;
+        let candidates = config
+            .spatial_pruning
+            .then(|| spatial::grid_nearest_neighbors(instance, config.candidate_list_size))
+            .flatten()
+            .unwrap_or_else(|| nearest_neighbors(dist_matrix, config.candidate_list_size));
+        let values = candidates
+            .par_iter()
+            .enumerate()
+            .map(|(i, list)| {
+                list.iter()
+                    .map(|&j| config.heuristic.eta(dist_matrix, i, j))
+                    .collect()
+            })
+            .collect();
+        HeuristicTable {
+            values,
+            candidates: Some(candidates),
+        }
+    }
+}
+
+/// The `k` nearest neighbours of every node, closest first.
+fn nearest_neighbors(dist_matrix: &DistanceMatrix, k: usize) -> Vec<Vec<usize>> {
+    let n = dist_matrix.dimension();
+    (0..n)
+        .into_par_iter()
+        .map(|i| {
+            let mut others: Vec<(f64, usize)> = (0..n)
+                .filter(|&j| j != i)
+                .map(|j| (dist_matrix.get(i, j), j))
+                .collect();
+            if k < others.len() {
+                others.select_nth_unstable_by(k, |a, b| a.0.total_cmp(&b.0));
+                others.truncate(k);
+            }
+            others.sort_by(|a, b| a.0.total_cmp(&b.0));
+            others.into_iter().map(|(_, j)| j).collect()
+        })
+        .collect()
+}
+
+impl fmt::Debug for HeuristicStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HeuristicStrategy::InverseDistance => write!(f, "InverseDistance"),
+            HeuristicStrategy::InverseDistanceOffset(c) => {
+                write!(f, "InverseDistanceOffset({})", c)
+            }
+            HeuristicStrategy::Savings => write!(f, "Savings"),
+            HeuristicStrategy::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
+}
+
+/// Extra cost of passing through `current` between `prev` and `next`, added
+/// to the tour length, e.g. for sharp turns on roads or drill paths.
+#[derive(Clone)]
+pub struct TurnPenalty {
+    pub penalty: Arc<dyn Fn(usize, usize, usize) -> f64 + Send + Sync>,
+    pub in_heuristic: bool, // Also steer ants away from penalized transitions
+}
+
+impl TurnPenalty {
+    /// Penalty of `weight` per radian of direction change at every node.
+    pub fn turn_angle(nodes: &[Node], weight: f64) -> Self {
+        let points: Vec<(f64, f64)> = nodes.iter().map(|node| (node.x, node.y)).collect();
+        let penalty = move |prev: usize, current: usize, next: usize| {
+            let (px, py) = points[prev];
+            let (cx, cy) = points[current];
+            let (nx, ny) = points[next];
+            let (ax, ay) = (cx - px, cy - py);
+            let (bx, by) = (nx - cx, ny - cy);
+            let norms = (ax * ax + ay * ay).sqrt() * (bx * bx + by * by).sqrt();
+            if norms < 1e-12 {
+                return 0.0;
+            }
+            weight * ((ax * bx + ay * by) / norms).clamp(-1.0, 1.0).acos()
+        };
+        TurnPenalty {
+            penalty: Arc::new(penalty),
+            in_heuristic: false,
+        }
+    }
+
+    /// Summed penalty over all nodes of the closed `tour`.
+    pub fn tour_penalty(&self, tour: &[usize]) -> f64 {
+        let n = tour.len();
+        if n < 3 {
+            return 0.0;
+        }
+        (0..n)
+            .map(|k| (self.penalty)(tour[(k + n - 1) % n], tour[k], tour[(k + 1) % n]))
+            .sum()
+    }
+}
+
+impl fmt::Debug for TurnPenalty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "TurnPenalty {{ in_heuristic: {} }}", self.in_heuristic)
+    }
+}
+
+impl fmt::Display for InitStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InitStrategy::Constant(value) => write!(f, "{:.2}", value),
+            InitStrategy::FromNearestNeighbor => write!(f, "m/(rho*L_nn)"),
+            InitStrategy::FromGreedy => write!(f, "m/(rho*L_greedy)"),
+            InitStrategy::FromTour(_) => write!(f, "m/(rho*L_tour)"),
+        }
+    }
+}
 
 pub struct Ant {
     tour: Vec<usize>,
     visited: Vec<bool>,
     current_node_idx: usize,
     tour_length: f64,
+    cost: i64, // Integer tour length, only maintained in integer cost mode
 }
 
 impl Ant {
@@ -26,6 +454,7 @@ impl Ant {
             visited,
             current_node_idx: start_node,
             tour_length: 0.0,
+            cost: 0,
         }
     }
 
@@ -42,162 +471,1676 @@ impl Ant {
     }
 }
 
-pub fn solve_tsp_aco(instance: &TspInstance, config: &Config) -> (Vec<usize>, f64) {
-    let n_nodes = instance.dimension;
-    if n_nodes == 0 {
-        return (Vec::new(), 0.0);
+/// `ln(tau^alpha * eta^beta)`. A zero exponent contributes nothing, even
+/// for zero or infinite factors, as `x^0 = 1` does in the direct product.
+fn log_attractiveness(tau: f64, eta: f64, alpha: f64, beta: f64) -> f64 {
+    let term = |x: f64, exponent: f64| {
+        if exponent == 0.0 {
+            0.0
+        } else {
+            exponent * x.ln()
+        }
+    };
+    term(tau, alpha) + term(eta, beta)
+}
+
+/// Turns log weights into weights relative to the largest, `exp(w - max)`,
+/// and returns them with their sum. Nodes with a weight of zero (`-inf`) or
+/// an undefined one (NaN) are dropped.
+fn softmax_weights(log_weights: Vec<(usize, f64)>) -> (Vec<(usize, f64)>, f64) {
+    let max = log_weights
+        .iter()
+        .map(|&(_, w)| w)
+        .filter(|w| !w.is_nan())
+        .fold(f64::NEG_INFINITY, f64::max);
+    if !max.is_finite() {
+        // Everything is zero, or some weights are infinite even in log
+        // space: choose among the most attractive nodes uniformly.
+        let top: Vec<(usize, f64)> = log_weights
+            .into_iter()
+            .filter(|&(_, w)| w == max && max == f64::INFINITY)
+            .map(|(j, _)| (j, 1.0))
+            .collect();
+        let sum = top.len() as f64;
+        return (top, sum);
     }
-    if n_nodes == 1 {
-        return (vec![0], 0.0);
+    let weights: Vec<(usize, f64)> = log_weights
+        .into_iter()
+        .filter(|&(_, w)| w > f64::NEG_INFINITY)
+        .map(|(j, w)| (j, (w - max).exp()))
+        .collect();
+    let sum = weights.iter().map(|&(_, w)| w).sum();
+    (weights, sum)
+}
+
+/// Random generator for one ant. With `Config::seed` set it depends only on
+/// the seed, the iteration and the ant's index, so seeded runs are
+/// reproducible however rayon schedules the ants.
+pub(crate) fn ant_rng(seed: Option<u64>, iteration: usize, ant_idx: usize) -> StdRng {
+    match seed {
+        Some(seed) => {
+            // SplitMix64 finalizer to decorrelate neighbouring streams.
+            let mut z = seed
+                ^ (iteration as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+                ^ (ant_idx as u64).wrapping_mul(0xD1B5_4A32_D192_ED03);
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            StdRng::seed_from_u64(z ^ (z >> 31))
+        }
+        None => StdRng::from_rng(&mut rand::rng()),
     }
+}
 
-    let dist_matrix = &instance.dist_matrix;
-    let heuristic_matrix = {
-        let mut matrix = vec![vec![0.0f64; n_nodes]; n_nodes];
-        for i in 0..n_nodes {
-            for j in 0..n_nodes {
-                if i != j {
-                    let dist = dist_matrix[i][j];
-                    matrix[i][j] = if dist > 1e-9 { 1.0 / dist } else { 1.0 / 1e-9 };
-                }
-            }
+#[allow(clippy::too_many_arguments)]
+fn construct_ant(
+    n_nodes: usize,
+    dist_matrix: &DistanceMatrix,
+    heuristic: &HeuristicTable,
+    pheromone_matrix: &[Vec<Pheromone>],
+    config: &Config,
+    constraints: &TourConstraints,
+    start: Option<usize>,
+    rng: &mut StdRng,
+) -> Ant {
+    // Locked nodes are only ever placed at their own position, chains are
+    // only entered at their head and fixed paths at their ends.
+    let locks = constraints.locks.as_ref();
+    let nothing_visited = vec![false; n_nodes];
+    let start = start.filter(|&node| constraints.can_enter(node, 0, &nothing_visited));
+    let start_node = match (locks.and_then(|l| l.node_at(0)), start) {
+        (Some(node), _) | (None, Some(node)) => node,
+        (None, None) if constraints.is_empty() && n_nodes > 0 => rng.random_range(0..n_nodes),
+        (None, None) => {
+            let free: Vec<usize> = (0..n_nodes)
+                .filter(|&i| constraints.can_enter(i, 0, &nothing_visited))
+                .collect();
+            free.choose(rng).copied().unwrap_or(0)
         }
-        matrix
     };
+    let mut ant = Ant::new(start_node, n_nodes);
+    let mut weights = if heuristic.candidates.is_some() {
+        Vec::new()
+    } else {
+        vec![0.0; n_nodes]
+    };
+
+    for _step in 1..n_nodes {
+        let current_node = ant.current_node_idx;
+        let position = ant.tour.len();
+        let forced = locks
+            .and_then(|l| l.node_at(position))
+            .or_else(|| constraints.successor(current_node, &ant.visited));
+        if let Some(forced) = forced {
+            ant.visit_node(forced, dist_matrix.get(current_node, forced));
+            continue;
+        }
+        let mut choices: Vec<(usize, f64)> = Vec::with_capacity(n_nodes);
+        let mut current_choices_sum = 0.0;
+        // Allowed next nodes with their heuristic, and whether a weight
+        // overflowed, for the log-space retry below.
+        let mut admissible: Vec<(usize, f64)> = Vec::new();
+        let mut overflow = false;
 
-    let mut pheromone_matrix = vec![vec![config.init_pheromone; n_nodes]; n_nodes];
-    let mut best_tour_overall: Vec<usize> = Vec::with_capacity(n_nodes);
-    let mut best_tour_length_overall = f64::MAX;
-
-    for iteration in 0..config.num_iters {
-        let ants: Vec<Ant> = (0..config.num_ants.min(n_nodes))
-            .into_par_iter()
-            .map(|_| {
-                let mut rng = rand::rng();
-                let start_node = if n_nodes > 0 {
-                    rng.random_range(0..n_nodes)
-                } else {
-                    0
+        // Turn penalties depend on the previous node, so they scale eta_ij
+        // to 1 / (d_ij + penalty) here rather than in the heuristic.
+        let turn = config
+            .turn_penalty
+            .as_ref()
+            .filter(|t| t.in_heuristic && position >= 2);
+        let turn_factor = |turn: &TurnPenalty, next_node_idx: usize| {
+            let prev = ant.tour[position - 2];
+            let penalty = (turn.penalty)(prev, current_node, next_node_idx);
+            if penalty > 0.0 {
+                let dist = dist_matrix.get(current_node, next_node_idx).max(1e-9);
+                (dist / (dist + penalty)).powf(config.beta)
+            } else {
+                1.0
+            }
+        };
+        let log_turn_factor = |turn: &TurnPenalty, next_node_idx: usize| {
+            let prev = ant.tour[position - 2];
+            let penalty = (turn.penalty)(prev, current_node, next_node_idx);
+            if penalty > 0.0 {
+                let dist = dist_matrix.get(current_node, next_node_idx).max(1e-9);
+                config.beta * (dist / (dist + penalty)).ln()
+            } else {
+                0.0
+            }
+        };
+
+        if let Some(candidates) = &heuristic.candidates {
+            let row = candidates[current_node]
+                .iter()
+                .zip(&heuristic.values[current_node]);
+            for (&next_node_idx, &eta) in row {
+                if ant.visited[next_node_idx]
+                    || !constraints.can_enter(next_node_idx, position, &ant.visited)
+                {
+                    continue;
+                }
+                admissible.push((next_node_idx, eta));
+                let pheromone = widen(pheromone_matrix[current_node][next_node_idx]);
+                let mut prob_num = pheromone.powf(config.alpha) * eta.powf(config.beta);
+                if let Some(turn) = turn {
+                    prob_num *= turn_factor(turn, next_node_idx);
+                }
+                overflow |= prob_num.is_infinite();
+                if prob_num.is_finite() && prob_num > 1e-12 {
+                    choices.push((next_node_idx, prob_num));
+                    current_choices_sum += prob_num;
+                }
+            }
+            if admissible.is_empty() {
+                // All candidates are visited: move to the most attractive
+                // remaining node, as Ant Colony System does.
+                let score = |j: usize| {
+                    log_attractiveness(
+                        widen(pheromone_matrix[current_node][j]),
+                        config.heuristic.eta(dist_matrix, current_node, j),
+                        config.alpha,
+                        config.beta,
+                    )
                 };
-                let mut ant = Ant::new(start_node, n_nodes);
-
-                for _step in 1..n_nodes {
-                    let current_node = ant.current_node_idx;
-                    let mut choices: Vec<(usize, f64)> = Vec::with_capacity(n_nodes);
-                    let mut current_choices_sum = 0.0;
-
-                    for next_node_idx in 0..n_nodes {
-                        if !ant.visited[next_node_idx] {
-                            // Read from shared matrices
-                            let pheromone = pheromone_matrix[current_node][next_node_idx];
-                            let heuristic = heuristic_matrix[current_node][next_node_idx];
-                            let prob_num =
-                                pheromone.powf(config.alpha) * heuristic.powf(config.beta);
-
-                            if prob_num.is_finite() && prob_num > 1e-12 {
-                                choices.push((next_node_idx, prob_num));
-                                current_choices_sum += prob_num;
-                            }
-                        }
+                let best = (0..n_nodes)
+                    .filter(|&j| {
+                        !ant.visited[j] && constraints.can_enter(j, position, &ant.visited)
+                    })
+                    .max_by(|&a, &b| score(a).total_cmp(&score(b)));
+                if let Some(next_node_idx) = best {
+                    ant.visit_node(next_node_idx, dist_matrix.get(current_node, next_node_idx));
+                    continue;
+                }
+            }
+        } else {
+            kernels::attractiveness(
+                &pheromone_matrix[current_node],
+                &heuristic.values[current_node],
+                config.alpha,
+                config.beta,
+                &mut weights,
+            );
+            if let Some(turn) = turn {
+                for (next_node_idx, weight) in weights.iter_mut().enumerate() {
+                    if !ant.visited[next_node_idx] {
+                        *weight *= turn_factor(turn, next_node_idx);
                     }
+                }
+            }
+            let etas = &heuristic.values[current_node];
+            for (next_node_idx, &prob_num) in weights.iter().enumerate() {
+                if ant.visited[next_node_idx]
+                    || !constraints.can_enter(next_node_idx, position, &ant.visited)
+                {
+                    continue;
+                }
+                admissible.push((next_node_idx, etas[next_node_idx]));
+                overflow |= prob_num.is_infinite();
+                if prob_num.is_finite() && prob_num > 1e-12 {
+                    choices.push((next_node_idx, prob_num));
+                    current_choices_sum += prob_num;
+                }
+            }
+        }
+
+        if !admissible.is_empty() && (overflow || current_choices_sum < 1e-12) {
+            // Extreme alpha or beta over- or underflowed the products: redo
+            // them in log space, relative to the most attractive node.
+            let log_weights = admissible.iter().map(|&(next_node_idx, eta)| {
+                let pheromone = widen(pheromone_matrix[current_node][next_node_idx]);
+                let mut log_weight = log_attractiveness(pheromone, eta, config.alpha, config.beta);
+                if let Some(turn) = turn {
+                    log_weight += log_turn_factor(turn, next_node_idx);
+                }
+                (next_node_idx, log_weight)
+            });
+            (choices, current_choices_sum) = softmax_weights(log_weights.collect());
+        }
+
+        if choices.is_empty() || current_choices_sum < 1e-12 {
+            let unvisited: Vec<usize> = (0..n_nodes)
+                .filter(|&i| !ant.visited[i] && constraints.can_enter(i, position, &ant.visited))
+                .collect();
+            if let Some(&fallback_node) = unvisited.choose(rng) {
+                ant.visit_node(fallback_node, dist_matrix.get(current_node, fallback_node));
+            } else {
+                break;
+            }
+        } else {
+            let rand_val = rng.random::<f64>() * current_choices_sum;
+            let mut cumulative_prob = 0.0;
+            let mut chosen_node = choices[0].0;
+            for (node_idx, prob_val) in &choices {
+                cumulative_prob += *prob_val;
+                if rand_val <= cumulative_prob {
+                    chosen_node = *node_idx;
+                    break;
+                }
+            }
+            ant.visit_node(chosen_node, dist_matrix.get(current_node, chosen_node));
+        }
+    }
+    // Complete the tour by adding distance to return to start
+    if ant.tour_completed(n_nodes) {
+        let last_node = ant.current_node_idx;
+        let start_node = ant.tour[0];
+        ant.tour_length += dist_matrix.get(last_node, start_node);
+    }
+    ant
+}
+
+/// Runs the configured local search pipeline on `tour` and returns the new
+/// length. With constraints the result is only kept if it still satisfies
+/// them; otherwise the tour is left untouched and `None` is returned.
+fn improve_tour(
+    config: &Config,
+    tour: &mut Vec<usize>,
+    instance: &TspInstance,
+    constraints: &TourConstraints,
+) -> Option<f64> {
+    if constraints.is_empty() {
+        return Some(local_search::apply_pipeline(
+            &config.local_search,
+            tour,
+            instance,
+        ));
+    }
+    let mut candidate = tour.clone();
+    let length = local_search::apply_pipeline(&config.local_search, &mut candidate, instance);
+    constraints.is_satisfied(&candidate).then(|| {
+        *tour = candidate;
+        length
+    })
+}
 
-                    if choices.is_empty() || current_choices_sum < 1e-12 {
-                        let unvisited: Vec<usize> =
-                            (0..n_nodes).filter(|&i| !ant.visited[i]).collect();
-                        if let Some(&fallback_node) = unvisited.choose(&mut rng) {
-                            ant.visit_node(fallback_node, dist_matrix[current_node][fallback_node]);
-                        } else {
-                            break;
-                        }
+fn build_int_matrix(instance: &TspInstance) -> Vec<Vec<i64>> {
+    let n_nodes = instance.dimension;
+    (0..n_nodes)
+        .map(|i| {
+            (0..n_nodes)
+                .map(|j| {
+                    let dist = instance.dist_matrix.get(i, j);
+                    if instance.distance_policy == DistancePolicy::Exact {
+                        instance.edge_weight_type.tsplib_round(dist)
                     } else {
-                        let rand_val = rng.random::<f64>() * current_choices_sum;
-                        let mut cumulative_prob = 0.0;
-                        let mut chosen_node = choices[0].0;
-                        for (node_idx, prob_val) in &choices {
-                            cumulative_prob += *prob_val;
-                            if rand_val <= cumulative_prob {
-                                chosen_node = *node_idx;
-                                break;
-                            }
-                        }
-                        ant.visit_node(chosen_node, dist_matrix[current_node][chosen_node]);
+                        dist.round() as i64
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Length of `tour` as the solver compares tours: the rounded integer cost
+/// in integer mode, plus any turn penalties. The second value is the integer
+/// cost and only meaningful in integer mode.
+fn evaluate(
+    instance: &TspInstance,
+    int_matrix: Option<&[Vec<i64>]>,
+    config: &Config,
+    tour: &[usize],
+) -> (f64, i64) {
+    let penalty = config
+        .turn_penalty
+        .as_ref()
+        .map_or(0.0, |turn| turn.tour_penalty(tour));
+    match int_matrix {
+        Some(int_matrix) => {
+            let cost = tour_cost(int_matrix, tour).saturating_add(penalty.round() as i64);
+            (cost as f64, cost)
+        }
+        None => (instance.tour_length(tour) + penalty, 0),
+    }
+}
+
+/// `Config::q_val`, times the nearest-neighbour tour length with
+/// `Config::normalize_deposit`: a tour as long as that one then deposits
+/// `q_val` whatever the scale of the distances.
+fn deposit_factor(instance: &TspInstance, config: &Config) -> f64 {
+    if !config.normalize_deposit || instance.dimension < 2 {
+        return config.q_val;
+    }
+    let nn_length = instance.tour_length(&construction::nearest_neighbor_tour(instance, 0));
+    if nn_length > 1e-9 && nn_length.is_finite() {
+        config.q_val * nn_length
+    } else {
+        config.q_val
+    }
+}
+
+// Saturates rather than wrapping for huge or infinite (`i64::MAX`) weights.
+fn tour_cost(int_matrix: &[Vec<i64>], tour: &[usize]) -> i64 {
+    tour.iter()
+        .zip(tour.iter().cycle().skip(1))
+        .fold(0, |cost, (&a, &b)| cost.saturating_add(int_matrix[a][b]))
+}
+
+pub fn solve_tsp_aco(instance: &TspInstance, config: &Config) -> (Vec<usize>, f64) {
+    AcoSolver::new(instance, config).run()
+}
+
+/// The [`Ablation::RandomTours`] baseline for `config`: the best of
+/// `num_ants * num_iters` random tours (`tour_budget`, if set), drawn from
+/// the configured seed.
+pub fn solve_random(instance: &TspInstance, config: &Config) -> (Vec<usize>, f64) {
+    let samples = config
+        .tour_budget
+        .unwrap_or(config.num_ants.saturating_mul(config.num_iters));
+    let (tour, length) =
+        construction::random_baseline(instance, samples, &mut ant_rng(config.seed, 0, 0));
+    (tour, length.round())
+}
+
+/// Largest instance [`solve_brute_force`] accepts; 10! orderings remain.
+pub const BRUTE_FORCE_MAX_DIMENSION: usize = 11;
+
+/// Exact solution by depth-first enumeration of all tours starting at node
+/// 0, pruning partial tours already longer than the incumbent. Meant for
+/// correctness checks on tiny instances (at most
+/// [`BRUTE_FORCE_MAX_DIMENSION`] nodes). The length is exact, not rounded.
+pub fn solve_brute_force(instance: &TspInstance) -> Result<(Vec<usize>, f64), String> {
+    let n = instance.dimension;
+    if n > BRUTE_FORCE_MAX_DIMENSION {
+        return Err(format!(
+            "Brute force is limited to {} nodes, instance has {}",
+            BRUTE_FORCE_MAX_DIMENSION, n
+        ));
+    }
+    if n < 2 {
+        return Ok(((0..n).collect(), 0.0));
+    }
+
+    fn extend(
+        instance: &TspInstance,
+        tour: &mut Vec<usize>,
+        visited: &mut [bool],
+        length: f64,
+        best: &mut (Vec<usize>, f64),
+    ) {
+        let n = visited.len();
+        let last = tour[tour.len() - 1];
+        if tour.len() == n {
+            let total = length + instance.dist_matrix.get(last, tour[0]);
+            if total < best.1 {
+                best.0.clone_from(tour);
+                best.1 = total;
+            }
+            return;
+        }
+        for next in 1..n {
+            if visited[next] {
+                continue;
+            }
+            let partial = length + instance.dist_matrix.get(last, next);
+            if partial >= best.1 {
+                continue;
+            }
+            visited[next] = true;
+            tour.push(next);
+            extend(instance, tour, visited, partial, best);
+            tour.pop();
+            visited[next] = false;
+        }
+    }
+
+    let mut visited = vec![false; n];
+    visited[0] = true;
+    let mut best = (Vec::new(), f64::MAX);
+    extend(instance, &mut vec![0], &mut visited, 0.0, &mut best);
+    Ok(best)
+}
+
+/// Solves several instances at once, distributing whole instances across the
+/// rayon thread pool instead of the ants within a single instance. This is the
+/// better fit for benchmark sweeps over many small instances. Results are
+/// returned in the same order as `instances`.
+pub fn solve_many(instances: &[TspInstance], config: &Config) -> Vec<(Vec<usize>, f64)> {
+    instances
+        .par_iter()
+        .map(|instance| solve_quiet(&PreparedInstance::new(instance), config))
+        .collect()
+}
+
+/// Like [`solve_tsp_aco`], but constructs ants sequentially and prints
+/// nothing; for callers that parallelize over whole runs instead.
+pub(crate) fn solve_quiet(prepared: &PreparedInstance, config: &Config) -> (Vec<usize>, f64) {
+    AcoSolver::quiet(prepared, config).run()
+}
+
+/// Storage type of pheromone levels: `f32` with the `f32-pheromone` feature,
+/// halving pheromone memory and bandwidth on big instances, `f64` otherwise.
+/// Pheromone is noisy by nature, so single precision loses nothing that
+/// matters; all arithmetic on levels is still done in `f64`.
+#[cfg(feature = "f32-pheromone")]
+pub type Pheromone = f32;
+#[cfg(not(feature = "f32-pheromone"))]
+pub type Pheromone = f64;
+
+/// Widens a stored pheromone level for arithmetic.
+#[inline(always)]
+#[allow(clippy::useless_conversion)] // Identity unless `f32-pheromone` is enabled.
+pub(crate) fn widen(level: Pheromone) -> f64 {
+    f64::from(level)
+}
+
+/// Adds `amount` to `level` in `f64`, saturating at the largest storable value.
+#[inline]
+fn deposit(level: &mut Pheromone, amount: f64) {
+    *level = (widen(*level) + amount).min(widen(Pheromone::MAX)) as Pheromone;
+}
+
+// Edges of the closed tour as stored in the pheromone matrix: `(min, max)`
+// pairs unless `directed`.
+fn tour_edges(tour: &[usize], directed: bool) -> impl Iterator<Item = (usize, usize)> + '_ {
+    tour.iter()
+        .zip(tour.iter().cycle().skip(1))
+        .map(move |(&a, &b)| {
+            if directed {
+                (a, b)
+            } else {
+                (a.min(b), a.max(b))
+            }
+        })
+}
+
+// Scales the trail of an edge, in both directions unless `directed`.
+fn scale_edge(
+    pheromone_matrix: &mut [Vec<Pheromone>],
+    (a, b): (usize, usize),
+    retain: f64,
+    min: f64,
+    directed: bool,
+) {
+    let level = (widen(pheromone_matrix[a][b]) * retain).max(min) as Pheromone;
+    pheromone_matrix[a][b] = level;
+    if !directed {
+        pheromone_matrix[b][a] = level;
+    }
+}
+
+fn evaporate_by_usage(
+    pheromone_matrix: &mut [Vec<Pheromone>],
+    tours: &[&[usize]],
+    config: &Config,
+    directed: bool,
+) {
+    let mut usage: HashMap<(usize, usize), usize> = HashMap::new();
+    for tour in tours {
+        for edge in tour_edges(tour, directed) {
+            *usage.entry(edge).or_default() += 1;
+        }
+    }
+    let most = usage.values().copied().max().unwrap_or(1) as f64;
+    for (edge, count) in usage {
+        let retain = 1.0 - config.evap_rate * count as f64 / most;
+        scale_edge(
+            pheromone_matrix,
+            edge,
+            retain,
+            config.min_pheromone_val,
+            directed,
+        );
+    }
+}
+
+fn evaporate_by_rank(
+    pheromone_matrix: &mut [Vec<Pheromone>],
+    ants: &[Ant],
+    n_nodes: usize,
+    k: usize,
+    config: &Config,
+    directed: bool,
+) {
+    let mut ranked: Vec<&Ant> = ants
+        .iter()
+        .filter(|ant| ant.tour_completed(n_nodes))
+        .collect();
+    ranked.sort_by(|a, b| a.tour_length.total_cmp(&b.tour_length));
+    // Best rank of every edge of the top tours, with its level before the
+    // uniform evaporation so it can be scaled by its own rate afterwards.
+    let mut rank_of: HashMap<(usize, usize), usize> = HashMap::new();
+    for (rank, ant) in ranked.iter().take(k).enumerate() {
+        for edge in tour_edges(&ant.tour, directed) {
+            rank_of.entry(edge).or_insert(rank);
+        }
+    }
+    let protected: Vec<((usize, usize), Pheromone, usize)> = rank_of
+        .into_iter()
+        .map(|((a, b), rank)| ((a, b), pheromone_matrix[a][b], rank))
+        .collect();
+    let rows_per_task = threads::evaporation_rows(n_nodes, config);
+    pheromone_matrix
+        .par_iter_mut()
+        .with_min_len(rows_per_task)
+        .for_each(|row| kernels::evaporate(row, 1.0 - config.evap_rate, config.min_pheromone_val));
+    for ((a, b), level, rank) in protected {
+        pheromone_matrix[a][b] = level;
+        if !directed {
+            pheromone_matrix[b][a] = level;
+        }
+        let retain = 1.0 - config.evap_rate * rank as f64 / k as f64;
+        scale_edge(
+            pheromone_matrix,
+            (a, b),
+            retain,
+            config.min_pheromone_val,
+            directed,
+        );
+    }
+}
+
+/// Deposits `amount` on every edge of the closed `tour`. On `directed`
+/// (asymmetric) instances only the travelled direction `a -> b` is
+/// reinforced, otherwise both.
+fn deposit_tour(
+    pheromone_matrix: &mut [Vec<Pheromone>],
+    tour: &[usize],
+    amount: f64,
+    directed: bool,
+) {
+    let n = pheromone_matrix.len();
+    if tour.len() != n {
+        return;
+    }
+    for k in 0..n {
+        let (a, b) = (tour[k], tour[(k + 1) % n]);
+        if a < n && b < n {
+            deposit(&mut pheromone_matrix[a][b], amount);
+            if !directed {
+                deposit(&mut pheromone_matrix[b][a], amount);
+            }
+        }
+    }
+}
+
+/// Whether distances depend on the direction of travel, so pheromone has to
+/// be laid per direction. Only dense storage can hold such data.
+fn is_directed(instance: &TspInstance) -> bool {
+    !instance.dist_matrix.is_symmetric_storage() && !instance.is_symmetric()
+}
+
+/// Normalized entropy of the edges used by `tours`: 1.0 when no two tours
+/// share an edge, 0.0 when every tour is identical. Values near zero mean the
+/// colony has converged and further iterations are unlikely to explore.
+pub fn edge_entropy(tours: &[&[usize]]) -> f64 {
+    let m = tours.len();
+    let n = tours.first().map_or(0, |tour| tour.len());
+    if m < 2 || n < 3 {
+        return 0.0;
+    }
+    let mut edges: Vec<(usize, usize)> = tours
+        .iter()
+        .flat_map(|tour| {
+            (0..tour.len()).map(move |k| {
+                let (a, b) = (tour[k], tour[(k + 1) % tour.len()]);
+                (a.min(b), a.max(b))
+            })
+        })
+        .collect();
+    edges.sort_unstable();
+    let total = edges.len() as f64;
+    let entropy: f64 = edges
+        .chunk_by(|a, b| a == b)
+        .map(|run| {
+            let p = run.len() as f64 / total;
+            -p * p.ln()
+        })
+        .sum();
+    // Identical tours give ln(n), pairwise edge-disjoint tours ln(m * n).
+    ((entropy - (n as f64).ln()) / (m as f64).ln()).clamp(0.0, 1.0)
+}
+
+/// A new global-best tour found during a run.
+#[derive(Debug, Clone)]
+pub struct Improvement {
+    pub iteration: usize,
+    pub tour: Vec<usize>,
+    pub length: f64,
+}
+
+/// Step-wise ACO solver. Each call to [`AcoSolver::step`] runs one iteration
+/// (tour construction, evaporation, deposit), so callers can inspect the
+/// incumbent between iterations or stop early.
+pub struct AcoSolver<'a> {
+    // Borrowed until the instance is modified through the dynamic TSP API.
+    instance: Cow<'a, TspInstance>,
+    config: &'a Config,
+    // Shared with other solvers of the same `PreparedInstance`.
+    heuristic: Arc<HeuristicTable>,
+    pheromone_matrix: Vec<Vec<Pheromone>>,
+    best_tour: Vec<usize>,
+    best_tour_length: f64,
+    // Integer distances and best cost for `Config::integer_costs`.
+    int_matrix: Option<Vec<Vec<i64>>>,
+    best_cost: i64,
+    constraints: TourConstraints,
+    init_pheromone: Pheromone,
+    // Deposit factor, see `deposit_factor`.
+    q: f64,
+    // Asymmetric distances, pheromone is deposited per direction.
+    directed: bool,
+    iteration: usize,
+    iteration_limit: usize,
+    // Ants per iteration; only a time budget changes it during the run.
+    num_ants: usize,
+    // Re-plans `iteration_limit` and `num_ants` under `Config::time_budget`.
+    budget: Option<BudgetPlanner>,
+    // Ant tours constructed, for `Config::tour_budget`.
+    tours: usize,
+    // `Config::stopping`, checked after the iteration limit.
+    stopping: Composite,
+    started: Instant,
+    last_improvement: usize,
+    edge_entropy: f64,
+    replay: Option<ReplayRecorder>,
+    snapshots: Option<SnapshotRecorder>,
+    // Construct ants sequentially and stay quiet (used by `solve_many`).
+    batch: bool,
+}
+
+impl<'a> AcoSolver<'a> {
+    pub fn new(instance: &'a TspInstance, config: &'a Config) -> Self {
+        let heuristic = Arc::new(HeuristicTable::build(instance, config));
+        AcoSolver::with_heuristic(instance, config, heuristic)
+    }
+
+    /// Like [`AcoSolver::new`], reusing the heuristic table of `prepared`
+    /// if it has one for `config`.
+    pub fn prepared(prepared: &PreparedInstance<'a>, config: &'a Config) -> Self {
+        AcoSolver::with_heuristic(prepared.instance(), config, prepared.heuristic(config))
+    }
+
+    fn with_heuristic(
+        instance: &'a TspInstance,
+        config: &'a Config,
+        heuristic: Arc<HeuristicTable>,
+    ) -> Self {
+        let n_nodes = instance.dimension;
+
+        let (mut best_tour, mut best_tour_length) = if n_nodes == 1 {
+            (vec![0], 0.0)
+        } else {
+            (Vec::with_capacity(n_nodes), f64::MAX)
+        };
+
+        let init_pheromone = config.init_pheromone.initial_pheromone(instance, config) as Pheromone;
+        let q = deposit_factor(instance, config);
+        let directed = is_directed(instance);
+
+        let int_matrix = config.integer_costs.then(|| build_int_matrix(instance));
+        let mut best_cost = if n_nodes == 1 { 0 } else { i64::MAX };
+
+        let mut pheromone_matrix = vec![vec![init_pheromone; n_nodes]; n_nodes];
+        if let Some(warm_start) = &config.warm_start {
+            match constraints::validate_permutation(warm_start, n_nodes) {
+                Ok(()) => {
+                    best_tour.clone_from(warm_start);
+                    (best_tour_length, best_cost) =
+                        evaluate(instance, int_matrix.as_deref(), config, warm_start);
+                    // Seed the trails with the warm start like a global-best deposit.
+                    if best_tour_length > 1e-9 {
+                        let amount = config.elitist_weight.max(1.0) * q / best_tour_length;
+                        deposit_tour(&mut pheromone_matrix, warm_start, amount, directed);
                     }
                 }
-                // Complete the tour by adding distance to return to start
-                if ant.tour_completed(n_nodes) {
-                    let last_node = ant.current_node_idx;
-                    let start_node = ant.tour[0];
-                    ant.tour_length += dist_matrix[last_node][start_node];
+                Err(e) => events::warn(
+                    &config.event_sender,
+                    format!("ignoring invalid warm start tour: {}", e),
+                ),
+            }
+        }
+        let constraints = TourConstraints::for_instance(instance, config);
+        if !best_tour.is_empty() && !constraints.is_satisfied(&best_tour) {
+            events::warn(
+                &config.event_sender,
+                "warm start tour violates the tour constraints".to_string(),
+            );
+            best_tour.clear();
+            best_tour_length = f64::MAX;
+            best_cost = i64::MAX;
+        }
+
+        let (mut iteration, mut last_improvement, mut tours) = (0, 0, 0);
+        if let Some(resume) = &config.resume {
+            if resume.dimension() != n_nodes {
+                events::warn(
+                    &config.event_sender,
+                    format!(
+                        "ignoring checkpoint for {} nodes, instance has {}",
+                        resume.dimension(),
+                        n_nodes
+                    ),
+                );
+            } else {
+                for (row, saved) in pheromone_matrix.iter_mut().zip(&resume.pheromone) {
+                    for (level, &value) in row.iter_mut().zip(saved) {
+                        *level = value as Pheromone;
+                    }
+                }
+                if !resume.best_tour.is_empty()
+                    && constraints::validate_permutation(&resume.best_tour, n_nodes).is_ok()
+                    && constraints.is_satisfied(&resume.best_tour)
+                {
+                    best_tour.clone_from(&resume.best_tour);
+                    (best_tour_length, best_cost) =
+                        evaluate(instance, int_matrix.as_deref(), config, &best_tour);
                 }
-                ant // Return the fully constructed ant
+                iteration = resume.iteration;
+                last_improvement = resume.last_improvement;
+                // Every iteration before the last one builds the full colony.
+                tours = iteration.saturating_mul(config.start_policy.num_ants(config, n_nodes));
+            }
+        }
+
+        AcoSolver {
+            instance: Cow::Borrowed(instance),
+            config,
+            heuristic,
+            pheromone_matrix,
+            best_tour,
+            best_tour_length,
+            int_matrix,
+            best_cost,
+            constraints,
+            init_pheromone,
+            q,
+            directed,
+            iteration,
+            iteration_limit: if config.time_budget.is_some() {
+                usize::MAX // Planned after the first iteration.
+            } else {
+                config.num_iters
+            },
+            num_ants: config.start_policy.num_ants(config, n_nodes),
+            budget: config.time_budget.map(|budget| {
+                BudgetPlanner::new(budget, config.start_policy.num_ants(config, n_nodes))
+            }),
+            tours,
+            stopping: Composite::any(config.stopping.clone()),
+            started: Instant::now(),
+            last_improvement,
+            edge_entropy: 1.0,
+            replay: None,
+            snapshots: None,
+            batch: false,
+        }
+    }
+
+    /// A solver that constructs ants sequentially and prints nothing, see
+    /// [`solve_quiet`].
+    pub(crate) fn quiet(prepared: &PreparedInstance<'a>, config: &'a Config) -> Self {
+        let mut solver = AcoSolver::prepared(prepared, config);
+        solver.batch = true;
+        solver
+    }
+
+    pub fn config(&self) -> &'a Config {
+        self.config
+    }
+
+    /// The instance being solved, including any dynamic modifications.
+    pub fn instance(&self) -> &TspInstance {
+        &self.instance
+    }
+
+    /// Number of iterations run so far.
+    pub fn iteration(&self) -> usize {
+        self.iteration
+    }
+
+    /// Best tour found so far (empty if no complete tour was constructed yet).
+    pub fn best_tour(&self) -> &[usize] {
+        &self.best_tour
+    }
+
+    /// Length of the best tour found so far, `f64::MAX` if none yet.
+    pub fn best_tour_length(&self) -> f64 {
+        self.best_tour_length
+    }
+
+    /// Current pheromone trail levels.
+    pub fn pheromone_matrix(&self) -> &[Vec<Pheromone>] {
+        &self.pheromone_matrix
+    }
+
+    /// Pheromone on the edge from `i` to `j`, `None` if either index is out
+    /// of bounds.
+    pub fn pheromone(&self, i: usize, j: usize) -> Option<f64> {
+        self.pheromone_matrix
+            .get(i)
+            .and_then(|row| row.get(j))
+            .map(|&level| widen(level))
+    }
+
+    /// Whether pheromone is laid per direction of travel, as on asymmetric
+    /// instances. Otherwise `pheromone(i, j) == pheromone(j, i)`.
+    pub fn is_directed(&self) -> bool {
+        self.directed
+    }
+
+    /// The `k` edges with the most pheromone as `(i, j, level)`, strongest
+    /// first (ties by node indices). Undirected colonies list each edge once
+    /// with `i < j`.
+    pub fn strongest_edges(&self, k: usize) -> Vec<(usize, usize, f64)> {
+        let n = self.pheromone_matrix.len();
+        let mut edges: Vec<(usize, usize, f64)> = (0..n)
+            .flat_map(|i| {
+                let first = if self.directed { 0 } else { i + 1 };
+                (first..n)
+                    .filter(move |&j| j != i)
+                    .map(move |j| (i, j, widen(self.pheromone_matrix[i][j])))
             })
-            .collect(); // Collect all ants processed
+            .collect();
+        let by_level = |a: &(usize, usize, f64), b: &(usize, usize, f64)| {
+            b.2.total_cmp(&a.2).then((a.0, a.1).cmp(&(b.0, b.1)))
+        };
+        if k < edges.len() {
+            edges.select_nth_unstable_by(k, by_level);
+            edges.truncate(k);
+        }
+        edges.sort_unstable_by(by_level);
+        edges
+    }
+
+    /// Total pheromone on the edges leaving each node. A node whose mass is
+    /// concentrated on few edges has settled on its neighbours.
+    pub fn node_pheromone_mass(&self) -> Vec<f64> {
+        self.pheromone_matrix
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                row.iter()
+                    .enumerate()
+                    .filter(|&(j, _)| j != i)
+                    .map(|(_, &level)| widen(level))
+                    .sum()
+            })
+            .collect()
+    }
+
+    /// Normalized edge-usage entropy of the last iteration's ant tours.
+    pub fn edge_entropy(&self) -> f64 {
+        self.edge_entropy
+    }
+
+    /// Allows `additional` more iterations beyond the current limit.
+    pub fn extend_iterations(&mut self, additional: usize) {
+        self.iteration_limit = self
+            .iteration_limit
+            .max(self.iteration)
+            .saturating_add(additional);
+    }
+
+    /// Offers an externally found tour (e.g. from another colony). It
+    /// replaces the incumbent if it is a valid permutation, respects the
+    /// tour constraints and is shorter; being the global best it then
+    /// reinforces its edges through the elitist update. Returns whether the
+    /// tour was adopted.
+    pub fn offer_tour(&mut self, tour: &[usize]) -> Result<bool, String> {
+        constraints::validate_permutation(tour, self.instance.dimension)?;
+        if !self.constraints.is_satisfied(tour) {
+            return Err("Tour violates the tour constraints".to_string());
+        }
+        let (length, cost) = evaluate(
+            &self.instance,
+            self.int_matrix.as_deref(),
+            self.config,
+            tour,
+        );
+        let better = if self.int_matrix.is_some() {
+            cost < self.best_cost
+        } else {
+            length < self.best_tour_length
+        };
+        if better {
+            self.best_tour = tour.to_vec();
+            self.best_tour_length = length;
+            self.best_cost = cost;
+        }
+        Ok(better)
+    }
+
+    /// Applies `perturbation` to all trails, keeping levels within the
+    /// bounds evaporation and deposits respect. Noise is drawn from the
+    /// configured seed and the current iteration, so scripted restarts stay
+    /// reproducible.
+    pub fn perturb_pheromone(&mut self, perturbation: Perturbation) {
+        let init = widen(self.init_pheromone);
+        let (lower, upper) = (self.config.min_pheromone_val, widen(Pheromone::MAX));
+        let mut rng = ant_rng(self.config.seed, self.iteration, usize::MAX);
+        for row in &mut self.pheromone_matrix {
+            for level in row.iter_mut() {
+                let old = widen(*level);
+                let new = match perturbation {
+                    Perturbation::Blend(fraction) => old + fraction.clamp(0.0, 1.0) * (init - old),
+                    Perturbation::Noise(amplitude) => {
+                        let amplitude = amplitude.clamp(0.0, 1.0);
+                        old * rng.random_range(1.0 - amplitude..=1.0 + amplitude)
+                    }
+                };
+                *level = new.clamp(lower, upper) as Pheromone;
+            }
+        }
+    }
+
+    /// Progress as seen by the stopping criteria.
+    pub fn search_state(&self) -> SearchState {
+        SearchState {
+            iteration: self.iteration,
+            tours: self.tours,
+            elapsed: self.started.elapsed(),
+            best_length: self.best_tour_length,
+            last_improvement: self.last_improvement,
+        }
+    }
+
+    /// Why the run is over, `None` while it continues: the iteration limit
+    /// (the tour budget, if one is set) or one of `Config::stopping` was met,
+    /// or the instance is trivial.
+    pub fn termination(&self) -> Option<Termination> {
+        if self.instance.dimension < 2 {
+            return Some(Termination::Completed);
+        }
+        let state = self.search_state();
+        let limit = match self.config.tour_budget {
+            // The iteration bound ends colonies of zero ants.
+            Some(budget) => (self.tours >= budget || self.iteration >= budget)
+                .then_some(Termination::TourBudget),
+            None => MaxIterations(self.iteration_limit)
+                .check(&state)
+                .map(|termination| match self.budget {
+                    Some(_) => Termination::TimeLimit,
+                    None => termination,
+                }),
+        };
+        limit.or_else(|| self.stopping.check(&state))
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.termination().is_some()
+    }
+
+    /// Runs a single iteration. Returns `true` if a new global best was found.
+    pub fn step(&mut self) -> bool {
+        if self.is_finished() {
+            return false;
+        }
+        let n_nodes = self.instance.dimension;
+        let config = self.config;
+        let instance: &TspInstance = &self.instance;
+        let iteration = self.iteration;
+        let iteration_started = Instant::now();
+
+        let num_ants = self.num_ants;
+        // The last iteration under a tour budget builds only what is left.
+        let colony_size = config
+            .tour_budget
+            .map_or(num_ants, |budget| num_ants.min(budget - self.tours));
+        let improve_ants =
+            !config.local_search.is_empty() && config.local_search_target.ant_tours();
+        let build_ant = |ant_idx| {
+            let mut ant = construct_ant(
+                n_nodes,
+                &instance.dist_matrix,
+                &self.heuristic,
+                &self.pheromone_matrix,
+                config,
+                &self.constraints,
+                config
+                    .start_policy
+                    .start_node(ant_idx, iteration, num_ants, n_nodes),
+                &mut ant_rng(config.seed, iteration, ant_idx),
+            );
+            if improve_ants
+                && ant.tour_completed(n_nodes)
+                && let Some(length) =
+                    improve_tour(config, &mut ant.tour, instance, &self.constraints)
+            {
+                ant.tour_length = length;
+            }
+            if ant.tour_completed(n_nodes)
+                && (self.int_matrix.is_some() || config.turn_penalty.is_some())
+            {
+                (ant.tour_length, ant.cost) =
+                    evaluate(instance, self.int_matrix.as_deref(), config, &ant.tour);
+            }
+            ant
+        };
+        let ants: Vec<Ant> = if self.batch {
+            (0..colony_size).map(build_ant).collect()
+        } else {
+            (0..colony_size)
+                .into_par_iter()
+                .with_min_len(config.ant_chunk.max(1))
+                .map(build_ant)
+                .collect()
+        };
+        self.tours += colony_size;
+
+        let tours: Vec<&[usize]> = ants
+            .iter()
+            .filter(|ant| ant.tour_completed(n_nodes))
+            .map(|ant| ant.tour.as_slice())
+            .collect();
+        self.edge_entropy = edge_entropy(&tours);
+
+        let pheromone_matrix = &mut self.pheromone_matrix;
 
         // --- Pheromone Evaporation ---
-        pheromone_matrix.par_iter_mut().for_each(|row| {
-            for val in row.iter_mut() {
-                *val *= 1.0 - config.evap_rate;
-                if *val < config.min_pheromone_val {
-                    *val = config.min_pheromone_val;
-                }
+        match config.evaporation {
+            EvaporationModel::Uniform => {
+                let rows_per_task = threads::evaporation_rows(n_nodes, config);
+                pheromone_matrix
+                    .par_iter_mut()
+                    .with_min_len(rows_per_task)
+                    .for_each(|row| {
+                        kernels::evaporate(row, 1.0 - config.evap_rate, config.min_pheromone_val)
+                    });
+            }
+            EvaporationModel::Usage => {
+                evaporate_by_usage(pheromone_matrix, &tours, config, self.directed)
             }
-        });
+            EvaporationModel::Rank(k) => {
+                evaporate_by_rank(pheromone_matrix, &ants, n_nodes, k, config, self.directed)
+            }
+        }
 
         // --- Sequential Pheromone Deposit & Best Tour Update ---
-        for ant in &ants {
+        let mut improved = false;
+        let mut iteration_best_length = f64::MAX;
+        let mut iteration_best = None;
+        for (ant_idx, ant) in ants.iter().enumerate() {
             // Pheromone Deposit
-            if ant.tour_completed(n_nodes) && ant.tour_length > 1e-9 {
-                let pheromone_to_deposit = config.q_val / ant.tour_length;
-                for k in 0..n_nodes {
-                    let node1_idx = ant.tour[k];
-                    let node2_idx = ant.tour[(k + 1) % n_nodes];
-                    if node1_idx < n_nodes && node2_idx < n_nodes {
-                        pheromone_matrix[node1_idx][node2_idx] += pheromone_to_deposit;
-                        pheromone_matrix[node2_idx][node1_idx] += pheromone_to_deposit;
-                    }
-                }
+            if config.deposit_schedule.is_none()
+                && ant.tour_completed(n_nodes)
+                && ant.tour_length > 1e-9
+            {
+                let pheromone_to_deposit = self.q / ant.tour_length;
+                deposit_tour(
+                    pheromone_matrix,
+                    &ant.tour,
+                    pheromone_to_deposit,
+                    self.directed,
+                );
             }
 
             // Update Best Tour
-            if ant.tour_completed(n_nodes) && ant.tour_length < best_tour_length_overall {
-                best_tour_length_overall = ant.tour_length;
-                best_tour_overall.clone_from(&ant.tour);
+            if ant.tour_completed(n_nodes) && ant.tour_length < iteration_best_length {
+                iteration_best_length = ant.tour_length;
+                iteration_best = Some(ant_idx);
+            }
+            let better = if self.int_matrix.is_some() {
+                ant.cost < self.best_cost
+            } else {
+                ant.tour_length < self.best_tour_length
+            };
+            // Forced positions can clash with the pairs and fixed edges, so
+            // check the result.
+            let feasible = (self.constraints.precedences.is_none()
+                && self.constraints.fixed_edges.is_none())
+                || self.constraints.is_satisfied(&ant.tour);
+            if ant.tour_completed(n_nodes) && better && feasible {
+                self.best_tour_length = ant.tour_length;
+                self.best_cost = ant.cost;
+                self.best_tour.clone_from(&ant.tour);
+                improved = true;
+            }
+        }
+
+        if improved
+            && !config.local_search.is_empty()
+            && config.local_search_target.global_best()
+            && improve_tour(
+                config,
+                &mut self.best_tour,
+                &self.instance,
+                &self.constraints,
+            )
+            .is_some()
+        {
+            (self.best_tour_length, self.best_cost) = evaluate(
+                &self.instance,
+                self.int_matrix.as_deref(),
+                config,
+                &self.best_tour,
+            );
+        }
+
+        // Ant lengths are summed edge by edge, so the incumbent's length is
+        // recomputed from the matrix now and then to keep drift out of it.
+        let recheck = (improved && config.verify_lengths)
+            || (config.length_check_interval > 0
+                && (iteration + 1).is_multiple_of(config.length_check_interval));
+        if recheck && self.best_tour.len() == n_nodes && n_nodes > 1 {
+            let (length, cost) = evaluate(
+                &self.instance,
+                self.int_matrix.as_deref(),
+                config,
+                &self.best_tour,
+            );
+            if config.verify_lengths {
+                let drift = (length - self.best_tour_length).abs();
+                assert!(
+                    drift <= 1e-9 * length.abs().max(1.0),
+                    "Best tour length {} drifted by {} from its exact length {} in iteration {}",
+                    self.best_tour_length,
+                    drift,
+                    length,
+                    iteration
+                );
             }
+            (self.best_tour_length, self.best_cost) = (length, cost);
         }
 
+        if let Some(schedule) = &config.deposit_schedule {
+            let global_best = !self.best_tour.is_empty()
+                && (schedule.uses_global_best(iteration) || iteration_best.is_none());
+            let (tour, length) = match iteration_best {
+                _ if global_best => (&self.best_tour, self.best_tour_length),
+                Some(ant_idx) => (&ants[ant_idx].tour, ants[ant_idx].tour_length),
+                None => (&self.best_tour, f64::MAX),
+            };
+            if length > 1e-9 && length < f64::MAX - 1e-9 {
+                deposit_tour(pheromone_matrix, tour, self.q / length, self.directed);
+            }
+        }
         // --- Elitist Ant System Update ---
-        if config.elitist_weight > 0.0
-            && !best_tour_overall.is_empty()
-            && best_tour_length_overall < f64::MAX - 1e-9
+        else if config.elitist_weight > 0.0
+            && !self.best_tour.is_empty()
+            && self.best_tour_length < f64::MAX - 1e-9
         {
-            let elite_pheromone_amount =
-                config.elitist_weight * config.q_val / best_tour_length_overall;
-            for k in 0..n_nodes {
-                let node1_idx = best_tour_overall[k];
-                let node2_idx = best_tour_overall[(k + 1) % n_nodes];
-                if node1_idx < n_nodes && node2_idx < n_nodes {
-                    pheromone_matrix[node1_idx][node2_idx] += elite_pheromone_amount;
-                    pheromone_matrix[node2_idx][node1_idx] += elite_pheromone_amount;
+            let elite_pheromone_amount = config.elitist_weight * self.q / self.best_tour_length;
+            deposit_tour(
+                pheromone_matrix,
+                &self.best_tour,
+                elite_pheromone_amount,
+                self.directed,
+            );
+        }
+
+        if improved {
+            self.last_improvement = iteration;
+            events::emit(
+                &config.event_sender,
+                SolverEvent::NewBest {
+                    iteration,
+                    tour: self.best_tour.clone(),
+                    length: self.best_tour_length,
+                },
+            );
+        }
+        let stale = iteration - self.last_improvement;
+        let stagnated = config.stagnation_limit > 0
+            && stale > 0
+            && stale.is_multiple_of(config.stagnation_limit);
+        if stagnated {
+            events::emit(
+                &config.event_sender,
+                SolverEvent::Stagnation {
+                    iteration,
+                    iterations_without_improvement: stale,
+                },
+            );
+        }
+        if let Some(schedule) = config.reheat {
+            let due = match schedule.trigger {
+                ReheatTrigger::Every(k) => k > 0 && (iteration + 1).is_multiple_of(k),
+                ReheatTrigger::OnStagnation => stagnated,
+            };
+            if due {
+                self.perturb_pheromone(schedule.perturbation);
+            }
+        }
+        events::emit(
+            &config.event_sender,
+            SolverEvent::IterationFinished {
+                iteration,
+                iteration_best_length,
+                best_length: self.best_tour_length,
+                edge_entropy: self.edge_entropy,
+            },
+        );
+
+        self.iteration += 1;
+        if let Some(planner) = self.budget.as_mut() {
+            let plan = planner.replan(
+                self.iteration,
+                self.started.elapsed(),
+                iteration_started.elapsed(),
+                colony_size,
+            );
+            self.iteration_limit = plan.iterations;
+            self.num_ants = plan.ants;
+        }
+        self.export_pheromones();
+        self.record_replay(iteration, improved, &ants);
+        self.record_snapshot(iteration);
+        improved
+    }
+
+    /// Appends the hashes of the pheromone matrix and best tour to
+    /// `Config::snapshot_path`. Skipped for batch solvers like replays.
+    fn record_snapshot(&mut self, iteration: usize) {
+        let Some(path) = &self.config.snapshot_path else {
+            return;
+        };
+        if self.batch {
+            return;
+        }
+        if self.snapshots.is_none() {
+            if iteration > 0 {
+                return; // Creating the file failed, already reported.
+            }
+            match SnapshotRecorder::create(path) {
+                Ok(recorder) => self.snapshots = Some(recorder),
+                Err(e) => {
+                    events::warn(&self.config.event_sender, e);
+                    return;
                 }
             }
         }
+        let snapshot = Snapshot::new(
+            iteration,
+            &self.pheromone_matrix,
+            &self.best_tour,
+            self.best_tour_length,
+        );
+        if let Some(Err(e)) = self.snapshots.as_mut().map(|r| r.record(&snapshot)) {
+            events::warn(&self.config.event_sender, e);
+            self.snapshots = None;
+        }
+    }
 
-        if iteration % 100 == 0 || iteration == config.num_iters - 1 {
-            if best_tour_length_overall == f64::MAX {
-                println!("Iter {}: No complete tour found yet.", iteration);
-            } else {
-                println!(
-                    "Iter {}: Best tour length so far: {:.2}",
-                    iteration, best_tour_length_overall
-                );
+    /// Appends new best tours, and every `replay_pheromone_interval`
+    /// iterations a pheromone snapshot, to `Config::replay_path`. Every
+    /// `replay_ants_interval` iterations the complete tours of up to
+    /// `replay_ants` ants, spread evenly over the colony, are added too. Like
+    /// pheromone export this is skipped for batch solvers.
+    fn record_replay(&mut self, iteration: usize, improved: bool, ants: &[Ant]) {
+        let Some(path) = &self.config.replay_path else {
+            return;
+        };
+        if self.batch {
+            return;
+        }
+        if self.replay.is_none() {
+            if iteration > 0 {
+                return; // Creating the file failed, already reported.
             }
+            match ReplayRecorder::create(path, &self.instance) {
+                Ok(recorder) => self.replay = Some(recorder),
+                Err(e) => {
+                    events::warn(&self.config.event_sender, e);
+                    return;
+                }
+            }
+        }
+        let Some(recorder) = self.replay.as_mut() else {
+            return;
+        };
+        let mut result = Ok(());
+        if improved {
+            result = recorder.record_best(iteration, self.best_tour_length, &self.best_tour);
+        }
+        let interval = self.config.replay_pheromone_interval;
+        if result.is_ok() && interval > 0 && iteration.is_multiple_of(interval) {
+            result = recorder.record_pheromone(iteration, &self.pheromone_matrix);
+        }
+        let interval = self.config.replay_ants_interval;
+        if interval > 0 && iteration.is_multiple_of(interval) {
+            let n_nodes = self.instance.dimension;
+            let complete: Vec<(usize, &Ant)> = ants
+                .iter()
+                .enumerate()
+                .filter(|(_, ant)| ant.tour_completed(n_nodes))
+                .collect();
+            let sampled = self.config.replay_ants.min(complete.len());
+            for k in 0..sampled {
+                let (ant_idx, ant) = complete[k * complete.len() / sampled];
+                if result.is_ok() {
+                    result = recorder.record_ant(iteration, ant_idx, ant.tour_length, &ant.tour);
+                }
+            }
+        }
+        if let Err(e) = result {
+            events::warn(&self.config.event_sender, e);
+            self.replay = None;
         }
     }
 
-    let final_length = if best_tour_length_overall == f64::MAX {
-        0.0
-    } else {
-        best_tour_length_overall.round()
-    };
-    (best_tour_overall, final_length)
+    /// Writes the pheromone matrix to `Config::pheromone_export` after the
+    /// last iteration, and to a copy suffixed with the iteration number every
+    /// `pheromone_export_interval` iterations. Batch solvers never export,
+    /// they would all write to the same file.
+    fn export_pheromones(&self) {
+        let Some(path) = &self.config.pheromone_export else {
+            return;
+        };
+        if self.batch {
+            return;
+        }
+        let format = MatrixFormat::from_path(path);
+        let interval = self.config.pheromone_export_interval;
+        if interval > 0 && self.iteration.is_multiple_of(interval) {
+            let periodic = match path.rsplit_once('.') {
+                Some((stem, ext)) => format!("{}_{}.{}", stem, self.iteration, ext),
+                None => format!("{}_{}", path, self.iteration),
+            };
+            if let Err(e) = export::write_matrix(&self.pheromone_matrix, &periodic, format) {
+                events::warn(&self.config.event_sender, e);
+            }
+        }
+        if self.is_finished()
+            && let Err(e) = export::write_matrix(&self.pheromone_matrix, path, format)
+        {
+            events::warn(&self.config.event_sender, e);
+        }
+    }
+
+    /// Snapshot of the search state from which [`AcoSolver::new`] can
+    /// continue via `Config::resume`. Provenance is left to the caller, as
+    /// hashing an explicit instance visits every distance.
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            iteration: self.iteration,
+            last_improvement: self.last_improvement,
+            seed: self.config.seed,
+            best_tour: self.best_tour.clone(),
+            best_length: self.best_tour_length,
+            pheromone: self
+                .pheromone_matrix
+                .iter()
+                .map(|row| row.iter().map(|&level| widen(level)).collect())
+                .collect(),
+            provenance: None,
+        }
+    }
+
+    /// Runs all remaining iterations and returns the best tour and its
+    /// (rounded) length. With `Config::checkpoint_path` set, a shutdown
+    /// requested by a signal stops the run after the current iteration and
+    /// writes a checkpoint first.
+    pub fn run(self) -> (Vec<usize>, f64) {
+        let solution = self.solve();
+        (solution.tour, solution.length)
+    }
+
+    /// Like [`AcoSolver::run`], but returns the best tour as a [`Solution`]
+    /// with seed, duration and the reason the run stopped.
+    pub fn solve(mut self) -> Solution {
+        while !self.is_finished() {
+            self.step();
+            if let Some(path) = &self.config.checkpoint_path
+                && !self.batch
+                && checkpoint::shutdown_requested()
+            {
+                let mut checkpoint = self.checkpoint();
+                checkpoint.provenance = Some(Provenance::new(&self.instance, self.config));
+                match checkpoint.write(path) {
+                    Ok(()) => events::emit(
+                        &self.config.event_sender,
+                        SolverEvent::CheckpointWritten {
+                            iteration: self.iteration,
+                            path: path.clone(),
+                        },
+                    ),
+                    Err(e) => events::warn(&self.config.event_sender, e),
+                }
+                break;
+            }
+        }
+        let final_length = if self.best_tour_length == f64::MAX {
+            0.0
+        } else {
+            self.best_tour_length.round()
+        };
+        let termination = self.termination().unwrap_or(Termination::Interrupted);
+        let mut solution = Solution::new(
+            &self.instance,
+            self.best_tour,
+            final_length,
+            "aco",
+            termination,
+        );
+        if self.best_tour_length != f64::MAX {
+            solution.exact_length = self.best_tour_length;
+            solution.violations = self.constraints.violations(&solution.tour);
+        }
+        solution.seed = self.config.seed;
+        solution.duration = self.started.elapsed();
+        solution.tours = self.tours;
+        solution
+    }
+
+    /// Turns the solver into a blocking iterator that yields every new global
+    /// best as soon as it is found, for consumers acting on intermediate
+    /// solutions. Iteration ends when the configured iterations are used up.
+    pub fn improvements(self) -> Improvements<'a> {
+        Improvements { solver: self }
+    }
+}
+
+// --- Dynamic TSP: modifying the instance between iterations ---
+impl AcoSolver<'_> {
+    /// Changes the cost of edge `i`-`j`, in both directions unless the
+    /// instance is declared ATSP or SOP. The heuristic is refreshed and the
+    /// pheromone on the edge is reset to its initial level, since what was
+    /// learned about it is stale.
+    pub fn update_edge_cost(&mut self, i: usize, j: usize, cost: f64) -> Result<(), String> {
+        let n = self.instance.dimension;
+        if i >= n || j >= n || i == j {
+            return Err(format!("Invalid edge ({}, {}) for dimension {}", i, j, n));
+        }
+        let instance = self.instance.to_mut();
+        // Dense storage keeps symmetric instances in a full matrix, so the
+        // declared type decides rather than the storage.
+        let symmetric = !["ATSP", "SOP"]
+            .iter()
+            .any(|tsp_type| instance.tsp_type.eq_ignore_ascii_case(tsp_type));
+        if symmetric {
+            instance.dist_matrix.set_symmetric(i, j, cost);
+        } else {
+            instance.dist_matrix.set(i, j, cost);
+        }
+        self.refresh_after_change();
+        self.pheromone_matrix[i][j] = self.init_pheromone;
+        if symmetric {
+            self.pheromone_matrix[j][i] = self.init_pheromone;
+        }
+        Ok(())
+    }
+
+    /// Adds a node at `(x, y)` to a coordinate based instance and returns its
+    /// index. Its pheromone row and column start at the initial level and the
+    /// best tour is patched by cheapest insertion. Instances with a 3D metric
+    /// need [`AcoSolver::insert_node_3d`].
+    pub fn insert_node(&mut self, x: f64, y: f64) -> Result<usize, String> {
+        let metric = &self.instance.edge_weight_type;
+        if metric.is_three_dimensional() {
+            return Err(format!(
+                "Inserting a node into a {} instance requires a z coordinate",
+                metric.keyword()
+            ));
+        }
+        self.insert_node_3d(x, y, 0.0)
+    }
+
+    /// Like [`AcoSolver::insert_node`], at `(x, y, z)`.
+    pub fn insert_node_3d(&mut self, x: f64, y: f64, z: f64) -> Result<usize, String> {
+        let instance = self.instance.to_mut();
+        let metric = instance.edge_weight_type.clone();
+        let coords = instance
+            .node_coords
+            .as_mut()
+            .ok_or("Inserting nodes requires node coordinates")?;
+        let id = coords.iter().map(|node| node.id).max().unwrap_or(0) + 1;
+        let node = Node { id, x, y, z };
+        let distances = coords
+            .iter()
+            .map(|other| {
+                metric
+                    .distance(other, &node)
+                    .map(|dist| instance.distance_policy.apply(dist))
+            })
+            .collect::<Option<Vec<f64>>>()
+            .ok_or_else(|| format!("Cannot compute distances for {:?}", metric))?;
+        instance.dist_matrix.push_node(&node, &distances);
+        coords.push(node);
+        instance.dimension += 1;
+        let new_idx = instance.dimension - 1;
+
+        for row in &mut self.pheromone_matrix {
+            row.push(self.init_pheromone);
+        }
+        self.pheromone_matrix
+            .push(vec![self.init_pheromone; instance.dimension]);
+
+        if !self.best_tour.is_empty() {
+            let position =
+                construction::cheapest_insertion_position(instance, &self.best_tour, new_idx);
+            self.best_tour.insert(position + 1, new_idx);
+        }
+        self.refresh_after_change();
+        Ok(new_idx)
+    }
+
+    /// Moves node `k` of a coordinate based instance to `(x, y)`, e.g. when
+    /// a point is dragged in an interactive tool. Only the node's distances
+    /// are recomputed; its pheromone row and column are reset to the initial
+    /// level and the best tour keeps its order with the new length.
+    pub fn move_node(&mut self, k: usize, x: f64, y: f64) -> Result<(), String> {
+        let instance = self.instance.to_mut();
+        if k >= instance.dimension {
+            return Err(format!(
+                "Node index {} out of bounds for dimension {}",
+                k, instance.dimension
+            ));
+        }
+        let id = instance.node_id(k);
+        instance.update_node_coord(id, x, y)?;
+        for (i, row) in self.pheromone_matrix.iter_mut().enumerate() {
+            row[k] = self.init_pheromone;
+            if i == k {
+                row.fill(self.init_pheromone);
+            }
+        }
+        self.refresh_after_change();
+        Ok(())
+    }
+
+    /// Removes node `k`; later nodes shift down by one index. The node is
+    /// dropped from the best tour, which stays valid for the smaller instance.
+    pub fn remove_node(&mut self, k: usize) -> Result<(), String> {
+        let instance = self.instance.to_mut();
+        if k >= instance.dimension {
+            return Err(format!(
+                "Node index {} out of bounds for dimension {}",
+                k, instance.dimension
+            ));
+        }
+        instance.dist_matrix.remove_node(k);
+        if let Some(coords) = instance.node_coords.as_mut() {
+            coords.remove(k);
+        }
+        instance.dimension -= 1;
+
+        self.pheromone_matrix.remove(k);
+        for row in &mut self.pheromone_matrix {
+            row.remove(k);
+        }
+        self.best_tour.retain(|&node| node != k);
+        for node in &mut self.best_tour {
+            if *node > k {
+                *node -= 1;
+            }
+        }
+        self.refresh_after_change();
+        Ok(())
+    }
+
+    /// Recomputes everything derived from distances after a modification.
+    /// The heuristic is rebuilt as a whole since savings and custom heuristics
+    /// may depend on more than the modified edges.
+    fn refresh_after_change(&mut self) {
+        let instance: &TspInstance = &self.instance;
+        let n = instance.dimension;
+        self.heuristic = Arc::new(HeuristicTable::build(instance, self.config));
+        self.directed = is_directed(instance);
+        // Positions and indices shift when nodes are added or removed.
+        if self
+            .constraints
+            .locks
+            .as_ref()
+            .is_some_and(|l| l.dimension() != n)
+        {
+            self.constraints.locks = None;
+        }
+        if self
+            .constraints
+            .subsequences
+            .as_ref()
+            .is_some_and(|s| s.dimension() != n)
+        {
+            self.constraints.subsequences = None;
+        }
+        if self
+            .constraints
+            .precedences
+            .as_ref()
+            .is_some_and(|p| p.dimension() != n)
+        {
+            self.constraints.precedences = None;
+        }
+        if self
+            .constraints
+            .fixed_edges
+            .as_ref()
+            .is_some_and(|f| f.dimension() != n)
+        {
+            self.constraints.fixed_edges = None;
+        }
+        if self.int_matrix.is_some() {
+            self.int_matrix = Some(build_int_matrix(instance));
+        }
+        if self.best_tour.len() == n && n > 0 {
+            (self.best_tour_length, self.best_cost) = evaluate(
+                instance,
+                self.int_matrix.as_deref(),
+                self.config,
+                &self.best_tour,
+            );
+        } else {
+            self.best_tour.clear();
+            self.best_tour_length = f64::MAX;
+            self.best_cost = i64::MAX;
+        }
+    }
+}
+
+/// Iterator over the successive global-best tours of an [`AcoSolver`].
+pub struct Improvements<'a> {
+    solver: AcoSolver<'a>,
+}
+
+impl Improvements<'_> {
+    /// The underlying solver, e.g. to read the iteration count.
+    pub fn solver(&self) -> &AcoSolver<'_> {
+        &self.solver
+    }
+}
+
+impl Iterator for Improvements<'_> {
+    type Item = Improvement;
+
+    fn next(&mut self) -> Option<Improvement> {
+        while !self.solver.is_finished() {
+            if self.solver.step() {
+                return Some(Improvement {
+                    iteration: self.solver.iteration - 1,
+                    tour: self.solver.best_tour.clone(),
+                    length: self.solver.best_tour_length,
+                });
+            }
+        }
+        None
+    }
 }
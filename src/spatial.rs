@@ -0,0 +1,96 @@
+//! Grid based candidate search for geometric instances. Nodes are bucketed
+//! into a uniform grid and each node only looks at the cells around its
+//! own, so building candidate lists costs about O(n·k) distance
+//! evaluations instead of O(n²). Pairs the search never reaches are treated
+//! as non-candidates without their distance ever being computed.
+
+use crate::distance::DistanceMatrix;
+use crate::parser::{EdgeWeightType, TspInstance};
+use rayon::prelude::*;
+
+// Average nodes per grid cell.
+const NODES_PER_CELL: f64 = 2.0;
+
+/// The `k` nearest neighbours of every node among those in nearby grid
+/// cells, closest first, or `None` if the instance is not planar: it needs
+/// coordinates for every node, a planar metric (not GEO, GEODESIC or a 3D
+/// one) and no edge adjustments that could make distant nodes close.
+///
+/// Rings of cells around a node are searched until they hold `k` other
+/// nodes, plus one more ring, so neighbours just across a cell border are
+/// found too. The lists are exact for uniformly spread points and may miss
+/// a true neighbour only in very uneven data.
+pub fn grid_nearest_neighbors(instance: &TspInstance, k: usize) -> Option<Vec<Vec<usize>>> {
+    let nodes = instance
+        .node_coords
+        .as_deref()
+        .filter(|nodes| nodes.len() == instance.dimension)?;
+    let planar = matches!(
+        instance.edge_weight_type,
+        EdgeWeightType::Euc2D
+            | EdgeWeightType::Ceil2D
+            | EdgeWeightType::Att
+            | EdgeWeightType::Man2D
+            | EdgeWeightType::Max2D
+    );
+    if !planar || matches!(instance.dist_matrix, DistanceMatrix::Adjusted { .. }) {
+        return None;
+    }
+    let n = nodes.len();
+    let k = k.min(n.saturating_sub(1));
+
+    let (min_x, max_x) = nodes.iter().fold((f64::MAX, f64::MIN), |(lo, hi), node| {
+        (lo.min(node.x), hi.max(node.x))
+    });
+    let (min_y, max_y) = nodes.iter().fold((f64::MAX, f64::MIN), |(lo, hi), node| {
+        (lo.min(node.y), hi.max(node.y))
+    });
+    let side = ((n as f64 / NODES_PER_CELL).sqrt().ceil() as usize).max(1);
+    let width = (max_x - min_x).max(max_y - min_y).max(f64::EPSILON) / side as f64;
+    let cell_of = |value: f64, min: f64| (((value - min) / width) as usize).min(side - 1);
+    let mut cells: Vec<Vec<usize>> = vec![Vec::new(); side * side];
+    let positions: Vec<(usize, usize)> = nodes
+        .iter()
+        .map(|node| (cell_of(node.x, min_x), cell_of(node.y, min_y)))
+        .collect();
+    for (i, &(cx, cy)) in positions.iter().enumerate() {
+        cells[cy * side + cx].push(i);
+    }
+
+    let dist_matrix = &instance.dist_matrix;
+    let lists = (0..n)
+        .into_par_iter()
+        .map(|i| {
+            let (cx, cy) = positions[i];
+            let mut found: Vec<usize> = Vec::new();
+            let mut extra_ring = false;
+            for ring in 0..side {
+                let (x0, x1) = (cx.saturating_sub(ring), (cx + ring).min(side - 1));
+                let (y0, y1) = (cy.saturating_sub(ring), (cy + ring).min(side - 1));
+                for y in y0..=y1 {
+                    for x in x0..=x1 {
+                        // Only the border of the square is new in this ring.
+                        if x.abs_diff(cx) == ring || y.abs_diff(cy) == ring {
+                            found.extend(cells[y * side + x].iter().filter(|&&j| j != i));
+                        }
+                    }
+                }
+                if extra_ring {
+                    break;
+                }
+                extra_ring = found.len() >= k;
+            }
+            let mut others: Vec<(f64, usize)> = found
+                .into_iter()
+                .map(|j| (dist_matrix.get(i, j), j))
+                .collect();
+            if k < others.len() {
+                others.select_nth_unstable_by(k, |a, b| a.0.total_cmp(&b.0));
+                others.truncate(k);
+            }
+            others.sort_by(|a, b| a.0.total_cmp(&b.0));
+            others.into_iter().map(|(_, j)| j).collect()
+        })
+        .collect();
+    Some(lists)
+}
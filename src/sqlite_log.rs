@@ -0,0 +1,152 @@
+//! SQLite lab notebook of bench and experiment runs (`--sqlite`): every
+//! session adds its runs to the same database, so results of a tuning
+//! session stay queryable across invocations.
+//!
+//! - `configurations`: one row per distinct version and settings, with
+//!   `settings` holding them as `(configuration_id, key, value)` rows,
+//! - `runs`: one row per seeded run, with its session, configuration and
+//!   result,
+//! - `iterations`: the per-iteration lengths of each run,
+//! - `tours`: the final tour of each run, one row per position.
+
+use crate::config::Config;
+use crate::provenance::VERSION;
+use crate::results::RunRecord;
+use rusqlite::{Connection, OptionalExtension, Transaction, params};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS configurations (
+    id INTEGER PRIMARY KEY,
+    version TEXT NOT NULL,
+    settings TEXT NOT NULL,
+    UNIQUE (version, settings)
+);
+CREATE TABLE IF NOT EXISTS settings (
+    configuration_id INTEGER NOT NULL REFERENCES configurations (id),
+    key TEXT NOT NULL,
+    value TEXT NOT NULL,
+    PRIMARY KEY (configuration_id, key)
+);
+CREATE TABLE IF NOT EXISTS runs (
+    id INTEGER PRIMARY KEY,
+    session TEXT NOT NULL,
+    recorded_at INTEGER NOT NULL,
+    configuration_id INTEGER NOT NULL REFERENCES configurations (id),
+    instance TEXT NOT NULL,
+    instance_hash TEXT NOT NULL,
+    seed INTEGER NOT NULL,
+    length REAL NOT NULL,
+    gap REAL,
+    seconds REAL NOT NULL
+);
+CREATE TABLE IF NOT EXISTS iterations (
+    run_id INTEGER NOT NULL REFERENCES runs (id),
+    iteration INTEGER NOT NULL,
+    iteration_best_length REAL,
+    best_length REAL,
+    edge_entropy REAL,
+    PRIMARY KEY (run_id, iteration)
+);
+CREATE TABLE IF NOT EXISTS tours (
+    run_id INTEGER NOT NULL REFERENCES runs (id),
+    position INTEGER NOT NULL,
+    node INTEGER NOT NULL,
+    PRIMARY KEY (run_id, position)
+);
+";
+
+/// Adds `runs`, all made with `config`, to the database at `path` under
+/// `session` (the experiment name, or `bench`), creating it if needed.
+pub fn record_runs(
+    runs: &[RunRecord],
+    config: &Config,
+    session: &str,
+    path: &str,
+) -> Result<(), String> {
+    let error = |e: rusqlite::Error| format!("Failed to record runs in {}: {}", path, e);
+    let mut connection = Connection::open(path).map_err(error)?;
+    connection.execute_batch(SCHEMA).map_err(error)?;
+    let transaction = connection.transaction().map_err(error)?;
+    let configuration_id = configuration_id(&transaction, config).map_err(error)?;
+    let recorded_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    for run in runs {
+        insert_run(&transaction, run, session, recorded_at, configuration_id).map_err(error)?;
+    }
+    transaction.commit().map_err(error)
+}
+
+// The id of `config`'s row in `configurations`, inserted with its settings
+// the first time it is seen.
+fn configuration_id(transaction: &Transaction, config: &Config) -> rusqlite::Result<i64> {
+    let settings = config.settings();
+    let existing = transaction
+        .query_row(
+            "SELECT id FROM configurations WHERE version = ?1 AND settings = ?2",
+            params![VERSION, settings],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if let Some(id) = existing {
+        return Ok(id);
+    }
+    transaction.execute(
+        "INSERT INTO configurations (version, settings) VALUES (?1, ?2)",
+        params![VERSION, settings],
+    )?;
+    let id = transaction.last_insert_rowid();
+    let mut insert = transaction
+        .prepare("INSERT INTO settings (configuration_id, key, value) VALUES (?1, ?2, ?3)")?;
+    for (key, value) in config.setting_pairs() {
+        insert.execute(params![id, key, value])?;
+    }
+    Ok(id)
+}
+
+fn insert_run(
+    transaction: &Transaction,
+    run: &RunRecord,
+    session: &str,
+    recorded_at: u64,
+    configuration_id: i64,
+) -> rusqlite::Result<()> {
+    transaction.execute(
+        "INSERT INTO runs (session, recorded_at, configuration_id, instance, instance_hash, seed, length, gap, seconds)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            session,
+            recorded_at,
+            configuration_id,
+            run.instance,
+            run.instance_hash,
+            run.seed,
+            run.length,
+            run.gap,
+            run.seconds
+        ],
+    )?;
+    let run_id = transaction.last_insert_rowid();
+    // Lengths stay at f64::MAX until an ant completes a tour.
+    let found = |length: f64| (length != f64::MAX).then_some(length);
+    let mut insert = transaction.prepare_cached(
+        "INSERT INTO iterations (run_id, iteration, iteration_best_length, best_length, edge_entropy)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+    )?;
+    for record in &run.history {
+        insert.execute(params![
+            run_id,
+            record.iteration,
+            found(record.iteration_best_length),
+            found(record.best_length),
+            record.edge_entropy
+        ])?;
+    }
+    let mut insert = transaction
+        .prepare_cached("INSERT INTO tours (run_id, position, node) VALUES (?1, ?2, ?3)")?;
+    for (position, node) in run.tour.iter().enumerate() {
+        insert.execute(params![run_id, position, node])?;
+    }
+    Ok(())
+}
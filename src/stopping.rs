@@ -0,0 +1,108 @@
+//! Rules deciding when a run ends. The solver always stops at
+//! `Config::num_iters` iterations (or `Config::tour_budget` tours, if set)
+//! and additionally evaluates the criteria in `Config::stopping` after every
+//! iteration.
+
+use crate::solution::Termination;
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Progress of a run as seen by the stopping criteria.
+#[derive(Debug, Clone, Copy)]
+pub struct SearchState {
+    /// Iterations completed so far.
+    pub iteration: usize,
+    /// Ant tours constructed so far.
+    pub tours: usize,
+    /// Time since the solver was created.
+    pub elapsed: Duration,
+    /// Best tour length so far, `f64::MAX` if none yet.
+    pub best_length: f64,
+    /// Iteration in which the best tour was found.
+    pub last_improvement: usize,
+}
+
+pub trait StoppingCriterion: Debug + Send + Sync {
+    /// Why the run should stop in `state`, or `None` to continue.
+    fn check(&self, state: &SearchState) -> Option<Termination>;
+}
+
+/// Stops once the given number of iterations has run.
+#[derive(Debug, Clone, Copy)]
+pub struct MaxIterations(pub usize);
+
+impl StoppingCriterion for MaxIterations {
+    fn check(&self, state: &SearchState) -> Option<Termination> {
+        (state.iteration >= self.0).then_some(Termination::IterationLimit)
+    }
+}
+
+/// Stops at the first iteration ending after the time limit.
+#[derive(Debug, Clone, Copy)]
+pub struct TimeLimit(pub Duration);
+
+impl StoppingCriterion for TimeLimit {
+    fn check(&self, state: &SearchState) -> Option<Termination> {
+        (state.elapsed >= self.0).then_some(Termination::TimeLimit)
+    }
+}
+
+/// Stops once a tour at most this long has been found.
+#[derive(Debug, Clone, Copy)]
+pub struct TargetLength(pub f64);
+
+impl StoppingCriterion for TargetLength {
+    fn check(&self, state: &SearchState) -> Option<Termination> {
+        (state.best_length <= self.0).then_some(Termination::TargetReached)
+    }
+}
+
+/// Stops after this many iterations without a new best tour.
+#[derive(Debug, Clone, Copy)]
+pub struct NoImprovementFor(pub usize);
+
+impl StoppingCriterion for NoImprovementFor {
+    fn check(&self, state: &SearchState) -> Option<Termination> {
+        (state.iteration.saturating_sub(state.last_improvement) >= self.0)
+            .then_some(Termination::NoImprovement)
+    }
+}
+
+/// Combines criteria: stops when any of them (reporting the first one's
+/// reason) or all of them (reporting the last one's) are met. An empty `all`
+/// never stops.
+#[derive(Debug, Clone)]
+pub struct Composite {
+    criteria: Vec<Arc<dyn StoppingCriterion>>,
+    all: bool,
+}
+
+impl Composite {
+    pub fn any(criteria: Vec<Arc<dyn StoppingCriterion>>) -> Self {
+        Composite {
+            criteria,
+            all: false,
+        }
+    }
+
+    pub fn all(criteria: Vec<Arc<dyn StoppingCriterion>>) -> Self {
+        Composite {
+            criteria,
+            all: true,
+        }
+    }
+}
+
+impl StoppingCriterion for Composite {
+    fn check(&self, state: &SearchState) -> Option<Termination> {
+        if !self.all {
+            return self.criteria.iter().find_map(|c| c.check(state));
+        }
+        let mut reason = None;
+        for criterion in &self.criteria {
+            reason = Some(criterion.check(state)?);
+        }
+        reason
+    }
+}
@@ -0,0 +1,69 @@
+//! Granularity of the colony's parallel loops and placement of the rayon
+//! worker threads.
+//!
+//! Evaporation touches every pheromone row each iteration. Split per row,
+//! small instances spend more time scheduling tasks than scaling levels, so
+//! rows are grouped into tasks of at least [`MIN_CELLS_PER_TASK`] levels
+//! unless `Config::evaporation_chunk` says otherwise.
+
+use crate::config::Config;
+
+/// Pheromone levels below which an evaporation task is not worth scheduling.
+pub const MIN_CELLS_PER_TASK: usize = 16 * 1024;
+
+/// Rows of an `n_nodes` pheromone matrix evaporated by one rayon task.
+pub fn evaporation_rows(n_nodes: usize, config: &Config) -> usize {
+    config
+        .evaporation_chunk
+        .unwrap_or_else(|| MIN_CELLS_PER_TASK.div_ceil(n_nodes.max(1)))
+        .max(1)
+}
+
+/// Builds the global rayon pool with worker `i` pinned to CPU `i` (modulo
+/// the available CPUs), so threads keep their caches between iterations.
+/// Must run before anything else uses rayon; fails if the pool already
+/// exists, off Linux, or if a worker could not be pinned (the pool is then
+/// still built, with that worker unpinned).
+pub fn pin_global_pool() -> Result<(), String> {
+    #[cfg(target_os = "linux")]
+    {
+        let cpus = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let (sender, receiver) = std::sync::mpsc::channel();
+        rayon::ThreadPoolBuilder::new()
+            .start_handler(move |index| {
+                let _ = sender.send(pin_current_thread(index % cpus));
+            })
+            .build_global()
+            .map_err(|e| format!("Failed to pin the thread pool: {}", e))?;
+        // Every worker reports once it has started.
+        receiver
+            .iter()
+            .take(rayon::current_num_threads())
+            .find_map(Result::err)
+            .map_or(Ok(()), Err)
+    }
+    #[cfg(not(target_os = "linux"))]
+    Err("Thread pinning is only supported on Linux".to_string())
+}
+
+#[cfg(target_os = "linux")]
+fn pin_current_thread(cpu: usize) -> Result<(), String> {
+    // cpu_set_t of glibc and musl: a 1024 bit mask.
+    const CPU_SET_WORDS: usize = 16;
+    unsafe extern "C" {
+        fn sched_setaffinity(pid: i32, cpusetsize: usize, mask: *const u64) -> i32;
+    }
+    if cpu >= CPU_SET_WORDS * 64 {
+        return Err(format!("CPU {} is beyond the affinity mask", cpu));
+    }
+    let mut mask = [0u64; CPU_SET_WORDS];
+    mask[cpu / 64] |= 1 << (cpu % 64);
+    // SAFETY: the mask is a valid cpu_set_t of the given size; pid 0 is the
+    // calling thread.
+    let status = unsafe { sched_setaffinity(0, std::mem::size_of_val(&mask), mask.as_ptr()) };
+    if status == 0 {
+        Ok(())
+    } else {
+        Err(format!("Failed to pin thread to CPU {}", cpu))
+    }
+}
@@ -0,0 +1,92 @@
+//! Runs sized by wall clock instead of iteration counts. Given only
+//! `Config::time_budget`, the solver measures what an iteration costs and
+//! re-plans after every iteration how many more fit into the remaining
+//! time, shrinking the colony when too few would, so the run ends close to
+//! the budget whatever the machine and instance.
+
+use crate::config::Config;
+use std::time::Duration;
+
+/// Fewest iterations a plan aims for; below that the colony shrinks.
+pub const MIN_PLANNED_ITERATIONS: usize = 100;
+/// Smallest colony the planner shrinks to.
+pub const MIN_BUDGET_ANTS: usize = 5;
+/// Candidate list size for budgeted runs on instances above
+/// [`BUDGET_CANDIDATE_THRESHOLD`] nodes, unless one is configured.
+pub const BUDGET_CANDIDATE_LIST_SIZE: usize = 15;
+pub const BUDGET_CANDIDATE_THRESHOLD: usize = 300;
+
+// Weight of the latest iteration in the running estimate of its cost.
+const SMOOTHING: f64 = 0.3;
+
+/// `config` with the settings a budgeted run fixes before it starts: a
+/// candidate list on large instances, which the solver cannot switch on
+/// once its heuristic table is built.
+pub fn budgeted_config(config: &Config, dimension: usize) -> Config {
+    let mut config = config.clone();
+    if config.candidate_list_size == 0 && dimension > BUDGET_CANDIDATE_THRESHOLD {
+        config.candidate_list_size = BUDGET_CANDIDATE_LIST_SIZE;
+    }
+    config
+}
+
+/// What the planner expects to fit into the rest of the budget.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BudgetPlan {
+    /// Total iterations of the run, including those already done.
+    pub iterations: usize,
+    /// Ants per iteration from now on.
+    pub ants: usize,
+}
+
+/// Running estimate of the cost of an ant, turned into a plan after every
+/// iteration.
+#[derive(Debug, Clone)]
+pub struct BudgetPlanner {
+    budget: Duration,
+    // Never more ants than configured.
+    max_ants: usize,
+    // Seconds per ant tour, smoothed over the iterations so far.
+    seconds_per_ant: Option<f64>,
+}
+
+impl BudgetPlanner {
+    pub fn new(budget: Duration, max_ants: usize) -> Self {
+        BudgetPlanner {
+            budget,
+            max_ants: max_ants.max(1),
+            seconds_per_ant: None,
+        }
+    }
+
+    pub fn budget(&self) -> Duration {
+        self.budget
+    }
+
+    /// Updates the estimate with an iteration of `ants` ants that took
+    /// `last_iteration`, and plans the rest of the run after `done`
+    /// iterations and `elapsed` time.
+    pub fn replan(
+        &mut self,
+        done: usize,
+        elapsed: Duration,
+        last_iteration: Duration,
+        ants: usize,
+    ) -> BudgetPlan {
+        let sample = last_iteration.as_secs_f64() / ants.max(1) as f64;
+        let per_ant = match self.seconds_per_ant {
+            Some(estimate) => SMOOTHING * sample + (1.0 - SMOOTHING) * estimate,
+            None => sample,
+        }
+        .max(f64::EPSILON);
+        self.seconds_per_ant = Some(per_ant);
+
+        let remaining = self.budget.saturating_sub(elapsed).as_secs_f64();
+        let ant_tours = remaining / per_ant;
+        let ants = ((ant_tours / MIN_PLANNED_ITERATIONS as f64) as usize)
+            .clamp(MIN_BUDGET_ANTS.min(self.max_ants), self.max_ants);
+        // Soft: one more iteration runs if at least half of it fits.
+        let iterations = done + (ant_tours / ants as f64).round() as usize;
+        BudgetPlan { iterations, ants }
+    }
+}
@@ -0,0 +1,81 @@
+use crate::distance::{DistanceMatrix, FORBIDDEN_DISTANCE};
+use crate::parser::{DistancePolicy, EdgeWeightFormat, EdgeWeightType, TspInstance};
+
+/// Symmetric 2n-node equivalent of an asymmetric instance (Jonker and
+/// Volgenant). City `i` becomes an "in" node `i` and an "out" node `n + i`
+/// joined by a zero-cost edge; the arc `i -> j` becomes the edge between
+/// `n + i` and `j`, and all other pairs are forbidden. An optimal symmetric
+/// tour uses every zero-cost edge, so its length equals the ATSP tour's.
+/// Map tours back with [`stsp_tour_to_atsp`].
+pub fn atsp_to_stsp(instance: &TspInstance) -> TspInstance {
+    let n = instance.dimension;
+    let mut dist_matrix = DistanceMatrix::triangular(2 * n);
+    for i in 0..2 * n {
+        for j in (i + 1)..2 * n {
+            let cost = match (i < n, j < n) {
+                (true, false) if j - n == i => 0.0,
+                (true, false) => instance.dist_matrix.get(j - n, i),
+                _ => FORBIDDEN_DISTANCE,
+            };
+            dist_matrix.set(i, j, cost);
+        }
+    }
+    TspInstance {
+        name: format!("{}_sym", instance.name),
+        tsp_type: "TSP".to_string(),
+        comment: format!("Symmetric transformation of {}", instance.name),
+        dimension: 2 * n,
+        edge_weight_type: EdgeWeightType::Explicit,
+        edge_weight_format: Some(EdgeWeightFormat::UpperRow),
+        distance_policy: DistancePolicy::Exact,
+        node_coords: None,
+        dist_matrix,
+        node_metadata: None,
+        precedences: None,
+        capacity: None,
+        demands: None,
+        depots: None,
+        fixed_edges: None,
+    }
+}
+
+/// Maps a tour (0-based) of the instance built by [`atsp_to_stsp`] back to
+/// the `n`-city asymmetric tour it represents. Fails if the tour leaves any
+/// in/out pair apart, i.e. uses a forbidden edge.
+pub fn stsp_tour_to_atsp(tour: &[usize], n: usize) -> Result<Vec<usize>, String> {
+    if tour.len() != 2 * n {
+        return Err(format!(
+            "Expected a tour of {} nodes, got {}",
+            2 * n,
+            tour.len()
+        ));
+    }
+    if n == 0 {
+        return Ok(Vec::new());
+    }
+    let len = tour.len();
+    let start = tour
+        .iter()
+        .position(|&node| node == 0)
+        .ok_or("Tour does not visit node 0")?;
+    // Walk in the direction that leaves the in node 0 towards its out node.
+    let step = if tour[(start + 1) % len] == n {
+        1
+    } else if tour[(start + len - 1) % len] == n {
+        len - 1
+    } else {
+        return Err("Tour separates a city from its copy".to_string());
+    };
+    let mut atsp_tour = Vec::with_capacity(n);
+    let mut seen = vec![false; n];
+    for k in 0..n {
+        let city = tour[(start + 2 * k * step) % len];
+        let copy = tour[(start + (2 * k + 1) * step) % len];
+        if city >= n || copy != city + n || seen[city] {
+            return Err("Tour separates a city from its copy".to_string());
+        }
+        seen[city] = true;
+        atsp_tour.push(city);
+    }
+    Ok(atsp_tour)
+}
@@ -0,0 +1,211 @@
+//! Racing-based automatic parameter tuning in the style of irace/F-race.
+//!
+//! Random candidate configurations are run block by block, one block being
+//! one seed for every surviving candidate. Once a few blocks are in, a
+//! Friedman test on the per-block ranks checks whether the candidates
+//! differ at all. If they do, a Conover post-hoc comparison drops every
+//! candidate significantly worse than the current leader, so the remaining
+//! budget is spent only on contenders.
+
+use crate::config::Config;
+use crate::events;
+use crate::parser::TspInstance;
+use crate::prepared::PreparedInstance;
+use crate::solver::solve_quiet;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+
+// Blocks every candidate gets before the first elimination test.
+const FIRST_TEST_BLOCK: usize = 5;
+// z-quantiles for the 5% significance level.
+const Z_ONE_SIDED: f64 = 1.645;
+const Z_TWO_SIDED: f64 = 1.96;
+
+/// Parameters explored by the tuner; everything else comes from the base
+/// configuration.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TuningCandidate {
+    pub alpha: f64,
+    pub beta: f64,
+    pub evap_rate: f64,
+    pub num_ants: usize,
+    pub elitist_weight: f64,
+}
+
+impl TuningCandidate {
+    fn from_config(config: &Config) -> Self {
+        TuningCandidate {
+            alpha: config.alpha,
+            beta: config.beta,
+            evap_rate: config.evap_rate,
+            num_ants: config.num_ants,
+            elitist_weight: config.elitist_weight,
+        }
+    }
+
+    fn sample(rng: &mut StdRng) -> Self {
+        TuningCandidate {
+            alpha: rng.random_range(0.5..2.0),
+            beta: rng.random_range(1.0..6.0),
+            evap_rate: rng.random_range(0.02..0.5),
+            num_ants: rng.random_range(10..=100),
+            elitist_weight: rng.random_range(0.0..3.0),
+        }
+    }
+
+    /// `config` with this candidate's parameters.
+    pub fn apply(&self, config: &Config) -> Config {
+        Config {
+            alpha: self.alpha,
+            beta: self.beta,
+            evap_rate: self.evap_rate,
+            num_ants: self.num_ants,
+            elitist_weight: self.elitist_weight,
+            event_sender: None,
+            ..config.clone()
+        }
+    }
+
+    /// Command line flags reproducing this candidate.
+    pub fn to_args(&self) -> String {
+        format!(
+            "-a {:.3} -b {:.3} -e {:.3} -n {} -w {:.3}",
+            self.alpha, self.beta, self.evap_rate, self.num_ants, self.elitist_weight
+        )
+    }
+}
+
+/// A candidate that survived the race.
+#[derive(Debug, Clone)]
+pub struct RaceResult {
+    pub candidate: TuningCandidate,
+    pub mean_length: f64,
+    pub runs: usize,
+}
+
+/// Races `num_candidates` configurations (the base `config` plus random
+/// samples) on `instance` within `budget` solver runs. Seeds start at
+/// `config.seed` (or 1) and also drive the sampling. Eliminations are sent
+/// to `config.event_sender`. Returns the survivors, best mean length first.
+pub fn race(
+    instance: &TspInstance,
+    config: &Config,
+    num_candidates: usize,
+    budget: usize,
+) -> Vec<RaceResult> {
+    let base_seed = config.seed.unwrap_or(1);
+    let mut rng = StdRng::seed_from_u64(base_seed);
+    let candidates: Vec<TuningCandidate> = std::iter::once(TuningCandidate::from_config(config))
+        .chain((1..num_candidates.max(2)).map(|_| TuningCandidate::sample(&mut rng)))
+        .collect();
+
+    let prepared = PreparedInstance::new(instance);
+    let mut alive: Vec<usize> = (0..candidates.len()).collect();
+    // results[c][b]: tour length of candidate c on block b.
+    let mut results: Vec<Vec<f64>> = vec![Vec::new(); candidates.len()];
+    let mut used = 0;
+    let mut block = 0;
+    while alive.len() > 1 && used + alive.len() <= budget {
+        let seed = base_seed + block as u64;
+        let lengths: Vec<f64> = alive
+            .par_iter()
+            .map(|&c| {
+                let run_config = Config {
+                    seed: Some(seed),
+                    ..candidates[c].apply(config)
+                };
+                solve_quiet(&prepared, &run_config).1
+            })
+            .collect();
+        for (&c, length) in alive.iter().zip(lengths) {
+            results[c].push(length);
+        }
+        used += alive.len();
+        block += 1;
+
+        if block >= FIRST_TEST_BLOCK {
+            let before = alive.len();
+            alive = eliminate(&alive, &results);
+            if alive.len() < before {
+                events::info(
+                    &config.event_sender,
+                    format!(
+                        "  Block {}: {} of {} candidates left",
+                        block,
+                        alive.len(),
+                        before
+                    ),
+                );
+            }
+        }
+    }
+
+    let mut survivors: Vec<RaceResult> = alive
+        .iter()
+        .map(|&c| RaceResult {
+            candidate: candidates[c],
+            mean_length: results[c].iter().sum::<f64>() / results[c].len().max(1) as f64,
+            runs: results[c].len(),
+        })
+        .collect();
+    survivors.sort_by(|a, b| a.mean_length.total_cmp(&b.mean_length));
+    survivors
+}
+
+/// Friedman test over all blocks run so far, followed by the Conover
+/// post-hoc test against the best ranked candidate. Returns the survivors.
+fn eliminate(alive: &[usize], results: &[Vec<f64>]) -> Vec<usize> {
+    let k = alive.len() as f64;
+    let blocks = results[alive[0]].len();
+    let b = blocks as f64;
+
+    // Ranks within each block (1 = shortest), ties get the average rank.
+    let mut rank_sums = vec![0.0; alive.len()];
+    let mut sum_sq_ranks = 0.0;
+    let columns: Vec<&[f64]> = alive.iter().map(|&c| results[c].as_slice()).collect();
+    for block in 0..blocks {
+        let lengths: Vec<f64> = columns.iter().map(|column| column[block]).collect();
+        for (i, &length) in lengths.iter().enumerate() {
+            let less = lengths.iter().filter(|&&l| l < length).count() as f64;
+            let equal = lengths.iter().filter(|&&l| l == length).count() as f64;
+            let rank = less + (equal + 1.0) / 2.0;
+            rank_sums[i] += rank;
+            sum_sq_ranks += rank * rank;
+        }
+    }
+
+    let tie_term = b * k * (k + 1.0).powi(2) / 4.0;
+    let denominator = sum_sq_ranks - tie_term;
+    if denominator <= 0.0 {
+        return alive.to_vec(); // All candidates tied in every block.
+    }
+    let statistic = (k - 1.0)
+        * rank_sums
+            .iter()
+            .map(|r| (r - b * (k + 1.0) / 2.0).powi(2))
+            .sum::<f64>()
+        / denominator;
+    if statistic <= chi_square_critical(k - 1.0) {
+        return alive.to_vec();
+    }
+
+    let best = rank_sums.iter().copied().fold(f64::MAX, f64::min);
+    let spread = (2.0 * b * denominator / ((b - 1.0) * (k - 1.0))
+        * (1.0 - statistic / (b * (k - 1.0))))
+        .max(0.0)
+        .sqrt();
+    let threshold = Z_TWO_SIDED * spread;
+    alive
+        .iter()
+        .zip(&rank_sums)
+        .filter(|&(_, &r)| r - best <= threshold)
+        .map(|(&c, _)| c)
+        .collect()
+}
+
+/// Upper 5% quantile of the chi-square distribution (Wilson-Hilferty).
+fn chi_square_critical(df: f64) -> f64 {
+    let h = 2.0 / (9.0 * df);
+    df * (1.0 - h + Z_ONE_SIDED * h.sqrt()).powi(3)
+}
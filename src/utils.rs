@@ -58,3 +58,85 @@ pub fn evaluate_solution(
         (None, None)
     }
 }
+
+/// Proven optimal tour lengths of common symmetric and asymmetric TSPLIB
+/// instances, used when no solutions file lists the instance.
+const TSPLIB_OPTIMA: [(&str, f64); 68] = [
+    ("a280", 2579.0),
+    ("att48", 10628.0),
+    ("att532", 27686.0),
+    ("bayg29", 1610.0),
+    ("bays29", 2020.0),
+    ("berlin52", 7542.0),
+    ("br17", 39.0),
+    ("brazil58", 25395.0),
+    ("burma14", 3323.0),
+    ("ch130", 6110.0),
+    ("ch150", 6528.0),
+    ("d198", 15780.0),
+    ("d493", 35002.0),
+    ("dantzig42", 699.0),
+    ("eil101", 629.0),
+    ("eil51", 426.0),
+    ("eil76", 538.0),
+    ("fl417", 11861.0),
+    ("fri26", 937.0),
+    ("ft53", 6905.0),
+    ("ft70", 38673.0),
+    ("ftv170", 2755.0),
+    ("ftv33", 1286.0),
+    ("gil262", 2378.0),
+    ("gr17", 2085.0),
+    ("gr21", 2707.0),
+    ("gr24", 1272.0),
+    ("gr48", 5046.0),
+    ("gr96", 55209.0),
+    ("hk48", 11461.0),
+    ("kro124p", 36230.0),
+    ("kroa100", 21282.0),
+    ("kroa150", 26524.0),
+    ("kroa200", 29368.0),
+    ("krob100", 22141.0),
+    ("krob150", 26130.0),
+    ("krob200", 29437.0),
+    ("kroc100", 20749.0),
+    ("krod100", 21294.0),
+    ("kroe100", 22068.0),
+    ("lin105", 14379.0),
+    ("lin318", 42029.0),
+    ("p43", 5620.0),
+    ("pcb442", 50778.0),
+    ("pr1002", 259045.0),
+    ("pr107", 44303.0),
+    ("pr124", 59030.0),
+    ("pr136", 96772.0),
+    ("pr144", 58537.0),
+    ("pr152", 73682.0),
+    ("pr226", 80369.0),
+    ("pr264", 49135.0),
+    ("pr299", 48191.0),
+    ("pr439", 107217.0),
+    ("pr76", 108159.0),
+    ("rat195", 2323.0),
+    ("rat575", 6773.0),
+    ("rat783", 8806.0),
+    ("rat99", 1211.0),
+    ("rd100", 7910.0),
+    ("rd400", 15281.0),
+    ("ry48p", 14422.0),
+    ("st70", 675.0),
+    ("swiss42", 1273.0),
+    ("ts225", 126643.0),
+    ("tsp225", 3916.0),
+    ("u159", 42080.0),
+    ("ulysses16", 6859.0),
+];
+
+/// Embedded optimum for `name` (case-insensitive, without extension).
+pub fn embedded_optimum(name: &str) -> Option<f64> {
+    let key = name.to_lowercase();
+    TSPLIB_OPTIMA
+        .iter()
+        .find(|(instance, _)| *instance == key)
+        .map(|&(_, length)| length)
+}
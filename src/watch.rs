@@ -0,0 +1,99 @@
+//! Drop-folder mode: watches a directory for new instance files and solves
+//! each one with the configured settings, writing `<stem>.tour` (TSPLIB
+//! TOUR format) and `<stem>.result` next to it. Failed instances get a
+//! `<stem>.error` instead so they are not retried on every poll.
+
+use crate::config::Config;
+use crate::events;
+use crate::export;
+use crate::parser::parse_tsp_file_with_options;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+// Files modified more recently may still be being written.
+const SETTLE_TIME: Duration = Duration::from_secs(1);
+
+/// Polls `dir` forever, solving every `.tsp` or `.npy` file that has no
+/// `.result` or `.error` file yet. Progress and failures are sent to
+/// `Config::event_sender`. Only fails if the directory cannot be read.
+pub fn watch_directory(dir: &str, config: &Config) -> Result<(), String> {
+    loop {
+        for path in pending_instances(Path::new(dir))? {
+            let file_path = path.to_string_lossy();
+            let events = &config.event_sender;
+            events::info(events, format!("\n Solving {}...", file_path));
+            match solve_file(&path, config) {
+                Ok(length) => events::info(events, format!("  Done: tour length {:.2}", length)),
+                Err(e) => {
+                    events::warn(events, format!("{} failed: {}", file_path, e));
+                    if let Err(write_err) = fs::write(path.with_extension("error"), e + "\n") {
+                        events::warn(
+                            events,
+                            format!(
+                                "Could not record the failure of {}: {}",
+                                file_path, write_err
+                            ),
+                        );
+                    }
+                }
+            }
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn pending_instances(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let entries =
+        fs::read_dir(dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+    let now = SystemTime::now();
+    let mut pending: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let is_instance = path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| {
+                    ext.eq_ignore_ascii_case("tsp") || ext.eq_ignore_ascii_case("npy")
+                });
+            let settled = fs::metadata(path)
+                .and_then(|meta| meta.modified())
+                .ok()
+                .and_then(|modified| now.duration_since(modified).ok())
+                .is_some_and(|age| age >= SETTLE_TIME);
+            is_instance
+                && settled
+                && !path.with_extension("result").exists()
+                && !path.with_extension("error").exists()
+        })
+        .collect();
+    pending.sort();
+    Ok(pending)
+}
+
+fn solve_file(path: &Path, config: &Config) -> Result<f64, String> {
+    let file_path = path.to_string_lossy();
+    let instance = parse_tsp_file_with_options(&file_path, &config.into())?;
+    let solution = crate::solve_instance(&instance, config)?;
+
+    fs::write(
+        path.with_extension("tour"),
+        export::tsplib_tour(&instance.name, &solution.node_ids),
+    )
+    .map_err(|e| format!("Failed to write tour: {}", e))?;
+    let result = format!(
+        "NAME: {}\nDIMENSION: {}\nLENGTH: {}\nTIME_SECONDS: {:.3}\nITERATIONS: {}\nANTS: {}\n",
+        instance.name,
+        instance.dimension,
+        solution.length,
+        solution.duration.as_secs_f64(),
+        config.num_iters,
+        config.num_ants
+    );
+    fs::write(path.with_extension("result"), result)
+        .map_err(|e| format!("Failed to write result: {}", e))?;
+    Ok(solution.length)
+}
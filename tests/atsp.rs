@@ -0,0 +1,148 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::fmt::Write;
+use tsp_solver::constraints::validate_permutation;
+use tsp_solver::local_search::local_search_by_name;
+use tsp_solver::{
+    AcoSolver, Config, LocalSearch, ParseOptions, TwoOpt, parse_tsp_reader, solve_brute_force,
+    solve_tsp_aco,
+};
+
+// Full matrix where going "forward" (j = i + 1 mod n) is cheap and every
+// other arc, including the way back, is expensive.
+fn one_way_ring(n: usize, rng: &mut StdRng) -> String {
+    let mut contents = format!(
+        "NAME: ring{}\nTYPE: ATSP\nDIMENSION: {}\nEDGE_WEIGHT_TYPE: EXPLICIT\nEDGE_WEIGHT_FORMAT: FULL_MATRIX\nEDGE_WEIGHT_SECTION\n",
+        n, n
+    );
+    for i in 0..n {
+        for j in 0..n {
+            let weight = if i == j {
+                9999
+            } else if j == (i + 1) % n {
+                rng.random_range(1..5)
+            } else {
+                rng.random_range(20..100)
+            };
+            write!(contents, "{} ", weight).unwrap();
+        }
+        contents.push('\n');
+    }
+    contents.push_str("EOF\n");
+    contents
+}
+
+// Full matrix with independent random weights in both directions.
+fn random_matrix(n: usize, rng: &mut StdRng) -> String {
+    let mut contents = format!(
+        "NAME: random{}\nTYPE: ATSP\nDIMENSION: {}\nEDGE_WEIGHT_TYPE: EXPLICIT\nEDGE_WEIGHT_FORMAT: FULL_MATRIX\nEDGE_WEIGHT_SECTION\n",
+        n, n
+    );
+    for i in 0..n {
+        for j in 0..n {
+            let weight = if i == j {
+                9999
+            } else {
+                rng.random_range(1..100)
+            };
+            write!(contents, "{} ", weight).unwrap();
+        }
+        contents.push('\n');
+    }
+    contents.push_str("EOF\n");
+    contents
+}
+
+#[test]
+fn atsp_matrices_stay_asymmetric() {
+    let contents = "NAME: asym3\nTYPE: ATSP\nDIMENSION: 3\nEDGE_WEIGHT_TYPE: EXPLICIT\nEDGE_WEIGHT_FORMAT: FULL_MATRIX\nEDGE_WEIGHT_SECTION\n9999 1 10\n10 9999 1\n1 10 9999\nEOF\n";
+    let instance = parse_tsp_reader(contents.as_bytes(), &ParseOptions::default()).unwrap();
+    assert_eq!(instance.tsp_type, "ATSP");
+    assert!(!instance.is_symmetric());
+    assert_eq!(instance.tour_length(&[0, 1, 2]), 3.0);
+    assert_eq!(instance.tour_length(&[0, 2, 1]), 30.0);
+
+    let mirrored = contents.replace("FULL_MATRIX", "UPPER_ROW");
+    assert!(parse_tsp_reader(mirrored.as_bytes(), &ParseOptions::default()).is_err());
+}
+
+#[test]
+fn pheromone_is_deposited_in_the_direction_of_travel() {
+    let mut rng = StdRng::seed_from_u64(3);
+    let instance = parse_tsp_reader(
+        one_way_ring(6, &mut rng).as_bytes(),
+        &ParseOptions::default(),
+    )
+    .unwrap();
+    let config = Config {
+        num_iters: 1,
+        num_ants: 1,
+        evap_rate: 0.0,
+        seed: Some(1),
+        ..Config::default()
+    };
+    let mut solver = AcoSolver::new(&instance, &config);
+    solver.step();
+    let tour = solver.best_tour().to_vec();
+    let pheromone = solver.pheromone_matrix();
+    for k in 0..tour.len() {
+        let (a, b) = (tour[k], tour[(k + 1) % tour.len()]);
+        assert!(pheromone[a][b] > pheromone[b][a]);
+    }
+}
+
+#[test]
+fn aco_reaches_optimum_on_tiny_atsp_instances() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let config = Config {
+        num_iters: 200,
+        num_ants: 20,
+        seed: Some(5),
+        ..Config::default()
+    };
+    for n in [5, 7, 9] {
+        let contents = one_way_ring(n, &mut rng);
+        let instance = parse_tsp_reader(contents.as_bytes(), &ParseOptions::default()).unwrap();
+        let (_, optimum) = solve_brute_force(&instance).unwrap();
+        let (tour, length) = solve_tsp_aco(&instance, &config);
+        assert_eq!(instance.tour_length(&tour), length);
+        assert_eq!(length, optimum, "ring{}", n);
+    }
+}
+
+#[test]
+fn two_opt_counts_reversed_arcs_on_atsp_instances() {
+    let mut rng = StdRng::seed_from_u64(11);
+    let contents = random_matrix(30, &mut rng);
+    let instance = parse_tsp_reader(contents.as_bytes(), &ParseOptions::default()).unwrap();
+
+    for _ in 0..5 {
+        let mut tour: Vec<usize> = (0..30).collect();
+        tour.shuffle(&mut rng);
+        let before = instance.tour_length(&tour);
+        let after = TwoOpt.improve(&mut tour, &instance);
+        validate_permutation(&tour, 30).unwrap();
+        assert_eq!(after, instance.tour_length(&tour));
+        assert!(after <= before);
+        // A 2-opt local optimum: no segment reversal, with its arcs now
+        // running backwards, shortens the tour.
+        for i in 0..29 {
+            for j in i + 2..30 {
+                let mut reversed = tour.clone();
+                reversed[i + 1..=j].reverse();
+                assert!(instance.tour_length(&reversed) >= after);
+            }
+        }
+    }
+
+    let config = Config {
+        num_iters: 50,
+        seed: Some(1),
+        local_search: vec![local_search_by_name("2opt").unwrap()],
+        ..Config::default()
+    };
+    let (tour, length) = solve_tsp_aco(&instance, &config);
+    validate_permutation(&tour, 30).unwrap();
+    assert_eq!(instance.tour_length(&tour), length);
+}
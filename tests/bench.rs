@@ -0,0 +1,224 @@
+use common::{temp_dir, tsp_text, write_file};
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::path::{Path, PathBuf};
+use tsp_solver::bench::{
+    ablation_study, bench_instance, bench_instances, best_known_length, format_ablations,
+    summarize, wilcoxon_signed_rank,
+};
+use tsp_solver::edge_frequency::write_edge_frequencies;
+use tsp_solver::{Ablation, Config, embedded_optimum};
+
+mod common;
+
+fn write_circle(dir: &Path, name: &str, n: usize) -> PathBuf {
+    let coords: Vec<(f64, f64)> = (0..n)
+        .map(|i| 2.0 * PI * i as f64 / n as f64)
+        .map(|angle| (100.0 * angle.cos(), 100.0 * angle.sin()))
+        .collect();
+    let path = dir.join(format!("{}.tsp", name));
+    std::fs::write(&path, tsp_text(name, &coords)).unwrap();
+    path
+}
+
+#[test]
+fn solutions_file_overrides_embedded_optima() {
+    assert_eq!(embedded_optimum("Berlin52"), Some(7542.0));
+    let solutions = HashMap::from([("berlin52".to_string(), 7000.0)]);
+    assert_eq!(best_known_length("berlin52.tsp", &solutions), Some(7000.0));
+    assert_eq!(best_known_length("eil51", &solutions), Some(426.0));
+    assert_eq!(best_known_length("unknown", &solutions), None);
+}
+
+#[test]
+fn bench_reports_gaps_against_best_known() {
+    let dir = temp_dir("bench");
+    write_circle(&dir, "circle12", 12);
+    write_circle(&dir, "circle20", 20);
+    std::fs::write(dir.join("notes.txt"), "not an instance").unwrap();
+
+    let instances = bench_instances(dir.to_str().unwrap()).unwrap();
+    assert_eq!(instances.len(), 2);
+    // Convex position: the circle order is optimal (12 * chord of 30 degrees).
+    let optimum = (12.0 * 2.0 * 100.0 * (PI / 12.0).sin()).round();
+    let solutions = HashMap::from([("circle12".to_string(), optimum)]);
+    let config = Config {
+        num_iters: 100,
+        num_ants: 10,
+        num_seeds: 3,
+        ..Config::default()
+    };
+    let rows: Vec<_> = instances
+        .iter()
+        .map(|path| bench_instance(path, &config, &solutions).unwrap())
+        .collect();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(rows[0].instance, "circle12");
+    assert_eq!(rows[0].runs, 3);
+    assert_eq!(rows[0].best, optimum);
+    assert_eq!(rows[0].best_gap, Some(0.0));
+    assert!(rows[0].mean_gap.is_some_and(|gap| gap >= 0.0));
+    assert_eq!(rows[1].best_known, None);
+    assert_eq!(rows[1].mean_gap, None);
+    let records = &rows[0].records;
+    assert_eq!(
+        records.iter().map(|r| r.seed).collect::<Vec<_>>(),
+        [1, 2, 3]
+    );
+    assert!(
+        records
+            .iter()
+            .all(|r| r.instance_hash == rows[0].instance_hash)
+    );
+    assert!(
+        records
+            .iter()
+            .any(|r| r.length == optimum && r.gap == Some(0.0))
+    );
+    assert!(rows[1].records.iter().all(|r| r.gap.is_none()));
+
+    // Every run that reached the optimum also passed the looser targets,
+    // no later than the optimum itself.
+    let ttt = &rows[0].time_to_target;
+    assert_eq!(
+        ttt.iter().map(|t| t.gap).collect::<Vec<_>>(),
+        config.target_gaps
+    );
+    assert!(ttt.iter().all(|t| t.reached >= ttt[3].reached));
+    assert!(ttt[3].reached >= 1);
+    assert!(ttt[0].mean_iterations.unwrap() <= ttt[3].mean_iterations.unwrap());
+    assert!(ttt[3].mean_iterations.unwrap() <= config.num_iters as f64);
+    assert!(rows[1].time_to_target.is_empty());
+
+    let summary = summarize(&rows);
+    assert_eq!(summary.instances, 2);
+    assert_eq!(summary.with_best_known, 1);
+    assert_eq!(summary.optimal_found, 1);
+    assert_eq!(summary.mean_best_gap, Some(0.0));
+}
+
+#[test]
+fn signed_rank_test_matches_exact_distribution() {
+    let test = wilcoxon_signed_rank(&[1.0, 2.0, 3.0, 4.0, 5.0]);
+    assert_eq!(test.pairs, 5);
+    assert_eq!(test.statistic, 15.0);
+    assert!((test.p_value - 2.0 / 32.0).abs() < 1e-12);
+
+    // Zero differences are dropped, tied magnitudes share a rank.
+    let test = wilcoxon_signed_rank(&[0.0, -1.0, 2.0, 3.0, 4.0, 5.0]);
+    assert_eq!(test.pairs, 5);
+    assert_eq!(test.statistic, 14.0);
+    assert!((test.p_value - 4.0 / 32.0).abs() < 1e-12);
+    let test = wilcoxon_signed_rank(&[-1.0, 1.0, 2.0]);
+    assert_eq!(test.statistic, 4.5);
+    assert_eq!(wilcoxon_signed_rank(&[0.0, 0.0]).p_value, 1.0);
+}
+
+#[test]
+fn signed_rank_test_uses_normal_approximation_for_many_pairs() {
+    let shifted: Vec<f64> = (0..80).map(|i| (i % 7) as f64 + 0.5).collect();
+    assert!(wilcoxon_signed_rank(&shifted).p_value < 1e-6);
+    let balanced: Vec<f64> = (1..=80)
+        .map(|i| if i % 2 == 0 { i as f64 } else { -(i as f64) })
+        .collect();
+    assert!(wilcoxon_signed_rank(&balanced).p_value > 0.5);
+}
+
+#[test]
+fn config_files_apply_on_top_of_the_command_line() {
+    let path = write_file(
+        "cfg.toml",
+        "# Candidate B\n[aco]\nbeta = 4.5\nevap_rate = 0.2\ninteger-costs = true\nheuristic = \"savings\"\n",
+    );
+    let base = Config {
+        num_ants: 7,
+        ..Config::default()
+    };
+    let config = base.with_file(path.to_str().unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(config.num_ants, 7);
+    assert_eq!(config.beta, 4.5);
+    assert_eq!(config.evap_rate, 0.2);
+    assert!(config.integer_costs);
+    assert!(base.with_file("/nonexistent/cfg.toml").is_err());
+}
+
+#[test]
+fn ablations_fall_behind_the_full_colony() {
+    let dir = temp_dir("ablation");
+    let path = write_circle(&dir, "circle30", 30);
+    let optimum = (30.0 * 2.0 * 100.0 * (PI / 30.0).sin()).round();
+    let solutions = HashMap::from([("circle30".to_string(), optimum)]);
+    let config = Config {
+        num_iters: 50,
+        num_ants: 10,
+        num_seeds: 2,
+        ..Config::default()
+    };
+
+    let rows = ablation_study(&path, &config, &solutions).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(rows.len(), 1 + Ablation::ALL.len());
+    let gap = |k: usize| rows[k].mean_gap.unwrap();
+    // Heuristic-only ants still build decent tours; random tours on 30
+    // nodes are hopeless.
+    assert!(gap(0) < 5.0, "full colony gap {}", gap(0));
+    assert!(gap(3) > 100.0, "random gap {}", gap(3));
+    assert!(gap(0) <= gap(1) && gap(0) <= gap(2));
+    let table = format_ablations(&[rows]);
+    assert!(table.contains("heuristic-only") && table.contains("Mean gap"));
+}
+
+#[test]
+fn ablation_flag_zeroes_exponents_after_other_flags() {
+    let args = ["--ablation", "pheromone-only", "-b", "4"].map(String::from);
+    let config = Config::default().with_args(args.into_iter()).unwrap();
+    assert_eq!(config.ablation, Some(Ablation::PheromoneOnly));
+    assert_eq!((config.alpha, config.beta), (1.0, 0.0));
+}
+
+#[test]
+fn edge_frequencies_count_edges_over_the_runs_best_tours() {
+    let dir = temp_dir("bench-edges");
+    let path = write_circle(&dir, "circle8", 8);
+    let config = Config {
+        num_iters: 50,
+        num_ants: 8,
+        num_seeds: 4,
+        ..Config::default()
+    };
+
+    // Every run finds the circle, so all of its edges are in every tour.
+    let row = bench_instance(&path, &config, &HashMap::new()).unwrap();
+    let frequencies = &row.edge_frequencies;
+    assert_eq!(frequencies.runs, 4);
+    assert_eq!(frequencies.edges.len(), 8);
+    assert_eq!(frequencies.stable_edges(), 8);
+    assert!(frequencies.edges.iter().all(|edge| edge.from < edge.to));
+
+    let csv_path = dir.join("edges.csv");
+    write_edge_frequencies(
+        std::slice::from_ref(frequencies),
+        csv_path.to_str().unwrap(),
+    )
+    .unwrap();
+    let csv = std::fs::read_to_string(&csv_path).unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(lines.next(), Some("instance,from,to,count,frequency"));
+    assert_eq!(lines.next(), Some("circle8,1,2,4,1"));
+    assert_eq!(lines.count(), 7);
+
+    let mut unstable = frequencies.clone();
+    unstable.instance = "other".to_string();
+    unstable.runs = 8;
+    let svg_path = dir.join("edges.svg");
+    write_edge_frequencies(&[frequencies.clone(), unstable], svg_path.to_str().unwrap()).unwrap();
+    let svg = std::fs::read_to_string(dir.join("edges-circle8.svg")).unwrap();
+    assert!(svg.contains("8 of 8 edges in every best tour"));
+    assert_eq!(svg.matches("<line").count(), 8);
+    let svg = std::fs::read_to_string(dir.join("edges-other.svg")).unwrap();
+    assert!(svg.contains("0 of 8 edges in every best tour"));
+    std::fs::remove_dir_all(dir).unwrap();
+}
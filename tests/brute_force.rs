@@ -0,0 +1,53 @@
+use common::write_instance;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use tsp_solver::{BRUTE_FORCE_MAX_DIMENSION, Config, solve_brute_force, solve_tsp_aco};
+
+mod common;
+
+fn random_coords(rng: &mut StdRng, n: usize) -> Vec<(f64, f64)> {
+    (0..n)
+        .map(|_| {
+            (
+                rng.random_range(0..1000) as f64,
+                rng.random_range(0..1000) as f64,
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn brute_force_solves_square() {
+    let coords = [(0.0, 0.0), (10.0, 10.0), (10.0, 0.0), (0.0, 10.0)];
+    let instance = write_instance("square4", &coords);
+    let (tour, length) = solve_brute_force(&instance).unwrap();
+    assert_eq!(length, 40.0);
+    assert_eq!(tour.len(), 4);
+    assert_eq!(instance.tour_length(&tour), 40.0);
+}
+
+#[test]
+fn brute_force_rejects_large_instances() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let coords = random_coords(&mut rng, BRUTE_FORCE_MAX_DIMENSION + 1);
+    let instance = write_instance("too-large", &coords);
+    assert!(solve_brute_force(&instance).is_err());
+}
+
+#[test]
+fn aco_reaches_optimum_on_tiny_random_instances() {
+    let mut rng = StdRng::seed_from_u64(42);
+    let config = Config {
+        num_iters: 300,
+        num_ants: 20,
+        ..Config::default()
+    };
+    for (k, n) in [6, 7, 8, 9, 10].into_iter().enumerate() {
+        let coords = random_coords(&mut rng, n);
+        let instance = write_instance(&format!("tiny{}", k), &coords);
+        let (_, optimum) = solve_brute_force(&instance).unwrap();
+        let (tour, length) = solve_tsp_aco(&instance, &config);
+        assert_eq!(tour.len(), n);
+        assert_eq!(length, optimum.round(), "instance with {} nodes", n);
+    }
+}
@@ -0,0 +1,63 @@
+use common::{temp_path, write_instance};
+use tsp_solver::{Config, ResultCache};
+
+mod common;
+
+fn coords() -> Vec<(f64, f64)> {
+    (0..10)
+        .map(|i| (((i * 41) % 89) as f64, ((i * 29) % 83) as f64))
+        .collect()
+}
+
+#[test]
+fn seeded_runs_are_served_from_the_cache() {
+    let dir = temp_path("tsp-cache");
+    let cache = ResultCache::new(&dir);
+    let instance = write_instance("cache10", &coords());
+    let config = Config {
+        num_iters: 20,
+        num_ants: 10,
+        seed: Some(9),
+        ..Config::default()
+    };
+
+    let (first, hit) = cache.solve(&instance, &config).unwrap();
+    assert!(!hit);
+    let (second, hit) = cache.solve(&instance, &config).unwrap();
+    assert!(hit);
+    assert_eq!(second.tour, first.tour);
+    assert_eq!(second.length, first.length);
+    assert_eq!(second.provenance, first.provenance);
+
+    // Other seeds and settings are separate entries.
+    let other_seed = Config {
+        seed: Some(10),
+        ..config.clone()
+    };
+    assert!(cache.get(&instance, &other_seed).is_none());
+    let other_beta = Config {
+        beta: 4.0,
+        ..config.clone()
+    };
+    assert_ne!(
+        ResultCache::key(&instance, &config),
+        ResultCache::key(&instance, &other_beta)
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn unseeded_runs_are_not_cached() {
+    let dir = temp_path("tsp-cache-unseeded");
+    let cache = ResultCache::new(&dir);
+    let instance = write_instance("cache-unseeded", &coords());
+    let config = Config {
+        num_iters: 5,
+        num_ants: 5,
+        ..Config::default()
+    };
+    assert_eq!(ResultCache::key(&instance, &config), None);
+    let (_, hit) = cache.solve(&instance, &config).unwrap();
+    assert!(!hit);
+    assert!(!dir.exists());
+}
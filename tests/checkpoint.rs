@@ -0,0 +1,58 @@
+use common::{temp_path, write_instance};
+use tsp_solver::{AcoSolver, Checkpoint, Config};
+
+mod common;
+
+fn seeded_config(num_iters: usize) -> Config {
+    Config {
+        num_iters,
+        num_ants: 10,
+        seed: Some(7),
+        ..Config::default()
+    }
+}
+
+fn coords() -> Vec<(f64, f64)> {
+    (0..20)
+        .map(|i| (((i * 37) % 101) as f64, ((i * 53) % 97) as f64))
+        .collect()
+}
+
+#[test]
+fn checkpoint_round_trips_through_file() {
+    let instance = write_instance("checkpoint_io", &coords());
+    let config = seeded_config(5);
+    let mut solver = AcoSolver::new(&instance, &config);
+    while !solver.is_finished() {
+        solver.step();
+    }
+    let checkpoint = solver.checkpoint();
+    let path = temp_path("checkpoint.txt");
+    let path = path.to_str().unwrap();
+    checkpoint.write(path).unwrap();
+    let read = Checkpoint::read(path).unwrap();
+    std::fs::remove_file(path).unwrap();
+    assert_eq!(read, checkpoint);
+    assert_eq!(read.iteration, 5);
+    assert_eq!(read.dimension(), 20);
+}
+
+#[test]
+fn resumed_seeded_run_matches_uninterrupted_run() {
+    let instance = write_instance("checkpoint_resume", &coords());
+    let full_config = seeded_config(30);
+    let mut full = AcoSolver::new(&instance, &full_config);
+    for _ in 0..12 {
+        full.step();
+    }
+    let checkpoint = full.checkpoint();
+    let uninterrupted = full.run();
+
+    let resume_config = Config {
+        resume: Some(checkpoint),
+        ..seeded_config(30)
+    };
+    let resumed = AcoSolver::new(&instance, &resume_config);
+    assert_eq!(resumed.iteration(), 12);
+    assert_eq!(resumed.run(), uninterrupted);
+}
@@ -0,0 +1,45 @@
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use tsp_solver::{ColonyParams, ColonyRanges, Config};
+
+fn ranges(value: &str) -> Result<Option<ColonyRanges>, &'static str> {
+    Config::default()
+        .with_args(["--colony-ranges", value].map(String::from).into_iter())
+        .map(|config| config.colony_ranges)
+}
+
+#[test]
+fn cli_ranges_leave_omitted_parameters_fixed() {
+    assert_eq!(
+        ranges("alpha=0.5:2, rho=0.1:0.1"),
+        Ok(Some(ColonyRanges {
+            alpha: Some((0.5, 2.0)),
+            beta: None,
+            evap_rate: Some((0.1, 0.1)),
+        }))
+    );
+    assert!(ranges("alpha=2:1").is_err());
+    assert!(ranges("gamma=0:1").is_err());
+}
+
+#[test]
+fn colonies_draw_parameters_within_the_ranges() {
+    let config = Config {
+        beta: 4.0,
+        ..Config::default()
+    };
+    let ranges = ColonyRanges {
+        alpha: Some((0.5, 2.0)),
+        beta: None,
+        evap_rate: Some((0.2, 0.2)),
+    };
+    let mut rng = StdRng::seed_from_u64(5);
+    let draws: Vec<ColonyParams> = (0..50).map(|_| ranges.sample(&config, &mut rng)).collect();
+    assert!(draws.iter().all(|p| (0.5..=2.0).contains(&p.alpha)));
+    assert!(draws.iter().all(|p| p.beta == 4.0 && p.evap_rate == 0.2));
+    assert!(draws.iter().any(|p| p.alpha != draws[0].alpha));
+
+    let mut colony = config.clone();
+    draws[0].apply(&mut colony);
+    assert_eq!(ColonyParams::of(&colony), draws[0]);
+}
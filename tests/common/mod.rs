@@ -0,0 +1,61 @@
+// Each test crate uses only some of these helpers.
+#![allow(dead_code)]
+
+use std::fmt::Write;
+use std::path::PathBuf;
+use tsp_solver::{ParseOptions, TspInstance, parse_tsp_file, parse_tsp_reader};
+
+/// A 10 by 10 square, whose optimal tour has length 40.
+pub const SQUARE: [(f64, f64); 4] = [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)];
+
+/// Parses TSPLIB text with the default options.
+pub fn parse(contents: &str) -> Result<TspInstance, String> {
+    parse_tsp_reader(contents.as_bytes(), &ParseOptions::default())
+}
+
+/// TSPLIB text of an EUC_2D instance with 1-based node ids.
+pub fn tsp_text(name: &str, coords: &[(f64, f64)]) -> String {
+    let mut contents = format!(
+        "NAME: {}\nTYPE: TSP\nDIMENSION: {}\nEDGE_WEIGHT_TYPE: EUC_2D\nNODE_COORD_SECTION\n",
+        name,
+        coords.len()
+    );
+    for (i, (x, y)) in coords.iter().enumerate() {
+        writeln!(contents, "{} {} {}", i + 1, x, y).unwrap();
+    }
+    contents + "EOF\n"
+}
+
+/// A path in the temporary directory, unique to this test process: the
+/// process id is added to `name` before its extension.
+pub fn temp_path(name: &str) -> PathBuf {
+    let id = std::process::id();
+    std::env::temp_dir().join(match name.rsplit_once('.') {
+        Some((stem, extension)) => format!("{}-{}.{}", stem, id, extension),
+        None => format!("{}-{}", name, id),
+    })
+}
+
+/// Writes `contents` to [`temp_path`] and returns the path.
+pub fn write_file(name: &str, contents: impl AsRef<[u8]>) -> PathBuf {
+    let path = temp_path(name);
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+/// An empty directory in the temporary directory, unique to this test
+/// process; leftovers of an earlier run are removed.
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = temp_path(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Builds an EUC_2D instance by writing and parsing a temporary TSPLIB file.
+pub fn write_instance(name: &str, coords: &[(f64, f64)]) -> TspInstance {
+    let path = write_file(&format!("{}.tsp", name), tsp_text(name, coords));
+    let instance = parse_tsp_file(path.to_str().unwrap()).unwrap();
+    std::fs::remove_file(path).unwrap();
+    instance
+}
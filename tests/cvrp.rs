@@ -0,0 +1,75 @@
+use tsp_solver::{ParseOptions, parse_tsp_reader};
+
+const CVRP: &str = "NAME: toy-n5-k2
+COMMENT: depot in the middle
+TYPE: CVRP
+DIMENSION: 5
+EDGE_WEIGHT_TYPE: EUC_2D
+CAPACITY: 30
+NODE_COORD_SECTION
+1 0 0
+2 10 0
+3 0 10
+4 -10 0
+5 0 -10
+DEMAND_SECTION
+1 0
+2 12
+3 9
+4 17
+5 4
+DEPOT_SECTION
+1
+-1
+EOF
+";
+
+#[test]
+fn cvrp_sections_are_stored_on_the_instance() {
+    let instance = parse_tsp_reader(CVRP.as_bytes(), &ParseOptions::default()).unwrap();
+    assert_eq!(instance.dimension, 5);
+    assert_eq!(instance.capacity, Some(30.0));
+    assert_eq!(instance.demands, Some(vec![0.0, 12.0, 9.0, 17.0, 4.0]));
+    assert_eq!(instance.depots, Some(vec![0]));
+    assert_eq!(instance.dist_matrix.get(0, 1), 10.0);
+
+    let sub = instance.sub_instance("sub".to_string(), &[3, 0, 2]);
+    assert_eq!(sub.capacity, Some(30.0));
+    assert_eq!(sub.demands, Some(vec![17.0, 0.0, 9.0]));
+    assert_eq!(sub.depots, Some(vec![1]));
+}
+
+#[test]
+fn plain_tsp_has_no_routing_data() {
+    let tsp = CVRP
+        .replace("CAPACITY: 30\n", "")
+        .split("DEMAND_SECTION")
+        .next()
+        .unwrap()
+        .to_string();
+    let instance = parse_tsp_reader(tsp.as_bytes(), &ParseOptions::default()).unwrap();
+    assert_eq!(instance.capacity, None);
+    assert_eq!(instance.demands, None);
+    assert_eq!(instance.depots, None);
+}
+
+#[test]
+fn malformed_routing_sections_are_rejected() {
+    let options = ParseOptions::default();
+    for (from, to) in [
+        ("CAPACITY: 30", "CAPACITY: many"),
+        ("5 4\n", "6 4\n"),
+        ("5 4\n", "3 4\n"),
+        ("5 4\n", ""),
+        ("2 12\n", "2\n"),
+        ("1\n-1", "9\n-1"),
+    ] {
+        let text = CVRP.replacen(from, to, 1);
+        assert!(
+            parse_tsp_reader(text.as_bytes(), &options).is_err(),
+            "{} -> {}",
+            from,
+            to
+        );
+    }
+}
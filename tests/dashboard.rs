@@ -0,0 +1,99 @@
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use tsp_solver::SolverEvent;
+use tsp_solver::dashboard::{Metrics, serve};
+
+fn value<'a>(metrics: &'a str, series: &str) -> Option<&'a str> {
+    metrics
+        .lines()
+        .find_map(|line| line.strip_prefix(series)?.strip_prefix(' '))
+}
+
+fn iteration(iteration: usize, best_length: f64) -> SolverEvent {
+    SolverEvent::IterationFinished {
+        iteration,
+        iteration_best_length: best_length,
+        best_length,
+        edge_entropy: 0.5,
+    }
+}
+
+#[test]
+fn metrics_follow_the_run() {
+    let mut metrics = Metrics::new("ber\"lin", Some(100.0));
+    let text = metrics.render();
+    assert!(value(&text, "tsp_solver_jobs_queued").is_none());
+    assert_eq!(value(&text, "tsp_solver_jobs_running"), Some("0"));
+    assert!(value(&text, "tsp_solver_best_length{job=\"ber\\\"lin\"}").is_none());
+
+    metrics.record(&iteration(0, f64::MAX));
+    metrics.record(&SolverEvent::NewBest {
+        iteration: 1,
+        tour: vec![0, 1, 2],
+        length: 110.0,
+    });
+    metrics.record(&iteration(1, 110.0));
+    let text = metrics.render();
+    let job = "{job=\"ber\\\"lin\"}";
+    assert_eq!(value(&text, "tsp_solver_jobs_running"), Some("1"));
+    assert_eq!(
+        value(&text, &format!("tsp_solver_iterations_total{}", job)),
+        Some("2")
+    );
+    assert_eq!(
+        value(&text, &format!("tsp_solver_new_best_total{}", job)),
+        Some("1")
+    );
+    assert_eq!(
+        value(&text, &format!("tsp_solver_best_length{}", job)),
+        Some("110")
+    );
+    assert_eq!(
+        value(&text, &format!("tsp_solver_best_gap_percent{}", job)),
+        Some("10")
+    );
+    assert!(text.contains("# TYPE tsp_solver_iterations_total counter\n"));
+
+    metrics.connection_failed();
+    metrics.finish();
+    let text = metrics.render();
+    assert_eq!(value(&text, "tsp_solver_jobs_running"), Some("0"));
+    assert_eq!(value(&text, "tsp_solver_jobs_finished_total"), Some("1"));
+    assert_eq!(
+        value(&text, "tsp_solver_dashboard_connection_errors_total"),
+        Some("1")
+    );
+}
+
+#[test]
+fn dashboard_serves_metrics() {
+    let addr = {
+        let probe = TcpListener::bind("127.0.0.1:0").unwrap();
+        probe.local_addr().unwrap().to_string()
+    };
+    let (sender, receiver) = mpsc::channel();
+    serve(&addr, "square", None, None, receiver).unwrap();
+    sender.send(iteration(0, 40.0)).unwrap();
+    sender.send(iteration(1, 40.0)).unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        let mut stream = TcpStream::connect(&addr).unwrap();
+        write!(stream, "GET /metrics HTTP/1.1\r\nHost: {}\r\n\r\n", addr).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        let iterations = value(&response, "tsp_solver_iterations_total{job=\"square\"}");
+        if iterations == Some("2") {
+            assert_eq!(
+                value(&response, "tsp_solver_best_length{job=\"square\"}"),
+                Some("40")
+            );
+            break;
+        }
+        assert!(Instant::now() < deadline, "{}", response);
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
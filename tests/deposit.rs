@@ -0,0 +1,78 @@
+use common::write_instance;
+use tsp_solver::{AcoSolver, Config, DepositSchedule, InitStrategy};
+
+mod common;
+
+fn coords(scale: f64) -> Vec<(f64, f64)> {
+    (0..12)
+        .map(|i| ((i * 37 % 101) as f64 * scale, (i * 59 % 103) as f64 * scale))
+        .collect()
+}
+
+#[allow(clippy::unnecessary_cast)] // `Pheromone` is f32 with `f32-pheromone`.
+fn trails_after_one_step(name: &str, scale: f64, config: Config) -> Vec<f64> {
+    let instance = write_instance(&format!("{}{}", name, scale), &coords(scale));
+    let config = Config {
+        num_ants: 8,
+        init_pheromone: InitStrategy::Constant(1.0),
+        seed: Some(6),
+        ..config
+    };
+    let mut solver = AcoSolver::new(&instance, &config);
+    solver.step();
+    solver
+        .pheromone_matrix()
+        .iter()
+        .flatten()
+        .map(|&level| level as f64)
+        .collect()
+}
+
+// Scaling by a power of two keeps distances exact, so the same ants walk
+// the same tours on both instances.
+#[test]
+fn normalized_deposits_ignore_the_distance_scale() {
+    let normalized = Config {
+        normalize_deposit: true,
+        ..Config::default()
+    };
+    let small = trails_after_one_step("normalized", 1.0, normalized.clone());
+    let large = trails_after_one_step("normalized", 1024.0, normalized);
+    for (a, b) in small.iter().zip(&large) {
+        assert!((a - b).abs() <= 1e-5 * a.abs(), "{} vs {}", a, b);
+    }
+
+    let small = trails_after_one_step("raw", 1.0, Config::default());
+    let large = trails_after_one_step("raw", 1024.0, Config::default());
+    assert!(small.iter().zip(&large).any(|(a, b)| (a - b).abs() > 1e-3));
+}
+
+#[test]
+fn mmas_schedule_shifts_to_the_global_best() {
+    let global = |iterations: std::ops::Range<usize>| {
+        iterations
+            .filter(|&i| DepositSchedule::Mmas.uses_global_best(i))
+            .count()
+    };
+    assert_eq!(global(0..25), 0);
+    assert_eq!(global(25..75), 10);
+    assert_eq!(global(250..260), 10);
+}
+
+#[test]
+fn single_tour_schedules_deposit_on_one_tour() {
+    for schedule in [DepositSchedule::IterationBest, DepositSchedule::GlobalBest] {
+        let trails = trails_after_one_step(
+            &format!("{:?}", schedule),
+            1.0,
+            Config {
+                deposit_schedule: Some(schedule.clone()),
+                ..Config::default()
+            },
+        );
+        // Everything evaporated to 0.9; one closed tour of 12 edges, in both
+        // directions, got a deposit on top.
+        let reinforced = trails.iter().filter(|&&level| level > 0.9 + 1e-6).count();
+        assert_eq!(reinforced, 24, "{:?}", schedule);
+    }
+}
@@ -0,0 +1,104 @@
+use common::write_instance;
+use tsp_solver::{AcoSolver, Config, EvaporationModel, solve_tsp_aco};
+
+mod common;
+
+fn coords() -> Vec<(f64, f64)> {
+    (0..12)
+        .map(|i| (((i * 37) % 83) as f64, ((i * 23) % 61) as f64))
+        .collect()
+}
+
+// One iteration without any deposit, so only evaporation moves the trails.
+fn evaporated(model: EvaporationModel) -> (Vec<usize>, Vec<Vec<f64>>) {
+    let instance = write_instance(&format!("evap-{:?}", model), &coords());
+    let config = Config {
+        num_ants: 8,
+        q_val: 0.0,
+        evaporation: model,
+        seed: Some(9),
+        ..Config::default()
+    };
+    let mut solver = AcoSolver::new(&instance, &config);
+    solver.step();
+    let levels = (0..12)
+        .map(|i| (0..12).map(|j| solver.pheromone(i, j).unwrap()).collect())
+        .collect();
+    (solver.best_tour().to_vec(), levels)
+}
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() < 1e-6
+}
+
+#[test]
+fn uniform_evaporation_scales_every_trail() {
+    let (_, levels) = evaporated(EvaporationModel::Uniform);
+    assert!(close(levels[0][5], 0.09));
+    assert!(close(levels[7][3], 0.09));
+}
+
+#[test]
+fn usage_evaporation_spares_unused_edges() {
+    let (_, levels) = evaporated(EvaporationModel::Usage);
+    let off_diagonal =
+        || (0..12).flat_map(|i| (0..12).filter(move |&j| j != i).map(move |j| (i, j)));
+    assert!(off_diagonal().any(|(i, j)| close(levels[i][j], 0.1)));
+    assert!(off_diagonal().any(|(i, j)| close(levels[i][j], 0.09)));
+    assert!(
+        off_diagonal().all(|(i, j)| levels[i][j] >= 0.09 - 1e-6 && levels[i][j] == levels[j][i])
+    );
+}
+
+#[test]
+fn rank_evaporation_keeps_the_best_tour() {
+    let (best, levels) = evaporated(EvaporationModel::Rank(1));
+    for (a, b) in best.iter().zip(best.iter().cycle().skip(1)) {
+        assert!(close(levels[*a][*b], 0.1));
+        assert!(close(levels[*b][*a], 0.1));
+    }
+    let in_best = |i: usize, j: usize| {
+        best.iter()
+            .zip(best.iter().cycle().skip(1))
+            .any(|(&a, &b)| (a, b) == (i, j) || (a, b) == (j, i))
+    };
+    let (i, j) = (0..12)
+        .flat_map(|i| (0..12).map(move |j| (i, j)))
+        .find(|&(i, j)| i != j && !in_best(i, j))
+        .unwrap();
+    assert!(close(levels[i][j], 0.09));
+}
+
+#[test]
+fn every_model_solves() {
+    let instance = write_instance("evap-solve", &coords());
+    for model in [
+        EvaporationModel::Uniform,
+        EvaporationModel::Usage,
+        EvaporationModel::Rank(3),
+    ] {
+        let config = Config {
+            num_iters: 30,
+            evaporation: model,
+            seed: Some(2),
+            ..Config::default()
+        };
+        let (tour, length) = solve_tsp_aco(&instance, &config);
+        assert_eq!(tour.len(), 12);
+        assert!(length > 0.0 && length < f64::MAX);
+    }
+}
+
+#[test]
+fn evaporation_model_is_parsed() {
+    let parse = |value: &str| {
+        Config::default()
+            .with_args(["--evaporation".to_string(), value.to_string()].into_iter())
+            .map(|config| config.evaporation)
+    };
+    assert_eq!(parse("usage"), Ok(EvaporationModel::Usage));
+    assert_eq!(parse("rank:4"), Ok(EvaporationModel::Rank(4)));
+    assert_eq!(parse("uniform"), Ok(EvaporationModel::Uniform));
+    assert!(parse("rank:0").is_err());
+    assert!(parse("global").is_err());
+}
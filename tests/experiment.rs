@@ -0,0 +1,125 @@
+use common::{temp_dir, tsp_text};
+use std::collections::HashMap;
+use std::fs;
+use tsp_solver::experiment::{experiment_seeds, run_experiment, utc_timestamp};
+use tsp_solver::{Command, Config, parse_tour_file, parse_tsp_file};
+
+mod common;
+
+fn write_tsp(dir: &std::path::Path, name: &str, n: usize) {
+    let coords: Vec<(f64, f64)> = (0..n)
+        .map(|i| (((i * 37) % 71) as f64, ((i * 19) % 53) as f64))
+        .collect();
+    fs::write(dir.join(format!("{}.tsp", name)), tsp_text(name, &coords)).unwrap();
+}
+
+#[test]
+fn experiment_writes_a_session_directory() {
+    let base = temp_dir("tsp-experiment");
+    let instances_dir = base.join("instances");
+    fs::create_dir_all(&instances_dir).unwrap();
+    write_tsp(&instances_dir, "alpha8", 8);
+    write_tsp(&instances_dir, "beta10", 10);
+    fs::write(instances_dir.join("broken.tsp"), "NAME: broken\nEOF\n").unwrap();
+    let config_path = base.join("colony.toml");
+    fs::write(&config_path, "iters = 15\nants = 6\n").unwrap();
+
+    let config = Config::default()
+        .with_file(config_path.to_str().unwrap())
+        .unwrap();
+    let instances = tsp_solver::bench::bench_instances(instances_dir.to_str().unwrap()).unwrap();
+    let solutions = HashMap::from([("alpha8".to_string(), 1.0)]);
+    let experiment = run_experiment(
+        "smoke test",
+        &base.join("out"),
+        &instances,
+        &[3, 4],
+        &config,
+        Some(config_path.to_str().unwrap()),
+        &solutions,
+    )
+    .unwrap();
+
+    let dir_name = experiment.dir.file_name().unwrap().to_string_lossy();
+    assert!(dir_name.starts_with("smoke_test-"), "{}", dir_name);
+    assert_eq!(experiment.runs.len(), 4);
+    assert_eq!(
+        experiment
+            .runs
+            .iter()
+            .map(|run| (run.instance.as_str(), run.seed))
+            .collect::<Vec<_>>(),
+        [("alpha8", 3), ("alpha8", 4), ("beta10", 3), ("beta10", 4)]
+    );
+    assert!(experiment.runs[0].gap.is_some());
+    assert!(experiment.runs[2].gap.is_none());
+
+    let dir = &experiment.dir;
+    assert_eq!(
+        fs::read_to_string(dir.join("config/colony.toml")).unwrap(),
+        "iters = 15\nants = 6\n"
+    );
+    let settings = fs::read_to_string(dir.join("config/settings.txt")).unwrap();
+    assert!(settings.contains("seeds=[3, 4]") && settings.contains("iters=15"));
+    let log = fs::read_to_string(dir.join("experiment.log")).unwrap();
+    assert_eq!(log.lines().count(), 5);
+    assert!(log.contains("Skipping") && log.contains("beta10 seed 4"));
+    assert!(dir.join("runs/alpha8-seed3.json").is_file());
+    let instance = parse_tsp_file(instances_dir.join("beta10.tsp").to_str().unwrap()).unwrap();
+    let tour = parse_tour_file(
+        dir.join("tours/beta10-seed4.tour").to_str().unwrap(),
+        &instance,
+    )
+    .unwrap();
+    assert_eq!(tour.len(), 10);
+    let csv = fs::read_to_string(dir.join("summary.csv")).unwrap();
+    assert_eq!(csv.lines().count(), 5);
+    let summary = fs::read_to_string(dir.join("summary.txt")).unwrap();
+    assert!(summary.contains("alpha8") && summary.contains("beta10"));
+
+    // A second session of the same name never reuses the directory.
+    let again = run_experiment(
+        "smoke test",
+        &base.join("out"),
+        &instances[..1],
+        &[3],
+        &config,
+        None,
+        &solutions,
+    )
+    .unwrap();
+    assert_ne!(again.dir, experiment.dir);
+    fs::remove_dir_all(&base).unwrap();
+}
+
+#[test]
+fn timestamps_are_utc_calendar_dates() {
+    assert_eq!(utc_timestamp(0), "19700101-000000");
+    assert_eq!(utc_timestamp(951_782_400), "20000229-000000");
+    assert_eq!(utc_timestamp(1_791_545_045), "20261009-112405");
+}
+
+#[test]
+fn experiment_command_is_parsed() {
+    let args = [
+        "tsp-solver",
+        "experiment",
+        "nightly",
+        "instances",
+        "--seed-list",
+        "5,7,9",
+        "--output-dir",
+        "runs",
+    ];
+    let config = Config::build(args.map(String::from).into_iter()).unwrap();
+    assert_eq!(config.command, Command::Experiment("nightly".to_string()));
+    assert_eq!(config.file_path.as_deref(), Some("instances"));
+    assert_eq!(config.output_dir, "runs");
+    assert_eq!(experiment_seeds(&config), vec![5, 7, 9]);
+    let default = Config {
+        seed: Some(10),
+        num_seeds: 3,
+        ..Config::default()
+    };
+    assert_eq!(experiment_seeds(&default), vec![10, 11, 12]);
+}
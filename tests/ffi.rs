@@ -0,0 +1,90 @@
+use common::{tsp_text, write_file};
+use std::ffi::{CString, c_void};
+use tsp_solver::ffi::tsp_solve_file;
+use tsp_solver::parse_tsp_file;
+
+mod common;
+
+struct Progress {
+    calls: usize,
+    last_length: f64,
+}
+
+unsafe extern "C" fn record(
+    user_data: *mut c_void,
+    _iteration: usize,
+    tour: *const usize,
+    tour_len: usize,
+    length: f64,
+) {
+    let progress = unsafe { &mut *(user_data as *mut Progress) };
+    let tour = unsafe { std::slice::from_raw_parts(tour, tour_len) };
+    assert_eq!(tour.len(), 6);
+    assert!(length <= progress.last_length);
+    progress.calls += 1;
+    progress.last_length = length;
+}
+
+#[test]
+fn incumbent_callback_reports_new_best_tours() {
+    let coords = [
+        (0.0, 0.0),
+        (3.0, 0.0),
+        (6.0, 0.0),
+        (6.0, 4.0),
+        (3.0, 4.0),
+        (0.0, 4.0),
+    ];
+    let path = write_file("ffi6.tsp", tsp_text("ffi6", &coords));
+    let instance = parse_tsp_file(path.to_str().unwrap()).unwrap();
+    let c_path = CString::new(path.to_str().unwrap()).unwrap();
+
+    let mut progress = Progress {
+        calls: 0,
+        last_length: f64::MAX,
+    };
+    let mut tour = [0usize; 6];
+    let mut length = 0.0;
+    let dimension = unsafe {
+        tsp_solve_file(
+            c_path.as_ptr(),
+            Some(record),
+            &mut progress as *mut Progress as *mut c_void,
+            tour.as_mut_ptr(),
+            tour.len(),
+            &mut length,
+        )
+    };
+    std::fs::remove_file(path).unwrap();
+
+    assert_eq!(dimension, 6);
+    assert!(progress.calls >= 1);
+    assert_eq!(progress.last_length, length);
+    assert_eq!(length, 20.0);
+    assert_eq!(instance.tour_length(&tour), 20.0);
+}
+
+#[test]
+fn short_tour_buffer_is_reported_and_left_untouched() {
+    let coords = [(0.0, 0.0), (3.0, 0.0), (3.0, 4.0), (0.0, 4.0)];
+    let path = write_file("ffi4.tsp", tsp_text("ffi4", &coords));
+    let c_path = CString::new(path.to_str().unwrap()).unwrap();
+
+    let mut tour = [usize::MAX; 3];
+    let mut length = 0.0;
+    let status = unsafe {
+        tsp_solve_file(
+            c_path.as_ptr(),
+            None,
+            std::ptr::null_mut(),
+            tour.as_mut_ptr(),
+            tour.len(),
+            &mut length,
+        )
+    };
+    std::fs::remove_file(path).unwrap();
+
+    assert_eq!(status, -2);
+    assert_eq!(tour, [usize::MAX; 3]);
+    assert_eq!(length, 14.0);
+}
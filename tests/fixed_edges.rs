@@ -0,0 +1,79 @@
+use std::sync::Arc;
+use tsp_solver::{
+    Config, FixedEdges, ParseOptions, TwoOpt, Violation, parse_tsp_reader, solve_tsp_aco,
+};
+
+// Eight nodes on a circle; the fixed edges cut across it.
+const FIXED: &str = "NAME: fixed8
+TYPE: TSP
+DIMENSION: 8
+EDGE_WEIGHT_TYPE: EUC_2D
+NODE_COORD_SECTION
+1 900 500
+2 783 783
+3 500 900
+4 217 783
+5 100 500
+6 217 217
+7 500 100
+8 783 217
+FIXED_EDGES_SECTION
+1 5
+5 3
+2 7
+-1
+EOF
+";
+
+fn has_edge(tour: &[usize], a: usize, b: usize) -> bool {
+    let n = tour.len();
+    (0..n).any(|i| {
+        let (x, y) = (tour[i], tour[(i + 1) % n]);
+        (x, y) == (a, b) || (x, y) == (b, a)
+    })
+}
+
+#[test]
+fn fixed_edges_section_is_parsed() {
+    let instance = parse_tsp_reader(FIXED.as_bytes(), &ParseOptions::default()).unwrap();
+    assert_eq!(instance.fixed_edges, Some(vec![(0, 4), (4, 2), (1, 6)]));
+    let sub = instance.sub_instance("sub".to_string(), &[4, 2, 0, 3]);
+    assert_eq!(sub.fixed_edges, Some(vec![(2, 0), (0, 1)]));
+
+    let odd = FIXED.replace("2 7\n", "2\n");
+    assert!(parse_tsp_reader(odd.as_bytes(), &ParseOptions::default()).is_err());
+}
+
+#[test]
+fn solver_tours_contain_the_fixed_edges() {
+    let instance = parse_tsp_reader(FIXED.as_bytes(), &ParseOptions::default()).unwrap();
+    for local_search in [vec![], vec![Arc::new(TwoOpt) as _]] {
+        let config = Config {
+            num_iters: 30,
+            seed: Some(3),
+            local_search,
+            ..Config::default()
+        };
+        let (tour, length) = solve_tsp_aco(&instance, &config);
+        assert_eq!(tour.len(), 8);
+        assert!(has_edge(&tour, 0, 4) && has_edge(&tour, 4, 2) && has_edge(&tour, 1, 6));
+        assert!((length - instance.tour_length(&tour)).abs() < 1.0);
+    }
+}
+
+#[test]
+fn fixed_edges_must_form_paths() {
+    assert!(FixedEdges::new(5, &[(0, 1), (0, 2), (0, 3)]).is_err());
+    assert!(FixedEdges::new(5, &[(0, 1), (1, 2), (2, 0)]).is_err());
+    assert!(FixedEdges::new(3, &[(0, 1), (1, 2), (2, 0)]).is_ok());
+    assert!(FixedEdges::new(3, &[(0, 3)]).is_err());
+
+    let fixed = FixedEdges::new(4, &[(0, 2), (2, 0)]).unwrap();
+    assert_eq!(fixed.edges(), &[(0, 2)]);
+    assert!(fixed.is_satisfied(&[1, 0, 2, 3]));
+    assert!(fixed.is_satisfied(&[2, 1, 3, 0]));
+    assert_eq!(
+        fixed.violations(&[0, 1, 2, 3]),
+        vec![Violation::FixedEdge { a: 0, b: 2 }]
+    );
+}
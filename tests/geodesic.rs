@@ -0,0 +1,32 @@
+use tsp_solver::parser::{EdgeWeightType, TspInstance};
+
+#[test]
+fn geodesic_matches_reference_distances() {
+    // Vincenty's Flinders Peak -> Buninyong example: 54972.271 m.
+    let coords = [
+        (
+            -(37.0 + 57.0 / 60.0 + 3.72030 / 3600.0),
+            144.0 + 25.0 / 60.0 + 29.52440 / 3600.0,
+        ),
+        (
+            -(37.0 + 39.0 / 60.0 + 10.15610 / 3600.0),
+            143.0 + 55.0 / 60.0 + 35.38390 / 3600.0,
+        ),
+        (0.0, 0.0),
+        (0.0, 1.0),
+    ];
+    let instance =
+        TspInstance::from_coordinates("geodesic", &coords, EdgeWeightType::Geodesic, None).unwrap();
+    assert!((instance.dist_matrix.get(0, 1) - 54_972.271).abs() < 0.01);
+    // One degree of longitude along the equator: a * pi / 180.
+    assert!((instance.dist_matrix.get(2, 3) - 111_319.491).abs() < 0.01);
+    // Nearly antipodal points fall back to the sphere instead of failing.
+    let antipodal = TspInstance::from_coordinates(
+        "antipodal",
+        &[(0.0, 0.0), (0.5, 179.7)],
+        EdgeWeightType::Geodesic,
+        None,
+    )
+    .unwrap();
+    assert!(antipodal.dist_matrix.get(0, 1) > 19_900_000.0);
+}
@@ -0,0 +1,72 @@
+use tsp_solver::{ParseOptions, parse_tsp_reader, solve_brute_force};
+
+// A 6-cycle 1-2-3-4-5-6 with the chords 1-4 and 2-5.
+const EDGE_LIST: &str = "NAME: hcp6
+TYPE: HCP
+DIMENSION: 6
+EDGE_DATA_FORMAT: EDGE_LIST
+EDGE_DATA_SECTION
+1 2
+2 3
+3 4
+4 5
+5 6
+6 1
+1 4
+2 5
+-1
+EOF
+";
+
+const ADJ_LIST: &str = "NAME: hcp6
+TYPE: HCP
+DIMENSION: 6
+EDGE_DATA_FORMAT: ADJ_LIST
+EDGE_DATA_SECTION
+1 2 6 4 -1
+2 3 5 -1
+3 4 -1
+4 5 -1
+5 6 -1
+-1
+EOF
+";
+
+#[test]
+fn edge_list_becomes_zero_one_matrix() {
+    let instance = parse_tsp_reader(EDGE_LIST.as_bytes(), &ParseOptions::default()).unwrap();
+    assert_eq!(instance.dimension, 6);
+    assert_eq!(instance.dist_matrix.get(0, 1), 0.0);
+    assert_eq!(instance.dist_matrix.get(3, 0), 0.0);
+    assert_eq!(instance.dist_matrix.get(0, 2), 1.0);
+    assert_eq!(instance.dist_matrix.get(2, 5), 1.0);
+
+    // A tour of length 0 is a Hamiltonian cycle of the graph.
+    let (tour, length) = solve_brute_force(&instance).unwrap();
+    assert_eq!(length, 0.0);
+    assert_eq!(instance.tour_length(&tour), 0.0);
+    // Leaving the graph costs one per missing edge.
+    assert_eq!(instance.tour_length(&[0, 2, 1, 3, 4, 5]), 2.0);
+}
+
+#[test]
+fn adjacency_list_matches_edge_list() {
+    let edges = parse_tsp_reader(EDGE_LIST.as_bytes(), &ParseOptions::default()).unwrap();
+    let adjacency = parse_tsp_reader(ADJ_LIST.as_bytes(), &ParseOptions::default()).unwrap();
+    for i in 0..6 {
+        for j in 0..6 {
+            assert_eq!(edges.dist_matrix.get(i, j), adjacency.dist_matrix.get(i, j));
+        }
+    }
+}
+
+#[test]
+fn malformed_edge_data_is_rejected() {
+    let options = ParseOptions::default();
+    let out_of_range = EDGE_LIST.replace("1 4\n", "1 7\n");
+    assert!(parse_tsp_reader(out_of_range.as_bytes(), &options).is_err());
+    let half_edge = EDGE_LIST.replace("2 5\n", "2\n");
+    assert!(parse_tsp_reader(half_edge.as_bytes(), &options).is_err());
+    let no_format = EDGE_LIST.replace("EDGE_DATA_FORMAT: EDGE_LIST\n", "");
+    assert!(parse_tsp_reader(no_format.as_bytes(), &options).is_err());
+}
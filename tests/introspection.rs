@@ -0,0 +1,52 @@
+use common::write_instance;
+use tsp_solver::{AcoSolver, Config};
+
+mod common;
+
+fn coords() -> Vec<(f64, f64)> {
+    (0..10)
+        .map(|i| (((i * 31) % 79) as f64, ((i * 17) % 71) as f64))
+        .collect()
+}
+
+#[test]
+fn colony_state_can_be_inspected_between_iterations() {
+    let instance = write_instance("inspect10", &coords());
+    let config = Config {
+        num_iters: 20,
+        num_ants: 10,
+        seed: Some(4),
+        ..Config::default()
+    };
+    let mut solver = AcoSolver::new(&instance, &config);
+    assert!((solver.pheromone(0, 1).unwrap() - 0.1).abs() < 1e-6);
+    assert_eq!(solver.pheromone(0, 10), None);
+    for _ in 0..5 {
+        solver.step();
+    }
+    assert!(!solver.is_directed());
+    assert_eq!(solver.pheromone(2, 7), solver.pheromone(7, 2));
+
+    let strongest = solver.strongest_edges(5);
+    assert_eq!(strongest.len(), 5);
+    assert!(strongest.windows(2).all(|pair| pair[0].2 >= pair[1].2));
+    assert!(
+        strongest
+            .iter()
+            .all(|&(i, j, level)| { i < j && solver.pheromone(i, j) == Some(level) })
+    );
+    let all = solver.strongest_edges(usize::MAX);
+    assert_eq!(all.len(), 45);
+    assert_eq!(all[..5], strongest[..]);
+    // The best tour's edges got the elitist deposit on top.
+    let tour = solver.best_tour();
+    assert!(strongest.iter().any(|&(i, j, _)| {
+        tour.windows(2)
+            .any(|w| (w[0].min(w[1]), w[0].max(w[1])) == (i, j))
+    }));
+
+    let mass = solver.node_pheromone_mass();
+    assert_eq!(mass.len(), 10);
+    let row: f64 = (1..10).map(|j| solver.pheromone(0, j).unwrap()).sum();
+    assert!((mass[0] - row).abs() < 1e-9);
+}
@@ -0,0 +1,60 @@
+use common::write_instance;
+use tsp_solver::{AcoSolver, Config};
+
+mod common;
+
+// Wide spread of magnitudes, where summation order matters most.
+fn coords() -> Vec<(f64, f64)> {
+    (0..15)
+        .map(|i| {
+            let scale = 10f64.powi(i % 5 * 3);
+            (
+                ((i * 29) % 67) as f64 * scale,
+                ((i * 13) % 59) as f64 * 0.37,
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn best_length_matches_the_exact_tour_length() {
+    let instance = write_instance("drift", &coords());
+    let config = Config {
+        num_iters: 40,
+        num_ants: 10,
+        length_check_interval: 1,
+        verify_lengths: true,
+        seed: Some(6),
+        ..Config::default()
+    };
+    let mut solver = AcoSolver::new(&instance, &config);
+    for _ in 0..40 {
+        solver.step();
+        assert_eq!(
+            solver.best_tour_length(),
+            instance.tour_length(solver.best_tour())
+        );
+    }
+}
+
+#[test]
+fn length_check_options_are_parsed() {
+    let config = Config::default()
+        .with_args(
+            ["--length-check-interval", "25", "--verify-lengths"]
+                .map(String::from)
+                .into_iter(),
+        )
+        .unwrap();
+    assert_eq!(config.length_check_interval, 25);
+    assert!(config.verify_lengths);
+    assert!(
+        Config::default()
+            .with_args(
+                ["--length-check-interval", "x"]
+                    .map(String::from)
+                    .into_iter()
+            )
+            .is_err()
+    );
+}
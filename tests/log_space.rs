@@ -0,0 +1,50 @@
+use common::write_instance;
+use tsp_solver::{Config, InitStrategy, solve_tsp_aco};
+
+mod common;
+
+fn circle(n: usize, radius: f64) -> Vec<(f64, f64)> {
+    (0..n)
+        .map(|i| {
+            let angle = i as f64 * std::f64::consts::TAU / n as f64;
+            (radius * angle.cos(), radius * angle.sin())
+        })
+        .collect()
+}
+
+fn is_circle_order(tour: &[usize]) -> bool {
+    let n = tour.len();
+    tour.windows(2)
+        .chain(std::iter::once([tour[n - 1], tour[0]].as_slice()))
+        .all(|pair| {
+            let step = (pair[1] + n - pair[0]) % n;
+            step == 1 || step == n - 1
+        })
+}
+
+// With a huge beta every ant is a nearest neighbour walk, which follows the
+// circle, as long as the weights survive outside the range of f64.
+#[test]
+fn extreme_exponents_still_prefer_near_nodes() {
+    // Far apart nodes underflow eta^beta; strong uniform trails overflow
+    // tau^alpha.
+    let cases = [
+        ("underflow16", 10_000.0, 1.0, 1.0),
+        ("overflow16", 10.0, 300.0, 100.0),
+    ];
+    for (name, radius, alpha, trail) in cases {
+        let instance = write_instance(name, &circle(16, radius));
+        let config = Config {
+            num_iters: 1,
+            num_ants: 4,
+            alpha,
+            beta: 300.0,
+            init_pheromone: InitStrategy::Constant(trail),
+            seed: Some(5),
+            ..Config::default()
+        };
+        let (tour, length) = solve_tsp_aco(&instance, &config);
+        assert_eq!(tour.len(), 16, "{}", name);
+        assert!(is_circle_order(&tour), "{}: {:?} ({})", name, tour, length);
+    }
+}
@@ -0,0 +1,51 @@
+use common::write_file;
+use tsp_solver::{
+    Config, FORBIDDEN_DISTANCE, MatrixResponseFormat, ParseOptions, RoadMetric,
+    import_matrix_response, solve_tsp_aco,
+};
+
+mod common;
+
+#[test]
+fn imports_openrouteservice_matrix() {
+    let path = write_file(
+        "ors.json",
+        r#"{"durations":[[0,5,9,4],[5,0,3,null],[9,3,0,6],[4,8,6,0]],"metadata":{}}"#,
+    );
+    let instance = import_matrix_response(
+        path.to_str().unwrap(),
+        None,
+        RoadMetric::Duration,
+        &ParseOptions::default(),
+    );
+    std::fs::remove_file(path).unwrap();
+    let instance = instance.unwrap();
+    assert_eq!(instance.dimension, 4);
+    assert_eq!(instance.dist_matrix.get(3, 1), 8.0);
+    assert_eq!(instance.dist_matrix.get(1, 3), FORBIDDEN_DISTANCE);
+    let (tour, length) = solve_tsp_aco(&instance, &Config::default());
+    assert_eq!(tour.len(), 4);
+    assert!(length < FORBIDDEN_DISTANCE);
+}
+
+#[test]
+fn imports_google_distance_matrix() {
+    let path = write_file(
+        "google.json",
+        r#"{"status":"OK","origin_addresses":["a","b"],"rows":[
+            {"elements":[{"status":"OK","distance":{"value":0},"duration":{"value":0}},
+                         {"status":"OK","distance":{"value":1200},"duration":{"value":95}}]},
+            {"elements":[{"status":"ZERO_RESULTS"},
+                         {"status":"OK","distance":{"value":0},"duration":{"value":0}}]}]}"#,
+    );
+    let instance = import_matrix_response(
+        path.to_str().unwrap(),
+        Some(MatrixResponseFormat::GoogleDistanceMatrix),
+        RoadMetric::Distance,
+        &ParseOptions::default(),
+    );
+    std::fs::remove_file(path).unwrap();
+    let instance = instance.unwrap();
+    assert_eq!(instance.dist_matrix.get(0, 1), 1200.0);
+    assert_eq!(instance.dist_matrix.get(1, 0), FORBIDDEN_DISTANCE);
+}
@@ -0,0 +1,148 @@
+use common::{tsp_text, write_file, write_instance};
+use std::f64::consts::PI;
+use tsp_solver::memory::{DEFAULT_CANDIDATE_LIST_SIZE, parse_byte_size};
+use tsp_solver::{
+    Config, DistanceMatrix, DistancePolicy, EdgeWeightType, ParseOptions, StorageMode,
+    parse_tsp_file_with_options, parse_tsp_reader, solve_tsp_aco,
+};
+
+mod common;
+
+fn circle(n: usize) -> Vec<(f64, f64)> {
+    (0..n)
+        .map(|i| {
+            let angle = 2.0 * PI * i as f64 / n as f64;
+            (1000.0 * angle.cos(), 1000.0 * angle.sin())
+        })
+        .collect()
+}
+
+#[test]
+fn budget_selects_fastest_mode_that_fits() {
+    let n = 10_000;
+    let dense = StorageMode::Dense.estimated_bytes(n);
+    let triangular = StorageMode::Triangular.estimated_bytes(n);
+    let lazy = StorageMode::Lazy {
+        candidates: DEFAULT_CANDIDATE_LIST_SIZE,
+    }
+    .estimated_bytes(n);
+    assert!(lazy < triangular && triangular < dense);
+
+    assert_eq!(StorageMode::select(n, dense, true), Ok(StorageMode::Dense));
+    assert_eq!(
+        StorageMode::select(n, dense - 1, true),
+        Ok(StorageMode::Triangular)
+    );
+    assert_eq!(
+        StorageMode::select(n, triangular - 1, true),
+        Ok(StorageMode::Lazy {
+            candidates: DEFAULT_CANDIDATE_LIST_SIZE
+        })
+    );
+    // Explicit matrices cannot be computed lazily.
+    assert!(StorageMode::select(n, triangular - 1, false).is_err());
+    assert!(StorageMode::select(n, lazy - 1, true).is_err());
+}
+
+#[test]
+fn byte_sizes_accept_binary_suffixes() {
+    assert_eq!(parse_byte_size("4096"), Some(4096));
+    assert_eq!(parse_byte_size("512M"), Some(512 << 20));
+    assert_eq!(parse_byte_size("2GiB"), Some(2 << 30));
+    assert_eq!(parse_byte_size("1.5k"), Some(1536));
+    assert_eq!(parse_byte_size("12X"), None);
+    assert_eq!(parse_byte_size("-1G"), None);
+}
+
+#[test]
+fn parser_stores_distances_as_selected() {
+    let coords = circle(200);
+    let path = write_file("budget200.tsp", tsp_text("budget200", &coords));
+    let path = path.to_str().unwrap();
+
+    let parse = |budget: usize| {
+        let options = ParseOptions {
+            max_memory: Some(budget),
+            ..ParseOptions::default()
+        };
+        parse_tsp_file_with_options(path, &options)
+    };
+    let dense = parse(StorageMode::Dense.estimated_bytes(200)).unwrap();
+    let triangular = parse(StorageMode::Triangular.estimated_bytes(200)).unwrap();
+    let lazy = parse(StorageMode::Triangular.estimated_bytes(200) - 1).unwrap();
+    let too_small = parse(1024);
+    std::fs::remove_file(path).unwrap();
+
+    assert!(matches!(dense.dist_matrix, DistanceMatrix::Full { .. }));
+    assert!(matches!(
+        triangular.dist_matrix,
+        DistanceMatrix::Triangular { .. }
+    ));
+    assert!(matches!(lazy.dist_matrix, DistanceMatrix::Lazy { .. }));
+    assert!(too_small.is_err());
+    for (i, j) in [(0, 1), (3, 150), (199, 42)] {
+        assert_eq!(dense.get_dist(i, j), triangular.get_dist(i, j));
+        assert_eq!(dense.get_dist(j, i), triangular.get_dist(i, j));
+    }
+}
+
+#[test]
+fn lazy_budget_never_allocates_a_matrix() {
+    // A triangle of 50k nodes alone takes 10 GB; the budget only covers the
+    // lazy mode, which keeps the coordinates.
+    let n = 50_000;
+    let contents = tsp_text("lazy", &circle(n));
+    let lazy = StorageMode::Lazy {
+        candidates: DEFAULT_CANDIDATE_LIST_SIZE,
+    }
+    .estimated_bytes(n);
+    assert!(lazy < StorageMode::Triangular.estimated_bytes(n));
+    let options = ParseOptions {
+        max_memory: Some(lazy),
+        ..ParseOptions::default()
+    };
+
+    let instance = parse_tsp_reader(contents.as_bytes(), &options).unwrap();
+    assert!(matches!(instance.dist_matrix, DistanceMatrix::Lazy { .. }));
+    assert_eq!(instance.get_dist(0, n / 2), Ok(2000.0));
+}
+
+#[test]
+fn lazy_matrices_need_a_coordinate_metric() {
+    let nodes = write_instance("lazy-set", &circle(4)).node_coords.unwrap();
+    assert!(
+        DistanceMatrix::lazy(
+            nodes.clone(),
+            EdgeWeightType::Explicit,
+            DistancePolicy::Exact
+        )
+        .is_err()
+    );
+    let mut lazy =
+        DistanceMatrix::lazy(nodes, EdgeWeightType::Euc2D, DistancePolicy::Exact).unwrap();
+    lazy.set(2, 2, 5.0);
+    assert!(matches!(lazy, DistanceMatrix::Lazy { .. }));
+    lazy.set(0, 2, 7.0);
+    assert_eq!(
+        (lazy.get(0, 2), lazy.get(2, 0), lazy.get(2, 2)),
+        (7.0, 7.0, 0.0)
+    );
+}
+
+#[test]
+fn candidate_lists_find_optimum_on_circle() {
+    let n = 40;
+    let instance = write_instance("candidates40", &circle(n));
+    let optimum = instance.tour_length(&(0..n).collect::<Vec<_>>());
+    let config = Config {
+        num_iters: 100,
+        num_ants: 20,
+        candidate_list_size: 5,
+        seed: Some(3),
+        ..Config::default()
+    };
+
+    let (tour, length) = solve_tsp_aco(&instance, &config);
+    assert_eq!(tour.len(), n);
+    assert_eq!(length, optimum.round());
+}
@@ -0,0 +1,45 @@
+use common::write_instance;
+use tsp_solver::{BoundingBox, FORBIDDEN_DISTANCE, ParseOptions, parse_tsp_reader};
+
+mod common;
+
+#[test]
+fn coordinate_queries() {
+    let instance = write_instance("rect4", &[(0.0, 0.0), (6.0, 0.0), (6.0, 8.0), (0.0, 8.0)]);
+    let bbox = instance.bounding_box().unwrap();
+    assert_eq!(
+        bbox,
+        BoundingBox {
+            min_x: 0.0,
+            min_y: 0.0,
+            max_x: 6.0,
+            max_y: 8.0
+        }
+    );
+    assert_eq!(bbox.diagonal(), 10.0);
+    assert_eq!(instance.centroid(), Some((3.0, 4.0)));
+
+    let stats = instance.edge_weight_stats().unwrap();
+    assert_eq!((stats.min, stats.max, stats.edges), (6.0, 10.0, 6));
+    assert_eq!(stats.mean, (6.0 + 8.0 + 6.0 + 8.0 + 10.0 + 10.0) / 6.0);
+    assert!(instance.is_symmetric());
+    assert_eq!(instance.density(), 1.0);
+}
+
+#[test]
+fn explicit_asymmetric_queries() {
+    let contents = format!(
+        "NAME: asym3\nTYPE: ATSP\nDIMENSION: 3\nEDGE_WEIGHT_TYPE: EXPLICIT\nEDGE_WEIGHT_FORMAT: FULL_MATRIX\nEDGE_WEIGHT_SECTION\n0 1 2\n3 0 {}\n5 6 0\nEOF\n",
+        FORBIDDEN_DISTANCE
+    );
+    let instance = parse_tsp_reader(contents.as_bytes(), &ParseOptions::default()).unwrap();
+    assert!(!instance.has_coordinates());
+    assert_eq!(instance.bounding_box(), None);
+    assert_eq!(instance.centroid(), None);
+    assert!(!instance.is_symmetric());
+
+    let stats = instance.edge_weight_stats().unwrap();
+    assert_eq!((stats.min, stats.max, stats.edges), (1.0, 6.0, 5));
+    assert_eq!(stats.mean, 17.0 / 5.0);
+    assert_eq!(instance.density(), 5.0 / 6.0);
+}
@@ -0,0 +1,169 @@
+use common::parse;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::sync::Arc;
+use tsp_solver::solver::HeuristicStrategy;
+use tsp_solver::{
+    AcoSolver, Backend, Config, EdgeWeightType, InitStrategy, OrOpt, ParseOptions, Popmusic,
+    StartPolicy, ThreeOpt, TspInstance, TwoOpt, parse_tour_reader, parse_tsp_reader,
+    solve_brute_force, solve_instance, solve_tsp_aco,
+};
+
+mod common;
+
+fn explicit(weights: &[&str]) -> TspInstance {
+    let n = (weights.len() as f64).sqrt() as usize;
+    parse(&format!(
+        "NAME: weights\nTYPE: TSP\nDIMENSION: {}\nEDGE_WEIGHT_TYPE: EXPLICIT\nEDGE_WEIGHT_FORMAT: FULL_MATRIX\nEDGE_WEIGHT_SECTION\n{}\nEOF\n",
+        n,
+        weights.join(" ")
+    ))
+    .unwrap()
+}
+
+/// Degenerate but parseable instances: tiny, coincident, non-finite.
+fn adversarial_instances() -> Vec<TspInstance> {
+    let coords = |coords: &[(f64, f64)]| {
+        TspInstance::from_coordinates("coords", coords, EdgeWeightType::Euc2D, None).unwrap()
+    };
+    vec![
+        coords(&[(0.0, 0.0)]),
+        coords(&[(0.0, 0.0), (1.0, 1.0)]),
+        coords(&[(5.0, 5.0); 6]),
+        coords(&[
+            (0.0, 0.0),
+            (f64::NAN, 1.0),
+            (2.0, f64::INFINITY),
+            (3.0, 3.0),
+        ]),
+        coords(&[(0.0, 0.0), (1e308, 0.0), (-1e308, 0.0), (0.0, 1e308)]),
+        explicit(&["0", "NaN", "1", "inf", "0", "-5", "1", "2", "0"]),
+        explicit(&["0", "-1", "-1", "-1", "0", "-1", "-1", "-1", "0"]),
+    ]
+}
+
+fn adversarial_configs() -> Vec<Config> {
+    let base = Config {
+        num_iters: 5,
+        num_ants: 4,
+        seed: Some(1),
+        ..Config::default()
+    };
+    vec![
+        base.clone(),
+        Config {
+            num_ants: 0,
+            ..base.clone()
+        },
+        Config {
+            num_iters: 0,
+            ..base.clone()
+        },
+        Config {
+            alpha: f64::NAN,
+            beta: f64::INFINITY,
+            ..base.clone()
+        },
+        Config {
+            alpha: -3.0,
+            beta: -3.0,
+            evap_rate: 2.0,
+            q_val: 0.0,
+            ..base.clone()
+        },
+        Config {
+            evap_rate: f64::NAN,
+            init_pheromone: InitStrategy::Constant(0.0),
+            min_pheromone_val: 0.0,
+            ..base.clone()
+        },
+        Config {
+            candidate_list_size: usize::MAX,
+            start_policy: StartPolicy::FixedNode(usize::MAX),
+            ..base.clone()
+        },
+        Config {
+            start_policy: StartPolicy::OnePerNode,
+            warm_start: Some(vec![usize::MAX, 0]),
+            ..base.clone()
+        },
+        Config {
+            locked_nodes: vec![usize::MAX],
+            pinned_positions: vec![(0, usize::MAX), (usize::MAX, 0)],
+            subsequences: vec![vec![0, usize::MAX]],
+            pickup_delivery: vec![(1, 1), (usize::MAX, 0)],
+            ..base.clone()
+        },
+        Config {
+            heuristic: HeuristicStrategy::Savings,
+            integer_costs: true,
+            max_memory: Some(0),
+            ..base.clone()
+        },
+        Config {
+            heuristic: HeuristicStrategy::InverseDistanceOffset(-1.0),
+            candidate_list_size: 1,
+            backend: Backend::Decomposed(usize::MAX),
+            ..base.clone()
+        },
+        Config {
+            backend: Backend::Partitioned(0),
+            ..base.clone()
+        },
+        Config {
+            backend: Backend::Multilevel(0),
+            ..base.clone()
+        },
+        Config {
+            local_search: vec![
+                Arc::new(TwoOpt),
+                Arc::new(OrOpt),
+                Arc::new(ThreeOpt),
+                Arc::new(Popmusic::default()),
+            ],
+            ..base.clone()
+        },
+    ]
+}
+
+#[test]
+fn solver_survives_adversarial_instances_and_configs() {
+    for instance in adversarial_instances() {
+        for config in adversarial_configs() {
+            let (tour, _) = solve_tsp_aco(&instance, &config);
+            assert!(tour.len() <= instance.dimension);
+            let mut solver = AcoSolver::new(&instance, &config);
+            solver.step();
+            let _ = solver.solve();
+            let _ = solve_instance(&instance, &config);
+        }
+        let _ = solve_brute_force(&instance);
+        assert!(instance.get_dist(instance.dimension, 0).is_err());
+        let n = instance.dimension;
+        assert!(instance.try_tour_length(&[0, n]).is_err());
+        assert!(instance.try_tour_length(&[n + 1]).is_err());
+        let identity: Vec<usize> = (0..n).collect();
+        assert!(instance.try_tour_length(&identity).is_ok());
+    }
+}
+
+#[test]
+fn parsers_reject_random_bytes() {
+    let instance = adversarial_instances().remove(3);
+    let mut rng = StdRng::seed_from_u64(9);
+    let alphabet = b"0123456789 .-:\nEOFNAMEDIMENSION_TYPEXPLICITUC2DSECTORFULLMATRIX";
+    for _ in 0..2000 {
+        let len = rng.random_range(0..200);
+        let bytes: Vec<u8> = (0..len)
+            .map(|_| {
+                if rng.random_bool(0.9) {
+                    alphabet[rng.random_range(0..alphabet.len())]
+                } else {
+                    rng.random()
+                }
+            })
+            .collect();
+        let _ = parse_tsp_reader(bytes.as_slice(), &ParseOptions::default());
+        let _ = parse_tour_reader(bytes.as_slice(), &instance);
+    }
+}
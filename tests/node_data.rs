@@ -0,0 +1,123 @@
+use common::{temp_path, write_file, write_instance};
+use tsp_solver::{
+    AcoSolver, Config, EdgeWeightType, NodeMetadata, ParseOptions, Solution, TspInstance,
+    parse_csv_instance, parse_tsp_file, write_visit_order,
+};
+
+mod common;
+
+const STOPS: &str = "\
+id,name,x,y,address
+10,Depot,0,0,\"1 Main St, Springfield\"
+20,Bakery,3,0,2 Mill Rd
+30,\"The \"\"Corner\"\" Shop\",3,4,
+40,Library,0,4,4 Elm St
+";
+
+#[test]
+fn csv_instances_keep_extra_columns_as_metadata() {
+    let instance = parse_csv_instance("stops", STOPS, &ParseOptions::default()).unwrap();
+    assert_eq!(instance.dimension, 4);
+    assert_eq!(instance.edge_weight_type, EdgeWeightType::Euc2D);
+    assert_eq!(instance.node_id(2), 30);
+    assert_eq!(instance.tour_length(&[0, 1, 2, 3]), 14.0);
+
+    let metadata = instance.node_metadata.as_ref().unwrap();
+    assert_eq!(metadata.columns(), ["name", "address"]);
+    assert_eq!(metadata.get(0, "address"), Some("1 Main St, Springfield"));
+    assert_eq!(metadata.get(2, "name"), Some("The \"Corner\" Shop"));
+    assert_eq!(metadata.get(2, "address"), Some(""));
+
+    let geodesic = parse_csv_instance(
+        "geo",
+        "lat,lon\n52.52,13.40\n48.14,11.58\n",
+        &ParseOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(geodesic.edge_weight_type, EdgeWeightType::Geodesic);
+    assert!(geodesic.node_metadata.is_none());
+    assert!(parse_csv_instance("bad", "name\nA\n", &ParseOptions::default()).is_err());
+    assert!(parse_csv_instance("bad", "x,y\n1\n", &ParseOptions::default()).is_err());
+
+    let path = write_file("stops.csv", STOPS);
+    let parsed = parse_tsp_file(path.to_str().unwrap()).unwrap();
+    std::fs::remove_file(path).unwrap();
+    assert_eq!(parsed.name, format!("stops-{}", std::process::id()));
+    assert_eq!(parsed.node_metadata, instance.node_metadata);
+}
+
+#[test]
+fn solutions_and_exports_carry_the_metadata_of_their_stops() {
+    let instance = parse_csv_instance("stops-export", STOPS, &ParseOptions::default()).unwrap();
+    let config = Config {
+        num_iters: 10,
+        num_ants: 4,
+        seed: Some(1),
+        ..Config::default()
+    };
+    let solution = AcoSolver::new(&instance, &config).solve();
+    let stops = solution.stop_metadata.as_ref().unwrap();
+    for (k, &node) in solution.tour.iter().enumerate() {
+        assert_eq!(
+            stops.row(k),
+            instance.node_metadata.as_ref().unwrap().row(node)
+        );
+    }
+    assert_eq!(
+        Solution::from_json(&solution.to_json()),
+        Ok(solution.clone())
+    );
+
+    let csv_path = temp_path("visits.csv");
+    write_visit_order(&instance, &[0, 1, 2, 3], csv_path.to_str().unwrap()).unwrap();
+    let csv = std::fs::read_to_string(&csv_path).unwrap();
+    std::fs::remove_file(csv_path).unwrap();
+    assert_eq!(
+        csv,
+        "position,id,name,address,distance,cumulative\n\
+         1,10,Depot,\"1 Main St, Springfield\",0,0\n\
+         2,20,Bakery,2 Mill Rd,3,3\n\
+         3,30,\"The \"\"Corner\"\" Shop\",,4,7\n\
+         4,40,Library,4 Elm St,3,10\n"
+    );
+
+    let geojson_path = temp_path("visits.geojson");
+    write_visit_order(&instance, &[0, 1, 2, 3], geojson_path.to_str().unwrap()).unwrap();
+    let geojson = std::fs::read_to_string(&geojson_path).unwrap();
+    std::fs::remove_file(geojson_path).unwrap();
+    assert!(geojson.contains("\"FeatureCollection\""));
+    assert_eq!(geojson.matches("\"Point\"").count(), 4);
+    assert!(geojson.contains("\"name\":\"Bakery\""));
+    assert!(geojson.contains("\"LineString\""));
+}
+
+#[test]
+fn node_data_joins_on_node_ids() {
+    let instance = write_instance(
+        "node-data",
+        &[(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)],
+    );
+    let metadata =
+        NodeMetadata::from_csv("name,id\nNorth,4\nSouth,1\n\"East, far\",2\n", &instance).unwrap();
+    assert_eq!(metadata.columns(), ["name"]);
+    assert_eq!(metadata.get(3, "name"), Some("North"));
+    assert_eq!(metadata.get(1, "name"), Some("East, far"));
+    assert_eq!(metadata.get(2, "name"), Some(""));
+    assert!(NodeMetadata::from_csv("id,name\n9,Nowhere\n", &instance).is_err());
+    assert!(NodeMetadata::from_csv("name\nA\n", &instance).is_err());
+
+    let mut programmatic = NodeMetadata::new(Vec::new(), 4);
+    programmatic.set(0, "name", "Home").unwrap();
+    assert!(programmatic.set(4, "name", "Away").is_err());
+    let instance: TspInstance = instance.with_node_metadata(programmatic).unwrap();
+    assert_eq!(
+        instance.node_metadata.as_ref().unwrap().get(0, "name"),
+        Some("Home")
+    );
+    assert!(
+        instance
+            .clone()
+            .with_node_metadata(NodeMetadata::new(Vec::new(), 3))
+            .is_err()
+    );
+}
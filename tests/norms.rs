@@ -0,0 +1,33 @@
+use tsp_solver::{EdgeWeightType, TspInstance};
+
+mod common;
+
+fn parse(edge_weight_type: &str) -> TspInstance {
+    common::parse(&format!(
+        "NAME: norms\nTYPE: TSP\nDIMENSION: 3\nEDGE_WEIGHT_TYPE: {}\nNODE_COORD_SECTION\n1 0 0\n2 3 -4\n3 -1 2\nEOF\n",
+        edge_weight_type
+    ))
+    .unwrap()
+}
+
+#[test]
+fn manhattan_and_maximum_norms_are_solvable_metrics() {
+    let manhattan = parse("MAN_2D");
+    assert_eq!(manhattan.edge_weight_type, EdgeWeightType::Man2D);
+    assert_eq!(manhattan.dist_matrix.get(0, 1), 7.0);
+    assert_eq!(manhattan.dist_matrix.get(1, 2), 10.0);
+    assert_eq!(manhattan.tour_length(&[0, 1, 2]), 20.0);
+
+    let maximum = parse("MAX_2D");
+    assert_eq!(maximum.edge_weight_type, EdgeWeightType::Max2D);
+    assert_eq!(maximum.dist_matrix.get(0, 1), 4.0);
+    assert_eq!(maximum.dist_matrix.get(1, 2), 6.0);
+    assert_eq!(maximum.dist_matrix.get(2, 0), 2.0);
+
+    let nodes = manhattan.node_coords.as_ref().unwrap();
+    assert_eq!(
+        EdgeWeightType::Max2D.distance(&nodes[0], &nodes[2]),
+        Some(2.0)
+    );
+    assert_eq!(EdgeWeightType::Man2D.keyword(), "MAN_2D");
+}
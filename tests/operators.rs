@@ -0,0 +1,74 @@
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use tsp_solver::constraints::validate_permutation;
+use tsp_solver::{edge_recombination, order_crossover, partially_mapped_crossover, random_cut};
+
+const P1: [usize; 9] = [0, 1, 2, 3, 4, 5, 6, 7, 8];
+const P2: [usize; 9] = [8, 2, 6, 7, 1, 5, 4, 0, 3];
+
+fn edges(tour: &[usize]) -> Vec<(usize, usize)> {
+    tour.iter()
+        .zip(tour.iter().cycle().skip(1))
+        .map(|(&a, &b)| (a.min(b), a.max(b)))
+        .collect()
+}
+
+#[test]
+fn order_crossover_keeps_the_cut_and_the_order_of_the_rest() {
+    let child = order_crossover(&P1, &P2, 3..6).unwrap();
+    // Kept: 3 4 5; after the cut, parent 2 reads 4 0 3 8 2 6 7 1 5, which
+    // without the kept nodes fills positions 6 7 8 0 1 2.
+    assert_eq!(child, vec![6, 7, 1, 3, 4, 5, 0, 8, 2]);
+}
+
+#[test]
+fn partially_mapped_crossover_follows_the_mapping() {
+    let child = partially_mapped_crossover(&P1, &P2, 3..6).unwrap();
+    // The cut maps 3 -> 7, 4 -> 1 and 5 -> 5, so parent 2's 4 and 3 outside
+    // the cut become 1 and 7.
+    assert_eq!(child, vec![8, 2, 6, 3, 4, 5, 1, 0, 7]);
+}
+
+#[test]
+fn edge_recombination_inherits_parent_edges() {
+    let mut rng = StdRng::seed_from_u64(5);
+    let child = edge_recombination(&P1, &P2, &mut rng).unwrap();
+    validate_permutation(&child, 9).unwrap();
+    assert_eq!(child[0], 0);
+    let parent_edges: Vec<(usize, usize)> = edges(&P1).into_iter().chain(edges(&P2)).collect();
+    let inherited = edges(&child)
+        .iter()
+        .filter(|edge| parent_edges.contains(edge))
+        .count();
+    assert!(inherited >= 7, "{} of 9 edges inherited", inherited);
+    // Identical parents have a single child.
+    assert_eq!(edge_recombination(&P2, &P2, &mut rng).unwrap(), P2.to_vec());
+}
+
+#[test]
+fn children_of_random_parents_are_tours() {
+    let mut rng = StdRng::seed_from_u64(11);
+    for n in [1, 2, 3, 10, 40] {
+        for _ in 0..20 {
+            let mut a: Vec<usize> = (0..n).collect();
+            let mut b = a.clone();
+            a.shuffle(&mut rng);
+            b.shuffle(&mut rng);
+            let cut = random_cut(n, &mut rng);
+            assert!(!cut.is_empty() && cut.end <= n);
+            validate_permutation(&order_crossover(&a, &b, cut.clone()).unwrap(), n).unwrap();
+            validate_permutation(&partially_mapped_crossover(&a, &b, cut).unwrap(), n).unwrap();
+            validate_permutation(&edge_recombination(&a, &b, &mut rng).unwrap(), n).unwrap();
+        }
+    }
+}
+
+#[test]
+fn invalid_parents_and_cuts_are_rejected() {
+    assert!(order_crossover(&P1, &P2[..8], 0..2).is_err());
+    assert!(order_crossover(&P1, &[0, 0, 1, 2, 3, 4, 5, 6, 7], 0..2).is_err());
+    assert!(partially_mapped_crossover(&P1, &P2, 4..10).is_err());
+    let mut rng = StdRng::seed_from_u64(1);
+    assert!(edge_recombination(&P1, &[9, 1, 2, 3, 4, 5, 6, 7, 8], &mut rng).is_err());
+}
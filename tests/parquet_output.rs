@@ -0,0 +1,99 @@
+#![cfg(feature = "parquet")]
+
+use arrow_array::{Array, Float64Array, StringArray, UInt64Array};
+use common::{SQUARE, temp_dir, temp_path, tsp_text};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use std::fs;
+use tsp_solver::parquet_output::write_parquet;
+use tsp_solver::results::RunRecord;
+use tsp_solver::{Config, SilentReporter, run};
+
+mod common;
+
+fn record(instance: &str, seed: u64, length: f64, gap: Option<f64>) -> RunRecord {
+    RunRecord {
+        instance: instance.to_string(),
+        instance_hash: format!("hash-{}", instance),
+        seed,
+        length,
+        gap,
+        seconds: 0.5,
+        tour: Vec::new(),
+        history: Vec::new(),
+    }
+}
+
+#[test]
+fn runs_round_trip_through_parquet() {
+    let path = temp_path("runs.parquet");
+    let config = Config {
+        num_ants: 7,
+        ..Config::default()
+    };
+    let runs = [
+        record("alpha", 1, 100.0, Some(2.5)),
+        record("alpha", 2, 98.0, Some(0.0)),
+        record("beta", 1, 40.0, None),
+    ];
+    write_parquet(&runs, &config, path.to_str().unwrap()).unwrap();
+
+    let file = fs::File::open(&path).unwrap();
+    let batches: Vec<_> = ParquetRecordBatchReaderBuilder::try_new(file)
+        .unwrap()
+        .build()
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(batches.len(), 1);
+    let batch = &batches[0];
+    assert_eq!(batch.num_rows(), 3);
+    let column = |name: &str| batch.column_by_name(name).unwrap().clone();
+
+    let instance = column("instance");
+    let instance = instance.as_any().downcast_ref::<StringArray>().unwrap();
+    assert_eq!(instance.value(2), "beta");
+    let seed = column("seed");
+    let seed = seed.as_any().downcast_ref::<UInt64Array>().unwrap();
+    assert_eq!(seed.values().to_vec(), [1, 2, 1]);
+    let gap = column("gap");
+    let gap = gap.as_any().downcast_ref::<Float64Array>().unwrap();
+    assert_eq!(gap.value(0), 2.5);
+    assert!(gap.is_null(2));
+    let ants = column("ants");
+    let ants = ants.as_any().downcast_ref::<Float64Array>().unwrap();
+    assert_eq!(ants.value(1), 7.0);
+    let backend = column("backend");
+    assert!(backend.as_any().downcast_ref::<StringArray>().is_some());
+    assert_eq!(batch.num_columns(), 7 + config.setting_pairs().len());
+}
+
+#[test]
+fn bench_mode_writes_one_row_per_run() {
+    let dir = temp_dir("parquet-bench");
+    fs::write(dir.join("square.tsp"), tsp_text("square", &SQUARE)).unwrap();
+    let path = dir.join("runs.parquet");
+    let args = [
+        "tsp_solver",
+        "bench",
+        dir.to_str().unwrap(),
+        "--iters",
+        "5",
+        "--seeds",
+        "3",
+        "--parquet",
+        path.to_str().unwrap(),
+    ];
+    let config = Config::build(args.iter().map(|arg| arg.to_string())).unwrap();
+    run(&config, &mut SilentReporter).unwrap();
+
+    let file = fs::File::open(&path).unwrap();
+    let rows: usize = ParquetRecordBatchReaderBuilder::try_new(file)
+        .unwrap()
+        .build()
+        .unwrap()
+        .map(|batch| batch.unwrap().num_rows())
+        .sum();
+    fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(rows, 3);
+}
@@ -0,0 +1,70 @@
+//! Solution quality must not depend on the pheromone storage type; run with
+//! and without `--features f32-pheromone`.
+
+use common::write_instance;
+use std::f64::consts::PI;
+use tsp_solver::{AcoSolver, Config, Pheromone, solve_tsp_aco};
+
+mod common;
+
+fn test_config() -> Config {
+    Config {
+        num_iters: 200,
+        num_ants: 20,
+        ..Config::default()
+    }
+}
+
+#[test]
+fn finds_optimum_on_circle() {
+    let n = 30;
+    let coords: Vec<(f64, f64)> = (0..n)
+        .map(|i| {
+            let angle = 2.0 * PI * i as f64 / n as f64;
+            (1000.0 * angle.cos(), 1000.0 * angle.sin())
+        })
+        .collect();
+    let instance = write_instance("circle30", &coords);
+    // Points in convex position: visiting them in order is optimal.
+    let optimum = instance.tour_length(&(0..n).collect::<Vec<_>>());
+
+    let (tour, length) = solve_tsp_aco(&instance, &test_config());
+    assert_eq!(tour.len(), n);
+    assert_eq!(length, optimum.round());
+}
+
+#[test]
+fn finds_optimum_on_grid() {
+    let coords: Vec<(f64, f64)> = (0..36)
+        .map(|i| ((i % 6) as f64 * 10.0, (i / 6) as f64 * 10.0))
+        .collect();
+    let instance = write_instance("grid36", &coords);
+
+    let (_, length) = solve_tsp_aco(&instance, &test_config());
+    // Gap below 5% of the optimum (360 for an even grid).
+    assert!(length <= 378.0, "length {} too far from 360", length);
+}
+
+#[test]
+fn pheromone_levels_stay_clamped_and_finite() {
+    let coords: Vec<(f64, f64)> = (0..20)
+        .map(|i| ((i * 37 % 101) as f64, (i * 59 % 103) as f64))
+        .collect();
+    let instance = write_instance("clamp20", &coords);
+    let config = Config {
+        evap_rate: 0.9,
+        q_val: 1e12,
+        ..test_config()
+    };
+
+    let mut solver = AcoSolver::new(&instance, &config);
+    for _ in 0..50 {
+        solver.step();
+    }
+    for row in solver.pheromone_matrix() {
+        for &level in row {
+            assert!(level.is_finite());
+            assert!(level >= (config.min_pheromone_val * 0.999) as Pheromone);
+        }
+    }
+}
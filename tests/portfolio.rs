@@ -0,0 +1,53 @@
+use common::write_instance;
+use std::time::Duration;
+use tsp_solver::{
+    Backend, Config, PortfolioPlan, PortfolioStrategy, solve_brute_force, solve_instance,
+};
+
+mod common;
+
+fn ring(n: usize) -> Vec<(f64, f64)> {
+    (0..n)
+        .map(|i| {
+            let angle = i as f64 * std::f64::consts::TAU / n as f64;
+            (100.0 * angle.cos(), 100.0 * angle.sin())
+        })
+        .collect()
+}
+
+#[test]
+fn the_portfolio_picks_a_backend_by_instance_size() {
+    let config = Config {
+        backend: Backend::Portfolio,
+        time_budget: Some(Duration::from_secs(2)),
+        seed: Some(7),
+        ..Config::default()
+    };
+
+    let tiny = write_instance("portfolio-tiny", &ring(9)[..]);
+    let plan = PortfolioPlan::new(&tiny, &config);
+    assert_eq!(plan.strategy, PortfolioStrategy::Exact);
+    assert!(plan.features.symmetric);
+    let solution = solve_instance(&tiny, &config).unwrap();
+    assert_eq!(solution.algorithm, "portfolio:exact");
+    assert_eq!(solution.exact_length, solve_brute_force(&tiny).unwrap().1);
+
+    let medium = write_instance("portfolio-medium", &ring(40));
+    let plan = PortfolioPlan::new(&medium, &config);
+    assert_eq!(plan.strategy, PortfolioStrategy::AcoLocalSearch);
+    assert_eq!(plan.config.local_search.len(), 1);
+    let solution = solve_instance(&medium, &config).unwrap();
+    assert_eq!(solution.algorithm, "portfolio:aco");
+    assert!(solution.duration < Duration::from_secs(3));
+    // 2-opt untangles a convex ring completely.
+    let ring_length = medium.tour_length(&(0..40).collect::<Vec<_>>());
+    assert!((solution.exact_length - ring_length).abs() < 1e-9);
+
+    let huge = write_instance("portfolio-huge", &ring(6000));
+    let plan = PortfolioPlan::new(&huge, &config);
+    assert_eq!(plan.strategy, PortfolioStrategy::SpaceFillingCurve);
+    let (tour, _) = plan.solve_without_aco(&huge).unwrap();
+    let mut sorted = tour.clone();
+    sorted.sort_unstable();
+    assert_eq!(sorted, (0..6000).collect::<Vec<_>>());
+}
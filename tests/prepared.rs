@@ -0,0 +1,58 @@
+use common::write_instance;
+use std::sync::Arc;
+use tsp_solver::solver::HeuristicStrategy;
+use tsp_solver::{AcoSolver, Config, PreparedInstance, solve_instance, solve_prepared};
+
+mod common;
+
+fn coords() -> Vec<(f64, f64)> {
+    (0..25)
+        .map(|i| (((i * 37) % 101) as f64, ((i * 53) % 97) as f64))
+        .collect()
+}
+
+fn config(alpha: f64, candidates: usize) -> Config {
+    Config {
+        num_iters: 10,
+        num_ants: 6,
+        alpha,
+        candidate_list_size: candidates,
+        seed: Some(4),
+        ..Config::default()
+    }
+}
+
+#[test]
+fn prepared_solves_match_plain_solves() {
+    let instance = write_instance("prepared25", &coords());
+    let prepared = PreparedInstance::new(&instance);
+    for config in [config(1.0, 0), config(2.0, 0), config(1.0, 5)] {
+        let plain = solve_instance(&instance, &config).unwrap();
+        let shared = solve_prepared(&prepared, &config).unwrap();
+        assert_eq!((shared.tour, shared.length), (plain.tour, plain.length));
+    }
+}
+
+#[test]
+fn heuristic_tables_are_shared_between_matching_configs() {
+    let instance = write_instance("prepared25-cache", &coords());
+    let prepared = PreparedInstance::new(&instance);
+    assert_eq!(prepared.cached_tables(), 0);
+
+    let (a, b, c) = (config(1.0, 0), config(3.0, 0), config(1.0, 5));
+    AcoSolver::prepared(&prepared, &a).run();
+    AcoSolver::prepared(&prepared, &b).run();
+    assert_eq!(prepared.cached_tables(), 1);
+    AcoSolver::prepared(&prepared, &c).run();
+    assert_eq!(prepared.cached_tables(), 2);
+
+    let custom: Arc<dyn Fn(usize, usize, f64) -> f64 + Send + Sync> = Arc::new(|_, _, d| 1.0 / d);
+    let with_custom = |alpha: f64| Config {
+        heuristic: HeuristicStrategy::Custom(Arc::clone(&custom)),
+        ..config(alpha, 0)
+    };
+    let (d, e) = (with_custom(1.0), with_custom(2.0));
+    AcoSolver::prepared(&prepared, &d).run();
+    AcoSolver::prepared(&prepared, &e).run();
+    assert_eq!(prepared.cached_tables(), 3);
+}
@@ -0,0 +1,93 @@
+use common::{temp_path, write_instance};
+use tsp_solver::{
+    AcoSolver, Checkpoint, Config, EdgeWeightType, ParseOptions, Provenance, Solution, TspInstance,
+    parse_tsp_reader, solve_instance,
+};
+
+mod common;
+
+fn coords() -> Vec<(f64, f64)> {
+    (0..12)
+        .map(|i| (((i * 37) % 101) as f64, ((i * 53) % 97) as f64))
+        .collect()
+}
+
+#[test]
+fn hash_follows_content_not_name_or_storage() {
+    let a = write_instance("provenance-a", &coords());
+    let b = write_instance("provenance-b", &coords());
+    assert_eq!(a.content_hash(), b.content_hash());
+    assert_eq!(a.content_hash_hex().len(), 16);
+
+    let lazy = TspInstance {
+        dist_matrix: tsp_solver::DistanceMatrix::Lazy {
+            nodes: a.node_coords.clone().unwrap(),
+            metric: EdgeWeightType::Euc2D,
+            policy: a.distance_policy,
+        },
+        ..a.clone()
+    };
+    assert_eq!(lazy.content_hash(), a.content_hash());
+
+    let mut moved = coords();
+    moved[3].0 += 1.0;
+    assert_ne!(
+        write_instance("provenance-moved", &moved).content_hash(),
+        a.content_hash()
+    );
+    let explicit = |weight: u32| {
+        parse_tsp_reader(
+            format!(
+                "NAME: e\nTYPE: TSP\nDIMENSION: 2\nEDGE_WEIGHT_TYPE: EXPLICIT\nEDGE_WEIGHT_FORMAT: FULL_MATRIX\nEDGE_WEIGHT_SECTION\n0 {0}\n{0} 0\nEOF\n",
+                weight
+            )
+            .as_bytes(),
+            &ParseOptions::default(),
+        )
+        .unwrap()
+    };
+    assert_eq!(explicit(5).content_hash(), explicit(5).content_hash());
+    assert_ne!(explicit(5).content_hash(), explicit(6).content_hash());
+}
+
+#[test]
+fn solutions_carry_provenance_through_json() {
+    let instance = write_instance("provenance-solution", &coords());
+    let config = Config {
+        num_iters: 5,
+        num_ants: 4,
+        seed: Some(3),
+        ..Config::default()
+    };
+    let solution = solve_instance(&instance, &config).unwrap();
+    let provenance = solution.provenance.clone().unwrap();
+    assert_eq!(provenance, Provenance::new(&instance, &config));
+    assert_eq!(provenance.version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(provenance.seed, Some(3));
+    assert!(provenance.config.contains("ants=4"));
+    assert!(solution.report(12).contains(&provenance.instance_hash));
+
+    let parsed = Solution::from_json(&solution.to_json()).unwrap();
+    assert_eq!(parsed.provenance, Some(provenance));
+}
+
+#[test]
+fn checkpoints_record_provenance() {
+    let instance = write_instance("provenance-checkpoint", &coords());
+    let config = Config {
+        num_iters: 3,
+        num_ants: 4,
+        seed: Some(5),
+        ..Config::default()
+    };
+    let mut solver = AcoSolver::new(&instance, &config);
+    solver.step();
+    let mut checkpoint = solver.checkpoint();
+    checkpoint.provenance = Some(Provenance::new(&instance, &config));
+    let path = temp_path("provenance.txt");
+    let path = path.to_str().unwrap();
+    checkpoint.write(path).unwrap();
+    let read = Checkpoint::read(path).unwrap();
+    std::fs::remove_file(path).unwrap();
+    assert_eq!(read, checkpoint);
+}
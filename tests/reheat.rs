@@ -0,0 +1,92 @@
+use common::write_instance;
+use tsp_solver::{
+    AcoSolver, Config, InitStrategy, Perturbation, Pheromone, ReheatSchedule, ReheatTrigger,
+};
+
+mod common;
+
+fn coords() -> Vec<(f64, f64)> {
+    (0..15)
+        .map(|i| ((i * 37 % 101) as f64, (i * 59 % 103) as f64))
+        .collect()
+}
+
+#[allow(clippy::unnecessary_cast)] // `Pheromone` is f32 with `f32-pheromone`.
+fn levels(solver: &AcoSolver) -> Vec<f64> {
+    solver
+        .pheromone_matrix()
+        .iter()
+        .flatten()
+        .map(|&level| level as f64)
+        .collect()
+}
+
+#[test]
+fn full_blend_restores_initial_trails() {
+    let instance = write_instance("reheat15", &coords());
+    let config = Config {
+        num_iters: 50,
+        num_ants: 10,
+        init_pheromone: InitStrategy::Constant(1.0),
+        seed: Some(4),
+        ..Config::default()
+    };
+    let mut solver = AcoSolver::new(&instance, &config);
+    for _ in 0..20 {
+        solver.step();
+    }
+    let converged = levels(&solver);
+
+    solver.perturb_pheromone(Perturbation::Blend(0.5));
+    for (&before, &after) in converged.iter().zip(&levels(&solver)) {
+        assert!((after - (before + 1.0) / 2.0).abs() < 1e-5);
+    }
+    solver.perturb_pheromone(Perturbation::Blend(1.0));
+    assert!(levels(&solver).iter().all(|&level| level == 1.0));
+}
+
+#[test]
+fn noise_is_bounded_and_seeded() {
+    let instance = write_instance("noise15", &coords());
+    let config = Config {
+        num_ants: 10,
+        init_pheromone: InitStrategy::Constant(1.0),
+        seed: Some(4),
+        ..Config::default()
+    };
+    let perturbed = || {
+        let mut solver = AcoSolver::new(&instance, &config);
+        solver.perturb_pheromone(Perturbation::Noise(0.2));
+        levels(&solver)
+    };
+    let noisy = perturbed();
+    assert_eq!(noisy, perturbed());
+    assert!(noisy.iter().all(|&level| (0.8..=1.2).contains(&level)));
+    assert!(noisy.iter().any(|&level| level != 1.0));
+}
+
+#[test]
+fn schedule_reheats_every_k_iterations() {
+    let instance = write_instance("schedule15", &coords());
+    let config = Config {
+        num_iters: 50,
+        num_ants: 10,
+        init_pheromone: InitStrategy::Constant(1.0),
+        reheat: Some(ReheatSchedule {
+            perturbation: Perturbation::Blend(1.0),
+            trigger: ReheatTrigger::Every(5),
+        }),
+        seed: Some(4),
+        ..Config::default()
+    };
+    let mut solver = AcoSolver::new(&instance, &config);
+    for iteration in 1..=10 {
+        solver.step();
+        let reset = solver
+            .pheromone_matrix()
+            .iter()
+            .flatten()
+            .all(|&level| level == 1.0 as Pheromone);
+        assert_eq!(reset, iteration % 5 == 0, "iteration {}", iteration);
+    }
+}
@@ -0,0 +1,30 @@
+use common::write_instance;
+use tsp_solver::construction::repair_tour;
+
+mod common;
+
+#[test]
+fn repair_drops_duplicates_and_inserts_missing_nodes_cheaply() {
+    // Corners of a square plus the midpoint of its bottom edge (node 4).
+    let coords = [
+        (0.0, 0.0),
+        (10.0, 0.0),
+        (10.0, 10.0),
+        (0.0, 10.0),
+        (5.0, 0.0),
+    ];
+    let instance = write_instance("repair5", &coords);
+
+    let (tour, length) = repair_tour(&instance, &[0, 1, 2, 2, 3, 1]);
+    assert_eq!(tour, vec![0, 4, 1, 2, 3]);
+    assert_eq!(length, 40.0);
+
+    let (tour, length) = repair_tour(&instance, &[3, 9, 0, 4, 1, 2]);
+    assert_eq!(tour, vec![3, 0, 4, 1, 2]);
+    assert_eq!(length, 40.0);
+
+    let (tour, _) = repair_tour(&instance, &[]);
+    let mut sorted = tour.clone();
+    sorted.sort();
+    assert_eq!(sorted, vec![0, 1, 2, 3, 4]);
+}
@@ -0,0 +1,49 @@
+use common::{temp_path, write_instance};
+use tsp_solver::{AcoSolver, Config, render_svg};
+
+mod common;
+
+#[test]
+fn sampled_ant_tours_are_recorded_and_rendered() {
+    let coords: Vec<(f64, f64)> = (0..10)
+        .map(|i| (((i * 37) % 101) as f64, ((i * 53) % 97) as f64))
+        .collect();
+    let instance = write_instance("replay-ants", &coords);
+    let (replay, svg) = (temp_path("replay-ants.txt"), temp_path("replay-ants.svg"));
+    let (replay, svg) = (replay.to_str().unwrap(), svg.to_str().unwrap());
+    let config = Config {
+        num_iters: 5,
+        num_ants: 8,
+        seed: Some(2),
+        replay_path: Some(replay.to_string()),
+        replay_ants_interval: 2,
+        replay_ants: 3,
+        ..Config::default()
+    };
+    AcoSolver::new(&instance, &config).run();
+
+    let contents = std::fs::read_to_string(replay).unwrap();
+    let ants: Vec<Vec<usize>> = contents
+        .lines()
+        .filter_map(|line| line.strip_prefix("ANT "))
+        .map(|line| {
+            line.split_whitespace()
+                .map(|v| v.parse::<f64>().unwrap() as usize)
+                .collect()
+        })
+        .collect();
+    assert_eq!(ants.len(), 9);
+    for (k, ant) in ants.iter().enumerate() {
+        assert_eq!(ant[0], [0, 2, 4][k / 3]);
+        assert_eq!(ant[2], [0, 2, 5][k % 3]);
+        let mut tour = ant[4..].to_vec();
+        tour.sort();
+        assert_eq!(tour, (0..10).collect::<Vec<_>>());
+    }
+
+    render_svg(replay, svg).unwrap();
+    let drawing = std::fs::read_to_string(svg).unwrap();
+    std::fs::remove_file(replay).unwrap();
+    std::fs::remove_file(svg).unwrap();
+    assert_eq!(drawing.matches("stroke=\"#777\"").count(), 9);
+}
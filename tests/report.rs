@@ -0,0 +1,169 @@
+use common::{SQUARE, tsp_text, write_file};
+use tsp_solver::{Config, Depot, NdjsonReporter, Reporter, Solution, TspInstance, run};
+
+mod common;
+
+#[derive(Default)]
+struct Recorder {
+    events: Vec<String>,
+    messages: Vec<String>,
+    warnings: Vec<String>,
+    solution: Option<Solution>,
+}
+
+impl Reporter for Recorder {
+    fn on_message(&mut self, message: &str) {
+        self.messages.push(message.to_string());
+    }
+
+    fn on_warning(&mut self, message: &str) {
+        self.warnings.push(message.to_string());
+    }
+
+    fn on_parse(&mut self, instance: &TspInstance) {
+        self.events.push(format!("parse {}", instance.dimension));
+    }
+
+    fn on_iteration(&mut self, iteration: usize, _best_length: f64, _edge_entropy: f64) {
+        self.events.push(format!("iteration {}", iteration));
+    }
+
+    fn on_new_best(&mut self, _iteration: usize, tour: &[usize], _length: f64) {
+        self.events.push(format!("new_best {}", tour.len()));
+    }
+
+    fn on_finish(&mut self, _instance: &TspInstance, solution: &Solution) {
+        self.events.push("finish".to_string());
+        self.solution = Some(solution.clone());
+    }
+}
+
+fn square_file(name: &str) -> String {
+    let path = write_file(&format!("{}.tsp", name), tsp_text("square", &SQUARE));
+    path.to_str().unwrap().to_string()
+}
+
+#[test]
+fn run_hands_progress_to_the_reporter() {
+    let path = square_file("report4");
+    let config = Config {
+        file_path: Some(path.clone()),
+        num_iters: 3,
+        num_ants: 4,
+        seed: Some(1),
+        ..Config::default()
+    };
+    let mut recorder = Recorder::default();
+    let result = run(&config, &mut recorder);
+    std::fs::remove_file(&path).unwrap();
+    result.unwrap();
+
+    let events: Vec<&str> = recorder
+        .events
+        .iter()
+        .map(String::as_str)
+        .filter(|event| !event.starts_with("new_best"))
+        .collect();
+    assert_eq!(
+        events,
+        [
+            "parse 4",
+            "iteration 0",
+            "iteration 1",
+            "iteration 2",
+            "finish"
+        ]
+    );
+    assert_eq!(recorder.events[1], "new_best 4");
+    assert_eq!(recorder.solution.unwrap().length, 40.0);
+}
+
+#[test]
+fn ndjson_reporter_writes_one_object_per_event() {
+    let path = square_file("report4-ndjson");
+    let config = Config {
+        file_path: Some(path.clone()),
+        num_iters: 2,
+        num_ants: 4,
+        seed: Some(1),
+        ..Config::default()
+    };
+    let mut reporter = NdjsonReporter::new(Vec::new());
+    let result = run(&config, &mut reporter);
+    std::fs::remove_file(&path).unwrap();
+    result.unwrap();
+
+    let output = String::from_utf8(reporter.into_inner()).unwrap();
+    let events: Vec<&str> = output
+        .lines()
+        .map(|line| {
+            assert!(line.starts_with("{\"event\":\"") && line.ends_with('}'));
+            line[10..].split('"').next().unwrap()
+        })
+        // The solutions file may be missing, which is only a warning.
+        .filter(|&event| event != "message" && event != "warning")
+        .collect();
+    assert_eq!(
+        events,
+        ["parse", "new_best", "iteration", "iteration", "finish"]
+    );
+    let finish = output.lines().find(|line| line.contains("finish")).unwrap();
+    let solution = &finish["{\"event\":\"finish\",\"solution\":".len()..finish.len() - 1];
+    assert_eq!(Solution::from_json(solution).unwrap().length, 40.0);
+}
+
+#[test]
+fn solver_warnings_reach_the_reporter() {
+    let path = square_file("report4-warning");
+    let config = Config {
+        file_path: Some(path.clone()),
+        num_iters: 2,
+        num_ants: 4,
+        seed: Some(1),
+        warm_start: Some(vec![0, 0, 1, 2]),
+        ..Config::default()
+    };
+    let mut recorder = Recorder::default();
+    let result = run(&config, &mut recorder);
+    std::fs::remove_file(&path).unwrap();
+    result.unwrap();
+
+    assert!(
+        recorder
+            .warnings
+            .iter()
+            .any(|warning| warning.starts_with("ignoring invalid warm start tour")),
+        "{:?}",
+        recorder.warnings
+    );
+    assert!(recorder.solution.is_some());
+}
+
+#[test]
+fn routing_progress_reaches_the_reporter() {
+    let path = square_file("report4-routing");
+    let config = Config {
+        file_path: Some(path.clone()),
+        num_iters: 2,
+        num_ants: 4,
+        seed: Some(1),
+        depots: vec![Depot {
+            node: 0,
+            salesmen: 1,
+        }],
+        ..Config::default()
+    };
+    let mut recorder = Recorder::default();
+    let result = run(&config, &mut recorder);
+    std::fs::remove_file(&path).unwrap();
+    result.unwrap();
+
+    assert!(
+        recorder
+            .messages
+            .iter()
+            .any(|message| message.starts_with("Iter 1: Best total route length")),
+        "{:?}",
+        recorder.messages
+    );
+}
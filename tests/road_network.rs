@@ -0,0 +1,68 @@
+#![cfg(feature = "road-network")]
+
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::thread;
+use tsp_solver::{FORBIDDEN_DISTANCE, RoadMetric, RoutingService, road_network_instance};
+
+/// Serves `body` to a single request and returns the server's base URL
+/// plus a handle yielding the request it received.
+fn serve_once(body: &'static str) -> (String, thread::JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let handle = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = vec![0; 8192];
+        let read = stream.read(&mut request).unwrap();
+        let response = format!(
+            "HTTP/1.0 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        stream.write_all(response.as_bytes()).unwrap();
+        String::from_utf8_lossy(&request[..read]).into_owned()
+    });
+    (base_url, handle)
+}
+
+#[test]
+fn osrm_table_becomes_asymmetric_instance() {
+    let (base_url, request) = serve_once(
+        r#"{"code":"Ok","durations":[[0,10.5,20],[12,0,null],[19,7,0]],"distances":[[0,1,2],[1,0,2],[2,2,0]]}"#,
+    );
+    let points = [(50.08, 14.42), (50.09, 14.43), (50.10, 14.40)];
+    let service = RoutingService::Osrm {
+        base_url,
+        profile: "driving".to_string(),
+    };
+    let instance =
+        road_network_instance("prague", &points, &service, RoadMetric::Duration).unwrap();
+    assert!(request.join().unwrap().starts_with(
+        "GET /table/v1/driving/14.42,50.08;14.43,50.09;14.4,50.1?annotations=duration,distance"
+    ));
+    assert_eq!(instance.dimension, 3);
+    assert_eq!(instance.dist_matrix.get(0, 1), 10.5);
+    assert_eq!(instance.dist_matrix.get(1, 0), 12.0);
+    assert_eq!(instance.dist_matrix.get(1, 2), FORBIDDEN_DISTANCE);
+}
+
+#[test]
+fn valhalla_distances_are_converted_to_meters() {
+    let (base_url, request) = serve_once(
+        r#"{"sources_to_targets":[[{"distance":0,"time":0},{"distance":1.5,"time":90}],[{"distance":1.25,"time":80},{"distance":0,"time":0}]],"units":"kilometers"}"#,
+    );
+    let points = [(50.08, 14.42), (50.09, 14.43)];
+    let service = RoutingService::Valhalla {
+        base_url,
+        costing: "auto".to_string(),
+    };
+    let instance = road_network_instance("pair", &points, &service, RoadMetric::Distance).unwrap();
+    assert!(
+        request
+            .join()
+            .unwrap()
+            .starts_with("POST /sources_to_targets")
+    );
+    assert_eq!(instance.dist_matrix.get(0, 1), 1500.0);
+    assert_eq!(instance.dist_matrix.get(1, 0), 1250.0);
+}
@@ -0,0 +1,62 @@
+use common::write_instance;
+use tsp_solver::{Config, perturbed_instance, robustness_analysis};
+
+mod common;
+
+fn coords() -> Vec<(f64, f64)> {
+    (0..12)
+        .map(|i| (((i * 37) % 101) as f64, ((i * 53) % 97) as f64))
+        .collect()
+}
+
+#[test]
+fn perturbation_stays_within_the_noise_level() {
+    let instance = write_instance("noise12", &coords());
+    let perturbed = perturbed_instance(&instance, 0.1, 7);
+    assert!(perturbed.dist_matrix.is_symmetric());
+    let mut changed = false;
+    for i in 0..instance.dimension {
+        for j in (i + 1)..instance.dimension {
+            let (original, noisy) = (
+                instance.dist_matrix.get(i, j),
+                perturbed.dist_matrix.get(i, j),
+            );
+            assert!((noisy - original).abs() <= 0.1 * original + 1e-9);
+            changed |= noisy != original;
+        }
+    }
+    assert!(changed);
+    assert_ne!(perturbed.content_hash(), instance.content_hash());
+    // Same seed, same noise; no noise, same distances.
+    assert_eq!(
+        perturbed_instance(&instance, 0.1, 7).content_hash(),
+        perturbed.content_hash()
+    );
+    let exact = perturbed_instance(&instance, 0.0, 7);
+    assert_eq!(
+        exact.tour_length(&[0, 3, 5, 1]),
+        instance.tour_length(&[0, 3, 5, 1])
+    );
+}
+
+#[test]
+fn analysis_reports_one_row_per_noise_level() {
+    let instance = write_instance("robust12", &coords());
+    let config = Config {
+        num_iters: 30,
+        num_ants: 10,
+        num_seeds: 2,
+        noise_levels: vec![0.0, 0.2],
+        seed: Some(3),
+        ..Config::default()
+    };
+    let (rows, (tour, length)) = robustness_analysis(&instance, &config);
+    assert_eq!(tour.len(), 12);
+    assert_eq!(length, instance.tour_length(&tour));
+    assert_eq!(rows.len(), 2);
+    assert!(rows[0].length_change.abs() < 1e-9);
+    for row in &rows {
+        assert!(row.mean_regret >= 0.0 && row.max_regret >= row.mean_regret);
+        assert!((0.0..=1.0).contains(&row.shared_edges));
+    }
+}
@@ -0,0 +1,374 @@
+use common::{parse, temp_path, write_file};
+use rand::rngs::StdRng;
+use rand::seq::{IndexedRandom, SliceRandom};
+use rand::{Rng, SeedableRng};
+use tsp_solver::{
+    ParseOptions, TspInstance, parse_npy_matrix, parse_tour_file, parse_tour_reader,
+    tsplib_instance, tsplib_tour, write_tour_file,
+};
+
+mod common;
+
+/// A valid TSPLIB file with up to 12 nodes of a random metric and format.
+fn random_tsplib(rng: &mut StdRng) -> String {
+    let n = rng.random_range(1..=12);
+    let metric = ["EUC_2D", "CEIL_2D", "GEO", "ATT", "GEODESIC", "EXPLICIT"]
+        .choose(rng)
+        .unwrap();
+    let mut contents = format!(
+        "NAME: random{}\nTYPE: TSP\nCOMMENT: generated\nDIMENSION: {}\nEDGE_WEIGHT_TYPE: {}\n",
+        n, n, metric
+    );
+    if *metric == "EXPLICIT" {
+        let format = [
+            "FULL_MATRIX",
+            "UPPER_ROW",
+            "LOWER_ROW",
+            "UPPER_DIAG_ROW",
+            "LOWER_DIAG_ROW",
+        ]
+        .choose(rng)
+        .unwrap();
+        contents.push_str(&format!(
+            "EDGE_WEIGHT_FORMAT: {}\nEDGE_WEIGHT_SECTION\n",
+            format
+        ));
+        for i in 0..n {
+            let row = match *format {
+                "FULL_MATRIX" => 0..n,
+                "UPPER_ROW" => i + 1..n,
+                "LOWER_ROW" => 0..i,
+                "UPPER_DIAG_ROW" => i..n,
+                _ => 0..i + 1,
+            };
+            for j in row {
+                let weight = if i == j { 0 } else { rng.random_range(1..1000) };
+                contents.push_str(&format!("{} ", weight));
+            }
+            contents.push('\n');
+        }
+    } else {
+        contents.push_str("NODE_COORD_SECTION\n");
+        for id in 1..=n {
+            // Latitude and longitude ranges suit every coordinate metric.
+            let x: f64 = rng.random_range(-80.0..80.0);
+            let y: f64 = rng.random_range(-170.0..170.0);
+            contents.push_str(&format!("{} {} {}\n", id, x, y));
+        }
+    }
+    contents.push_str("EOF\n");
+    contents
+}
+
+/// A copy of `contents` with one random line-level or character-level edit.
+fn mutate(rng: &mut StdRng, contents: &str) -> String {
+    let mut lines: Vec<String> = contents.lines().map(String::from).collect();
+    if lines.is_empty() {
+        return contents.to_string();
+    }
+    let line = rng.random_range(0..lines.len());
+    match rng.random_range(0..6) {
+        0 => {
+            let cut = rng.random_range(0..=contents.len());
+            return contents[..cut].to_string();
+        }
+        1 => {
+            lines.remove(line);
+        }
+        2 => {
+            let copy = lines[line].clone();
+            lines.insert(line, copy);
+        }
+        3 => {
+            let other = rng.random_range(0..lines.len());
+            lines.swap(line, other);
+        }
+        4 => {
+            let garbage = [
+                "-1",
+                "x",
+                "1e400",
+                "NaN",
+                "",
+                ":",
+                "0",
+                "18446744073709551615",
+                "4294967296",
+            ]
+            .choose(rng)
+            .unwrap();
+            let mut tokens: Vec<&str> = lines[line].split_whitespace().collect();
+            if !tokens.is_empty() {
+                let token = rng.random_range(0..tokens.len());
+                tokens[token] = garbage;
+            }
+            lines[line] = tokens.join(" ");
+        }
+        _ => {
+            let dimension = [
+                "0",
+                "1",
+                "2",
+                "100000",
+                "4294967296",
+                "18446744073709551615",
+            ]
+            .choose(rng)
+            .unwrap();
+            for line in &mut lines {
+                if line.starts_with("DIMENSION") {
+                    *line = format!("DIMENSION: {}", dimension);
+                }
+            }
+        }
+    }
+    lines.join("\n")
+}
+
+fn assert_same_distances(a: &TspInstance, b: &TspInstance) {
+    assert_eq!(a.dimension, b.dimension);
+    for i in 0..a.dimension {
+        for j in 0..a.dimension {
+            let (x, y) = (a.dist_matrix.get(i, j), b.dist_matrix.get(i, j));
+            assert!(x == y || (x.is_nan() && y.is_nan()), "({}, {})", i, j);
+        }
+    }
+}
+
+#[test]
+fn written_instances_parse_back_unchanged() {
+    let mut rng = StdRng::seed_from_u64(7);
+    for _ in 0..200 {
+        let original = parse(&random_tsplib(&mut rng)).unwrap();
+        let written = tsplib_instance(&original);
+        let reparsed = parse(&written).unwrap_or_else(|e| panic!("{}\n{}", e, written));
+
+        assert_same_distances(&original, &reparsed);
+        assert_eq!(reparsed.name, original.name);
+        assert_eq!(reparsed.comment, original.comment);
+        let coords = |instance: &TspInstance| {
+            instance.node_coords.as_ref().map(|nodes| {
+                nodes
+                    .iter()
+                    .map(|node| (node.id, node.x, node.y))
+                    .collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(coords(&reparsed), coords(&original));
+        assert_eq!(tsplib_instance(&reparsed), written);
+    }
+}
+
+#[test]
+fn malformed_instances_are_errors_not_panics() {
+    let mut rng = StdRng::seed_from_u64(11);
+    for _ in 0..2000 {
+        let mut contents = random_tsplib(&mut rng);
+        for _ in 0..rng.random_range(1..=3) {
+            contents = mutate(&mut rng, &contents);
+        }
+        // Whatever still parses must survive a round trip too.
+        if let Ok(instance) = parse(&contents) {
+            let reparsed = parse(&tsplib_instance(&instance)).unwrap();
+            assert_same_distances(&instance, &reparsed);
+        }
+    }
+}
+
+#[test]
+fn lower_row_fills_both_triangles() {
+    let explicit = |format: &str, weights: &str| {
+        parse(&format!(
+            "NAME: four\nTYPE: TSP\nDIMENSION: 4\nEDGE_WEIGHT_TYPE: EXPLICIT\nEDGE_WEIGHT_FORMAT: {}\nEDGE_WEIGHT_SECTION\n{}\nEOF\n",
+            format, weights
+        ))
+        .unwrap()
+    };
+    let lower = explicit("LOWER_ROW", "1\n2 3\n4 5 6");
+    let full = explicit("FULL_MATRIX", "0 1 2 4\n1 0 3 5\n2 3 0 6\n4 5 6 0");
+    assert_same_distances(&lower, &full);
+    assert_eq!(lower.dist_matrix.get(3, 1), 5.0);
+    assert_eq!(lower.dist_matrix.get(1, 3), 5.0);
+
+    let short = "NAME: four\nTYPE: TSP\nDIMENSION: 4\nEDGE_WEIGHT_TYPE: EXPLICIT\nEDGE_WEIGHT_FORMAT: LOWER_ROW\nEDGE_WEIGHT_SECTION\n1 2 3\nEOF\n";
+    assert!(parse(short).is_err());
+}
+
+#[test]
+fn every_triangular_format_matches_the_full_matrix() {
+    let n = 5;
+    let weight = |i: usize, j: usize| {
+        if i == j {
+            0
+        } else {
+            10 * (i + j) + i.abs_diff(j)
+        }
+    };
+    let header = |format: &str| {
+        format!(
+            "NAME: five\nTYPE: TSP\nDIMENSION: {}\nEDGE_WEIGHT_TYPE: EXPLICIT\nEDGE_WEIGHT_FORMAT: {}\nEDGE_WEIGHT_SECTION\n",
+            n, format
+        )
+    };
+    let mut full = header("FULL_MATRIX");
+    for i in 0..n {
+        for j in 0..n {
+            full.push_str(&format!("{} ", weight(i, j)));
+        }
+    }
+    let full = parse(&(full + "\nEOF\n")).unwrap();
+
+    // The name says which triangle is listed, with or without the
+    // diagonal, and whether row by row or column by column.
+    let formats = [
+        "UPPER_ROW",
+        "LOWER_ROW",
+        "UPPER_DIAG_ROW",
+        "LOWER_DIAG_ROW",
+        "UPPER_COL",
+        "LOWER_COL",
+        "UPPER_DIAG_COL",
+        "LOWER_DIAG_COL",
+    ];
+    for format in formats {
+        let (upper, diagonal) = (format.starts_with("UPPER"), format.contains("DIAG"));
+        let row_wise = format.ends_with("ROW");
+        let in_triangle = |i: usize, j: usize| if i == j { diagonal } else { (i < j) == upper };
+        let mut contents = header(format);
+        for outer in 0..n {
+            for inner in 0..n {
+                let (i, j) = if row_wise {
+                    (outer, inner)
+                } else {
+                    (inner, outer)
+                };
+                if in_triangle(i, j) {
+                    contents.push_str(&format!("{} ", weight(i, j)));
+                }
+            }
+            contents.push('\n');
+        }
+        let instance = parse(&(contents + "EOF\n")).unwrap_or_else(|e| panic!("{}: {}", format, e));
+        assert_same_distances(&instance, &full);
+        assert_eq!(instance.edge_weight_format.unwrap().keyword(), format);
+    }
+}
+
+#[test]
+fn oversized_dimensions_fail_before_allocating() {
+    for (format, dimension) in [
+        ("FULL_MATRIX", "18446744073709551615"),
+        ("FULL_MATRIX", "4294967296"),
+        ("UPPER_ROW", "18446744073709551615"),
+        ("LOWER_ROW", "18446744073709551615"),
+        ("UPPER_DIAG_COL", "1000000000000"),
+        ("LOWER_DIAG_ROW", "1000000000000"),
+    ] {
+        let contents = format!(
+            "NAME: huge\nTYPE: TSP\nDIMENSION: {}\nEDGE_WEIGHT_TYPE: EXPLICIT\nEDGE_WEIGHT_FORMAT: {}\nEDGE_WEIGHT_SECTION\n0 1\n1 0\nEOF\n",
+            dimension, format
+        );
+        assert!(parse(&contents).is_err(), "{} {}", format, dimension);
+    }
+
+    let header = "{'descr': '<f8', 'fortran_order': False, 'shape': (4294967296, 4294967296), }";
+    let mut npy = b"\x93NUMPY\x01\x00".to_vec();
+    npy.extend_from_slice(&(header.len() as u16).to_le_bytes());
+    npy.extend_from_slice(header.as_bytes());
+    npy.extend_from_slice(&[0; 16]);
+    let path = write_file("huge.npy", npy);
+    let result = parse_npy_matrix(path.to_str().unwrap(), &ParseOptions::default());
+    std::fs::remove_file(&path).unwrap();
+    assert!(result.is_err());
+}
+
+#[test]
+fn written_tours_parse_back_unchanged() {
+    let mut rng = StdRng::seed_from_u64(3);
+    for _ in 0..100 {
+        let instance = parse(&random_tsplib(&mut rng)).unwrap();
+        let mut tour: Vec<usize> = (0..instance.dimension).collect();
+        tour.shuffle(&mut rng);
+        let node_ids: Vec<usize> = match &instance.node_coords {
+            Some(nodes) => tour.iter().map(|&idx| nodes[idx].id).collect(),
+            None => tour.iter().map(|&idx| idx + 1).collect(),
+        };
+        let written = tsplib_tour(&instance.name, &node_ids);
+        assert_eq!(parse_tour_reader(written.as_bytes(), &instance), Ok(tour));
+
+        let mutated = mutate(&mut rng, &written);
+        let _ = parse_tour_reader(mutated.as_bytes(), &instance);
+    }
+}
+
+#[test]
+fn tour_files_use_node_ids_and_the_tsplib_layout() {
+    let instance = parse(
+        "NAME: ids4\nTYPE: TSP\nDIMENSION: 4\nEDGE_WEIGHT_TYPE: EUC_2D\nNODE_COORD_SECTION\n\
+         10 0 0\n20 5 0\n30 5 5\n40 0 5\nEOF\n",
+    )
+    .unwrap();
+    let path = temp_path("ids4.tour");
+    let path = path.to_str().unwrap();
+    write_tour_file(&instance, &[2, 1, 0, 3], path).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(path).unwrap(),
+        "NAME: ids4.tour\nTYPE: TOUR\nDIMENSION: 4\nTOUR_SECTION\n30\n20\n10\n40\n-1\nEOF\n"
+    );
+    assert_eq!(parse_tour_file(path, &instance), Ok(vec![2, 1, 0, 3]));
+    std::fs::remove_file(path).unwrap();
+
+    assert!(write_tour_file(&instance, &[2, 1, 0], path).is_err());
+    assert!(write_tour_file(&instance, &[2, 1, 0, 2], path).is_err());
+}
+
+#[test]
+fn weight_tokens_parse_like_str_parse() {
+    let tokens = [
+        "0",
+        "-0",
+        "+7",
+        "-42",
+        "123456789012345",
+        "1234567890123456789",
+        "3.25",
+        "-1e3",
+        "2E-2",
+        ".5",
+        "inf",
+        "NaN",
+    ];
+    let n = 4;
+    let mut rng = StdRng::seed_from_u64(5);
+    let weights: Vec<&str> = (0..n * n)
+        .map(|_| *tokens.choose(&mut rng).unwrap())
+        .collect();
+    let rows: Vec<String> = weights.chunks(n).map(|row| row.join(" \t ")).collect();
+    let contents = format!(
+        "NAME: tokens\r\nTYPE: TSP\r\nDIMENSION: {}\r\nEDGE_WEIGHT_TYPE: EXPLICIT\r\nEDGE_WEIGHT_FORMAT: FULL_MATRIX\r\nEDGE_WEIGHT_SECTION\r\n{}\r\nEOF\r\n",
+        n,
+        rows.join("\r\n")
+    );
+    let instance = parse(&contents).unwrap();
+    for (k, token) in weights.iter().enumerate() {
+        let (expected, actual) = (
+            token.parse::<f64>().unwrap(),
+            instance.dist_matrix.get(k / n, k % n),
+        );
+        assert!(
+            expected.to_bits() == actual.to_bits() || (expected.is_nan() && actual.is_nan()),
+            "{}: {} vs {}",
+            token,
+            expected,
+            actual
+        );
+    }
+
+    let bad = contents.replace("SECTION\r\n", "SECTION\r\n1 5x ");
+    let error = parse(&bad).err().unwrap();
+    assert!(
+        error.contains("Invalid edge weight number: '5x'"),
+        "{}",
+        error
+    );
+}
@@ -0,0 +1,69 @@
+use common::{temp_path, write_instance};
+use tsp_solver::{AcoSolver, Config, diff_snapshots, read_snapshots};
+
+mod common;
+
+fn record(seed: u64, iterations: usize, name: &str) -> String {
+    let instance = write_instance(
+        "snapshots",
+        &[
+            (0.0, 0.0),
+            (5.0, 1.0),
+            (9.0, 4.0),
+            (6.0, 8.0),
+            (2.0, 9.0),
+            (-3.0, 5.0),
+            (-1.0, 2.0),
+        ],
+    );
+    let path = temp_path(&format!("{}.txt", name));
+    let config = Config {
+        num_iters: iterations,
+        num_ants: 6,
+        seed: Some(seed),
+        snapshot_path: Some(path.to_string_lossy().into_owned()),
+        ..Config::default()
+    };
+    AcoSolver::new(&instance, &config).solve();
+    path.to_string_lossy().into_owned()
+}
+
+#[test]
+fn seeded_runs_have_identical_snapshots_until_they_diverge() {
+    let paths = [
+        record(11, 20, "snapshots-a"),
+        record(11, 20, "snapshots-b"),
+        record(12, 20, "snapshots-c"),
+        record(11, 15, "snapshots-d"),
+    ];
+    let runs: Vec<_> = paths
+        .iter()
+        .map(|path| read_snapshots(path).unwrap())
+        .collect();
+    for path in &paths {
+        std::fs::remove_file(path).unwrap();
+    }
+    assert_eq!(runs[0].len(), 20);
+    assert_eq!(runs[0].last().unwrap().iteration, 19);
+    assert_eq!(diff_snapshots(&runs[0], &runs[1]), None);
+
+    let reseeded = diff_snapshots(&runs[0], &runs[2]).unwrap();
+    assert_eq!(reseeded.iteration, 0);
+    assert!(reseeded.to_string().contains("pheromone matrices"));
+
+    let shorter = diff_snapshots(&runs[0], &runs[3]).unwrap();
+    assert_eq!(shorter.iteration, 15);
+    assert_eq!(shorter.right, None);
+
+    let mut tampered = runs[0].clone();
+    tampered[7].pheromone_hash ^= 1;
+    let divergence = diff_snapshots(&runs[0], &tampered).unwrap();
+    assert_eq!(divergence.iteration, 7);
+    assert_eq!(
+        divergence.to_string(),
+        format!(
+            "Runs diverge at iteration 7: pheromone matrices differ (best length {0} vs {0})",
+            runs[0][7].best_length
+        )
+    );
+}
@@ -0,0 +1,138 @@
+use common::{temp_path, write_instance};
+use tsp_solver::{
+    AcoSolver, Backend, Config, LengthPrecision, Solution, Termination, solve_instance, write_tour,
+};
+
+mod common;
+
+fn square() -> Vec<(f64, f64)> {
+    vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)]
+}
+
+#[test]
+fn aco_solution_carries_run_metadata() {
+    let instance = write_instance("solution4", &square());
+    let config = Config {
+        num_iters: 5,
+        num_ants: 4,
+        seed: Some(u64::MAX),
+        ..Config::default()
+    };
+
+    let solution = AcoSolver::new(&instance, &config).solve();
+    assert_eq!(solution.algorithm, "aco");
+    assert_eq!(solution.termination, Termination::IterationLimit);
+    assert_eq!(solution.seed, Some(u64::MAX));
+    assert_eq!(solution.length, 40.0);
+    assert!(solution.is_complete(4));
+    let ids: Vec<usize> = solution.tour.iter().map(|&idx| idx + 1).collect();
+    assert_eq!(solution.node_ids, ids);
+}
+
+#[test]
+fn every_backend_returns_a_solution() {
+    let instance = write_instance("solution4-backends", &square());
+    for (backend, algorithm) in [
+        (Backend::Aco, "aco"),
+        (Backend::Partitioned(2), "partitioned"),
+    ] {
+        let config = Config {
+            num_iters: 5,
+            num_ants: 4,
+            backend,
+            seed: Some(1),
+            ..Config::default()
+        };
+        let solution = solve_instance(&instance, &config).unwrap();
+        assert_eq!(solution.algorithm, algorithm);
+        assert_eq!(solution.length, 40.0);
+    }
+}
+
+#[test]
+fn solution_round_trips_through_json() {
+    let instance = write_instance("solution4-json", &square());
+    let config = Config {
+        num_iters: 5,
+        num_ants: 4,
+        seed: Some(u64::MAX),
+        ..Config::default()
+    };
+    let mut solution = AcoSolver::new(&instance, &config).solve();
+    solution.set_optimum(38.0);
+    solution.duration = std::time::Duration::from_millis(1500);
+    solution.algorithm = "aco \"quoted\"".to_string();
+
+    let json = solution.to_json();
+    assert_eq!(Solution::from_json(&json), Ok(solution.clone()));
+
+    solution.gap = None;
+    solution.seed = None;
+    assert_eq!(Solution::from_json(&solution.to_json()), Ok(solution));
+    assert!(Solution::from_json("{\"tour\":[]}").is_err());
+}
+
+#[test]
+fn solutions_keep_the_exact_length_beside_the_rounded_one() {
+    // 3 + 1 + sqrt(10) = 7.16...
+    let instance = write_instance("solution3-exact", &[(0.0, 0.0), (3.0, 0.0), (0.0, 1.0)]);
+    let exact = 4.0 + 10f64.sqrt();
+    for backend in [Backend::Aco, Backend::Partitioned(2)] {
+        let mut config = Config {
+            num_iters: 3,
+            num_ants: 3,
+            backend,
+            seed: Some(1),
+            ..Config::default()
+        };
+        let mut solution = solve_instance(&instance, &config).unwrap();
+        assert_eq!(solution.length, 7.0);
+        assert!((solution.exact_length - exact).abs() < 1e-9);
+        assert!(solution.report(3).contains("(exact 7.1623)"));
+
+        solution.set_optimum(7.0);
+        assert_eq!(solution.gap, Some(0.0));
+        solution.set_optimum_with(exact, LengthPrecision::Exact);
+        assert_eq!(solution.gap, Some(0.0));
+
+        config.gap_precision = LengthPrecision::Exact;
+        assert_eq!(
+            solution.length_with(config.gap_precision),
+            solution.exact_length
+        );
+        assert_eq!(Solution::from_json(&solution.to_json()), Ok(solution));
+    }
+
+    let config = Config::default()
+        .with_args(["--gap-precision", "exact"].map(String::from).into_iter())
+        .unwrap();
+    assert_eq!(config.gap_precision, LengthPrecision::Exact);
+}
+
+#[test]
+fn long_tours_are_previewed_and_written_in_full() {
+    let coords: Vec<(f64, f64)> = (0..40).map(|i| (i as f64, (i * i % 7) as f64)).collect();
+    let instance = write_instance("solution40", &coords);
+    let tour: Vec<usize> = (0..40).collect();
+    let solution = Solution::new(&instance, tour, 100.0, "aco", Termination::Completed);
+
+    let report = solution.report(40);
+    assert!(
+        report.contains("Route (Node IDs, 40 cities): [1, 2, 3, 4, 5, 6, 7, 8, 9, 10] ... [31,")
+    );
+
+    let (plain, tsplib) = (temp_path("solution40.txt"), temp_path("solution40.tour"));
+    for path in [&plain, &tsplib] {
+        write_tour(&instance.name, &solution.node_ids, path.to_str().unwrap()).unwrap();
+    }
+    let plain_contents = std::fs::read_to_string(&plain).unwrap();
+    let tsplib_contents = std::fs::read_to_string(&tsplib).unwrap();
+    std::fs::remove_file(&plain).unwrap();
+    std::fs::remove_file(&tsplib).unwrap();
+
+    let ids: Vec<&str> = plain_contents.lines().collect();
+    assert_eq!(ids.len(), 40);
+    assert_eq!((ids[0], ids[39]), ("1", "40"));
+    assert!(tsplib_contents.starts_with("NAME: solution40.tour\nTYPE: TOUR\nDIMENSION: 40\n"));
+    assert!(tsplib_contents.ends_with("39\n40\n-1\nEOF\n"));
+}
@@ -0,0 +1,32 @@
+use common::write_instance;
+use tsp_solver::constraints::validate_permutation;
+use tsp_solver::{Config, solve_many, solve_tsp_aco};
+
+mod common;
+
+#[test]
+fn solve_many_matches_single_runs_in_instance_order() {
+    let instances: Vec<_> = [(12, 7), (20, 11), (16, 3)]
+        .iter()
+        .map(|&(n, step)| {
+            let coords: Vec<(f64, f64)> = (0..n)
+                .map(|i| ((i * step % 97) as f64, (i * 31 % 89) as f64))
+                .collect();
+            write_instance(&format!("many{}", n), &coords)
+        })
+        .collect();
+    let config = Config {
+        num_iters: 15,
+        num_ants: 8,
+        seed: Some(5),
+        ..Config::default()
+    };
+
+    let results = solve_many(&instances, &config);
+    assert_eq!(results.len(), instances.len());
+    for (instance, (tour, length)) in instances.iter().zip(&results) {
+        validate_permutation(tour, instance.dimension).unwrap();
+        assert_eq!((tour.clone(), *length), solve_tsp_aco(instance, &config));
+    }
+    assert_eq!(solve_many(&instances, &config), results);
+}
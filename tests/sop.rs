@@ -0,0 +1,56 @@
+use tsp_solver::{FORBIDDEN_DISTANCE, ParseOptions, parse_tsp_reader, solve_brute_force};
+
+// Source 1, sink 5; node 4 has to come before node 3.
+const SOP5: &str = "NAME: sop5
+TYPE: SOP
+DIMENSION: 5
+EDGE_WEIGHT_TYPE: EXPLICIT
+EDGE_WEIGHT_FORMAT: FULL_MATRIX
+EDGE_WEIGHT_SECTION
+5
+0 2 4 3 1000000
+-1 0 5 1 7
+-1 2 0 -1 3
+-1 6 1 0 2
+-1 -1 -1 -1 0
+EOF
+";
+
+#[test]
+fn sop_precedences_are_read_from_negative_weights() {
+    let instance = parse_tsp_reader(SOP5.as_bytes(), &ParseOptions::default()).unwrap();
+    assert_eq!(instance.dimension, 5);
+    let precedences = instance.precedences.as_ref().unwrap();
+    assert_eq!(precedences.predecessors(0), &[] as &[usize]);
+    assert_eq!(precedences.predecessors(2), &[0, 3]);
+    assert_eq!(precedences.predecessors(4), &[0, 1, 2, 3]);
+    assert_eq!(precedences.len(), 8);
+
+    // Arcs against a precedence are forbidden, the sink returns for free.
+    assert_eq!(instance.dist_matrix.get(2, 3), FORBIDDEN_DISTANCE);
+    assert_eq!(instance.dist_matrix.get(1, 0), FORBIDDEN_DISTANCE);
+    assert_eq!(instance.dist_matrix.get(4, 0), 0.0);
+    assert_eq!(instance.dist_matrix.get(3, 2), 1.0);
+
+    assert!(precedences.is_satisfied(&[0, 1, 3, 2, 4]));
+    assert!(!precedences.is_satisfied(&[0, 1, 2, 3, 4]));
+    assert!(!precedences.is_satisfied(&[0, 3, 2, 1]));
+
+    // The cheapest tour is the cheapest feasible sequence.
+    let (tour, length) = solve_brute_force(&instance).unwrap();
+    assert!(precedences.is_satisfied(&tour));
+    assert_eq!(length, 2.0 + 1.0 + 1.0 + 3.0);
+}
+
+#[test]
+fn plain_instances_have_no_precedences() {
+    let atsp = SOP5
+        .replace("TYPE: SOP", "TYPE: ATSP")
+        .replace("SECTION\n5\n", "SECTION\n");
+    let instance = parse_tsp_reader(atsp.as_bytes(), &ParseOptions::default()).unwrap();
+    assert!(instance.precedences.is_none());
+    assert_eq!(instance.dist_matrix.get(1, 0), -1.0);
+
+    let triangular = SOP5.replace("FULL_MATRIX", "UPPER_ROW");
+    assert!(parse_tsp_reader(triangular.as_bytes(), &ParseOptions::default()).is_err());
+}
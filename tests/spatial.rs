@@ -0,0 +1,70 @@
+use common::write_instance;
+use tsp_solver::spatial::grid_nearest_neighbors;
+use tsp_solver::{AcoSolver, Config, EdgeWeightType, TspInstance};
+
+mod common;
+
+fn scattered(n: usize) -> Vec<(f64, f64)> {
+    // Deterministic, roughly uniform points.
+    (0..n)
+        .map(|i| {
+            let a = (i as f64 * 0.618_033_988_75).fract();
+            let b = (i as f64 * 0.754_877_666_25).fract();
+            (1000.0 * a, 1000.0 * b)
+        })
+        .collect()
+}
+
+fn exact_neighbors(instance: &TspInstance, i: usize, k: usize) -> Vec<f64> {
+    let mut distances: Vec<f64> = (0..instance.dimension)
+        .filter(|&j| j != i)
+        .map(|j| instance.dist_matrix.get(i, j))
+        .collect();
+    distances.sort_by(f64::total_cmp);
+    distances.truncate(k);
+    distances
+}
+
+#[test]
+fn grid_candidates_match_the_exact_nearest_neighbors() {
+    let instance = write_instance("grid-candidates", &scattered(600));
+    let lists = grid_nearest_neighbors(&instance, 8).unwrap();
+    assert_eq!(lists.len(), 600);
+    for (i, list) in lists.iter().enumerate() {
+        assert!(!list.contains(&i));
+        let distances: Vec<f64> = list
+            .iter()
+            .map(|&j| instance.dist_matrix.get(i, j))
+            .collect();
+        assert_eq!(distances, exact_neighbors(&instance, i, 8));
+    }
+
+    let tiny = write_instance("grid-tiny", &scattered(3));
+    assert_eq!(grid_nearest_neighbors(&tiny, 8).unwrap()[0].len(), 2);
+
+    let geo =
+        TspInstance::from_coordinates("geo", &scattered(10), EdgeWeightType::Geo, None).unwrap();
+    assert!(grid_nearest_neighbors(&geo, 4).is_none());
+}
+
+#[test]
+fn spatially_pruned_runs_find_complete_tours() {
+    let instance = write_instance("grid-solve", &scattered(150));
+    let config = Config {
+        num_iters: 15,
+        num_ants: 8,
+        candidate_list_size: 10,
+        seed: Some(5),
+        ..Config::default()
+    };
+    let pruned = Config {
+        spatial_pruning: true,
+        ..config.clone()
+    };
+    let full = AcoSolver::new(&instance, &config).solve();
+    let grid = AcoSolver::new(&instance, &pruned).solve();
+    assert!(grid.is_complete(150));
+    // Equidistant neighbours may be listed in another order, so the runs
+    // need not be identical, only comparable.
+    assert!(grid.length < full.length * 1.1);
+}
@@ -0,0 +1,69 @@
+use tsp_solver::{
+    AcoSolver, Config, EdgeWeightType, Node, ParseOptions, SpecialDistance, parse_tsp_reader,
+    tsplib_instance,
+};
+
+const SPECIAL: &str = "NAME: special
+TYPE: TSP
+DIMENSION: 4
+EDGE_WEIGHT_TYPE: SPECIAL
+NODE_COORD_SECTION
+1 0 0
+2 3 0
+3 3 4
+4 0 4
+EOF
+";
+
+// Walking is free downhill (decreasing y) and costs double uphill.
+fn hiking(a: &Node, b: &Node) -> f64 {
+    (a.x - b.x).abs() + 2.0 * (b.y - a.y).max(0.0)
+}
+
+#[test]
+fn special_instances_use_the_registered_distance() {
+    assert!(parse_tsp_reader(SPECIAL.as_bytes(), &ParseOptions::default()).is_err());
+
+    let options = ParseOptions {
+        special_distance: Some(SpecialDistance::new(hiking)),
+        ..ParseOptions::default()
+    };
+    let instance = parse_tsp_reader(SPECIAL.as_bytes(), &options).unwrap();
+    assert!(matches!(
+        instance.edge_weight_type,
+        EdgeWeightType::Special(_)
+    ));
+    assert_eq!(instance.edge_weight_type.keyword(), "SPECIAL");
+    assert_eq!(instance.dist_matrix.get(1, 2), 8.0);
+    assert_eq!(instance.dist_matrix.get(2, 1), 0.0);
+    assert_eq!(instance.tour_length(&[0, 1, 2, 3]), 14.0);
+
+    let config = Config {
+        num_iters: 20,
+        num_ants: 4,
+        seed: Some(2),
+        ..Config::default()
+    };
+    let solution = AcoSolver::new(&instance, &config).solve();
+    assert!(solution.is_complete(4));
+
+    // Exported as weights, since the function does not travel with the file.
+    let exported = tsplib_instance(&instance);
+    assert!(exported.contains("EDGE_WEIGHT_TYPE: EXPLICIT"));
+    let reparsed = parse_tsp_reader(exported.as_bytes(), &ParseOptions::default()).unwrap();
+    assert_eq!(reparsed.dist_matrix.get(1, 2), 8.0);
+    assert_eq!(reparsed.dist_matrix.get(2, 1), 0.0);
+}
+
+#[test]
+fn coordinate_instances_accept_special_distances() {
+    let instance = tsp_solver::TspInstance::from_coordinates(
+        "hike",
+        &[(0.0, 0.0), (3.0, 0.0), (3.0, 4.0)],
+        EdgeWeightType::Special(SpecialDistance::new(hiking)),
+        None,
+    )
+    .unwrap();
+    assert_eq!(instance.dist_matrix.get(1, 2), 8.0);
+    assert_eq!(instance.dist_matrix.get(2, 1), 0.0);
+}
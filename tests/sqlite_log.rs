@@ -0,0 +1,137 @@
+#![cfg(feature = "sqlite")]
+
+use common::{SQUARE, temp_dir, temp_path, tsp_text};
+use rusqlite::Connection;
+use std::fs;
+use tsp_solver::results::{IterationRecord, RunRecord};
+use tsp_solver::sqlite_log::record_runs;
+use tsp_solver::{Config, SilentReporter, run};
+
+mod common;
+
+fn count(db: &Connection, sql: &str) -> i64 {
+    db.query_row(sql, [], |row| row.get(0)).unwrap()
+}
+
+#[test]
+fn sessions_add_to_the_same_database() {
+    let path = temp_path("runs.sqlite");
+    let _ = fs::remove_file(&path);
+    let history = vec![
+        IterationRecord {
+            iteration: 0,
+            iteration_best_length: f64::MAX,
+            best_length: f64::MAX,
+            edge_entropy: 1.0,
+        },
+        IterationRecord {
+            iteration: 1,
+            iteration_best_length: 12.0,
+            best_length: 12.0,
+            edge_entropy: 0.5,
+        },
+    ];
+    let runs = [RunRecord {
+        instance: "square".to_string(),
+        instance_hash: "abc".to_string(),
+        seed: 7,
+        length: 12.0,
+        gap: None,
+        seconds: 0.25,
+        tour: vec![0, 2, 1, 3],
+        history,
+    }];
+    let config = Config::default();
+    let path_str = path.to_str().unwrap();
+    record_runs(&runs, &config, "first", path_str).unwrap();
+    record_runs(&runs, &config, "second", path_str).unwrap();
+    let tuned = Config {
+        alpha: 2.0,
+        ..Config::default()
+    };
+    record_runs(&runs, &tuned, "tuned", path_str).unwrap();
+
+    let db = Connection::open(&path).unwrap();
+    assert_eq!(count(&db, "SELECT COUNT(*) FROM runs"), 3);
+    assert_eq!(count(&db, "SELECT COUNT(*) FROM configurations"), 2);
+    assert_eq!(
+        count(&db, "SELECT COUNT(*) FROM settings") as usize,
+        2 * config.setting_pairs().len()
+    );
+    let alpha: String = db
+        .query_row(
+            "SELECT value FROM settings JOIN runs USING (configuration_id)
+             WHERE session = 'tuned' AND key = 'alpha'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(alpha, "2");
+    assert_eq!(count(&db, "SELECT COUNT(*) FROM iterations"), 6);
+    assert_eq!(
+        count(
+            &db,
+            "SELECT COUNT(*) FROM iterations WHERE best_length IS NULL"
+        ),
+        3
+    );
+    let tour: Vec<i64> = db
+        .prepare("SELECT node FROM tours WHERE run_id = 1 ORDER BY position")
+        .unwrap()
+        .query_map([], |row| row.get(0))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(tour, [0, 2, 1, 3]);
+    drop(db);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn experiment_mode_records_histories_and_tours() {
+    let dir = temp_dir("sqlite-experiment");
+    fs::create_dir_all(dir.join("instances")).unwrap();
+    fs::write(
+        dir.join("instances").join("square.tsp"),
+        tsp_text("square", &SQUARE),
+    )
+    .unwrap();
+    let (instances, out, path) = (
+        dir.join("instances"),
+        dir.join("out"),
+        dir.join("runs.sqlite"),
+    );
+    let args = [
+        "tsp_solver",
+        "experiment",
+        "notebook",
+        instances.to_str().unwrap(),
+        "--output-dir",
+        out.to_str().unwrap(),
+        "--iters",
+        "5",
+        "--seeds",
+        "2",
+        "--sqlite",
+        path.to_str().unwrap(),
+    ];
+    let config = Config::build(args.iter().map(|arg| arg.to_string())).unwrap();
+    run(&config, &mut SilentReporter).unwrap();
+
+    let db = Connection::open(&path).unwrap();
+    assert_eq!(
+        count(&db, "SELECT COUNT(*) FROM runs WHERE session = 'notebook'"),
+        2
+    );
+    assert_eq!(count(&db, "SELECT COUNT(*) FROM iterations"), 10);
+    assert_eq!(count(&db, "SELECT COUNT(*) FROM tours"), 8);
+    assert_eq!(
+        count(
+            &db,
+            "SELECT MIN(best_length) = MIN(length) FROM iterations, runs"
+        ),
+        1
+    );
+    drop(db);
+    fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,66 @@
+use common::write_instance;
+use std::collections::HashSet;
+use tsp_solver::{Config, StartPolicy, solve_tsp_aco};
+
+mod common;
+
+fn circle(n: usize) -> Vec<(f64, f64)> {
+    (0..n)
+        .map(|i| {
+            let angle = i as f64 * std::f64::consts::TAU / n as f64;
+            (100.0 * angle.cos(), 100.0 * angle.sin())
+        })
+        .collect()
+}
+
+#[test]
+fn policies_spread_starts_as_documented() {
+    let config = Config {
+        num_ants: 4,
+        ..Config::default()
+    };
+    let n = 10;
+    let starts = |policy: StartPolicy, iteration: usize| -> Vec<Option<usize>> {
+        (0..policy.num_ants(&config, n))
+            .map(|ant| policy.start_node(ant, iteration, policy.num_ants(&config, n), n))
+            .collect()
+    };
+
+    assert_eq!(starts(StartPolicy::Random, 0), vec![None; 4]);
+    assert_eq!(starts(StartPolicy::FixedNode(3), 5), vec![Some(3); 4]);
+    assert_eq!(starts(StartPolicy::FixedNode(10), 0), vec![None; 4]);
+    assert_eq!(
+        starts(StartPolicy::RoundRobin, 2),
+        vec![Some(8), Some(9), Some(0), Some(1)]
+    );
+    let every_node: HashSet<Option<usize>> =
+        starts(StartPolicy::OnePerNode, 7).into_iter().collect();
+    assert_eq!(every_node, (0..n).map(Some).collect());
+}
+
+#[test]
+fn fixed_start_is_where_the_best_tour_begins() {
+    let instance = write_instance("start12", &circle(12));
+    let config = Config {
+        num_iters: 20,
+        num_ants: 5,
+        start_policy: StartPolicy::FixedNode(7),
+        seed: Some(3),
+        ..Config::default()
+    };
+    let (tour, _) = solve_tsp_aco(&instance, &config);
+    assert_eq!(tour.len(), 12);
+    assert_eq!(tour[0], 7);
+}
+
+#[test]
+fn cli_start_node_is_one_based() {
+    let parse = |value: &str| {
+        Config::default()
+            .with_args(["--start", value].map(String::from).into_iter())
+            .map(|config| config.start_policy)
+    };
+    assert_eq!(parse("1"), Ok(StartPolicy::FixedNode(0)));
+    assert_eq!(parse("one-per-node"), Ok(StartPolicy::OnePerNode));
+    assert!(parse("0").is_err());
+}
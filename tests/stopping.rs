@@ -0,0 +1,181 @@
+use common::write_instance;
+use std::sync::Arc;
+use std::time::Duration;
+use tsp_solver::time_budget::{BudgetPlan, BudgetPlanner, MIN_BUDGET_ANTS};
+use tsp_solver::{
+    AcoSolver, Composite, Config, MaxIterations, NoImprovementFor, SearchState, StoppingCriterion,
+    TargetLength, Termination, TimeLimit,
+};
+
+mod common;
+
+fn state(iteration: usize, last_improvement: usize, best_length: f64) -> SearchState {
+    SearchState {
+        iteration,
+        tours: 0,
+        elapsed: Duration::from_secs(1),
+        best_length,
+        last_improvement,
+    }
+}
+
+#[test]
+fn composites_combine_reasons() {
+    let any = Composite::any(vec![
+        Arc::new(TargetLength(100.0)),
+        Arc::new(NoImprovementFor(10)),
+    ]);
+    assert_eq!(any.check(&state(5, 0, 120.0)), None);
+    assert_eq!(
+        any.check(&state(5, 0, 100.0)),
+        Some(Termination::TargetReached)
+    );
+    assert_eq!(
+        any.check(&state(15, 5, 120.0)),
+        Some(Termination::NoImprovement)
+    );
+
+    let all = Composite::all(vec![
+        Arc::new(MaxIterations(10)),
+        Arc::new(TimeLimit(Duration::from_millis(500))),
+    ]);
+    assert_eq!(all.check(&state(5, 0, 120.0)), None);
+    assert_eq!(
+        all.check(&state(10, 0, 120.0)),
+        Some(Termination::TimeLimit)
+    );
+    assert_eq!(Composite::all(Vec::new()).check(&state(10, 0, 0.0)), None);
+}
+
+#[test]
+fn solver_stops_on_configured_criteria() {
+    let coords = [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)];
+    let instance = write_instance("stopping4", &coords);
+    let solve = |criterion: Arc<dyn StoppingCriterion>| {
+        let config = Config {
+            num_iters: 1000,
+            num_ants: 4,
+            seed: Some(2),
+            stopping: vec![criterion],
+            ..Config::default()
+        };
+        let mut solver = AcoSolver::new(&instance, &config);
+        while !solver.is_finished() {
+            solver.step();
+        }
+        (solver.iteration(), solver.solve().termination)
+    };
+
+    let (iterations, termination) = solve(Arc::new(TargetLength(40.0)));
+    assert_eq!(termination, Termination::TargetReached);
+    assert!(iterations < 1000);
+
+    let (iterations, termination) = solve(Arc::new(NoImprovementFor(20)));
+    assert_eq!(termination, Termination::NoImprovement);
+    assert!((20..1000).contains(&iterations));
+
+    let (iterations, termination) = solve(Arc::new(TimeLimit(Duration::ZERO)));
+    assert_eq!(termination, Termination::TimeLimit);
+    assert_eq!(iterations, 0);
+
+    let (iterations, termination) = solve(Arc::new(NoImprovementFor(usize::MAX)));
+    assert_eq!(termination, Termination::IterationLimit);
+    assert_eq!(iterations, 1000);
+}
+
+#[test]
+fn tour_budget_counts_constructed_tours_across_colony_sizes() {
+    let coords = [
+        (0.0, 0.0),
+        (10.0, 0.0),
+        (10.0, 10.0),
+        (0.0, 10.0),
+        (5.0, 5.0),
+    ];
+    let instance = write_instance("stopping5-budget", &coords);
+    let solve = |num_ants: usize| {
+        let config = Config {
+            num_iters: 2,
+            num_ants,
+            tour_budget: Some(30),
+            seed: Some(4),
+            ..Config::default()
+        };
+        let mut solver = AcoSolver::new(&instance, &config);
+        while !solver.is_finished() {
+            solver.step();
+        }
+        (
+            solver.iteration(),
+            solver.search_state().tours,
+            solver.solve(),
+        )
+    };
+
+    // The budget replaces the iteration limit; the last colony is cut short.
+    let (iterations, tours, solution) = solve(4);
+    assert_eq!((iterations, tours), (8, 30));
+    assert_eq!(solution.tours, 30);
+    assert_eq!(solution.termination, Termination::TourBudget);
+    // Colonies are capped at one ant per node.
+    let (iterations, tours, _) = solve(10);
+    assert_eq!((iterations, tours), (6, 30));
+    let (iterations, tours, solution) = solve(0);
+    assert_eq!((iterations, tours), (30, 0));
+    assert_eq!(solution.tours_per_second(), None);
+
+    let parsed = Config::default()
+        .with_args(["--tour-budget", "500"].map(String::from).into_iter())
+        .unwrap();
+    assert_eq!(parsed.tour_budget, Some(500));
+}
+
+#[test]
+fn budget_planner_fills_the_remaining_time() {
+    let mut planner = BudgetPlanner::new(Duration::from_secs(1), 50);
+    // 50 ants in 10 ms: 0.2 ms per ant leaves room for 4950 tours, 101
+    // more iterations of 49 ants.
+    let plan = planner.replan(1, Duration::from_millis(10), Duration::from_millis(10), 50);
+    assert_eq!(
+        plan,
+        BudgetPlan {
+            iterations: 102,
+            ants: 49
+        }
+    );
+
+    // Ants getting ten times dearer shrink the colony to keep iterating.
+    let mut slow = BudgetPlanner::new(Duration::from_secs(1), 50);
+    let plan = slow.replan(
+        1,
+        Duration::from_millis(500),
+        Duration::from_millis(500),
+        50,
+    );
+    assert_eq!(plan.ants, MIN_BUDGET_ANTS);
+    assert_eq!(plan.iterations, 1 + 10);
+
+    // An exhausted budget plans no further iterations.
+    let plan = slow.replan(11, Duration::from_secs(2), Duration::from_millis(50), 5);
+    assert_eq!(plan.iterations, 11);
+}
+
+#[test]
+fn time_budgeted_runs_end_near_the_budget() {
+    let coords: Vec<(f64, f64)> = (0..60)
+        .map(|i| ((i * 37 % 101) as f64, (i * 53 % 97) as f64))
+        .collect();
+    let instance = write_instance("time-budget", &coords);
+    let config = Config {
+        num_iters: 3, // Ignored under a time budget.
+        num_ants: 10,
+        seed: Some(1),
+        time_budget: Some(Duration::from_millis(400)),
+        ..Config::default()
+    };
+    let solution = AcoSolver::new(&instance, &config).solve();
+    assert_eq!(solution.termination, Termination::TimeLimit);
+    assert!(solution.tours > 30);
+    assert!(solution.duration >= Duration::from_millis(200));
+    assert!(solution.duration < Duration::from_millis(1500));
+}
@@ -0,0 +1,61 @@
+use common::write_instance;
+use tsp_solver::threads::{MIN_CELLS_PER_TASK, evaporation_rows};
+use tsp_solver::{Config, solve_tsp_aco};
+
+mod common;
+
+#[test]
+fn evaporation_tasks_grow_for_small_instances() {
+    let config = Config::default();
+    assert_eq!(evaporation_rows(8, &config), MIN_CELLS_PER_TASK / 8);
+    assert_eq!(evaporation_rows(MIN_CELLS_PER_TASK * 2, &config), 1);
+    assert_eq!(evaporation_rows(0, &config), MIN_CELLS_PER_TASK);
+
+    let fixed = Config {
+        evaporation_chunk: Some(0),
+        ..Config::default()
+    };
+    assert_eq!(evaporation_rows(8, &fixed), 1);
+}
+
+#[test]
+fn chunking_does_not_change_seeded_results() {
+    let coords: Vec<(f64, f64)> = (0..30)
+        .map(|i| ((i * 37 % 101) as f64, (i * 53 % 89) as f64))
+        .collect();
+    let instance = write_instance("threads30", &coords);
+    let solve = |ant_chunk: usize, evaporation_chunk: Option<usize>| {
+        let config = Config {
+            num_iters: 20,
+            num_ants: 12,
+            seed: Some(8),
+            ant_chunk,
+            evaporation_chunk,
+            ..Config::default()
+        };
+        solve_tsp_aco(&instance, &config)
+    };
+    let reference = solve(1, Some(1));
+    assert_eq!(solve(5, None), reference);
+    assert_eq!(solve(0, Some(1000)), reference);
+}
+
+#[test]
+fn cli_sets_chunks_and_pinning() {
+    let config = Config::default()
+        .with_args(
+            [
+                "--ant-chunk",
+                "4",
+                "--evaporation-chunk",
+                "16",
+                "--pin-threads",
+            ]
+            .map(String::from)
+            .into_iter(),
+        )
+        .unwrap();
+    assert_eq!(config.ant_chunk, 4);
+    assert_eq!(config.evaporation_chunk, Some(16));
+    assert!(config.pin_threads);
+}
@@ -0,0 +1,97 @@
+use common::parse;
+use tsp_solver::{AcoSolver, Config, EdgeWeightType, tsplib_instance};
+
+mod common;
+
+const CUBE: &str = "NODE_COORD_SECTION
+1 0 0 0
+2 1 2 2
+3 4 6 2
+EOF
+";
+
+#[test]
+fn three_dimensional_metrics_use_the_z_coordinate() {
+    let euclidean = parse(&format!(
+        "NAME: cube\nTYPE: TSP\nDIMENSION: 3\nEDGE_WEIGHT_TYPE: EUC_3D\n{}",
+        CUBE
+    ))
+    .unwrap();
+    assert_eq!(euclidean.edge_weight_type, EdgeWeightType::Euc3D);
+    assert_eq!(euclidean.node_coords.as_ref().unwrap()[1].z, 2.0);
+    assert_eq!(euclidean.dist_matrix.get(0, 1), 3.0);
+    assert_eq!(euclidean.dist_matrix.get(1, 2), 5.0);
+
+    let manhattan = parse(&format!(
+        "NAME: cube\nDIMENSION: 3\nEDGE_WEIGHT_TYPE: MAN_3D\nNODE_COORD_TYPE: THREED_COORDS\n{}",
+        CUBE
+    ))
+    .unwrap();
+    assert_eq!(manhattan.dist_matrix.get(0, 1), 5.0);
+    assert_eq!(manhattan.dist_matrix.get(0, 2), 12.0);
+
+    let missing_z = "NAME: flat\nDIMENSION: 2\nEDGE_WEIGHT_TYPE: EUC_3D\nNODE_COORD_SECTION\n1 0 0\n2 1 1\nEOF\n";
+    assert!(parse(missing_z).is_err_and(|e| e.contains("expected id x y z")));
+}
+
+#[test]
+fn three_dimensional_instances_round_trip_through_tsplib() {
+    let instance = parse(&format!(
+        "NAME: cube\nDIMENSION: 3\nEDGE_WEIGHT_TYPE: EUC_3D\n{}",
+        CUBE
+    ))
+    .unwrap();
+    let written = tsplib_instance(&instance);
+    assert!(written.contains("NODE_COORD_TYPE: THREED_COORDS"));
+    let parsed = parse(&written).unwrap();
+    assert_eq!(parsed.edge_weight_type, EdgeWeightType::Euc3D);
+    for i in 0..3 {
+        for j in 0..3 {
+            assert_eq!(parsed.dist_matrix.get(i, j), instance.dist_matrix.get(i, j));
+        }
+    }
+}
+
+#[test]
+fn xray_distances_wait_for_the_slowest_motor() {
+    let goniometer = "NODE_COORD_TYPE: THREED_COORDS\nNODE_COORD_SECTION\n1 350 10 20\n2 20 25 26\n3 60 25 126\nEOF\n";
+    let xray1 = parse(&format!(
+        "NAME: xray\nDIMENSION: 3\nEDGE_WEIGHT_TYPE: XRAY1\n{}",
+        goniometer
+    ))
+    .unwrap();
+    assert_eq!(xray1.edge_weight_type, EdgeWeightType::Xray1);
+    // Phi turns 30 degrees across 0 instead of 330 the other way.
+    assert!((xray1.dist_matrix.get(0, 1) - 3000.0).abs() < 1e-9);
+    assert!((xray1.dist_matrix.get(1, 2) - 10_000.0).abs() < 1e-9);
+
+    let xray2 = parse(&format!(
+        "NAME: xray\nDIMENSION: 3\nEDGE_WEIGHT_TYPE: XRAY2\n{}",
+        goniometer
+    ))
+    .unwrap();
+    assert!((xray2.dist_matrix.get(0, 1) - 2400.0).abs() < 1e-9);
+    assert!((xray2.dist_matrix.get(1, 2) - 10_000.0 / 1.15).abs() < 1e-9);
+}
+
+#[test]
+fn inserted_nodes_of_3d_instances_need_a_z_coordinate() {
+    let instance = parse(&format!(
+        "NAME: cube\nDIMENSION: 3\nEDGE_WEIGHT_TYPE: EUC_3D\n{}",
+        CUBE
+    ))
+    .unwrap();
+    let config = Config {
+        num_iters: 5,
+        seed: Some(1),
+        ..Config::default()
+    };
+    let mut solver = AcoSolver::new(&instance, &config);
+    solver.step();
+    assert!(solver.insert_node(0.0, 0.0).is_err());
+    assert_eq!(solver.insert_node_3d(1.0, 2.0, 5.0), Ok(3));
+    let grown = solver.instance();
+    assert_eq!(grown.node_coords.as_ref().unwrap()[3].z, 5.0);
+    assert_eq!(grown.dist_matrix.get(3, 1), 3.0);
+    assert!((grown.dist_matrix.get(0, 3) - 30f64.sqrt()).abs() < 1e-9);
+}
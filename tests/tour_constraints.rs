@@ -0,0 +1,198 @@
+use common::write_instance;
+use std::sync::Arc;
+use tsp_solver::{
+    AcoSolver, Config, Depot, LocalSearchTarget, RouteLimits, Solution, Termination,
+    TourConstraints, TwoOpt, Violation, solve_multi_depot, solve_tsp_aco,
+};
+
+mod common;
+
+fn circle(n: usize) -> Vec<(f64, f64)> {
+    (0..n)
+        .map(|i| {
+            let angle = i as f64 * std::f64::consts::TAU / n as f64;
+            (500.0 + 400.0 * angle.cos(), 500.0 + 400.0 * angle.sin())
+        })
+        .collect()
+}
+
+#[test]
+fn pinned_nodes_keep_their_positions() {
+    let instance = write_instance("pins", &circle(12));
+    let config = Config {
+        num_iters: 50,
+        pinned_positions: vec![(7, 2), (0, 5), (3, 11)],
+        ..Config::default()
+    };
+    let (tour, _) = solve_tsp_aco(&instance, &config);
+    assert_eq!(tour.len(), 12);
+    assert_eq!(tour[2], 7);
+    assert_eq!(tour[5], 0);
+    assert_eq!(tour[11], 3);
+}
+
+#[test]
+fn local_search_respects_pins() {
+    let instance = write_instance("pins-ls", &circle(16));
+    let config = Config {
+        num_iters: 30,
+        pinned_positions: vec![(9, 0), (2, 3)],
+        local_search: vec![Arc::new(TwoOpt)],
+        local_search_target: LocalSearchTarget::Both,
+        ..Config::default()
+    };
+    let (tour, _) = solve_tsp_aco(&instance, &config);
+    assert_eq!(tour[0], 9);
+    assert_eq!(tour[3], 2);
+}
+
+#[test]
+fn subsequences_stay_contiguous_and_ordered() {
+    let instance = write_instance("chains", &circle(14));
+    let config = Config {
+        num_iters: 50,
+        subsequences: vec![vec![3, 10, 5], vec![0, 7]],
+        pinned_positions: vec![(12, 4)],
+        local_search: vec![Arc::new(TwoOpt)],
+        ..Config::default()
+    };
+    let (tour, _) = solve_tsp_aco(&instance, &config);
+    assert_eq!(tour.len(), 14);
+    assert_eq!(tour[4], 12);
+    let position = |node: usize| tour.iter().position(|&n| n == node).unwrap();
+    for chain in &config.subsequences {
+        for pair in chain.windows(2) {
+            assert_eq!(position(pair[1]), (position(pair[0]) + 1) % tour.len());
+        }
+    }
+}
+
+#[test]
+fn pickups_precede_their_deliveries() {
+    let instance = write_instance("pairs", &circle(12));
+    let pairs = vec![(9, 2), (4, 3), (11, 0)];
+    let config = Config {
+        num_iters: 50,
+        pickup_delivery: pairs.clone(),
+        local_search: vec![Arc::new(TwoOpt)],
+        ..Config::default()
+    };
+    let (tour, _) = solve_tsp_aco(&instance, &config);
+    assert_eq!(tour.len(), 12);
+    let position = |node: usize| tour.iter().position(|&n| n == node).unwrap();
+    for (pickup, delivery) in pairs {
+        assert!(position(pickup) < position(delivery));
+    }
+}
+
+#[test]
+fn multi_depot_routes_keep_pairs_together() {
+    let instance = write_instance("pairs-mtsp", &circle(16));
+    let pairs = vec![(3, 12), (7, 5), (14, 9)];
+    let depots = [
+        Depot {
+            node: 0,
+            salesmen: 2,
+        },
+        Depot {
+            node: 8,
+            salesmen: 1,
+        },
+    ];
+    let config = Config {
+        num_iters: 30,
+        pickup_delivery: pairs.clone(),
+        local_search: vec![Arc::new(TwoOpt)],
+        ..Config::default()
+    };
+    let route_set = solve_multi_depot(&instance, &config, &depots).unwrap();
+    let routes: Vec<&Vec<usize>> = route_set.depots.iter().flat_map(|d| &d.routes).collect();
+    assert_eq!(routes.iter().map(|r| r.len() - 1).sum::<usize>(), 14);
+    for (pickup, delivery) in pairs {
+        let route = routes.iter().find(|r| r.contains(&pickup)).unwrap();
+        let position = |node: usize| route.iter().position(|&n| n == node);
+        assert!(position(delivery).is_some_and(|d| position(pickup).unwrap() < d));
+    }
+    assert!(route_set.violations.is_empty());
+}
+
+#[test]
+fn violations_measure_how_far_a_tour_is_off() {
+    let instance = write_instance("violations", &circle(8));
+    let config = Config {
+        num_iters: 20,
+        pinned_positions: vec![(2, 0)],
+        subsequences: vec![vec![4, 5, 6]],
+        pickup_delivery: vec![(7, 1)],
+        ..Config::default()
+    };
+    let constraints = TourConstraints::from_config(&config, 8);
+    assert!(constraints.violations(&[2, 7, 0, 1, 3, 4, 5, 6]).is_empty());
+
+    let violations = constraints.violations(&[0, 1, 2, 3, 4, 6, 5, 7]);
+    assert_eq!(
+        violations,
+        vec![
+            Violation::Lock {
+                node: 2,
+                position: 0,
+                offset: 2
+            },
+            Violation::Subsequence {
+                chain: 0,
+                breaks: 2
+            },
+            Violation::Precedence {
+                pickup: 7,
+                delivery: 1,
+                positions: 6
+            },
+        ]
+    );
+    assert_eq!(violations[2].magnitude(), 6.0);
+
+    // The colony only builds tours that satisfy the constraints.
+    let solution = AcoSolver::new(&instance, &config).solve();
+    assert!(solution.is_feasible(8));
+
+    let mut solution = Solution::new(
+        &instance,
+        vec![0, 1, 2, 3, 4, 6, 5, 7],
+        3000.0,
+        "manual",
+        Termination::Completed,
+    );
+    solution.violations = violations;
+    assert!(!solution.is_feasible(8));
+    let report = solution.report(8);
+    assert!(report.contains("Best-effort tour, 3 constraint violations"));
+    assert!(report.contains("delivery 1 is 6 positions ahead of pickup 7"));
+    assert_eq!(Solution::from_json(&solution.to_json()), Ok(solution));
+}
+
+#[test]
+fn route_limits_are_reported_per_route() {
+    let instance = write_instance("violations-mtsp", &circle(10));
+    let depots = [Depot {
+        node: 0,
+        salesmen: 1,
+    }];
+    let config = Config {
+        num_iters: 5,
+        route_limits: RouteLimits {
+            capacity: Some(5.0),
+            ..RouteLimits::default()
+        },
+        ..Config::default()
+    };
+    let route_set = solve_multi_depot(&instance, &config, &depots).unwrap();
+    assert_eq!(
+        route_set.violations,
+        vec![Violation::Capacity {
+            depot: 0,
+            route: 0,
+            excess: 4.0
+        }]
+    );
+    assert_eq!(route_set.capacity_excess, 4.0);
+}
@@ -0,0 +1,59 @@
+use tsp_solver::{
+    DistanceMatrix, DistancePolicy, EdgeWeightFormat, EdgeWeightType, TspInstance, atsp_to_stsp,
+    solve_brute_force, stsp_tour_to_atsp,
+};
+
+fn asymmetric_instance() -> TspInstance {
+    let costs = [
+        [0.0, 3.0, 9.0, 7.0],
+        [8.0, 0.0, 2.0, 6.0],
+        [4.0, 9.0, 0.0, 1.0],
+        [2.0, 5.0, 8.0, 0.0],
+    ];
+    let mut dist_matrix = DistanceMatrix::full(4);
+    for (i, row) in costs.iter().enumerate() {
+        for (j, &cost) in row.iter().enumerate() {
+            dist_matrix.set(i, j, cost);
+        }
+    }
+    TspInstance {
+        name: "atsp4".to_string(),
+        tsp_type: "ATSP".to_string(),
+        comment: String::new(),
+        dimension: 4,
+        edge_weight_type: EdgeWeightType::Explicit,
+        edge_weight_format: Some(EdgeWeightFormat::FullMatrix),
+        distance_policy: DistancePolicy::Exact,
+        node_coords: None,
+        dist_matrix,
+        node_metadata: None,
+        precedences: None,
+        capacity: None,
+        demands: None,
+        depots: None,
+        fixed_edges: None,
+    }
+}
+
+#[test]
+fn symmetric_transformation_preserves_optimum() {
+    let atsp = asymmetric_instance();
+    let stsp = atsp_to_stsp(&atsp);
+    assert_eq!(stsp.dimension, 8);
+    assert!(stsp.dist_matrix.is_symmetric());
+
+    let (tour, length) = solve_brute_force(&stsp).unwrap();
+    let mapped = stsp_tour_to_atsp(&tour, 4).unwrap();
+    // 0 -> 1 -> 2 -> 3 -> 0 costs 3 + 2 + 1 + 2.
+    assert_eq!(length, 8.0);
+    assert_eq!(atsp.tour_length(&mapped), 8.0);
+}
+
+#[test]
+fn mapping_back_handles_both_directions() {
+    let forward = [0, 4, 2, 6, 1, 5, 3, 7];
+    assert_eq!(stsp_tour_to_atsp(&forward, 4).unwrap(), vec![0, 2, 1, 3]);
+    let reversed: Vec<usize> = forward.iter().rev().copied().collect();
+    assert_eq!(stsp_tour_to_atsp(&reversed, 4).unwrap(), vec![0, 2, 1, 3]);
+    assert!(stsp_tour_to_atsp(&[0, 4, 1, 2, 6, 5, 3, 7], 4).is_err());
+}
@@ -0,0 +1,100 @@
+use common::{tsp_text, write_file, write_instance};
+use tsp_solver::{AcoSolver, Config, ParseOptions, TspInstance, parse_tsp_file_with_options};
+
+mod common;
+
+const POINTS: [(f64, f64); 5] = [(0.0, 0.0), (4.0, 0.0), (4.0, 3.0), (1.0, 5.0), (-2.0, 2.0)];
+
+fn assert_same_distances(moved: &TspInstance, fresh: &TspInstance) {
+    for i in 0..fresh.dimension {
+        for j in 0..fresh.dimension {
+            assert_eq!(moved.dist_matrix.get(i, j), fresh.dist_matrix.get(i, j));
+        }
+    }
+}
+
+#[test]
+fn moving_a_node_matches_a_freshly_parsed_instance() {
+    let mut moved_points = POINTS;
+    moved_points[2] = (7.0, -1.0);
+    let fresh = write_instance("moved-fresh", &moved_points);
+
+    let mut triangular = write_instance("moved-triangular", &POINTS);
+    assert_eq!(triangular.update_node_coord(3, 7.0, -1.0), Ok(2));
+    assert_same_distances(&triangular, &fresh);
+    let coords = triangular.node_coords.as_ref().unwrap();
+    assert_eq!((coords[2].id, coords[2].x, coords[2].y), (3, 7.0, -1.0));
+
+    let mut dense = write_instance("moved-dense", &POINTS);
+    dense.dist_matrix = dense.dist_matrix.into_dense();
+    dense.update_node_coord(3, 7.0, -1.0).unwrap();
+    assert_same_distances(&dense, &fresh);
+
+    let path = write_file("moved-lazy.tsp", tsp_text("moved-lazy", &POINTS));
+    let options = ParseOptions {
+        lazy_distances: true,
+        ..ParseOptions::default()
+    };
+    let mut lazy = parse_tsp_file_with_options(path.to_str().unwrap(), &options).unwrap();
+    std::fs::remove_file(path).unwrap();
+    lazy.update_node_coord(3, 7.0, -1.0).unwrap();
+    assert_same_distances(&lazy, &fresh);
+
+    assert!(triangular.update_node_coord(9, 0.0, 0.0).is_err());
+}
+
+#[test]
+fn solvers_re_evaluate_the_best_tour_after_a_move() {
+    let instance = write_instance("moved-solver", &POINTS);
+    let config = Config {
+        num_iters: 20,
+        num_ants: 5,
+        seed: Some(3),
+        ..Config::default()
+    };
+    let mut solver = AcoSolver::new(&instance, &config);
+    for _ in 0..5 {
+        solver.step();
+    }
+    let tour = solver.best_tour().to_vec();
+    solver.move_node(2, 40.0, 30.0).unwrap();
+    assert_eq!(solver.instance().node_coords.as_ref().unwrap()[2].x, 40.0);
+    assert_eq!(
+        solver.best_tour_length(),
+        solver.instance().tour_length(&tour)
+    );
+    assert!(solver.move_node(5, 0.0, 0.0).is_err());
+    assert!(solver.solve().length > 0.0);
+}
+
+#[test]
+fn edge_updates_follow_the_declared_type_not_the_storage() {
+    let mut instance = write_instance("edge-dense", &POINTS);
+    instance.dist_matrix = instance.dist_matrix.into_dense();
+    let config = Config {
+        num_iters: 5,
+        num_ants: 5,
+        seed: Some(3),
+        ..Config::default()
+    };
+    let mut solver = AcoSolver::new(&instance, &config);
+    let initial = solver.pheromone(1, 3).unwrap();
+    for _ in 0..3 {
+        solver.step();
+    }
+    assert_ne!(solver.pheromone(3, 1), Some(initial));
+    solver.update_edge_cost(1, 3, 50.0).unwrap();
+    assert_eq!(solver.instance().dist_matrix.get(1, 3), 50.0);
+    assert_eq!(solver.instance().dist_matrix.get(3, 1), 50.0);
+    assert_eq!(solver.pheromone(1, 3), Some(initial));
+    assert_eq!(solver.pheromone(3, 1), Some(initial));
+
+    instance.tsp_type = "ATSP".to_string();
+    let mut solver = AcoSolver::new(&instance, &config);
+    solver.update_edge_cost(1, 3, 50.0).unwrap();
+    assert_eq!(solver.instance().dist_matrix.get(1, 3), 50.0);
+    assert_eq!(
+        solver.instance().dist_matrix.get(3, 1),
+        instance.dist_matrix.get(3, 1)
+    );
+}